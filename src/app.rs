@@ -3,13 +3,81 @@
 //! This module contains the `NodepatApp` struct which manages the overall
 //! application state including document content, settings, and UI state.
 
-use crate::config::Config;
-use crate::editor::EditorState;
-use crate::file_ops::FileState;
-use crate::format::FormatSettings;
-use crate::search::SearchState;
+use crate::autosave::{
+    AUTOSAVE_INTERVAL_SECS, Autosave, FailureEvent, FileAutosave, NOTICE_SECS, clock_time,
+    validate_recovery_dir,
+};
+use crate::cli::StartupArgs;
+use crate::clipboard_history::ClipboardHistory;
+use crate::config::{Config, DeferredSave, FilePosition, PREVIEW_SPLIT_RANGE};
+use crate::convert::ConvertDialogState;
+use crate::document::{
+    CloseQueue, CloseSet, Document, cycle_index, display_name, is_blank, moved_index,
+};
+use crate::editor::{EditorState, bookmark_from};
+use crate::file_ops::{BINARY_FILE_ERROR, DiskStamp, FileState};
+use crate::find_in_files::FindInFilesState;
+use crate::format::{FontFamily, FormatSettings};
+use crate::highlight::{HighlightCache, Language};
+use crate::macros::{MacroStep, PlaybackSettings, run_macro};
+use crate::markdown::MarkdownPreview;
+use crate::memory_trim::{KEEP_REDO_STEPS, TrimPolicy, UNDO_BYTE_BUDGET};
+use crate::messages::MessageQueue;
+use crate::placement::{ScreenRect, SharedMonitors, monitor_for, validate_placement};
+use crate::search::{IncrementalSearch, SearchState, find_incremental};
+use crate::spell::{Dictionary, Misspelling, SpellChecker};
+use crate::theme::Palette;
 use crate::ui::file_browser::FileBrowser;
+use crate::ui::search_bar::{SEARCH_FIELD_ID, SearchBarAction};
+use crate::ui::status_bar::StatusBarClick;
+use crate::ui::tab_bar::{TabBarClick, TabLabel};
+use crate::viewer::ViewerState;
 use eframe::egui;
+use raw_window_handle::HasDisplayHandle;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Seconds between checks of the open file for changes by other programs
+const DISK_CHECK_SECS: f64 = 2.0;
+
+/// Action that replaces or closes the current document
+///
+/// Stored on `NodepatApp` while the user decides what to do with
+/// unsaved changes, then resumed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingAction {
+    /// Start a new, empty document
+    NewFile,
+    /// Show the Open file dialog
+    OpenDialog,
+    /// Load a file and add it to the recent files list
+    OpenFile(String),
+    /// Load a file and move the cursor to a line (1-indexed)
+    OpenFileAtLine(String, usize),
+    /// Read the current file again with an encoding chosen by the user
+    ReopenWithEncoding(String),
+    /// Close the active tab
+    CloseTab,
+    /// Close the window and exit
+    Exit,
+}
+
+impl PendingAction {
+    /// Check whether the action drops the active document's changes
+    ///
+    /// New and opened documents get their own tab, so only these actions
+    /// need to ask about unsaved changes.
+    ///
+    /// # Returns
+    /// True for reopening the file, closing the tab, and exiting
+    #[must_use]
+    pub const fn discards_changes(&self) -> bool {
+        matches!(
+            self,
+            Self::ReopenWithEncoding(_) | Self::CloseTab | Self::Exit
+        )
+    }
+}
 
 /// Main application state
 ///
@@ -21,27 +89,161 @@ pub struct NodepatApp {
     pub file_state: FileState,
     /// Editor state (text content, cursor position, undo/redo)
     pub editor_state: EditorState,
+    /// Open documents in tab order; the state of the active one is
+    /// checked out into `file_state`, `editor_state` and the other
+    /// per-document fields while it is active
+    documents: Vec<Document>,
+    /// Index of the active document in `documents`
+    active_tab: usize,
+    /// Tabs waiting to be closed by Close Others or Close All to the Right
+    close_queue: Option<CloseQueue>,
     /// Format settings (word wrap, font)
     pub format_settings: FormatSettings,
     /// Search state (find/replace text, options)
     pub search_state: SearchState,
+    /// Incremental search bar below the editor, while it is open
+    pub search_bar: Option<IncrementalSearch>,
     /// Status bar visibility
     pub show_status_bar: bool,
+    /// Show line numbers next to the editor
+    pub show_line_numbers: bool,
     /// Dialog states
     pub show_find_dialog: bool,
     pub show_replace_dialog: bool,
     pub show_font_dialog: bool,
+    /// File browser of the Font dialog's Load Font File button
+    pub font_browser: Option<FileBrowser>,
     pub show_about_dialog: bool,
     pub show_goto_dialog: bool,
+    /// The Find dialog was just opened; focus its text field once
+    pub find_dialog_opened: bool,
+    /// The Replace dialog was just opened; focus its text field once
+    pub replace_dialog_opened: bool,
+    /// The Go To dialog was just opened; focus its text field once
+    pub goto_dialog_opened: bool,
     pub show_open_dialog: bool,
     pub show_save_dialog: bool,
+    pub show_convert_dialog: bool,
+    pub show_find_in_files_dialog: bool,
+    pub show_export_pdf_dialog: bool,
+    pub show_statistics_dialog: bool,
+    pub show_unsaved_dialog: bool,
     pub goto_line: String,
+    /// Error shown in the Go To dialog for input it could not use
+    pub goto_error: String,
+    /// Steps recorded so far, while a keyboard macro is being recorded
+    pub macro_recording: Option<Vec<MacroStep>>,
+    /// Last recorded keyboard macro
+    pub recorded_macro: Vec<MacroStep>,
+    /// Show the Run Macro Multiple Times dialog
+    pub show_macro_dialog: bool,
+    /// Number of runs in the Run Macro Multiple Times dialog
+    pub macro_runs: usize,
     /// Configuration
     pub config: Config,
     /// Dark mode enabled
     pub dark_mode: bool,
+    /// Window title last sent to the viewport
+    applied_title: String,
+    /// Theme last applied to the context, None before the first frame
+    applied_dark_mode: Option<bool>,
     /// File browser for open/save dialogs
     pub file_browser: Option<FileBrowser>,
+    /// Read-only viewer for huge and binary files (replaces the editor
+    /// while open)
+    pub viewer: Option<ViewerState>,
+    /// Whether the open dialog should open the file in the viewer
+    pub open_as_viewer: bool,
+    /// Convert Files' Encoding dialog state
+    pub convert_state: ConvertDialogState,
+    /// Find in Files dialog state and results
+    pub find_in_files: FindInFilesState,
+    /// Language chosen in View > Language; None picks it by file extension
+    pub language_override: Option<Language>,
+    /// Syntax highlighting of the current text
+    pub highlight_cache: HighlightCache,
+    /// Spell checker, loaded when spell checking is first turned on
+    pub spell_checker: Option<SpellChecker>,
+    /// Misspelled word whose suggestions the editor's context menu shows
+    pub spelling_menu: Option<Misspelling>,
+    /// URL of the link whose context menu is open
+    pub link_menu: Option<String>,
+    /// Rendered Markdown shown next to the editor
+    markdown_preview: MarkdownPreview,
+    /// Action waiting for the unsaved-changes decision
+    pub pending_action: Option<PendingAction>,
+    /// Resume the pending action once the Save As dialog succeeds
+    pub resume_after_save: bool,
+    /// The user confirmed exiting, so close requests are no longer cancelled
+    exit_confirmed: bool,
+    /// Send a close command to the window on the next frame
+    send_close: bool,
+    /// Errors and notifications shown in the message dialog
+    pub messages: MessageQueue,
+    /// Debounce for config changes saved by `save_config_later`
+    config_save: DeferredSave,
+    /// Time the window became minimized or unfocused
+    idle_since: Option<f64>,
+    /// Whether memory was already trimmed during the current idle period
+    trimmed_while_idle: bool,
+    /// Status bar notice about the last memory trim and when it was set
+    trim_notice: Option<(f64, String)>,
+    /// Line to move the editor cursor to on the next frame (1-indexed)
+    pub pending_goto_line: Option<usize>,
+    /// Connected monitors, kept up to date by the event loop
+    pub monitors: SharedMonitors,
+    /// Whether the saved window position was applied
+    placement_restored: bool,
+    /// Recovery file writes and their failures
+    pub autosave: Autosave,
+    /// Show the dialog about failing autosave writes
+    pub show_autosave_dialog: bool,
+    /// Periodic saves of the document to its own file
+    pub file_autosave: FileAutosave,
+    /// Read Only Mode, protecting the document from edits
+    pub read_only_mode: bool,
+    /// Time an edit was last rejected because the document is read-only
+    edit_rejected_at: Option<f64>,
+    /// Settings to restore when the Preferences dialog is cancelled; Some
+    /// while the dialog is open
+    pub preferences_baseline: Option<Config>,
+    /// Show the dialog offering to reload a file changed by another program
+    pub show_reload_dialog: bool,
+    /// Show the warning before saving over a file changed by another program
+    pub show_overwrite_dialog: bool,
+    /// Binary file the user tried to open, while asking whether to view it
+    pub binary_file_prompt: Option<String>,
+    /// Stamp of an external change the user chose to ignore
+    pub ignored_disk_stamp: Option<DiskStamp>,
+    /// Time the file on disk was last checked for external changes
+    last_disk_check: Option<f64>,
+    /// Texts recently cut or copied, for Paste from History
+    pub clipboard_history: ClipboardHistory,
+    /// Show the Paste from History popup
+    pub show_paste_history: bool,
+    /// The Paste from History popup was just opened; focus its newest
+    /// entry once
+    pub paste_history_opened: bool,
+    /// OS clipboard, for commands that read it outside of paste events
+    pub clipboard: Option<egui_winit::clipboard::Clipboard>,
+    /// Keyboard shortcuts of menu commands
+    pub keymap: crate::keymap::Keymap,
+}
+
+/// Resolve a command-line path against the working directory
+///
+/// # Arguments
+/// * `path` - Path as given on the command line
+/// * `cwd` - Current working directory
+///
+/// # Returns
+/// The absolute path
+fn resolve_startup_path(path: &Path, cwd: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    }
 }
 
 impl Default for NodepatApp {
@@ -50,31 +252,1283 @@ impl Default for NodepatApp {
         let mut app = Self {
             file_state: FileState::default(),
             editor_state: EditorState::default(),
+            documents: vec![Document::default()],
+            close_queue: None,
+            active_tab: 0,
             format_settings: FormatSettings::default(),
-            search_state: SearchState::default(),
+            search_state: SearchState {
+                case_sensitive: config.match_case,
+                wrap_around: config.wrap_around,
+                use_regex: config.use_regex,
+                whole_word: config.whole_word,
+                preserve_case: config.preserve_case,
+                extended: config.extended_search,
+                ..SearchState::default()
+            },
+            search_bar: None,
             show_status_bar: config.show_status_bar,
+            show_line_numbers: config.show_line_numbers,
             show_find_dialog: false,
             show_replace_dialog: false,
             show_font_dialog: false,
+            font_browser: None,
             show_about_dialog: false,
             show_goto_dialog: false,
+            find_dialog_opened: false,
+            replace_dialog_opened: false,
+            goto_dialog_opened: false,
             show_open_dialog: false,
             show_save_dialog: false,
+            show_convert_dialog: false,
+            show_find_in_files_dialog: false,
+            show_export_pdf_dialog: false,
+            show_statistics_dialog: false,
+            show_unsaved_dialog: false,
             goto_line: String::new(),
+            goto_error: String::new(),
+            macro_recording: None,
+            recorded_macro: Vec::new(),
+            show_macro_dialog: false,
+            macro_runs: 1,
             dark_mode: config.dark_mode,
+            applied_title: String::new(),
+            applied_dark_mode: None,
             config,
             file_browser: None,
+            viewer: None,
+            open_as_viewer: false,
+            convert_state: ConvertDialogState::default(),
+            find_in_files: FindInFilesState::default(),
+            language_override: None,
+            highlight_cache: HighlightCache::default(),
+            spell_checker: None,
+            spelling_menu: None,
+            link_menu: None,
+            markdown_preview: MarkdownPreview::default(),
+            pending_action: None,
+            resume_after_save: false,
+            exit_confirmed: false,
+            send_close: false,
+            messages: MessageQueue::default(),
+            config_save: DeferredSave::default(),
+            idle_since: None,
+            trimmed_while_idle: false,
+            trim_notice: None,
+            pending_goto_line: None,
+            monitors: SharedMonitors::default(),
+            placement_restored: false,
+            autosave: Autosave::default(),
+            show_autosave_dialog: false,
+            file_autosave: FileAutosave::default(),
+            read_only_mode: false,
+            edit_rejected_at: None,
+            preferences_baseline: None,
+            show_reload_dialog: false,
+            show_overwrite_dialog: false,
+            binary_file_prompt: None,
+            ignored_disk_stamp: None,
+            last_disk_check: None,
+            clipboard_history: ClipboardHistory::default(),
+            show_paste_history: false,
+            paste_history_opened: false,
+            clipboard: None,
+            keymap: crate::keymap::Keymap::default(),
         };
         // Apply config to format settings
         app.config.apply_to_format(&mut app.format_settings);
+        app.file_state.create_backup = app.config.create_backup;
+        app.file_state.legacy_encoding = app.config.legacy();
+        app.file_state
+            .encoding
+            .clone_from(&app.config.default_encoding);
         app
     }
 }
 
-impl eframe::App for NodepatApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Update window title
-        let title = if self.file_state.file_path.is_empty() {
+impl NodepatApp {
+    /// Create the application, optionally opening a file on startup
+    ///
+    /// A path that does not exist yet opens an empty document with that
+    /// path set, so saving creates the file.
+    ///
+    /// # Arguments
+    /// * `args` - Command-line arguments; the file is relative to the current directory
+    ///
+    /// # Returns
+    /// The application state
+    pub fn with_startup(args: &StartupArgs) -> Self {
+        let mut app = Self::default();
+        if !app.config.recovery_dir.is_empty()
+            && let Err(e) = validate_recovery_dir(&app.config.recovery_dir())
+        {
+            app.messages.warning(format!(
+                "The recovery folder cannot be used, using the default instead.\n{e}"
+            ));
+            app.config.recovery_dir.clear();
+        }
+        if let Some(path) = &args.file {
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let path = resolve_startup_path(path, &cwd)
+                .to_string_lossy()
+                .to_string();
+            if Path::new(&path).exists() {
+                app.perform_action(PendingAction::OpenFile(path));
+            } else {
+                app.file_state.file_path = path;
+                app.apply_document_zoom();
+            }
+            app.pending_goto_line = args.line;
+        }
+        app
+    }
+
+    /// Apply the settings in `config` to the running editor
+    ///
+    /// The zoom of the active document is kept.
+    pub fn apply_config(&mut self) {
+        self.dark_mode = self.config.dark_mode;
+        self.show_status_bar = self.config.show_status_bar;
+        self.show_line_numbers = self.config.show_line_numbers;
+        let zoom = self.format_settings.zoom_factor;
+        self.config.apply_to_format(&mut self.format_settings);
+        self.format_settings.zoom_factor = zoom;
+        self.file_state.create_backup = self.config.create_backup;
+        self.file_state.legacy_encoding = self.config.legacy();
+        for document in &mut self.documents {
+            document.file_state.create_backup = self.config.create_backup;
+            document.file_state.legacy_encoding = self.config.legacy();
+        }
+        if !self.config.recovery_enabled {
+            self.autosave = Autosave::default();
+        }
+    }
+
+    /// Open the Preferences dialog
+    pub fn open_preferences(&mut self) {
+        if self.preferences_baseline.is_none() {
+            self.preferences_baseline = Some(self.config.clone());
+        }
+    }
+
+    /// Save the settings changed in the Preferences dialog
+    ///
+    /// Cancelling the dialog afterwards reverts to these settings.
+    pub fn apply_preferences(&mut self) {
+        self.messages.report_config_save(self.config.save());
+        if self.preferences_baseline.is_some() {
+            self.preferences_baseline = Some(self.config.clone());
+        }
+    }
+
+    /// Close the Preferences dialog
+    ///
+    /// # Arguments
+    /// * `keep` - Save the changes (OK) instead of reverting them (Cancel)
+    pub fn close_preferences(&mut self, keep: bool) {
+        if keep {
+            self.messages.report_config_save(self.config.save());
+        } else if let Some(baseline) = self.preferences_baseline.clone() {
+            self.config = baseline;
+            self.apply_config();
+        }
+        self.preferences_baseline = None;
+    }
+
+    /// Check whether the document is read-only
+    ///
+    /// # Returns
+    /// True if the file is read-only on disk or Read Only Mode is on
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.file_state.read_only || self.read_only_mode
+    }
+
+    /// Check whether the document can be edited
+    ///
+    /// # Returns
+    /// False in the viewer and for read-only documents
+    #[must_use]
+    pub const fn is_editable(&self) -> bool {
+        self.viewer.is_none() && !self.is_read_only()
+    }
+
+    /// Get the hint shown for disabled editing commands
+    ///
+    /// # Returns
+    /// Why the document cannot be edited
+    #[must_use]
+    pub const fn read_only_hint(&self) -> &'static str {
+        if self.viewer.is_some() {
+            crate::viewer::READ_ONLY_HINT
+        } else {
+            crate::editor::READ_ONLY_DOCUMENT_HINT
+        }
+    }
+
+    /// Flash the read-only hint in the status bar after a rejected edit
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    pub const fn reject_edit(&mut self, now: f64) {
+        self.edit_rejected_at = Some(now);
+    }
+
+    /// Check whether the read-only hint is flashing
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    ///
+    /// # Returns
+    /// True within `NOTICE_SECS` of a rejected edit
+    #[must_use]
+    pub fn edit_rejected(&self, now: f64) -> bool {
+        self.edit_rejected_at
+            .is_some_and(|at| now - at < crate::autosave::NOTICE_SECS)
+    }
+
+    /// Get the language that highlights the document
+    ///
+    /// # Returns
+    /// The language chosen in View > Language, or the one of the file
+    /// extension
+    #[must_use]
+    pub fn document_language(&self) -> Language {
+        self.language_override
+            .unwrap_or_else(|| Language::from_path(&self.file_state.file_path))
+    }
+
+    /// Load the word lists once spell checking is turned on
+    ///
+    /// If the user dictionary cannot be read, spell checking is turned off
+    /// and an error is shown.
+    pub fn load_spell_checker(&mut self) {
+        if !self.config.spell_check || self.spell_checker.is_some() {
+            return;
+        }
+        match Dictionary::load(&Config::user_dictionary_path()) {
+            Ok(dictionary) => self.spell_checker = Some(SpellChecker::new(dictionary)),
+            Err(e) => {
+                self.config.spell_check = false;
+                self.messages.error(e);
+            }
+        }
+    }
+
+    /// Check whether the Markdown preview can be shown
+    ///
+    /// # Returns
+    /// True when the editor shows a Markdown document
+    #[must_use]
+    pub fn can_preview_markdown(&self) -> bool {
+        self.viewer.is_none() && self.document_language() == Language::Markdown
+    }
+
+    /// Open the Find dialog, searching for the selected text
+    pub fn open_find_dialog(&mut self) {
+        self.prefill_find_text();
+        self.show_find_dialog = true;
+        self.find_dialog_opened = true;
+    }
+
+    /// Open the incremental search bar, searching for the selected text
+    ///
+    /// The viewer has no search bar and opens the Find dialog instead.
+    pub fn open_search_bar(&mut self) {
+        if self.viewer.is_some() {
+            self.open_find_dialog();
+            return;
+        }
+        self.prefill_find_text();
+        match &mut self.search_bar {
+            Some(bar) => bar.just_opened = true,
+            None => self.search_bar = Some(IncrementalSearch::new(self.editor_state.selection)),
+        }
+    }
+
+    /// Add or remove a bookmark on the cursor's line
+    ///
+    /// The viewer has no bookmarks.
+    pub fn toggle_bookmark(&mut self) {
+        if self.viewer.is_none() {
+            self.editor_state.toggle_bookmark();
+        }
+    }
+
+    /// Move the cursor to the next or previous bookmark, wrapping around
+    ///
+    /// # Arguments
+    /// * `forward` - Go to the next bookmark instead of the previous one
+    pub fn jump_to_bookmark(&mut self, forward: bool) {
+        if self.viewer.is_some() {
+            return;
+        }
+        let editor = &self.editor_state;
+        if let Some(line) = bookmark_from(&editor.bookmarks, editor.cursor_line, forward) {
+            self.pending_goto_line = Some(line);
+        }
+    }
+
+    /// Start recording a keyboard macro, or stop and keep the recording
+    ///
+    /// Stopping an empty recording keeps the previous macro.
+    pub fn toggle_macro_recording(&mut self) {
+        match self.macro_recording.take() {
+            Some(steps) if !steps.is_empty() => self.recorded_macro = steps,
+            Some(_) => {}
+            None => self.macro_recording = Some(Vec::new()),
+        }
+    }
+
+    /// Add a command to the keyboard macro being recorded
+    ///
+    /// # Arguments
+    /// * `step` - Command to record
+    pub fn record_macro_step(&mut self, step: MacroStep) {
+        if let Some(steps) = &mut self.macro_recording {
+            steps.push(step);
+        }
+    }
+
+    /// Check whether the recorded keyboard macro can be played back
+    #[must_use]
+    pub const fn can_play_macro(&self) -> bool {
+        self.is_editable() && self.macro_recording.is_none() && !self.recorded_macro.is_empty()
+    }
+
+    /// Play the recorded keyboard macro back at the cursor
+    ///
+    /// Each run is one undo step.
+    ///
+    /// # Arguments
+    /// * `runs` - Number of times to run the macro
+    pub fn play_macro(&mut self, runs: usize) {
+        if !self.can_play_macro() {
+            return;
+        }
+        let settings = PlaybackSettings {
+            indent_unit: self.format_settings.indent_unit(),
+            tab_width: usize::from(self.format_settings.tab_width),
+            auto_close: self.config.auto_close_brackets,
+        };
+        if run_macro(
+            &mut self.editor_state,
+            &self.recorded_macro,
+            &settings,
+            runs,
+        ) {
+            self.file_state.update_modified(&self.editor_state.text);
+        }
+    }
+
+    /// Put text on the clipboard and in the clipboard history
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context for clipboard access
+    /// * `text` - Cut or copied text
+    pub fn copy_text(&mut self, ctx: &egui::Context, text: String) {
+        self.clipboard_history.push(text.clone());
+        ctx.copy_text(text);
+    }
+
+    /// Check whether Paste from History has anything to offer
+    ///
+    /// # Returns
+    /// True if the document is editable and something was cut or copied
+    #[must_use]
+    pub fn can_paste_from_history(&self) -> bool {
+        self.is_editable() && !self.clipboard_history.is_empty()
+    }
+
+    /// Open the Paste from History popup
+    ///
+    /// Nothing happens before anything was cut or copied.
+    pub fn open_paste_history(&mut self) {
+        if self.can_paste_from_history() {
+            self.show_paste_history = true;
+            self.paste_history_opened = true;
+        }
+    }
+
+    /// Insert a clipboard history entry at the cursor, replacing the
+    /// selection
+    ///
+    /// # Arguments
+    /// * `index` - Position in the history, 0 for the newest entry
+    pub fn paste_from_history(&mut self, index: usize) {
+        if !self.is_editable() {
+            return;
+        }
+        if let Some(text) = self.clipboard_history.get(index) {
+            let text = text.to_string();
+            self.editor_state.insert_at_cursor(&text);
+            self.file_state.update_modified(&self.editor_state.text);
+        }
+    }
+
+    /// Open the Run Macro Multiple Times dialog
+    pub const fn open_macro_dialog(&mut self) {
+        self.show_macro_dialog = true;
+    }
+
+    /// Load the configured custom font, if any
+    ///
+    /// A font file that is gone or broken is reported and a built-in font
+    /// is used instead.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    pub fn install_custom_font(&mut self, ctx: &egui::Context) {
+        if self.format_settings.font_file.is_empty() {
+            return;
+        }
+        let path = PathBuf::from(&self.format_settings.font_file);
+        if let Err(e) = self.load_custom_font(ctx, &path) {
+            self.messages.warning(format!(
+                "The font {} could not be loaded, so a built-in font is used.\n{e}",
+                path.display()
+            ));
+            self.format_settings.font_file.clear();
+            if self.format_settings.font_family_type == FontFamily::Custom {
+                self.format_settings.font_family_type = FontFamily::Monospace;
+            }
+        }
+    }
+
+    /// Load a font file as the custom font
+    ///
+    /// egui uses the font from the next frame on.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    /// * `path` - Path of a .ttf or .otf file
+    ///
+    /// # Returns
+    /// Ok, or an error message if the file is not a usable font
+    pub fn load_custom_font(&mut self, ctx: &egui::Context, path: &Path) -> Result<(), String> {
+        let data = crate::fonts::read_font(path)?;
+        ctx.set_fonts(crate::fonts::font_definitions(data));
+        self.format_settings.font_file = path.to_string_lossy().to_string();
+        Ok(())
+    }
+
+    /// Drop the custom font and go back to egui's built-in fonts
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    pub fn use_built_in_fonts(&mut self, ctx: &egui::Context) {
+        ctx.set_fonts(egui::FontDefinitions::default());
+        self.format_settings.font_file.clear();
+        if self.format_settings.font_family_type == FontFamily::Custom {
+            self.format_settings.font_family_type = FontFamily::Monospace;
+        }
+    }
+
+    /// Open or close the incremental search bar
+    pub fn toggle_search_bar(&mut self) {
+        if self.search_bar.take().is_none() {
+            self.open_search_bar();
+        }
+    }
+
+    /// Open the Replace dialog, searching for the selected text
+    pub fn open_replace_dialog(&mut self) {
+        self.prefill_find_text();
+        self.show_replace_dialog = true;
+        self.replace_dialog_opened = true;
+    }
+
+    /// Open the Find in Files dialog
+    ///
+    /// Searches for the selected text, and starts in the folder of the
+    /// current file unless a folder was already chosen.
+    pub fn open_find_in_files_dialog(&mut self) {
+        let state = &mut self.find_in_files;
+        let selected = self.editor_state.selected_text();
+        if self.viewer.is_none() && !selected.is_empty() && !selected.contains('\n') {
+            state.find_text = selected.to_string();
+        }
+        if state.directory.is_empty()
+            && let Some(dir) = Path::new(&self.file_state.file_path).parent()
+        {
+            state.directory = dir.to_string_lossy().to_string();
+        }
+        self.show_find_in_files_dialog = true;
+    }
+
+    /// Open the Go To dialog
+    pub fn open_goto_dialog(&mut self) {
+        self.show_goto_dialog = true;
+        self.goto_dialog_opened = true;
+        self.goto_error.clear();
+    }
+
+    /// Use the editor selection as the find text
+    ///
+    /// Multi-line selections are ignored, like in Notepad.
+    fn prefill_find_text(&mut self) {
+        if self.viewer.is_some() {
+            return;
+        }
+        let selected = self.editor_state.selected_text();
+        if selected.is_empty() {
+            return;
+        }
+        // Extended mode can hold a multi-line selection as escapes
+        if self.search_state.extended && !self.search_state.use_regex {
+            self.search_state.find_text = crate::search::escape(selected);
+        } else if !selected.contains('\n') {
+            self.search_state.find_text = selected.to_string();
+        } else {
+            return;
+        }
+        self.search_state.status.clear();
+    }
+
+    /// Request an action that discards the current buffer
+    ///
+    /// If the document has unsaved changes, the action is stored and the
+    /// Save / Don't Save / Cancel dialog is shown instead. Exit first
+    /// switches to a tab with unsaved changes, so it asks about each one.
+    ///
+    /// # Arguments
+    /// * `action` - Action to perform
+    pub fn request_action(&mut self, action: PendingAction) {
+        if action == PendingAction::Exit
+            && let Some(index) = self.modified_tab()
+        {
+            self.switch_tab(index);
+        }
+        if self.file_state.is_modified && action.discards_changes() {
+            self.pending_action = Some(action);
+            self.show_unsaved_dialog = true;
+        } else {
+            self.perform_action(action);
+        }
+    }
+
+    /// Perform an action without asking about unsaved changes
+    ///
+    /// # Arguments
+    /// * `action` - Action to perform
+    pub fn perform_action(&mut self, action: PendingAction) {
+        match action {
+            PendingAction::NewFile => {
+                self.viewer = None;
+                if !is_blank(&self.file_state, &self.editor_state) {
+                    self.add_tab();
+                }
+            }
+            PendingAction::OpenDialog => {
+                self.open_as_viewer = false;
+                self.show_open_dialog = true;
+            }
+            PendingAction::OpenFile(path) => self.open_file(&path),
+            PendingAction::OpenFileAtLine(path, line) => {
+                self.open_file(&path);
+                if self.file_state.file_path == path {
+                    self.pending_goto_line = Some(line);
+                }
+            }
+            PendingAction::ReopenWithEncoding(encoding) => self.reopen_with_encoding(&encoding),
+            PendingAction::CloseTab => {
+                self.remove_active_tab();
+                self.close_next_queued();
+            }
+            PendingAction::Exit => {
+                // The active tab's slot holds no state, so this finds the
+                // other tabs with unsaved changes
+                let others_modified = self
+                    .documents
+                    .iter()
+                    .any(|document| document.file_state.is_modified);
+                if others_modified {
+                    // Only reached once the active tab's prompt was
+                    // answered, so it can go; ask about the next one
+                    self.remove_active_tab();
+                    self.request_action(PendingAction::Exit);
+                } else {
+                    self.exit_confirmed = true;
+                    self.send_close = true;
+                }
+            }
+        }
+    }
+
+    /// Open a file in the editor without asking about unsaved changes
+    ///
+    /// Every way of opening a file ends here, so each one resets the undo
+    /// history, moves the file to the top of the recent files list and
+    /// reports errors in the error dialog. The file gets a new tab unless
+    /// it is open already or the active document is a blank Untitled one.
+    /// Binary files are not loaded; the user is asked whether to view them
+    /// as a hex dump instead.
+    ///
+    /// # Arguments
+    /// * `path` - File to open
+    pub fn open_file(&mut self, path: &str) {
+        if let Some(index) = self.tab_of_file(path) {
+            self.viewer = None;
+            self.switch_tab(index);
+            let saved = self.file_state.add_to_recent_files(&mut self.config);
+            self.messages.report_config_save(saved);
+            return;
+        }
+        let mut file_state = self.new_file_state();
+        match file_state.load_file(path) {
+            Ok(content) => {
+                self.viewer = None;
+                if !is_blank(&self.file_state, &self.editor_state) {
+                    self.add_tab();
+                }
+                self.file_state = file_state;
+                self.language_override = None;
+                self.ignored_disk_stamp = None;
+                self.read_only_mode = false;
+                self.editor_state.set_document(content);
+                self.restore_position();
+                let saved = self.file_state.add_to_recent_files(&mut self.config);
+                self.messages.report_config_save(saved);
+                self.apply_document_zoom();
+            }
+            Err(e) if e == BINARY_FILE_ERROR => {
+                self.binary_file_prompt = Some(path.to_string());
+            }
+            Err(e) => {
+                // Drop a recent entry whose file disappeared
+                if !Path::new(path).exists() {
+                    self.config.remove_recent_file(path);
+                    self.messages.report_config_save(self.config.save());
+                }
+                self.messages.error(e);
+            }
+        }
+    }
+
+    /// Load the document again from its file, discarding unsaved changes
+    pub fn reload_document(&mut self) {
+        let path = self.file_state.file_path.clone();
+        match self.file_state.load_file(&path) {
+            Ok(content) => {
+                self.editor_state.set_document(content);
+                self.ignored_disk_stamp = None;
+            }
+            Err(e) => self.messages.error(e),
+        }
+    }
+
+    /// Load the document again from its file, decoding it with an encoding
+    ///
+    /// Unsaved changes are discarded; use `request_action` with
+    /// `PendingAction::ReopenWithEncoding` to ask about them first.
+    ///
+    /// # Arguments
+    /// * `encoding` - Encoding name (one of `file_ops::ENCODINGS`)
+    pub fn reopen_with_encoding(&mut self, encoding: &str) {
+        match self.file_state.reload_with_encoding(encoding) {
+            Ok(content) => {
+                self.editor_state.set_document(content);
+                self.ignored_disk_stamp = None;
+            }
+            Err(e) => self.messages.error(e),
+        }
+    }
+
+    /// Offer to reload the file if another program changed it
+    ///
+    /// A change the user chose to ignore is not reported again, but the
+    /// next one is.
+    pub fn detect_disk_change(&mut self) {
+        if self.viewer.is_some() || self.show_reload_dialog || self.show_overwrite_dialog {
+            return;
+        }
+        if let Some(stamp) = self.file_state.changed_on_disk()
+            && self.ignored_disk_stamp != Some(stamp)
+        {
+            self.show_reload_dialog = true;
+        }
+    }
+
+    /// Resume the pending action after the document was saved
+    pub fn resume_pending_action(&mut self) {
+        self.resume_after_save = false;
+        if let Some(action) = self.pending_action.take() {
+            self.perform_action(action);
+        }
+    }
+
+    /// Drop the pending action, keeping the current document
+    pub fn cancel_pending_action(&mut self) {
+        self.resume_after_save = false;
+        self.pending_action = None;
+        // Cancel also keeps the tabs queued after this one
+        self.close_queue = None;
+    }
+
+    /// Get the display name of the current document
+    ///
+    /// # Returns
+    /// File name, or "Untitled" for a document without a path
+    #[must_use]
+    pub fn document_name(&self) -> String {
+        display_name(&self.file_state.file_path)
+    }
+
+    /// Get the name, modified flag and path of every tab
+    ///
+    /// # Returns
+    /// One entry per open document, in tab order
+    #[must_use]
+    pub fn tab_labels(&self) -> Vec<TabLabel> {
+        self.documents
+            .iter()
+            .enumerate()
+            .map(|(index, document)| {
+                let (name, file_state) = if index == self.active_tab {
+                    (self.document_name(), &self.file_state)
+                } else {
+                    (document.name(), &document.file_state)
+                };
+                TabLabel {
+                    name,
+                    modified: file_state.is_modified,
+                    path: file_state.file_path.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Find the tab showing a file
+    ///
+    /// # Arguments
+    /// * `path` - File path
+    ///
+    /// # Returns
+    /// Index of the tab, or None if the file is not open
+    fn tab_of_file(&self, path: &str) -> Option<usize> {
+        if self.file_state.file_path == path {
+            return Some(self.active_tab);
+        }
+        self.documents
+            .iter()
+            .position(|document| document.file_state.file_path == path)
+    }
+
+    /// Find a tab with unsaved changes, preferring the active one
+    ///
+    /// # Returns
+    /// Index of the tab, or None if every document is saved
+    fn modified_tab(&self) -> Option<usize> {
+        if self.file_state.is_modified {
+            return Some(self.active_tab);
+        }
+        self.documents
+            .iter()
+            .position(|document| document.file_state.is_modified)
+    }
+
+    /// Create the file state of a new document from the settings
+    ///
+    /// # Returns
+    /// File state with the default encoding and the save settings
+    fn new_file_state(&self) -> FileState {
+        let mut file_state = FileState::default();
+        file_state
+            .encoding
+            .clone_from(&self.config.default_encoding);
+        file_state.create_backup = self.config.create_backup;
+        file_state.legacy_encoding = self.config.legacy();
+        file_state
+    }
+
+    /// Move the active document's state into its tab
+    fn stash_active(&mut self) {
+        self.documents[self.active_tab] = Document {
+            file_state: std::mem::take(&mut self.file_state),
+            editor_state: std::mem::take(&mut self.editor_state),
+            language_override: self.language_override.take(),
+            ignored_disk_stamp: self.ignored_disk_stamp.take(),
+            read_only_mode: std::mem::take(&mut self.read_only_mode),
+            recovery: std::mem::take(&mut self.autosave.document),
+        };
+    }
+
+    /// Move the state of the tab at `active_tab` into the editor
+    fn restore_active(&mut self) {
+        let document = std::mem::take(&mut self.documents[self.active_tab]);
+        self.file_state = document.file_state;
+        self.editor_state = document.editor_state;
+        self.language_override = document.language_override;
+        self.ignored_disk_stamp = document.ignored_disk_stamp;
+        self.read_only_mode = document.read_only_mode;
+        self.autosave.document = document.recovery;
+        self.editor_state.reactivate();
+        self.spelling_menu = None;
+        self.link_menu = None;
+        self.last_disk_check = None;
+        if let Some(bar) = &mut self.search_bar {
+            bar.origin = self.editor_state.selection;
+        }
+        self.apply_document_zoom();
+    }
+
+    /// Activate a tab
+    ///
+    /// # Arguments
+    /// * `index` - Index of the tab; out of range indexes are ignored
+    pub fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.documents.len() {
+            return;
+        }
+        self.stash_active();
+        self.active_tab = index;
+        self.viewer = None;
+        self.restore_active();
+    }
+
+    /// Activate the next or previous tab, wrapping around
+    ///
+    /// # Arguments
+    /// * `forward` - Move right instead of left
+    pub fn cycle_tab(&mut self, forward: bool) {
+        self.switch_tab(cycle_index(self.active_tab, self.documents.len(), forward));
+    }
+
+    /// Add a blank Untitled document after the others and activate it
+    fn add_tab(&mut self) {
+        self.stash_active();
+        self.documents.push(Document {
+            file_state: self.new_file_state(),
+            ..Document::default()
+        });
+        self.active_tab = self.documents.len() - 1;
+        self.restore_active();
+    }
+
+    /// Close a tab, asking about its unsaved changes first
+    ///
+    /// # Arguments
+    /// * `index` - Index of the tab
+    pub fn close_tab(&mut self, index: usize) {
+        self.switch_tab(index);
+        self.request_action(PendingAction::CloseTab);
+    }
+
+    /// Close several tabs, asking about each one's unsaved changes in turn
+    ///
+    /// Cancelling a prompt keeps that tab and the ones not asked about yet.
+    /// Afterwards the clicked tab is active.
+    ///
+    /// # Arguments
+    /// * `clicked` - Index of the tab the context menu was opened on
+    /// * `set` - Which tabs to close
+    pub fn close_tabs(&mut self, clicked: usize, set: CloseSet) {
+        if clicked >= self.documents.len() {
+            return;
+        }
+        self.close_queue = Some(CloseQueue::new(self.documents.len(), clicked, set));
+        self.close_next_queued();
+    }
+
+    /// Close the next queued tab, or activate the clicked tab when done
+    fn close_next_queued(&mut self) {
+        let Some(queue) = &mut self.close_queue else {
+            return;
+        };
+        if let Some(index) = queue.next() {
+            self.close_tab(index);
+        } else {
+            let keep = queue.keep();
+            self.close_queue = None;
+            self.switch_tab(keep);
+        }
+    }
+
+    /// Move a tab to another place in the tab order
+    ///
+    /// The active document stays active.
+    ///
+    /// # Arguments
+    /// * `from` - Index of the tab to move
+    /// * `to` - Index of the tab after the move
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        let count = self.documents.len();
+        if from >= count || to >= count || from == to {
+            return;
+        }
+        let document = self.documents.remove(from);
+        self.documents.insert(to, document);
+        self.active_tab = moved_index(self.active_tab, from, to);
+        // Queued indexes no longer point at the same tabs
+        self.close_queue = None;
+    }
+
+    /// Close the active tab without asking about unsaved changes
+    ///
+    /// Closing the last tab leaves a blank Untitled document.
+    fn remove_active_tab(&mut self) {
+        self.remember_position();
+        self.autosave.document.discard();
+        self.documents.remove(self.active_tab);
+        if self.documents.is_empty() {
+            self.documents.push(Document {
+                file_state: self.new_file_state(),
+                ..Document::default()
+            });
+        }
+        self.active_tab = self.active_tab.min(self.documents.len() - 1);
+        self.viewer = None;
+        self.restore_active();
+    }
+
+    /// Change the zoom of the active document
+    ///
+    /// The new zoom is remembered for the current file (or as the global
+    /// zoom for an untitled document) and saved to the config.
+    ///
+    /// # Arguments
+    /// * `zoom` - New zoom factor (1.0 = 100%)
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.format_settings.zoom_factor = zoom;
+        let file_path = Some(self.file_state.file_path.as_str()).filter(|p| !p.is_empty());
+        self.config.remember_zoom(file_path, zoom);
+        self.save_config_later();
+    }
+
+    /// Save the config once no further changes follow for a moment
+    ///
+    /// For settings that change in quick succession, like the zoom while
+    /// scrolling the mouse wheel. Pending changes are written on exit.
+    pub const fn save_config_later(&mut self) {
+        self.config_save.mark();
+    }
+
+    /// Save the config now, reporting a failure once
+    pub fn save_config_now(&mut self) {
+        let result = self.config.save();
+        let result = self.config_save.record(result);
+        self.messages.report_config_save(result);
+    }
+
+    /// Remember the cursor and scroll position in the active document's
+    /// file, e.g. when it is saved or closed
+    pub fn remember_position(&mut self) {
+        if Self::remember_document_position(&mut self.config, &self.file_state, &self.editor_state)
+        {
+            self.save_config_later();
+        }
+    }
+
+    /// Record a document's cursor and scroll position in the config
+    ///
+    /// # Arguments
+    /// * `config` - Config to record the position in
+    /// * `file_state` - File state of the document
+    /// * `editor_state` - Editor state of the document
+    ///
+    /// # Returns
+    /// True if the document has a file and its position was recorded
+    fn remember_document_position(
+        config: &mut Config,
+        file_state: &FileState,
+        editor_state: &EditorState,
+    ) -> bool {
+        if file_state.file_path.is_empty() {
+            return false;
+        }
+        let position = FilePosition {
+            cursor: editor_state.selection.0,
+            scroll_y: editor_state.scroll_y,
+        };
+        config.remember_position(&file_state.file_path, position);
+        true
+    }
+
+    /// Move to the remembered position in a just loaded file
+    ///
+    /// A position past the end of the file, e.g. after it was shortened
+    /// elsewhere, is ignored and the document starts at the top.
+    fn restore_position(&mut self) {
+        let Some(position) = self.config.position_for_file(&self.file_state.file_path) else {
+            return;
+        };
+        if position.cursor <= self.editor_state.text.chars().count() {
+            self.editor_state
+                .restore_position(position.cursor, position.scroll_y);
+        }
+    }
+
+    /// Start another Nodepat instance with an empty document
+    ///
+    /// Pending settings are written first so the new window starts with
+    /// the same config. Failures are reported in the message dialog.
+    pub fn open_new_window(&mut self) {
+        if self.config_save.is_pending() {
+            self.save_config_now();
+        }
+        let spawned =
+            std::env::current_exe().and_then(|exe| std::process::Command::new(exe).spawn());
+        if let Err(e) = spawned {
+            self.messages
+                .error(format!("Could not open a new window.\n{e}"));
+        }
+    }
+
+    /// Write the config when the debounce of `save_config_later` expires
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn run_config_save(&mut self, ctx: &egui::Context) {
+        let now = ctx.input(|i| i.time);
+        if self.config_save.poll(now) {
+            self.save_config_now();
+        } else if let Some(wait) = self.config_save.wait(now) {
+            ctx.request_repaint_after(Duration::from_secs_f64(wait));
+        }
+    }
+
+    /// Change the editor font size by a number of points
+    ///
+    /// The size stays within 8–72 points, like the font size itself.
+    ///
+    /// # Arguments
+    /// * `points` - Points to add; negative to zoom out
+    pub fn zoom_by(&mut self, points: f32) {
+        let old_size = self.format_settings.editor_font_size();
+        let new_size = (old_size + points).clamp(8.0, 72.0);
+        if (new_size - old_size).abs() > 0.1 && self.format_settings.font_size > 0.0 {
+            // Zoom so the editor font reaches the new size
+            self.set_zoom(new_size / self.format_settings.font_size);
+        }
+    }
+
+    /// Reset the zoom of the active document to the configured font size
+    pub fn reset_zoom(&mut self) {
+        self.format_settings.reset_zoom();
+        self.set_zoom(self.format_settings.zoom_factor);
+    }
+
+    /// Apply the remembered zoom for the current document
+    pub fn apply_document_zoom(&mut self) {
+        let file_path = Some(self.file_state.file_path.as_str()).filter(|p| !p.is_empty());
+        self.format_settings.zoom_factor = self.config.zoom_for_file(file_path);
+    }
+
+    /// Open the first file dropped onto the window
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.first().map(|f| f.path.clone()));
+        match dropped {
+            None => {}
+            Some(Some(path)) if path.is_dir() => {
+                self.messages
+                    .error(format!("{} is a directory, not a file.", path.display()));
+            }
+            Some(Some(path)) => match path.to_str() {
+                Some(path) => self.request_action(PendingAction::OpenFile(path.to_string())),
+                None => {
+                    self.messages.error("The dropped file has an invalid path.");
+                }
+            },
+            Some(None) => {
+                self.messages
+                    .error("The dropped item is not a file on disk.");
+            }
+        }
+    }
+
+    /// Paint a hint over the window while files are dragged over it
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn show_drop_overlay(ctx: &egui::Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("drop_overlay"),
+        ));
+        let rect = ctx.content_rect();
+        painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(160));
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Drop to open",
+            egui::FontId::proportional(24.0),
+            egui::Color32::WHITE,
+        );
+    }
+
+    /// Trim undo history and buffers when idle or over the undo budget
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn trim_memory(&mut self, ctx: &egui::Context) {
+        let (now, idle) = ctx.input(|i| {
+            let viewport = i.viewport();
+            let idle = viewport.minimized == Some(true) || viewport.focused == Some(false);
+            (i.time, idle)
+        });
+        if !idle {
+            self.idle_since = None;
+            self.trimmed_while_idle = false;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let idle_after_secs = self.config.trim_idle_secs as f64;
+        let idle_secs = match self.idle_since {
+            _ if !idle || self.trimmed_while_idle => 0.0,
+            Some(since) => now - since,
+            None => {
+                self.idle_since = Some(now);
+                // Wake up once the idle period has passed
+                ctx.request_repaint_after(std::time::Duration::from_secs(
+                    self.config.trim_idle_secs,
+                ));
+                0.0
+            }
+        };
+
+        let policy = TrimPolicy {
+            idle_after_secs,
+            undo_byte_budget: UNDO_BYTE_BUDGET,
+            min_undo_steps: self.config.undo_min_steps,
+            keep_redo_steps: KEEP_REDO_STEPS,
+        };
+        let Some((undo_steps, redo_steps)) = self.trim_documents(&policy, idle_secs) else {
+            return;
+        };
+        if idle {
+            self.trimmed_while_idle = true;
+        }
+        // Tell the user when undo steps are gone, not when only spare
+        // buffer capacity was released
+        if undo_steps + redo_steps > 0 {
+            self.trim_notice = Some((
+                now,
+                format!("Freed memory: dropped {undo_steps} undo and {redo_steps} redo steps"),
+            ));
+            ctx.request_repaint_after(Duration::from_secs_f64(NOTICE_SECS));
+        }
+    }
+
+    /// Trim the history of every open document
+    ///
+    /// # Arguments
+    /// * `policy` - Trim limits
+    /// * `idle_secs` - Seconds the window has been minimized or unfocused
+    ///
+    /// # Returns
+    /// Tuple of (undo steps, redo steps) dropped, or None if no document
+    /// was trimmed
+    fn trim_documents(&mut self, policy: &TrimPolicy, idle_secs: f64) -> Option<(usize, usize)> {
+        let editors = std::iter::once(&mut self.editor_state).chain(
+            self.documents
+                .iter_mut()
+                .map(|document| &mut document.editor_state),
+        );
+        editors
+            .filter_map(|editor| editor.trim(policy, idle_secs))
+            .map(|plan| (plan.drop_undo_steps, plan.drop_redo_steps))
+            .reduce(|(undo, redo), (more_undo, more_redo)| (undo + more_undo, redo + more_redo))
+    }
+
+    /// Show the status bar of the editor and handle clicks on it
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    fn show_editor_status_bar(&mut self, ui: &mut egui::Ui) {
+        let now = ui.input(|i| i.time);
+        let goto_hint = self
+            .keymap
+            .shortcut(crate::keymap::Action::GoTo)
+            .map_or_else(
+                || "Go to line".to_string(),
+                |shortcut| format!("Go to line ({})", ui.ctx().format_shortcut(&shortcut)),
+            );
+        let zoom = (self.config.always_show_zoom || !self.format_settings.is_default_zoom())
+            .then(|| self.format_settings.zoom_label());
+        let trim_notice = self
+            .trim_notice
+            .as_ref()
+            .filter(|(at, _)| now - at < NOTICE_SECS)
+            .map(|(_, text)| text.as_str());
+        let notices: Vec<&str> = self
+            .file_autosave
+            .notice(now)
+            .into_iter()
+            .chain(trim_notice)
+            .collect();
+        let clicked = crate::ui::status_bar::show_status_bar(
+            ui,
+            &self.editor_state,
+            self.autosave.tracker.last_failure(),
+            &notices,
+            self.is_read_only().then(|| self.edit_rejected(now)),
+            &goto_hint,
+            zoom.as_deref(),
+            &self.file_state.encoding_label(),
+        );
+        match clicked {
+            Some(StatusBarClick::GoToLine) => {
+                self.goto_line = format!(
+                    "{}:{}",
+                    self.editor_state.cursor_line, self.editor_state.cursor_column
+                );
+                self.open_goto_dialog();
+            }
+            Some(StatusBarClick::ResetZoom) => self.reset_zoom(),
+            None => {}
+        }
+    }
+
+    /// Send the window title and theme to egui when they change
+    ///
+    /// Sending them every frame makes some window managers busy.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn apply_title_and_theme(&mut self, ctx: &egui::Context) {
+        let title = self.window_title();
+        if title != self.applied_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.applied_title = title;
+        }
+        let dark = self.palette().dark;
+        if self.applied_dark_mode != Some(dark) {
+            ctx.set_visuals(if dark {
+                egui::Visuals::dark()
+            } else {
+                egui::Visuals::light()
+            });
+            self.applied_dark_mode = Some(dark);
+        }
+    }
+
+    /// Get the editor colors
+    ///
+    /// # Returns
+    /// The palette of the configured theme, or the built-in one for the
+    /// dark mode setting
+    #[must_use]
+    pub fn palette(&self) -> Palette {
+        Palette::from_settings(&self.config.theme, self.dark_mode)
+    }
+
+    /// Get the window title
+    ///
+    /// # Returns
+    /// The document name, marked when modified or open in the viewer
+    fn window_title(&self) -> String {
+        if let Some(viewer) = &self.viewer {
+            let filename = viewer
+                .path()
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+            return format!("{filename} [Viewer] - Nodepat");
+        }
+        if self.file_state.file_path.is_empty() {
             if self.file_state.is_modified {
                 "Untitled* - Nodepat".to_string()
             } else {
@@ -87,28 +1541,402 @@ impl eframe::App for NodepatApp {
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("Untitled");
-            if self.file_state.is_modified {
-                format!("{filename}* - Nodepat")
+            let modified = if self.file_state.is_modified { "*" } else { "" };
+            let read_only = if self.is_read_only() {
+                " [Read Only]"
             } else {
-                format!("{filename} - Nodepat")
+                ""
+            };
+            format!("{filename}{modified}{read_only} - Nodepat")
+        }
+    }
+
+    /// Collect the results of a running Find in Files search
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn poll_find_in_files(&mut self, ctx: &egui::Context) {
+        self.find_in_files.poll();
+        if self.find_in_files.is_running() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+
+    /// Show the tab bar between the menu bar and the editor
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn show_tab_bar_panel(&mut self, ctx: &egui::Context) {
+        let mut tabs = self.tab_labels();
+        let clicked = egui::TopBottomPanel::top("tab_bar")
+            .show(ctx, |ui| {
+                crate::ui::tab_bar::show_tab_bar(ui, &tabs, self.active_tab)
+            })
+            .inner;
+        match clicked {
+            Some(TabBarClick::Select(index)) => self.switch_tab(index),
+            Some(TabBarClick::Close(index)) => self.close_tab(index),
+            Some(TabBarClick::CloseSet(index, set)) => self.close_tabs(index, set),
+            Some(TabBarClick::CopyPath(index)) if index < tabs.len() => {
+                let path = std::mem::take(&mut tabs[index].path);
+                self.copy_text(ctx, path);
             }
-        };
-        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+            Some(TabBarClick::Move { from, to }) => self.move_tab(from, to),
+            Some(TabBarClick::CopyPath(_)) | None => {}
+        }
+    }
 
-        // Apply theme (light/dark mode)
-        ctx.set_visuals(if self.dark_mode {
-            egui::Visuals::dark()
+    /// Show the incremental search bar above the status bar while it is open
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn show_search_bar_panel(&mut self, ctx: &egui::Context) {
+        let field_id = egui::Id::new(SEARCH_FIELD_ID);
+        if self.viewer.is_some() {
+            self.search_bar = None;
+        }
+        let Some(bar) = &mut self.search_bar else {
+            // Give the keyboard focus back once the bar is gone
+            if ctx.memory(|m| m.has_focus(field_id)) {
+                crate::editor::focus_editor(ctx);
+            }
+            return;
+        };
+        let searching = !self.search_state.find_text.is_empty();
+        let no_match = searching && bar.matches.is_empty();
+        let mut summary = if searching {
+            bar.summary(&self.editor_state.text, self.editor_state.selection)
         } else {
-            egui::Visuals::light()
-        });
+            String::new()
+        };
+        if let Some(error) = self.search_state.pattern_error() {
+            summary = error;
+        } else if !self.search_state.status.is_empty() {
+            summary = format!("{summary} · {}", self.search_state.status);
+        }
+        let action = egui::TopBottomPanel::bottom("search_bar")
+            .show(ctx, |ui| {
+                crate::ui::search_bar::show_search_bar(
+                    ui,
+                    &mut self.search_state.find_text,
+                    &mut bar.just_opened,
+                    &summary,
+                    no_match,
+                )
+            })
+            .inner;
+        let Some(action) = action else {
+            return;
+        };
+        let origin = bar.origin;
+        let (start, end) = self.editor_state.selection;
+        match action {
+            SearchBarAction::Changed => {
+                if !find_incremental(self, origin.0, true) {
+                    self.editor_state.select(origin.0, origin.1);
+                }
+            }
+            SearchBarAction::Next => {
+                find_incremental(self, end, true);
+            }
+            SearchBarAction::Previous => {
+                find_incremental(self, start, false);
+            }
+            SearchBarAction::Cancel | SearchBarAction::Close => {
+                if action == SearchBarAction::Cancel {
+                    let len = self.editor_state.text.chars().count();
+                    self.editor_state
+                        .select(origin.0.min(len), origin.1.min(len));
+                }
+                self.search_bar = None;
+                crate::editor::focus_editor(ctx);
+            }
+        }
+        // Count the matches of the new search text
+        ctx.request_repaint();
+    }
+
+    /// Show the Markdown preview to the right of the editor when enabled
+    ///
+    /// Must run before the central panel so the editor takes the rest of
+    /// the window. The split is saved once the user stops dragging it.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn show_markdown_preview_panel(&mut self, ctx: &egui::Context) {
+        if !self.config.markdown_preview || !self.can_preview_markdown() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        if let Some(wait) = self.markdown_preview.update(&self.editor_state.text, now) {
+            ctx.request_repaint_after(Duration::from_secs_f64(wait));
+        }
+        let total = ctx.content_rect().width();
+        let (min, max) = PREVIEW_SPLIT_RANGE;
+        let font_size = self.format_settings.editor_font_size();
+        let response = egui::SidePanel::right("markdown_preview")
+            .resizable(true)
+            .default_width(total * self.config.preview_split)
+            .width_range(total * min..=total * max)
+            .show(ctx, |ui| {
+                crate::ui::markdown_preview::show_markdown_preview(
+                    ui,
+                    self.markdown_preview.blocks(),
+                    font_size,
+                );
+            });
+        let split = (response.response.rect.width() / total).clamp(min, max);
+        if (split - self.config.preview_split).abs() > 0.01 && !ctx.input(|i| i.pointer.any_down())
+        {
+            self.config.preview_split = split;
+            self.save_config_later();
+        }
+    }
+
+    /// Check the open file for changes by other programs every `DISK_CHECK_SECS`
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn check_disk_changes(&mut self, ctx: &egui::Context) {
+        if self.file_state.file_path.is_empty() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        if self
+            .last_disk_check
+            .is_none_or(|last| now - last >= DISK_CHECK_SECS)
+        {
+            self.last_disk_check = Some(now);
+            self.detect_disk_change();
+        }
+        ctx.request_repaint_after(Duration::from_secs_f64(DISK_CHECK_SECS));
+    }
+
+    /// Save a modified document to its file when the autosave interval passed
+    ///
+    /// Saving does not touch the editor, so focus and cursor stay put.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn run_file_autosave(&mut self, ctx: &egui::Context) {
+        if !self.config.autosave_enabled
+            || !self.is_editable()
+            || self.file_state.file_path.is_empty()
+        {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let interval = Duration::from_secs(self.config.autosave_interval_secs).as_secs_f64();
+        if self
+            .file_autosave
+            .is_due(now, interval, self.file_state.is_modified)
+        {
+            let path = self.file_state.file_path.clone();
+            // Never overwrite changes made by another program unasked
+            let result = if self.file_state.changed_on_disk().is_some() {
+                Err("the file was changed by another program".to_string())
+            } else {
+                self.file_state.save_file(&path, &self.editor_state.text)
+            };
+            let notice = match result {
+                Ok(()) if self.file_state.backup_warning.take().is_some() => {
+                    "Autosaved, but the backup failed".to_string()
+                }
+                Ok(()) => format!(
+                    "Autosaved {}",
+                    clock_time(chrono::Local::now().naive_local())
+                ),
+                Err(e) => format!("Autosave failed: {e}"),
+            };
+            self.file_autosave.set_notice(now, notice);
+        }
+        if let Some(secs) = self.file_autosave.next_wakeup(now, interval) {
+            // Wake up for the next save or to hide the notice
+            ctx.request_repaint_after(Duration::from_secs_f64(secs));
+        }
+    }
+
+    /// Write unsaved changes to the recovery directory
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn run_autosave(&mut self, ctx: &egui::Context) {
+        if !self.config.recovery_enabled || self.viewer.is_some() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let event = self.autosave.tick(
+            now,
+            &self.config.recovery_dir(),
+            &self.file_state.file_path,
+            &self.editor_state.text,
+            self.file_state.is_modified,
+        );
+        if event == FailureEvent::Escalate {
+            self.show_autosave_dialog = true;
+        }
+        if self.file_state.is_modified {
+            // Wake up for the next write even when no input arrives
+            ctx.request_repaint_after(Duration::from_secs_f64(AUTOSAVE_INTERVAL_SECS));
+        }
+    }
+
+    /// Handle a request to close the window
+    ///
+    /// Cancels the close and asks about unsaved changes when the document
+    /// is modified; otherwise flushes the config before the window closes.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn handle_close_request(&mut self, ctx: &egui::Context) {
+        if std::mem::take(&mut self.send_close) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+        if ctx.input(|i| i.viewport().close_requested()) {
+            if let Some(index) = self.modified_tab()
+                && !self.exit_confirmed
+            {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                self.switch_tab(index);
+                self.request_action(PendingAction::Exit);
+            } else {
+                self.flush_config(ctx);
+            }
+        }
+    }
+
+    /// Store the window size and save the config before exiting
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn flush_config(&mut self, ctx: &egui::Context) {
+        let (inner, outer) = ctx.input(|i| (i.viewport().inner_rect, i.viewport().outer_rect));
+        if let Some(rect) = inner {
+            self.config.window_width = rect.width();
+            self.config.window_height = rect.height();
+        }
+        if let Some(rect) = outer {
+            self.config.window_x = Some(rect.min.x);
+            self.config.window_y = Some(rect.min.y);
+            let window = ScreenRect {
+                x: rect.min.x,
+                y: rect.min.y,
+                width: self.config.window_width,
+                height: self.config.window_height,
+            };
+            if let Ok(monitors) = self.monitors.lock()
+                && let Some(name) =
+                    monitor_for(&window, &monitors).and_then(|idx| monitors[idx].name.clone())
+            {
+                self.config.window_monitor = name;
+            }
+        }
+        Self::remember_document_position(&mut self.config, &self.file_state, &self.editor_state);
+        for document in &self.documents {
+            Self::remember_document_position(
+                &mut self.config,
+                &document.file_state,
+                &document.editor_state,
+            );
+        }
+        self.save_config_now();
+    }
+
+    /// Open the OS clipboard once the window exists
+    ///
+    /// # Arguments
+    /// * `frame` - eframe frame providing the display handle
+    fn connect_clipboard(&mut self, frame: &eframe::Frame) {
+        if self.clipboard.is_none() {
+            let display = frame.display_handle().ok().map(|handle| handle.as_raw());
+            self.clipboard = Some(egui_winit::clipboard::Clipboard::new(display));
+        }
+    }
+
+    /// Move the window back to its saved position once monitors are known
+    ///
+    /// The saved position is validated against the connected monitors so
+    /// the window never opens off-screen.
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context
+    fn restore_placement(&mut self, ctx: &egui::Context) {
+        if self.placement_restored {
+            return;
+        }
+        let (Some(x), Some(y)) = (self.config.window_x, self.config.window_y) else {
+            self.placement_restored = true;
+            return;
+        };
+        let Ok(monitors) = self.monitors.lock() else {
+            return;
+        };
+        if monitors.is_empty() {
+            // Not probed yet
+            return;
+        }
+        let [width, height] = self.config.window_size();
+        let saved = ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        };
+        let saved_monitor =
+            Some(self.config.window_monitor.as_str()).filter(|name| !name.is_empty());
+        let placed = validate_placement(saved, saved_monitor, &monitors);
+        drop(monitors);
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+            placed.x, placed.y,
+        )));
+        if placed != saved {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
+                placed.width,
+                placed.height,
+            )));
+        }
+        self.placement_restored = true;
+    }
+}
+
+impl eframe::App for NodepatApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.connect_clipboard(frame);
+
+        // Restore the saved window position on a connected monitor
+        self.restore_placement(ctx);
+
+        // Warn about unsaved changes before the window closes
+        self.handle_close_request(ctx);
+
+        // Offer to reload the file when another program changed it
+        self.check_disk_changes(ctx);
+
+        // Write settings changed a moment ago
+        self.run_config_save(ctx);
+
+        // Save to the document's file, then write what is left unsaved
+        // to a recovery file
+        self.run_file_autosave(ctx);
+        self.run_autosave(ctx);
+
+        // Collect Find in Files results from the search thread
+        self.poll_find_in_files(ctx);
+
+        // Release memory while idle or when the undo history grows too large
+        self.trim_memory(ctx);
+
+        // Open files dropped onto the window
+        self.handle_dropped_files(ctx);
+        Self::show_drop_overlay(ctx);
+
+        self.apply_title_and_theme(ctx);
 
         // Apply font settings only to Monospace (used by editor)
         // Don't modify TextStyle::Body as it affects UI elements like checkboxes
-        let font_size = self.format_settings.font_size;
-        let font_id = match self.format_settings.font_family_type {
-            crate::format::FontFamily::Monospace => egui::FontId::monospace(font_size),
-            crate::format::FontFamily::Proportional => egui::FontId::proportional(font_size),
-        };
+        let font_size = self.format_settings.editor_font_size();
+        let font_id =
+            crate::fonts::editor_font(ctx, self.format_settings.font_family_type, font_size);
         ctx.style_mut(|style| {
             style
                 .text_styles
@@ -121,16 +1949,21 @@ impl eframe::App for NodepatApp {
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             crate::menu::show_menu_bar(ui, self);
         });
+        self.show_tab_bar_panel(ctx);
+
+        // Show the Markdown preview beside the editor
+        self.show_markdown_preview_panel(ctx);
 
         // Show main text area - fill remaining space
-        let editor_bg = if self.dark_mode {
-            egui::Color32::from_rgb(30, 30, 30)
-        } else {
-            egui::Color32::from_rgb(255, 255, 255)
-        };
+        let palette = self.palette();
         egui::CentralPanel::default()
-            .frame(egui::Frame::default().fill(editor_bg).inner_margin(0.0)) // Remove inner margin to maximize space
+            .frame(
+                egui::Frame::default()
+                    .fill(palette.background)
+                    .inner_margin(0.0),
+            ) // Remove inner margin to maximize space
             .show(ctx, |ui| {
+                palette.apply(ui.visuals_mut());
                 // Handle Ctrl + Scroll for font size when over editor area
                 // Check raw input events to detect scroll while Ctrl is held
                 ui.input(|i| {
@@ -140,41 +1973,618 @@ impl eframe::App for NodepatApp {
                             if let egui::Event::MouseWheel { delta, .. } = event {
                                 let scroll_y = delta.y;
                                 if scroll_y.abs() > 0.0 {
-                                    // Increase or decrease font size based on scroll direction
-                                    let old_size = self.format_settings.font_size;
-                                    let new_size = if scroll_y > 0.0 {
-                                        // Scroll up: increase font size
-                                        (old_size + 1.0).min(72.0)
-                                    } else {
-                                        // Scroll down: decrease font size
-                                        (old_size - 1.0).max(8.0)
-                                    };
-
-                                    if (new_size - old_size).abs() > 0.1 {
-                                        self.format_settings.font_size = new_size;
-                                        // Save to config
-                                        self.config.update_from_format(&self.format_settings);
-                                        let _ = self.config.save();
-                                    }
+                                    // Scroll up: increase font size, down: decrease
+                                    self.zoom_by(scroll_y.signum());
                                 }
                             }
                         }
                     }
                 });
-                crate::editor::show_editor(ui, self);
+                if let Some(viewer) = &mut self.viewer {
+                    viewer.show(ui, &font_id);
+                } else {
+                    crate::editor::show_editor(ui, self);
+                }
             });
 
         // Show status bar if enabled
         if self.show_status_bar {
             egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
-                crate::ui::status_bar::show_status_bar(ui, &self.editor_state);
+                if let Some(viewer) = &self.viewer {
+                    crate::ui::status_bar::show_viewer_status_bar(ui, viewer);
+                } else {
+                    self.show_editor_status_bar(ui);
+                }
             });
         }
+        self.show_search_bar_panel(ctx);
 
         // Show dialogs
         crate::ui::dialogs::show_dialogs(ctx, self);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Settings changed after the close request was handled
+        if self.config_save.is_pending() {
+            self.save_config_now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageKind;
+
+    #[test]
+    fn test_external_change_offers_reload() {
+        let path = std::env::temp_dir().join("test_Nodepat_reload.txt");
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, "original").expect("Failed to write test file");
+        let mut app = NodepatApp::default();
+        app.perform_action(PendingAction::OpenFile(path_str));
+        app.detect_disk_change();
+        assert!(!app.show_reload_dialog);
+
+        std::fs::write(&path, "changed elsewhere").expect("Failed to write test file");
+        app.detect_disk_change();
+        assert!(app.show_reload_dialog);
+
+        // An ignored change is not reported again
+        app.show_reload_dialog = false;
+        app.ignored_disk_stamp = app.file_state.changed_on_disk();
+        app.detect_disk_change();
+        assert!(!app.show_reload_dialog);
+
+        app.reload_document();
+        assert_eq!(app.editor_state.text, "changed elsewhere");
+        assert!(!app.file_state.is_modified);
+        assert_eq!(app.file_state.changed_on_disk(), None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_open_file_moves_file_to_top_of_recent_files() {
+        let first = std::env::temp_dir().join("test_Nodepat_recent_a.txt");
+        let second = std::env::temp_dir().join("test_Nodepat_recent_b.txt");
+        std::fs::write(&first, "first").expect("Failed to write test file");
+        std::fs::write(&second, "second").expect("Failed to write test file");
+        let first = first.to_string_lossy().to_string();
+        let second = second.to_string_lossy().to_string();
+
+        let mut app = NodepatApp::default();
+        app.open_file(&first);
+        app.open_file(&second);
+        assert_eq!(
+            app.config.recent_files[..2],
+            [second.clone(), first.clone()]
+        );
+        app.open_file(&first);
+        assert_eq!(
+            app.config.recent_files[..2],
+            [first.clone(), second.clone()]
+        );
+        assert_eq!(app.editor_state.text, "first");
+        assert_eq!(app.documents.len(), 2);
+
+        let _ = std::fs::remove_file(&first);
+        let _ = std::fs::remove_file(&second);
+    }
+
+    #[test]
+    fn test_cancel_preferences_reverts_live_changes() {
+        let mut app = NodepatApp::default();
+        app.format_settings.zoom_factor = 1.5;
+        let dark_mode = app.dark_mode;
+        let word_wrap = app.format_settings.word_wrap;
+        app.open_preferences();
+
+        app.config.dark_mode = !dark_mode;
+        app.config.word_wrap = !word_wrap;
+        app.apply_config();
+        assert_eq!(app.dark_mode, !dark_mode);
+        assert_eq!(app.format_settings.word_wrap, !word_wrap);
+        assert!((app.format_settings.zoom_factor - 1.5).abs() < f32::EPSILON);
+
+        app.close_preferences(false);
+        assert!(app.preferences_baseline.is_none());
+        assert_eq!(app.dark_mode, dark_mode);
+        assert_eq!(app.format_settings.word_wrap, word_wrap);
+    }
+
+    #[test]
+    fn test_open_find_dialog_uses_selection() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "héllo wörld\nnext".to_string();
+        app.search_state.find_text = "old".to_string();
+
+        app.editor_state.selection = (6, 11);
+        app.open_find_dialog();
+        assert!(app.show_find_dialog && app.find_dialog_opened);
+        assert_eq!(app.search_state.find_text, "wörld");
+
+        // Empty and multi-line selections keep the previous find text
+        app.editor_state.selection = (3, 3);
+        app.open_replace_dialog();
+        assert_eq!(app.search_state.find_text, "wörld");
+        app.editor_state.selection = (6, 14);
+        app.open_replace_dialog();
+        assert!(app.show_replace_dialog && app.replace_dialog_opened);
+        assert_eq!(app.search_state.find_text, "wörld");
+        // Extended mode takes multi-line selections as escapes
+        app.search_state.extended = true;
+        app.open_find_dialog();
+        assert_eq!(app.search_state.find_text, "wörld\\nne");
+    }
+
+    #[test]
+    fn test_resolve_startup_path() {
+        let cwd = std::env::temp_dir();
+        assert_eq!(
+            resolve_startup_path(Path::new("notes.txt"), &cwd),
+            cwd.join("notes.txt")
+        );
+        let absolute = cwd.join("other.txt");
+        assert_eq!(
+            resolve_startup_path(&absolute, Path::new("/unused")),
+            absolute
+        );
+    }
+
+    #[test]
+    fn test_with_file_missing_path_presets_path() {
+        let path = std::env::temp_dir().join("test_Nodepat_startup_missing.txt");
+        let _ = std::fs::remove_file(&path);
+        let args = StartupArgs {
+            file: Some(path.clone()),
+            line: Some(5),
+        };
+        let app = NodepatApp::with_startup(&args);
+        assert_eq!(app.file_state.file_path, path.to_string_lossy());
+        assert!(app.editor_state.text.is_empty());
+        assert!(!app.file_state.is_modified);
+        assert_eq!(app.pending_goto_line, Some(5));
+    }
+
+    #[test]
+    fn test_request_action_defers_when_modified() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "unsaved".to_string();
+        app.file_state.is_modified = true;
+
+        app.request_action(PendingAction::CloseTab);
+        assert!(app.show_unsaved_dialog);
+        assert_eq!(app.pending_action, Some(PendingAction::CloseTab));
+        assert_eq!(app.editor_state.text, "unsaved");
+
+        app.cancel_pending_action();
+        assert_eq!(app.pending_action, None);
+        assert_eq!(app.editor_state.text, "unsaved");
+
+        app.request_action(PendingAction::CloseTab);
+        app.resume_pending_action();
+        assert!(app.editor_state.text.is_empty());
+        assert!(!app.file_state.is_modified);
+    }
+
+    #[test]
+    fn test_new_file_opens_tab_and_keeps_text() {
+        let mut app = NodepatApp::default();
+        app.request_action(PendingAction::NewFile);
+        assert_eq!(app.documents.len(), 1);
+
+        app.editor_state.text = "first".to_string();
+        app.file_state.is_modified = true;
+        app.request_action(PendingAction::NewFile);
+        assert!(!app.show_unsaved_dialog);
+        assert_eq!(app.documents.len(), 2);
+        assert_eq!(app.active_tab, 1);
+        assert!(app.editor_state.text.is_empty());
+        let labels: Vec<(String, bool)> = app
+            .tab_labels()
+            .into_iter()
+            .map(|tab| (tab.name, tab.modified))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("Untitled".to_string(), true),
+                ("Untitled".to_string(), false)
+            ]
+        );
+
+        app.cycle_tab(true);
+        assert_eq!(app.active_tab, 0);
+        assert_eq!(app.editor_state.text, "first");
+        assert!(app.file_state.is_modified);
+        app.cycle_tab(false);
+        assert_eq!(app.active_tab, 1);
+        assert!(app.editor_state.text.is_empty());
+    }
+
+    /// Create an app with one tab per text, the last one active
+    fn app_with_tabs(texts: &[&str]) -> NodepatApp {
+        let mut app = NodepatApp::default();
+        for (index, text) in texts.iter().enumerate() {
+            if index > 0 {
+                app.add_tab();
+            }
+            app.editor_state.text = (*text).to_string();
+            app.file_state.is_modified = true;
+        }
+        app
+    }
+
+    #[test]
+    fn test_trim_memory_reaches_inactive_tabs() {
+        let mut app = NodepatApp::default();
+        app.editor_state.set_document("state 0".to_string());
+        for i in 1..5 {
+            app.editor_state.save_undo_state();
+            app.editor_state.text = format!("state {i}");
+        }
+        for _ in 0..4 {
+            app.editor_state.undo();
+        }
+        app.add_tab();
+        let policy = TrimPolicy {
+            idle_after_secs: 1.0,
+            undo_byte_budget: UNDO_BYTE_BUDGET,
+            min_undo_steps: 0,
+            keep_redo_steps: 1,
+        };
+        assert_eq!(app.trim_documents(&policy, 0.0), None);
+        assert_eq!(app.trim_documents(&policy, 1.0), Some((0, 3)));
+        assert_eq!(app.documents[0].editor_state.redo_history.len(), 1);
+        assert_eq!(app.documents[0].editor_state.text, "state 0");
+    }
+
+    #[test]
+    fn test_move_tab_keeps_active_document() {
+        let mut app = app_with_tabs(&["a", "b", "c"]);
+        app.switch_tab(1);
+        app.move_tab(0, 2);
+        assert_eq!(app.active_tab, 0);
+        assert_eq!(app.editor_state.text, "b");
+        app.cycle_tab(true);
+        assert_eq!(app.editor_state.text, "c");
+        app.cycle_tab(true);
+        assert_eq!(app.editor_state.text, "a");
+    }
+
+    #[test]
+    fn test_close_others_asks_about_each_tab() {
+        let mut app = app_with_tabs(&["a", "b", "c", "d"]);
+        app.close_tabs(1, CloseSet::Others);
+        // The rightmost tab asks first
+        assert!(app.show_unsaved_dialog);
+        assert_eq!(app.editor_state.text, "d");
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+        assert!(app.show_unsaved_dialog);
+        assert_eq!(app.editor_state.text, "c");
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+        assert_eq!(app.editor_state.text, "a");
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+
+        assert_eq!(app.documents.len(), 1);
+        assert_eq!(app.editor_state.text, "b");
+        assert!(app.close_queue.is_none());
+    }
+
+    #[test]
+    fn test_cancel_stops_closing_tabs_to_the_right() {
+        let mut app = app_with_tabs(&["a", "b", "c", "d"]);
+        app.close_tabs(0, CloseSet::ToTheRight);
+        assert_eq!(app.editor_state.text, "d");
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+        assert_eq!(app.editor_state.text, "c");
+        app.show_unsaved_dialog = false;
+        app.cancel_pending_action();
+
+        assert_eq!(app.documents.len(), 3);
+        assert_eq!(app.editor_state.text, "c");
+        assert!(app.close_queue.is_none());
+    }
+
+    #[test]
+    fn test_open_file_reuses_blank_tab_and_open_tab() {
+        let path = std::env::temp_dir().join("test_Nodepat_tabs_open.txt");
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, "on disk").expect("Failed to write test file");
+        let mut app = NodepatApp::default();
+        app.open_file(&path_str);
+        assert_eq!(app.documents.len(), 1);
+        assert_eq!(app.editor_state.text, "on disk");
+
+        app.request_action(PendingAction::NewFile);
+        assert_eq!(app.documents.len(), 2);
+        app.open_file(&path_str);
+        assert_eq!(app.documents.len(), 2);
+        assert_eq!(app.active_tab, 0);
+        assert_eq!(app.document_name(), "test_Nodepat_tabs_open.txt");
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_reopened_file_restores_position() {
+        let path = std::env::temp_dir().join("test_Nodepat_position.txt");
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, "line one\nline two\n").expect("Failed to write test file");
+        let mut app = NodepatApp::default();
+        app.open_file(&path_str);
+        app.editor_state.select(12, 12);
+        app.editor_state.scroll_y = 40.0;
+        app.close_tab(0);
+        assert!(app.editor_state.text.is_empty());
+
+        app.open_file(&path_str);
+        assert_eq!(app.editor_state.selection, (12, 12));
+        assert!((app.editor_state.scroll_y - 40.0).abs() < f32::EPSILON);
+
+        // A position beyond the end of a shortened file is not used
+        app.close_tab(0);
+        std::fs::write(&path, "short").expect("Failed to write test file");
+        app.open_file(&path_str);
+        assert_eq!(app.editor_state.selection, (0, 0));
+        assert!(app.editor_state.scroll_y.abs() < f32::EPSILON);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_close_tab_asks_about_changes() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "keep".to_string();
+        app.file_state.is_modified = true;
+        app.request_action(PendingAction::NewFile);
+        app.editor_state.text = "draft".to_string();
+        app.file_state.is_modified = true;
+
+        app.close_tab(0);
+        assert!(app.show_unsaved_dialog);
+        assert_eq!(app.editor_state.text, "keep");
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+        assert_eq!(app.documents.len(), 1);
+        assert_eq!(app.editor_state.text, "draft");
+
+        // Closing the last tab leaves an empty Untitled document
+        app.file_state.is_modified = false;
+        app.close_tab(0);
+        assert!(!app.show_unsaved_dialog);
+        assert_eq!(app.documents.len(), 1);
+        assert!(is_blank(&app.file_state, &app.editor_state));
+        assert_eq!(app.document_name(), "Untitled");
+    }
+
+    #[test]
+    fn test_exit_asks_about_each_modified_tab() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "one".to_string();
+        app.file_state.is_modified = true;
+        app.request_action(PendingAction::NewFile);
+        app.editor_state.text = "two".to_string();
+        app.request_action(PendingAction::NewFile);
+        app.editor_state.text = "three".to_string();
+        app.file_state.is_modified = true;
+        assert_eq!(app.documents.len(), 3);
+
+        app.request_action(PendingAction::Exit);
+        assert_eq!(app.editor_state.text, "three");
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+        assert!(!app.exit_confirmed);
+        assert_eq!(app.editor_state.text, "one");
+        assert_eq!(app.pending_action, Some(PendingAction::Exit));
+
+        app.show_unsaved_dialog = false;
+        app.resume_pending_action();
+        assert!(app.exit_confirmed);
+
+        let mut app = app_with_tabs(&["one", "two", "three"]);
+        app.file_state.is_modified = false;
+        app.switch_tab(0);
+        app.file_state.is_modified = false;
+        app.switch_tab(2);
+
+        // The clean active tab stays while the user is asked about "two"
+        app.request_action(PendingAction::Exit);
+        assert!(app.show_unsaved_dialog);
+        assert_eq!(app.editor_state.text, "two");
+        assert_eq!(app.documents.len(), 3);
+
+        app.show_unsaved_dialog = false;
+        app.cancel_pending_action();
+        assert!(!app.exit_confirmed);
+        assert_eq!(app.documents.len(), 3);
+        assert_eq!(
+            app.tab_labels()
+                .iter()
+                .filter(|label| label.modified)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_exit_waits_for_decision() {
+        let mut app = NodepatApp::default();
+        app.file_state.is_modified = true;
+        app.request_action(PendingAction::Exit);
+        assert!(!app.exit_confirmed);
+
+        app.resume_pending_action();
+        assert!(app.exit_confirmed);
+        assert!(app.send_close);
+    }
+
+    #[test]
+    fn test_open_file_failure_shows_error() {
+        let mut app = NodepatApp::default();
+        let mut missing = std::env::temp_dir();
+        missing.push("test_Nodepat_missing_dropped_file.txt");
+        let missing = missing.to_string_lossy().to_string();
+
+        app.request_action(PendingAction::OpenFile(missing.clone()));
+        assert_eq!(
+            app.messages.front().map(|m| m.kind),
+            Some(MessageKind::Error)
+        );
+        assert!(app.file_state.file_path.is_empty());
+
+        // A failed open does not move the cursor of the current document
+        app.request_action(PendingAction::OpenFileAtLine(missing, 3));
+        assert_eq!(app.pending_goto_line, None);
+    }
+
+    #[test]
+    fn test_reopen_with_encoding_replaces_text() {
+        let path = std::env::temp_dir().join("test_Nodepat_reopen_encoding.txt");
+        std::fs::write(&path, [0xFF, 0xFE, b'h', 0, b'i', 0]).expect("Failed to write test file");
+        let path = path.to_string_lossy().to_string();
+
+        let mut app = NodepatApp::default();
+        app.open_file(&path);
+        assert_eq!(app.editor_state.text, "hi");
+        app.request_action(PendingAction::ReopenWithEncoding(
+            "Windows-1252".to_string(),
+        ));
+        assert_eq!(app.file_state.encoding, "Windows-1252");
+        assert_eq!(app.editor_state.text, "\u{ff}\u{fe}h\0i\0");
+        assert!(!app.file_state.is_modified);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_macro_recording_and_playback() {
+        let mut app = NodepatApp::default();
+        app.editor_state.set_document("a\nb".to_string());
+        assert!(!app.can_play_macro());
+        app.toggle_macro_recording();
+        app.record_macro_step(MacroStep::Duplicate);
+        assert!(!app.can_play_macro());
+        app.toggle_macro_recording();
+        assert_eq!(app.recorded_macro, vec![MacroStep::Duplicate]);
+
+        // Stopping an empty recording keeps the macro
+        app.toggle_macro_recording();
+        app.toggle_macro_recording();
+        app.play_macro(3);
+        assert_eq!(app.editor_state.text, "a\na\na\na\nb");
+        assert!(app.file_state.is_modified);
+        app.read_only_mode = true;
+        assert!(!app.can_play_macro());
+    }
+
+    #[test]
+    fn test_paste_from_history() {
+        let ctx = egui::Context::default();
+        let mut app = NodepatApp::default();
+        app.open_paste_history();
+        assert!(!app.show_paste_history);
+        app.copy_text(&ctx, "first".to_string());
+        app.copy_text(&ctx, "second".to_string());
+        app.open_paste_history();
+        assert!(app.show_paste_history);
+
+        app.editor_state.set_document("[]".to_string());
+        app.editor_state.select(1, 1);
+        app.paste_from_history(1);
+        assert_eq!(app.editor_state.text, "[first]");
+        assert!(app.file_state.is_modified);
+        app.paste_from_history(5);
+        assert_eq!(app.editor_state.text, "[first]");
+    }
+
+    #[test]
+    fn test_window_title() {
+        let mut app = NodepatApp::default();
+        assert_eq!(app.window_title(), "Untitled - Nodepat");
+        app.file_state.is_modified = true;
+        assert_eq!(app.window_title(), "Untitled* - Nodepat");
+
+        app.file_state.file_path = std::env::temp_dir()
+            .join("test_Nodepat_title.txt")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(app.window_title(), "test_Nodepat_title.txt* - Nodepat");
+        app.file_state.is_modified = false;
+        app.read_only_mode = true;
+        assert_eq!(
+            app.window_title(),
+            "test_Nodepat_title.txt [Read Only] - Nodepat"
+        );
+    }
+
+    #[test]
+    fn test_title_and_theme_sent_only_on_change() {
+        let ctx = egui::Context::default();
+        let mut app = NodepatApp {
+            dark_mode: false,
+            ..NodepatApp::default()
+        };
+        app.apply_title_and_theme(&ctx);
+        assert_eq!(app.applied_title, "Untitled - Nodepat");
+        assert!(!ctx.style().visuals.dark_mode);
+
+        // A theme set elsewhere is left alone until dark mode toggles
+        ctx.set_visuals(egui::Visuals::dark());
+        app.apply_title_and_theme(&ctx);
+        assert!(ctx.style().visuals.dark_mode);
+        app.dark_mode = true;
+        app.apply_title_and_theme(&ctx);
+        assert_eq!(app.applied_dark_mode, Some(true));
+        app.dark_mode = false;
+        app.apply_title_and_theme(&ctx);
+        assert!(!ctx.style().visuals.dark_mode);
+
+        app.file_state.is_modified = true;
+        app.apply_title_and_theme(&ctx);
+        assert_eq!(app.applied_title, "Untitled* - Nodepat");
+    }
+
+    #[test]
+    fn test_binary_file_asks_before_viewing() {
+        let path = std::env::temp_dir().join("test_Nodepat_open_binary.bin");
+        std::fs::write(&path, b"\x7fELF\x02\x01\x01\x00").expect("Failed to write test file");
+        let path = path.to_string_lossy().to_string();
+
+        let mut app = NodepatApp::default();
+        app.editor_state.set_document("kept".to_string());
+        app.open_file(&path);
+        assert_eq!(app.binary_file_prompt.as_deref(), Some(path.as_str()));
+        assert_eq!(app.editor_state.text, "kept");
+        assert!(app.file_state.file_path.is_empty());
+        assert!(app.messages.is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_open_file_at_line_moves_cursor() {
+        let path = std::env::temp_dir().join("test_Nodepat_open_at_line.txt");
+        std::fs::write(&path, "one\ntwo\nthree").expect("Failed to write test file");
+        let path = path.to_string_lossy().to_string();
+
+        let mut app = NodepatApp::default();
+        app.request_action(PendingAction::OpenFileAtLine(path.clone(), 3));
+        assert_eq!(app.file_state.file_path, path);
+        assert_eq!(app.pending_goto_line, Some(3));
+
+        let _ = std::fs::remove_file(&path);
+    }
 
-        // Save config on exit (would be better to do this in a proper cleanup)
-        // For now, we'll save when settings change
+    #[test]
+    fn test_request_action_runs_when_clean() {
+        let mut app = NodepatApp::default();
+        app.request_action(PendingAction::OpenDialog);
+        assert!(!app.show_unsaved_dialog);
+        assert!(app.show_open_dialog);
+        assert_eq!(app.pending_action, None);
     }
 }
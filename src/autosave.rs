@@ -0,0 +1,450 @@
+//! Autosave of unsaved changes to a recovery directory
+//!
+//! This module periodically writes modified documents to recovery files
+//! and tracks consecutive write failures, so a failing disk or a missing
+//! folder is reported instead of silently losing work. It also times the
+//! optional periodic saves of documents to their own file.
+
+use crate::file_ops::write_atomic;
+use crate::ui::file_browser::FileBrowser;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Seconds between recovery writes while the document is modified
+pub const AUTOSAVE_INTERVAL_SECS: f64 = 30.0;
+/// Consecutive failures before the autosave dialog is shown
+pub const ESCALATE_AFTER_FAILURES: u32 = 3;
+/// Seconds the result of a periodic save stays in the status bar
+pub const NOTICE_SECS: f64 = 5.0;
+
+/// A failed recovery write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteFailure {
+    /// Error message
+    pub error: String,
+    /// File that could not be written
+    pub target: PathBuf,
+}
+
+/// What the UI should do after a recovery write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureEvent {
+    /// Nothing changed
+    None,
+    /// Writes started failing: show the status bar warning
+    Warn,
+    /// Writes keep failing: show the autosave dialog
+    Escalate,
+    /// Writes work again: the warning was cleared
+    Recovered,
+}
+
+/// Counts consecutive recovery write failures
+#[derive(Debug, Default)]
+pub struct FailureTracker {
+    /// Number of failures since the last successful write
+    consecutive: u32,
+    /// Most recent failure
+    last_failure: Option<WriteFailure>,
+    /// Whether the dialog was already shown for this run of failures
+    escalated: bool,
+}
+
+impl FailureTracker {
+    /// Record a failed write
+    ///
+    /// # Arguments
+    /// * `failure` - The failed write
+    ///
+    /// # Returns
+    /// `Warn` on the first failure, `Escalate` once after
+    /// `ESCALATE_AFTER_FAILURES` failures, otherwise `None`
+    pub fn record_failure(&mut self, failure: WriteFailure) -> FailureEvent {
+        self.consecutive += 1;
+        self.last_failure = Some(failure);
+        if self.consecutive >= ESCALATE_AFTER_FAILURES && !self.escalated {
+            self.escalated = true;
+            FailureEvent::Escalate
+        } else if self.consecutive == 1 {
+            FailureEvent::Warn
+        } else {
+            FailureEvent::None
+        }
+    }
+
+    /// Record a successful write
+    ///
+    /// # Returns
+    /// `Recovered` if a warning was cleared, otherwise `None`
+    pub fn record_success(&mut self) -> FailureEvent {
+        let was_failing = self.consecutive > 0;
+        *self = Self::default();
+        if was_failing {
+            FailureEvent::Recovered
+        } else {
+            FailureEvent::None
+        }
+    }
+
+    /// Get the failure shown in the status bar warning
+    ///
+    /// # Returns
+    /// The most recent failure, or None if the last write succeeded
+    pub const fn last_failure(&self) -> Option<&WriteFailure> {
+        self.last_failure.as_ref()
+    }
+
+    /// Get the number of failures since the last successful write
+    pub const fn consecutive_failures(&self) -> u32 {
+        self.consecutive
+    }
+}
+
+/// Get the recovery file for a document
+///
+/// # Arguments
+/// * `dir` - Recovery directory
+/// * `document_path` - Document path, empty for an untitled document
+///
+/// # Returns
+/// Path of the recovery file
+#[must_use]
+pub fn recovery_path(dir: &Path, document_path: &str) -> PathBuf {
+    let name = Path::new(document_path).file_name().map_or_else(
+        || "Untitled".to_string(),
+        |n| n.to_string_lossy().to_string(),
+    );
+    // Documents with the same name in different folders get their own file
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    document_path.hash(&mut hasher);
+    dir.join(format!("{name}.{:016x}.recovery", hasher.finish()))
+}
+
+/// Check that a recovery directory can be written to
+///
+/// # Arguments
+/// * `dir` - Directory to check; created if missing
+///
+/// # Returns
+/// Result indicating success or error message
+pub fn validate_recovery_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {e}", dir.display()))?;
+    let probe = dir.join(".nodepat-probe");
+    fs::write(&probe, b"")
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|e| format!("Cannot write to {}: {e}", dir.display()))
+}
+
+/// Recovery file of one document
+///
+/// Every open document keeps its own, so activating another tab does not
+/// remove the recovery file of a document with unsaved changes.
+#[derive(Debug, Default)]
+pub struct RecoveryState {
+    /// Time of the last write attempt
+    last_attempt: Option<f64>,
+    /// Recovery file written for the current changes
+    recovery_file: Option<PathBuf>,
+}
+
+impl RecoveryState {
+    /// Remove the recovery file of a document whose changes were discarded
+    pub fn discard(&mut self) {
+        if let Some(file) = self.recovery_file.take() {
+            let _ = fs::remove_file(file);
+        }
+        self.last_attempt = None;
+    }
+}
+
+/// Autosave state
+#[derive(Default)]
+pub struct Autosave {
+    /// Recovery write failures
+    pub tracker: FailureTracker,
+    /// Directory picker for choosing another recovery directory
+    pub browser: Option<FileBrowser>,
+    /// Recovery file of the active document
+    pub document: RecoveryState,
+}
+
+impl Autosave {
+    /// Write a recovery file if the document has unsaved changes
+    ///
+    /// Writes at most once per `AUTOSAVE_INTERVAL_SECS`. The recovery file
+    /// is removed once the document is no longer modified.
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    /// * `dir` - Recovery directory
+    /// * `document_path` - Document path, empty for an untitled document
+    /// * `text` - Document content
+    /// * `is_modified` - Whether the document has unsaved changes
+    ///
+    /// # Returns
+    /// What the UI should do about the write result
+    pub fn tick(
+        &mut self,
+        now: f64,
+        dir: &Path,
+        document_path: &str,
+        text: &str,
+        is_modified: bool,
+    ) -> FailureEvent {
+        if !is_modified {
+            self.document.discard();
+            return FailureEvent::None;
+        }
+        if self
+            .document
+            .last_attempt
+            .is_some_and(|last| now - last < AUTOSAVE_INTERVAL_SECS)
+        {
+            return FailureEvent::None;
+        }
+        self.document.last_attempt = Some(now);
+
+        let target = recovery_path(dir, document_path);
+        let result = fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create directory: {e}"))
+            .and_then(|()| write_atomic(&target, text.as_bytes()));
+        match result {
+            Ok(()) => {
+                // The document may have been saved under a new name
+                if let Some(old) = self.document.recovery_file.replace(target.clone())
+                    && old != target
+                {
+                    let _ = fs::remove_file(old);
+                }
+                self.tracker.record_success()
+            }
+            Err(error) => self.tracker.record_failure(WriteFailure { error, target }),
+        }
+    }
+
+    /// Write again on the next tick, e.g. after changing the directory
+    pub const fn retry_now(&mut self) {
+        self.document.last_attempt = None;
+    }
+}
+
+/// Timer for periodic saves of a document to its own file
+#[derive(Debug, Default)]
+pub struct FileAutosave {
+    /// Time the document was first seen modified since the last save
+    modified_since: Option<f64>,
+    /// Status bar notice and the time it was set
+    notice: Option<(f64, String)>,
+}
+
+impl FileAutosave {
+    /// Check whether the document is due to be saved
+    ///
+    /// The interval starts with the first unsaved change, so a document is
+    /// never saved while it is unmodified.
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    /// * `interval_secs` - Seconds between saves
+    /// * `is_modified` - Whether the document has unsaved changes
+    ///
+    /// # Returns
+    /// True if the document should be saved now
+    pub fn is_due(&mut self, now: f64, interval_secs: f64, is_modified: bool) -> bool {
+        if !is_modified {
+            self.modified_since = None;
+            return false;
+        }
+        let since = *self.modified_since.get_or_insert(now);
+        if now - since < interval_secs {
+            return false;
+        }
+        // A failed save is retried after another interval
+        self.modified_since = None;
+        true
+    }
+
+    /// Get the seconds until the next check is due
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    /// * `interval_secs` - Seconds between saves
+    ///
+    /// # Returns
+    /// Seconds until the pending save or the notice expiring, if any
+    pub fn next_wakeup(&self, now: f64, interval_secs: f64) -> Option<f64> {
+        let save = self.modified_since.map(|since| since + interval_secs - now);
+        let notice = self.notice.as_ref().map(|(at, _)| at + NOTICE_SECS - now);
+        match (save, notice) {
+            (Some(save), Some(notice)) => Some(save.min(notice)),
+            (save, notice) => save.or(notice),
+        }
+        .map(|secs| secs.max(0.0))
+    }
+
+    /// Show a notice in the status bar for `NOTICE_SECS`
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    /// * `text` - Notice to show
+    pub fn set_notice(&mut self, now: f64, text: String) {
+        self.notice = Some((now, text));
+    }
+
+    /// Get the status bar notice
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    ///
+    /// # Returns
+    /// The notice, or None once it expired
+    pub fn notice(&self, now: f64) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(at, _)| now - at < NOTICE_SECS)
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+/// Format a time of day as hours and minutes
+///
+/// # Arguments
+/// * `time` - Time in local time
+///
+/// # Returns
+/// The time as "HH:MM"
+#[must_use]
+pub fn clock_time(time: chrono::NaiveDateTime) -> String {
+    time.format("%H:%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> WriteFailure {
+        WriteFailure {
+            error: "No space left on device".to_string(),
+            target: PathBuf::from("/recovery/notes.txt.recovery"),
+        }
+    }
+
+    #[test]
+    fn test_failure_tracker_warns_then_escalates_once() {
+        let mut tracker = FailureTracker::default();
+        assert_eq!(tracker.record_failure(failure()), FailureEvent::Warn);
+        assert_eq!(tracker.last_failure(), Some(&failure()));
+        for _ in 1..ESCALATE_AFTER_FAILURES - 1 {
+            assert_eq!(tracker.record_failure(failure()), FailureEvent::None);
+        }
+        assert_eq!(tracker.record_failure(failure()), FailureEvent::Escalate);
+        assert_eq!(tracker.record_failure(failure()), FailureEvent::None);
+        assert_eq!(tracker.consecutive_failures(), ESCALATE_AFTER_FAILURES + 1);
+    }
+
+    #[test]
+    fn test_failure_tracker_clears_on_success() {
+        let mut tracker = FailureTracker::default();
+        assert_eq!(tracker.record_success(), FailureEvent::None);
+        for _ in 0..ESCALATE_AFTER_FAILURES {
+            tracker.record_failure(failure());
+        }
+        assert_eq!(tracker.record_success(), FailureEvent::Recovered);
+        assert_eq!(tracker.last_failure(), None);
+        // A new run of failures warns and escalates again
+        assert_eq!(tracker.record_failure(failure()), FailureEvent::Warn);
+    }
+
+    #[test]
+    fn test_file_autosave_waits_for_interval() {
+        let mut autosave = FileAutosave::default();
+        assert!(!autosave.is_due(0.0, 60.0, false));
+        assert_eq!(autosave.next_wakeup(0.0, 60.0), None);
+        // The interval starts with the first change
+        assert!(!autosave.is_due(10.0, 60.0, true));
+        assert!(!autosave.is_due(69.0, 60.0, true));
+        assert_eq!(autosave.next_wakeup(69.0, 60.0), Some(1.0));
+        assert!(autosave.is_due(70.0, 60.0, true));
+        // Saving again needs a full interval
+        assert!(!autosave.is_due(71.0, 60.0, true));
+        // Saving by hand resets the timer
+        assert!(!autosave.is_due(72.0, 60.0, false));
+        assert!(!autosave.is_due(131.0, 60.0, true));
+    }
+
+    #[test]
+    fn test_file_autosave_notice_expires() {
+        let mut autosave = FileAutosave::default();
+        autosave.set_notice(100.0, "Autosaved 12:30".to_string());
+        assert_eq!(autosave.notice(101.0), Some("Autosaved 12:30"));
+        assert_eq!(autosave.next_wakeup(101.0, 60.0), Some(NOTICE_SECS - 1.0));
+        assert_eq!(autosave.notice(100.0 + NOTICE_SECS), None);
+    }
+
+    #[test]
+    fn test_clock_time() {
+        let time = |secs| {
+            chrono::DateTime::from_timestamp(secs, 0)
+                .expect("Valid timestamp")
+                .naive_utc()
+        };
+        assert_eq!(clock_time(time(0)), "00:00");
+        assert_eq!(
+            clock_time(time(86400 * 3 + 13 * 3600 + 5 * 60 + 59)),
+            "13:05"
+        );
+    }
+
+    #[test]
+    fn test_recovery_path_is_unique_per_document() {
+        let dir = Path::new("/recovery");
+        let a = recovery_path(dir, "/home/a/notes.txt");
+        let b = recovery_path(dir, "/home/b/notes.txt");
+        assert_ne!(a, b);
+        assert!(a.to_string_lossy().contains("notes.txt."));
+        assert!(
+            recovery_path(dir, "")
+                .to_string_lossy()
+                .contains("Untitled.")
+        );
+    }
+
+    #[test]
+    fn test_tick_writes_and_removes_recovery_file() {
+        let dir = std::env::temp_dir().join("test_Nodepat_autosave");
+        let mut autosave = Autosave::default();
+        assert_eq!(
+            autosave.tick(0.0, &dir, "notes.txt", "draft", true),
+            FailureEvent::None
+        );
+        let file = recovery_path(&dir, "notes.txt");
+        assert_eq!(
+            fs::read_to_string(&file).expect("Missing recovery file"),
+            "draft"
+        );
+        autosave.tick(1.0, &dir, "notes.txt", "draft", false);
+        assert!(!file.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_tick_reports_failures() {
+        // A file where the directory should be makes every write fail
+        let blocker = std::env::temp_dir().join("test_Nodepat_autosave_blocker");
+        fs::write(&blocker, b"").expect("Failed to create blocker");
+        let mut autosave = Autosave::default();
+        assert_eq!(
+            autosave.tick(0.0, &blocker, "", "draft", true),
+            FailureEvent::Warn
+        );
+        // Throttled until the interval has passed
+        assert_eq!(
+            autosave.tick(1.0, &blocker, "", "draft", true),
+            FailureEvent::None
+        );
+        assert_eq!(autosave.tracker.consecutive_failures(), 1);
+        assert!(validate_recovery_dir(&blocker).is_err());
+        let _ = fs::remove_file(&blocker);
+    }
+}
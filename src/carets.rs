@@ -0,0 +1,139 @@
+//! Editing at several carets
+//!
+//! This module applies one keystroke at many carets at once. Carets are
+//! character indices; each edit is made against the original text, so
+//! insertions before a caret shift it right and carets whose deleted
+//! ranges meet end up at the same place.
+
+use crate::editor::char_to_byte;
+
+/// Add a caret, or remove it if it is already there
+///
+/// # Arguments
+/// * `carets` - Carets as sorted character indices
+/// * `caret` - Character index of the caret
+pub fn toggle_caret(carets: &mut Vec<usize>, caret: usize) {
+    match carets.binary_search(&caret) {
+        Ok(index) => {
+            carets.remove(index);
+        }
+        Err(index) => carets.insert(index, caret),
+    }
+}
+
+/// Make the same edit at every caret
+///
+/// Each caret removes up to `remove_before` characters before it and
+/// `remove_after` characters after it, then inserts `inserted`. Ranges
+/// are clipped to the text and never overlap the previous caret's range.
+///
+/// # Arguments
+/// * `text` - Text to edit
+/// * `carets` - Carets as sorted, distinct character indices
+/// * `remove_before` - Characters to remove before each caret, e.g. 1 for
+///   Backspace
+/// * `remove_after` - Characters to remove after each caret, e.g. 1 for
+///   Delete
+/// * `inserted` - Text to insert at each caret
+///
+/// # Returns
+/// Tuple of (edited text, new position of each caret in input order);
+/// carets can share a position afterwards
+#[must_use]
+pub fn edit_at_carets(
+    text: &str,
+    carets: &[usize],
+    remove_before: usize,
+    remove_after: usize,
+    inserted: &str,
+) -> (String, Vec<usize>) {
+    let total = text.chars().count();
+    let inserted_chars = inserted.chars().count();
+    let mut edited = String::with_capacity(text.len() + carets.len() * inserted.len());
+    let mut moved = Vec::with_capacity(carets.len());
+    // Characters of `text` copied so far, and the characters written
+    let mut copied = 0;
+    let mut written = 0;
+    for &caret in carets {
+        let caret = caret.min(total);
+        let start = caret.saturating_sub(remove_before).max(copied);
+        let end = (caret + remove_after).min(total).max(start);
+        edited.push_str(&text[char_to_byte(text, copied)..char_to_byte(text, start)]);
+        edited.push_str(inserted);
+        written += start - copied + inserted_chars;
+        moved.push(written);
+        copied = end;
+    }
+    edited.push_str(&text[char_to_byte(text, copied)..]);
+    (edited, moved)
+}
+
+/// Move carets left or right by one character
+///
+/// # Arguments
+/// * `carets` - Carets as sorted character indices
+/// * `text` - Text the carets are in
+/// * `right` - Move right instead of left
+pub fn move_carets(carets: &mut Vec<usize>, text: &str, right: bool) {
+    let total = text.chars().count();
+    for caret in carets.iter_mut() {
+        *caret = if right {
+            (*caret + 1).min(total)
+        } else {
+            caret.saturating_sub(1)
+        };
+    }
+    carets.dedup();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_shifts_later_carets() {
+        let (text, carets) = edit_at_carets("aé\nbé\ncé", &[1, 4, 7], 0, 0, "→x");
+        assert_eq!(text, "a→xé\nb→xé\nc→xé");
+        assert_eq!(carets, vec![3, 8, 13]);
+    }
+
+    #[test]
+    fn test_backspace_merges_adjacent_carets() {
+        let (text, carets) = edit_at_carets("abcdé", &[0, 3, 4, 5], 1, 0, "");
+        assert_eq!(text, "ab");
+        assert_eq!(carets, vec![0, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_delete_clips_to_text_end() {
+        let (text, carets) = edit_at_carets("héllo", &[1, 2, 5], 0, 1, "");
+        assert_eq!(text, "hlo");
+        assert_eq!(carets, vec![1, 1, 3]);
+        let (text, carets) = edit_at_carets("ab", &[1, 7], 0, 0, "!");
+        assert_eq!(text, "a!b!");
+        assert_eq!(carets, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_replace_before_carets() {
+        // Backspace and type in one step, as the edits would overlap
+        let (text, carets) = edit_at_carets("ab", &[1, 2], 1, 0, "é");
+        assert_eq!(text, "éé");
+        assert_eq!(carets, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_toggle_and_move_carets() {
+        let mut carets = vec![2, 6];
+        toggle_caret(&mut carets, 4);
+        assert_eq!(carets, vec![2, 4, 6]);
+        toggle_caret(&mut carets, 2);
+        assert_eq!(carets, vec![4, 6]);
+        move_carets(&mut carets, "abcdef", true);
+        assert_eq!(carets, vec![5, 6]);
+        move_carets(&mut carets, "abcdef", true);
+        assert_eq!(carets, vec![6]);
+        move_carets(&mut carets, "abcdef", false);
+        assert_eq!(carets, vec![5]);
+    }
+}
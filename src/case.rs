@@ -0,0 +1,109 @@
+//! Case conversion (Edit > Convert Case)
+//!
+//! This module converts text to upper, lower, and title case. The
+//! mappings are Unicode-aware, so a letter may map to several letters,
+//! e.g. 'ß' becomes "SS".
+
+/// Case a text is converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseConversion {
+    /// UPPERCASE
+    Upper,
+    /// lowercase
+    Lower,
+    /// Title Case, capitalizing the first letter of each word
+    Title,
+}
+
+impl CaseConversion {
+    /// Get display name for the conversion
+    ///
+    /// # Returns
+    /// Menu label of the conversion
+    #[must_use]
+    pub const fn display_name(self) -> &'static str {
+        match self {
+            Self::Upper => "Uppercase",
+            Self::Lower => "Lowercase",
+            Self::Title => "Title Case",
+        }
+    }
+
+    /// Get all conversions
+    ///
+    /// # Returns
+    /// Conversions in menu order
+    #[must_use]
+    pub const fn all() -> [Self; 3] {
+        [Self::Upper, Self::Lower, Self::Title]
+    }
+}
+
+/// Convert the case of a text
+///
+/// # Arguments
+/// * `text` - Text to convert
+/// * `conversion` - Case to convert to
+///
+/// # Returns
+/// The converted text
+#[must_use]
+pub fn convert_case(text: &str, conversion: CaseConversion) -> String {
+    match conversion {
+        CaseConversion::Upper => text.chars().flat_map(char::to_uppercase).collect(),
+        CaseConversion::Lower => text.chars().flat_map(char::to_lowercase).collect(),
+        CaseConversion::Title => title_case(text),
+    }
+}
+
+/// Capitalize the first letter of each whitespace-delimited word
+///
+/// The other letters of a word are lowercased.
+///
+/// # Arguments
+/// * `text` - Text to convert
+///
+/// # Returns
+/// The text in title case
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = true;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            result.push(c);
+            word_start = true;
+        } else if word_start {
+            result.extend(c.to_uppercase());
+            word_start = false;
+        } else {
+            result.extend(c.to_lowercase());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_case() {
+        let text = "hello Wörld";
+        assert_eq!(convert_case(text, CaseConversion::Upper), "HELLO WÖRLD");
+        assert_eq!(convert_case(text, CaseConversion::Lower), "hello wörld");
+        assert_eq!(convert_case(text, CaseConversion::Title), "Hello Wörld");
+    }
+
+    #[test]
+    fn test_convert_case_changes_length() {
+        assert_eq!(convert_case("straße", CaseConversion::Upper), "STRASSE");
+        assert_eq!(
+            convert_case("İstanbul", CaseConversion::Lower),
+            "i\u{307}stanbul"
+        );
+        assert_eq!(
+            convert_case("ßig mIXED\tcase", CaseConversion::Title),
+            "SSig Mixed\tCase"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Command-line arguments
+//!
+//! This module parses the arguments Nodepat is started with: an optional
+//! file to open and an optional line to jump to (`+LINE` or `--line LINE`).
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Arguments given on the command line
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StartupArgs {
+    /// File to open
+    pub file: Option<PathBuf>,
+    /// Line to place the cursor on (1-indexed)
+    pub line: Option<usize>,
+}
+
+impl StartupArgs {
+    /// Parse command-line arguments
+    ///
+    /// The first argument that is not a line flag is the file. Invalid
+    /// line values are ignored, and the line is dropped when no file is given.
+    ///
+    /// # Arguments
+    /// * `args` - Arguments without the program name
+    ///
+    /// # Returns
+    /// The parsed startup arguments
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let text = arg.to_string_lossy();
+            let line = if text == "--line" {
+                args.next()
+                    .map(|value| value.to_string_lossy().into_owned())
+            } else if let Some(value) = text.strip_prefix("--line=") {
+                Some(value.to_string())
+            } else if let Some(value) = text.strip_prefix('+') {
+                Some(value.to_string())
+            } else {
+                if parsed.file.is_none() {
+                    parsed.file = Some(PathBuf::from(arg));
+                }
+                continue;
+            };
+            if let Some(line) = line.and_then(|value| value.trim().parse::<usize>().ok()) {
+                parsed.line = Some(line.max(1));
+            }
+        }
+        if parsed.file.is_none() {
+            parsed.line = None;
+        }
+        parsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> StartupArgs {
+        StartupArgs::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn test_parse_file_and_line() {
+        let expected = StartupArgs {
+            file: Some(PathBuf::from("file.log")),
+            line: Some(3421),
+        };
+        assert_eq!(parse(&["file.log", "+3421"]), expected);
+        assert_eq!(parse(&["+3421", "file.log"]), expected);
+        assert_eq!(parse(&["file.log", "--line", "3421"]), expected);
+        assert_eq!(parse(&["--line=3421", "file.log"]), expected);
+    }
+
+    #[test]
+    fn test_parse_ignores_invalid_or_orphan_line() {
+        assert_eq!(parse(&["+3421"]), StartupArgs::default());
+        assert_eq!(parse(&["file.log", "+abc"]).line, None);
+        assert_eq!(parse(&["file.log", "--line"]).line, None);
+        assert_eq!(parse(&["file.log", "+0"]).line, Some(1));
+        assert_eq!(parse(&[]), StartupArgs::default());
+    }
+}
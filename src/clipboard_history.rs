@@ -0,0 +1,150 @@
+//! Clipboard history
+//!
+//! This module keeps the texts recently cut or copied in Nodepat, newest
+//! first, for Edit > Paste from History. The history is limited both in
+//! entries and in total size, so copying huge selections cannot make it
+//! hold on to much memory.
+
+use std::collections::VecDeque;
+
+/// Most entries kept
+pub const MAX_HISTORY_ENTRIES: usize = 20;
+/// Most bytes kept across all entries
+pub const MAX_HISTORY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Texts recently cut or copied, newest first
+#[derive(Debug, Default)]
+pub struct ClipboardHistory {
+    /// Entries, newest first
+    entries: VecDeque<String>,
+    /// Total length of the entries in bytes
+    bytes: usize,
+}
+
+impl ClipboardHistory {
+    /// Add a cut or copied text as the newest entry
+    ///
+    /// Empty texts, a repeat of the newest entry, and texts larger than
+    /// `MAX_HISTORY_BYTES` are not added. The oldest entries are dropped
+    /// to stay within the limits.
+    ///
+    /// # Arguments
+    /// * `text` - Cut or copied text
+    pub fn push(&mut self, text: String) {
+        if text.is_empty() || text.len() > MAX_HISTORY_BYTES || self.entries.front() == Some(&text)
+        {
+            return;
+        }
+        self.bytes += text.len();
+        self.entries.push_front(text);
+        while self.entries.len() > MAX_HISTORY_ENTRIES || self.bytes > MAX_HISTORY_BYTES {
+            let Some(oldest) = self.entries.pop_back() else {
+                break;
+            };
+            self.bytes -= oldest.len();
+        }
+    }
+
+    /// Get an entry
+    ///
+    /// # Arguments
+    /// * `index` - Position in the history, 0 for the newest entry
+    ///
+    /// # Returns
+    /// The entry's text, if there is one at `index`
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Iterate over the entries, newest first
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+
+    /// Check whether nothing was cut or copied yet
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Get a one-line preview of a history entry
+///
+/// Line breaks and tabs are shown as symbols and long texts are cut off
+/// with an ellipsis.
+///
+/// # Arguments
+/// * `text` - Entry text
+/// * `max_chars` - Most characters to show before the ellipsis
+///
+/// # Returns
+/// The preview
+#[must_use]
+pub fn preview(text: &str, max_chars: usize) -> String {
+    let mut shown: String = text
+        .chars()
+        .take(max_chars)
+        .map(|c| match c {
+            '\n' => '⏎',
+            '\t' => '→',
+            '\r' => ' ',
+            c => c,
+        })
+        .collect();
+    if text.chars().nth(max_chars).is_some() {
+        shown.push('…');
+    }
+    shown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_keeps_newest_first_and_skips_repeats() {
+        let mut history = ClipboardHistory::default();
+        history.push("one".to_string());
+        history.push("two".to_string());
+        history.push("two".to_string());
+        history.push(String::new());
+        history.push("one".to_string());
+        assert_eq!(history.iter().collect::<Vec<_>>(), ["one", "two", "one"]);
+        assert_eq!(history.get(1), Some("two"));
+        assert_eq!(history.get(3), None);
+    }
+
+    #[test]
+    fn test_push_drops_oldest_entries() {
+        let mut history = ClipboardHistory::default();
+        for i in 0..=MAX_HISTORY_ENTRIES {
+            history.push(i.to_string());
+        }
+        assert_eq!(history.iter().count(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.get(0), Some("20"));
+        assert_eq!(history.iter().last(), Some("1"));
+    }
+
+    #[test]
+    fn test_push_stays_within_total_size() {
+        let mut history = ClipboardHistory::default();
+        let half = "x".repeat(MAX_HISTORY_BYTES / 2);
+        history.push(format!("{half}a"));
+        history.push(format!("{half}b"));
+        assert_eq!(history.iter().count(), 1);
+        assert_eq!(history.get(0).map(str::len), Some(half.len() + 1));
+
+        history.push("y".repeat(MAX_HISTORY_BYTES + 1));
+        assert_eq!(history.iter().count(), 1);
+        history.push("small".to_string());
+        assert_eq!(history.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("a\tb\r\nc", 10), "a→b ⏎c");
+        assert_eq!(preview("héllo world", 5), "héllo…");
+        assert_eq!(preview("héllo", 5), "héllo");
+    }
+}
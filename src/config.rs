@@ -3,44 +3,167 @@
 //! This module handles loading and saving configuration from config.jsonc
 //! including recent files, font settings, and window preferences.
 
+use crate::file_ops::{ENCODINGS, Encoding, encoding_by_name};
 use crate::format::{FontFamily, FontStyle, FormatSettings};
+use crate::theme::ThemeSettings;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Where the user last was in a file
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FilePosition {
+    /// Character index of the cursor
+    pub cursor: usize,
+    /// Vertical scroll offset of the editor, in points
+    pub scroll_y: f32,
+}
 
 /// Configuration structure
-#[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)] // Independent on/off preferences
+#[derive(Debug, Clone)]
 pub struct Config {
     /// Recent files list
     pub recent_files: Vec<String>,
+    /// Number of recent files kept; 0 turns the list off
+    pub max_recent_files: usize,
+    /// List hidden files in the file browser
+    pub show_hidden_files: bool,
+    /// Show the zoom in the status bar even at 100%
+    pub always_show_zoom: bool,
+    /// Timestamp format inserted by Time/Date
+    pub datetime_format: String,
+    /// Name of the last-used file type filter in the file browser
+    pub file_filter: String,
+    /// Folder the Open dialog was last used in
+    pub last_open_dir: String,
+    /// Folder the Save dialog was last used in
+    pub last_save_dir: String,
     /// Font family (kept for backward compatibility)
     pub font_family: String,
-    /// Font family type (Monospace or Proportional)
+    /// Font family type (Monospace, Proportional or Custom)
     pub font_family_type: FontFamily,
+    /// Path of the custom font file (.ttf or .otf); empty for none
+    pub font_file: String,
     /// Font style (Regular, Bold, Italic, `BoldItalic`)
     pub font_style: FontStyle,
     /// Font size
     pub font_size: f32,
     /// Status bar visible
     pub show_status_bar: bool,
+    /// Wrap long lines to the window width
+    pub word_wrap: bool,
+    /// Width of a tab in spaces
+    pub tab_width: u8,
+    /// Tab inserts spaces instead of a tab character
+    pub indent_with_spaces: bool,
+    /// Typing a bracket or quote also inserts its closing partner
+    pub auto_close_brackets: bool,
+    /// Show line numbers next to the editor
+    pub show_line_numbers: bool,
+    /// Tint the line the cursor is on
+    pub highlight_current_line: bool,
+    /// Color keywords, strings, comments, and numbers by file type
+    pub syntax_highlighting: bool,
+    /// Underline misspelled words in the editor
+    pub spell_check: bool,
+    /// Show the rendered preview next to Markdown documents
+    pub markdown_preview: bool,
+    /// Fraction of the window width taken by the Markdown preview
+    pub preview_split: f32,
+    /// Search option: match case
+    pub match_case: bool,
+    /// Search option: continue from the other end of the document
+    pub wrap_around: bool,
+    /// Search option: treat the search text as a regular expression
+    pub use_regex: bool,
+    /// Search option: only match whole words
+    pub whole_word: bool,
+    /// Replace option: give replacements the casing of the matches
+    pub preserve_case: bool,
+    /// Search option: interpret \n, \t and \\ in find and replacement text
+    pub extended_search: bool,
+    /// Ctrl+F opens the incremental search bar instead of the Find dialog
+    pub incremental_find: bool,
     /// Dark mode enabled
     pub dark_mode: bool,
+    /// Editor colors: a preset and colors replacing the palette's
+    pub theme: ThemeSettings,
     /// Window width
     pub window_width: f32,
     /// Window height
     pub window_height: f32,
+    /// Window left edge on the desktop, if known
+    pub window_x: Option<f32>,
+    /// Window top edge on the desktop, if known
+    pub window_y: Option<f32>,
+    /// Name of the monitor the window was last on
+    pub window_monitor: String,
+    /// Global zoom factor, used for files without a remembered zoom
+    pub zoom_factor: f32,
+    /// Remembered zoom factor per file path, most recently used first
+    pub file_zoom: Vec<(String, f32)>,
+    /// Remembered cursor and scroll position per file path, most recently
+    /// used first
+    pub file_positions: Vec<(String, FilePosition)>,
+    /// Seconds the window must be minimized or unfocused before trimming
+    /// memory, at least 1
+    pub trim_idle_secs: u64,
+    /// Undo steps that memory trimming never drops
+    pub undo_min_steps: usize,
+    /// Whether unsaved changes are written to recovery files
+    pub recovery_enabled: bool,
+    /// Whether modified documents with a file path are saved periodically
+    pub autosave_enabled: bool,
+    /// Seconds between periodic saves
+    pub autosave_interval_secs: u64,
+    /// Whether saving over a file keeps its old content in a .bak file
+    pub create_backup: bool,
+    /// Remove trailing spaces and tabs from each line when saving
+    pub trim_trailing_on_save: bool,
+    /// Encoding of new documents (one of `ENCODINGS`)
+    pub default_encoding: String,
+    /// Encoding of opened files that are not Unicode (a legacy entry of
+    /// `ENCODINGS`)
+    pub legacy_encoding: String,
+    /// Directory for recovery files; empty uses the default
+    pub recovery_dir: String,
+    /// Comments above the opening brace of the file, kept when saving
+    pub header_comment: String,
 }
 
+/// Maximum number of remembered per-file zoom factors
+const MAX_FILE_ZOOM: usize = 50;
+/// Maximum number of remembered per-file positions
+const MAX_FILE_POSITIONS: usize = 50;
+/// Largest allowed `max_recent_files`
+pub const MAX_RECENT_FILES_LIMIT: usize = 50;
+/// Largest allowed `tab_width`
+pub const MAX_TAB_WIDTH: u8 = 16;
+/// Smallest and largest fraction of the window the Markdown preview takes
+pub const PREVIEW_SPLIT_RANGE: (f32, f32) = (0.2, 0.8);
+/// Smallest window size, in points
+pub const MIN_WINDOW_SIZE: [f32; 2] = [400.0, 300.0];
+
 impl Config {
     /// Load configuration from file
     ///
+    /// Recent files and remembered positions of files that no longer exist
+    /// are dropped, and the cleaned lists are saved.
+    ///
     /// # Returns
     /// Config struct with loaded values or defaults
     #[must_use]
     pub fn load() -> Self {
         let config_path = Self::config_path();
         if let Ok(content) = fs::read_to_string(&config_path)
-            && let Ok(config) = Self::parse_json(&content)
+            && let Ok(mut config) = Self::parse_json(&content)
         {
+            let exists = |path: &str| Path::new(path).exists();
+            let pruned_recent = config.prune_recent_files(exists);
+            let pruned_positions = config.prune_file_positions(exists);
+            if pruned_recent || pruned_positions {
+                let _ = config.save();
+            }
             return config;
         }
         Self::create_default()
@@ -55,6 +178,8 @@ impl Config {
     /// Config struct or error
     fn parse_json(json: &str) -> Result<Self, String> {
         let mut config = Self::create_default();
+        config.header_comment = Self::header_comment(json);
+        let json = Self::strip_comments(json);
         let json = json.trim();
 
         // Remove outer braces
@@ -63,50 +188,361 @@ impl Config {
             .and_then(|s| s.strip_suffix('}'))
             .ok_or_else(|| "Invalid JSON: missing braces".to_string())?;
 
+        // Configs written before recovery files had their own switch used
+        // `autosave_enabled` for them
+        let mut legacy_autosave = None;
+        let mut has_recovery_enabled = false;
+
         // Parse each field
         for part in Self::split_json_fields(json) {
             let (key, value) = Self::parse_field(part)?;
             match key {
-                "recent_files" => {
-                    config.recent_files = Self::parse_string_array(value)?;
+                "recovery_enabled" => {
+                    config.recovery_enabled = Self::parse_bool(value)?;
+                    has_recovery_enabled = true;
                 }
-                "font_family" => {
-                    config.font_family = Self::parse_string(value)?;
+                "autosave_enabled" => {
+                    legacy_autosave = Some(Self::parse_bool(value)?);
                 }
-                "font_family_type" => {
-                    config.font_family_type = Self::parse_font_family(value)?;
+                _ => config.parse_setting(key, value)?,
+            }
+        }
+
+        config.recent_files.truncate(config.max_recent_files);
+
+        if let Some(enabled) = legacy_autosave {
+            if has_recovery_enabled {
+                config.autosave_enabled = enabled;
+            } else {
+                config.recovery_enabled = enabled;
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Apply a configuration field to the config
+    ///
+    /// # Arguments
+    /// * `key` - Field name
+    /// * `value` - JSON value of the field
+    ///
+    /// # Returns
+    /// Result indicating success or error; unknown fields are ignored
+    fn parse_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "recent_files" => {
+                self.recent_files = Self::parse_string_array(value)?;
+            }
+            "always_show_zoom" => {
+                self.always_show_zoom = Self::parse_bool(value)?;
+            }
+            "datetime_format" => {
+                self.datetime_format = Self::parse_string(value)?;
+            }
+            "max_recent_files" => {
+                if let Ok(max) = value.trim().parse::<usize>() {
+                    self.max_recent_files = max.min(MAX_RECENT_FILES_LIMIT);
                 }
-                "font_style" => {
-                    config.font_style = Self::parse_font_style(value)?;
+            }
+            "font_family" => {
+                self.font_family = Self::parse_string(value)?;
+            }
+            "font_family_type" => {
+                self.font_family_type = Self::parse_font_family(value)?;
+            }
+            "font_file" => {
+                self.font_file = Self::parse_string(value)?;
+            }
+            "font_style" => {
+                self.font_style = Self::parse_font_style(value)?;
+            }
+            "font_size" => {
+                if let Ok(size) = value.trim().parse::<f32>() {
+                    self.font_size = size;
                 }
-                "font_size" => {
-                    if let Ok(size) = value.trim().parse::<f32>() {
-                        config.font_size = size;
-                    }
+            }
+            "show_status_bar" => {
+                self.show_status_bar = Self::parse_bool(value)?;
+            }
+            "word_wrap" => {
+                self.word_wrap = Self::parse_bool(value)?;
+            }
+            "show_line_numbers" => {
+                self.show_line_numbers = Self::parse_bool(value)?;
+            }
+            "highlight_current_line" => {
+                self.highlight_current_line = Self::parse_bool(value)?;
+            }
+            "syntax_highlighting" => {
+                self.syntax_highlighting = Self::parse_bool(value)?;
+            }
+            "dark_mode" => {
+                self.dark_mode = Self::parse_bool(value)?;
+            }
+            "theme" => {
+                self.theme = Self::parse_theme(value)?;
+            }
+            "zoom_factor" => {
+                if let Ok(zoom) = value.trim().parse::<f32>() {
+                    self.zoom_factor = zoom;
+                }
+            }
+            "file_zoom" => {
+                self.file_zoom = Self::parse_number_map(value)?;
+            }
+            "file_positions" => {
+                self.file_positions = Self::parse_position_map(value)?;
+            }
+            "trim_idle_secs" => {
+                if let Ok(secs) = value.trim().parse::<u64>() {
+                    // With 0 the window would count as idle while in use
+                    self.trim_idle_secs = secs.max(1);
                 }
-                "show_status_bar" => {
-                    config.show_status_bar = Self::parse_bool(value)?;
+            }
+            "autosave_interval_secs" => {
+                if let Ok(secs) = value.trim().parse::<u64>() {
+                    self.autosave_interval_secs = secs.max(1);
                 }
-                "dark_mode" => {
-                    config.dark_mode = Self::parse_bool(value)?;
+            }
+            "create_backup" => {
+                self.create_backup = Self::parse_bool(value)?;
+            }
+            "recovery_dir" => {
+                self.recovery_dir = Self::parse_string(value)?;
+            }
+            "undo_min_steps" => {
+                if let Ok(steps) = value.trim().parse::<usize>() {
+                    self.undo_min_steps = steps;
                 }
-                "window_width" => {
-                    if let Ok(width) = value.trim().parse::<f32>() {
-                        config.window_width = width;
-                    }
+            }
+            _ => self.parse_window_setting(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Apply a window placement field to the config
+    ///
+    /// # Arguments
+    /// * `key` - Field name
+    /// * `value` - JSON value of the field
+    ///
+    /// # Returns
+    /// Result indicating success or error; unknown fields are ignored
+    fn parse_window_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "window_width" => {
+                if let Ok(width) = value.trim().parse::<f32>() {
+                    self.window_width = width;
+                }
+            }
+            "window_height" => {
+                if let Ok(height) = value.trim().parse::<f32>() {
+                    self.window_height = height;
+                }
+            }
+            "window_x" => {
+                self.window_x = value.trim().parse::<f32>().ok();
+            }
+            "window_y" => {
+                self.window_y = value.trim().parse::<f32>().ok();
+            }
+            "window_monitor" => {
+                self.window_monitor = Self::parse_string(value)?;
+            }
+            _ => self.parse_browser_setting(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Apply a file browser field to the config
+    ///
+    /// # Arguments
+    /// * `key` - Field name
+    /// * `value` - JSON value of the field
+    ///
+    /// # Returns
+    /// Result indicating success or error; unknown fields are ignored
+    fn parse_browser_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "show_hidden_files" => {
+                self.show_hidden_files = Self::parse_bool(value)?;
+            }
+            "file_filter" => {
+                self.file_filter = Self::parse_string(value)?;
+            }
+            "last_open_dir" => {
+                self.last_open_dir = Self::parse_string(value)?;
+            }
+            "last_save_dir" => {
+                self.last_save_dir = Self::parse_string(value)?;
+            }
+            _ => self.parse_editing_setting(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Apply an indentation or whitespace field to the config
+    ///
+    /// # Arguments
+    /// * `key` - Field name
+    /// * `value` - JSON value of the field
+    ///
+    /// # Returns
+    /// Result indicating success or error; unknown fields are ignored
+    fn parse_editing_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "tab_width" => {
+                if let Ok(width) = value.trim().parse::<u8>() {
+                    self.tab_width = width.clamp(1, MAX_TAB_WIDTH);
+                }
+            }
+            "indent_with_spaces" => {
+                self.indent_with_spaces = Self::parse_bool(value)?;
+            }
+            "auto_close_brackets" => {
+                self.auto_close_brackets = Self::parse_bool(value)?;
+            }
+            "trim_trailing_on_save" => {
+                self.trim_trailing_on_save = Self::parse_bool(value)?;
+            }
+            "default_encoding" => {
+                let encoding = Self::parse_string(value)?;
+                if let Some(encoding) = encoding_by_name(&encoding) {
+                    self.default_encoding = encoding.to_string();
+                }
+            }
+            "legacy_encoding" => {
+                let encoding = Self::parse_string(value)?;
+                if let Some(encoding) = Encoding::from_name(&encoding)
+                    && !encoding.is_unicode()
+                {
+                    self.legacy_encoding = encoding.name().to_string();
+                }
+            }
+            "spell_check" => {
+                self.spell_check = Self::parse_bool(value)?;
+            }
+            "markdown_preview" => {
+                self.markdown_preview = Self::parse_bool(value)?;
+            }
+            "preview_split" => {
+                if let Ok(split) = value.trim().parse::<f32>()
+                    && split.is_finite()
+                {
+                    self.preview_split = split.clamp(PREVIEW_SPLIT_RANGE.0, PREVIEW_SPLIT_RANGE.1);
+                }
+            }
+            _ => self.parse_search_setting(key, value)?,
+        }
+        Ok(())
+    }
+
+    /// Apply a search option field to the config
+    ///
+    /// # Arguments
+    /// * `key` - Field name
+    /// * `value` - JSON value of the field
+    ///
+    /// # Returns
+    /// Result indicating success or error; unknown fields are ignored
+    fn parse_search_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "match_case" => {
+                self.match_case = Self::parse_bool(value)?;
+            }
+            "wrap_around" => {
+                self.wrap_around = Self::parse_bool(value)?;
+            }
+            "use_regex" => {
+                self.use_regex = Self::parse_bool(value)?;
+            }
+            "whole_word" => {
+                self.whole_word = Self::parse_bool(value)?;
+            }
+            "preserve_case" => {
+                self.preserve_case = Self::parse_bool(value)?;
+            }
+            "extended_search" => {
+                self.extended_search = Self::parse_bool(value)?;
+            }
+            "incremental_find" => {
+                self.incremental_find = Self::parse_bool(value)?;
+            }
+            _ => {
+                // Ignore unknown fields
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove `//` line comments and `/* */` block comments
+    ///
+    /// Comment markers inside strings are kept. Comments are replaced by
+    /// whitespace so fields stay separated.
+    ///
+    /// # Arguments
+    /// * `json` - JSON with comments
+    ///
+    /// # Returns
+    /// JSON without comments
+    fn strip_comments(json: &str) -> String {
+        let mut result = String::with_capacity(json.len());
+        let mut chars = json.chars().peekable();
+        let mut in_string = false;
+        let mut escape_next = false;
+        while let Some(ch) = chars.next() {
+            if in_string {
+                if escape_next {
+                    escape_next = false;
+                } else if ch == '\\' {
+                    escape_next = true;
+                } else if ch == '"' {
+                    in_string = false;
                 }
-                "window_height" => {
-                    if let Ok(height) = value.trim().parse::<f32>() {
-                        config.window_height = height;
+                result.push(ch);
+                continue;
+            }
+            match (ch, chars.peek()) {
+                ('/', Some('/')) => {
+                    // Keep the line break that ends the comment
+                    while chars.next_if(|&c| c != '\n').is_some() {}
+                }
+                ('/', Some('*')) => {
+                    chars.next();
+                    let mut previous = ' ';
+                    for c in chars.by_ref() {
+                        if previous == '*' && c == '/' {
+                            break;
+                        }
+                        previous = c;
                     }
+                    result.push(' ');
                 }
                 _ => {
-                    // Ignore unknown fields
+                    in_string = ch == '"';
+                    result.push(ch);
                 }
             }
         }
+        result
+    }
 
-        Ok(config)
+    /// Get the comments above the opening brace
+    ///
+    /// # Arguments
+    /// * `json` - Contents of the config file
+    ///
+    /// # Returns
+    /// The comment lines, each ending with a line break, or an empty string
+    fn header_comment(json: &str) -> String {
+        let header = json.find('{').map_or("", |brace| &json[..brace]);
+        let header = header.trim();
+        if header.is_empty() {
+            String::new()
+        } else {
+            format!("{header}\n")
+        }
     }
 
     /// Split JSON fields, handling nested structures
@@ -149,9 +585,9 @@ impl Config {
                 _ => {}
             }
         }
-        if start < json.len() {
-            fields.push(&json[start..]);
-        }
+        fields.push(&json[start..]);
+        // Tolerate trailing commas
+        fields.retain(|field| !field.trim().is_empty());
         fields
     }
 
@@ -164,8 +600,11 @@ impl Config {
     /// Tuple of (key, value) or error
     fn parse_field(field: &str) -> Result<(&str, &str), String> {
         let field = field.trim();
-        let colon_pos = field
+        // Look for the colon after the quoted key, which may contain colons itself
+        let key_end = Self::string_end(field).unwrap_or(0);
+        let colon_pos = field[key_end..]
             .find(':')
+            .map(|pos| key_end + pos)
             .ok_or_else(|| "Invalid JSON field: missing colon".to_string())?;
         let key = field[..colon_pos].trim();
         let value = field[colon_pos + 1..].trim();
@@ -179,6 +618,125 @@ impl Config {
         Ok((key, value))
     }
 
+    /// Find the end of a JSON string at the start of a value
+    ///
+    /// # Arguments
+    /// * `value` - Text starting with a quoted string
+    ///
+    /// # Returns
+    /// Byte offset just past the closing quote, or None if not a string
+    fn string_end(value: &str) -> Option<usize> {
+        let rest = value.strip_prefix('"')?;
+        let mut escape_next = false;
+        for (i, ch) in rest.char_indices() {
+            if escape_next {
+                escape_next = false;
+            } else if ch == '\\' {
+                escape_next = true;
+            } else if ch == '"' {
+                return Some(i + 2);
+            }
+        }
+        None
+    }
+
+    /// Parse JSON object mapping strings to numbers
+    ///
+    /// # Arguments
+    /// * `value` - JSON object value
+    ///
+    /// # Returns
+    /// Key-number pairs in file order or error
+    fn parse_number_map(value: &str) -> Result<Vec<(String, f32)>, String> {
+        let content = value
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or_else(|| "Invalid JSON object: missing braces".to_string())?;
+        let mut map = Vec::new();
+        for field in Self::split_json_fields(content) {
+            if field.trim().is_empty() {
+                continue;
+            }
+            let (key, number) = Self::parse_field(field)?;
+            let key = Self::parse_string(&format!("\"{key}\""))?;
+            if let Ok(number) = number.parse::<f32>() {
+                map.push((key, number));
+            }
+        }
+        Ok(map)
+    }
+
+    /// Parse JSON object mapping file paths to `[cursor, scroll_y]` pairs
+    ///
+    /// # Arguments
+    /// * `value` - JSON object value
+    ///
+    /// # Returns
+    /// Path-position pairs in file order or error; malformed positions are
+    /// skipped
+    fn parse_position_map(value: &str) -> Result<Vec<(String, FilePosition)>, String> {
+        let content = value
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or_else(|| "Invalid JSON object: missing braces".to_string())?;
+        let mut map = Vec::new();
+        for field in Self::split_json_fields(content) {
+            let (key, pair) = Self::parse_field(field)?;
+            let key = Self::parse_string(&format!("\"{key}\""))?;
+            let numbers = pair
+                .trim()
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .and_then(|s| s.split_once(','));
+            if let Some((cursor, scroll_y)) = numbers
+                && let (Ok(cursor), Ok(scroll_y)) = (
+                    cursor.trim().parse::<usize>(),
+                    scroll_y.trim().parse::<f32>(),
+                )
+            {
+                map.push((key, FilePosition { cursor, scroll_y }));
+            }
+        }
+        Ok(map)
+    }
+
+    /// Parse the `theme` object
+    ///
+    /// Colors are kept as written; malformed ones are ignored when the
+    /// theme is applied.
+    ///
+    /// # Arguments
+    /// * `value` - JSON object value
+    ///
+    /// # Returns
+    /// Theme settings or error; unknown fields are ignored
+    fn parse_theme(value: &str) -> Result<ThemeSettings, String> {
+        let content = value
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or_else(|| "Invalid JSON object: missing braces".to_string())?;
+        let mut theme = ThemeSettings::default();
+        for field in Self::split_json_fields(content) {
+            if field.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = Self::parse_field(field)?;
+            let value = Self::parse_string(value)?;
+            match key {
+                "preset" => theme.preset = value,
+                "background" => theme.background = value,
+                "text" => theme.text = value,
+                "selection" => theme.selection = value,
+                "caret" => theme.caret = value,
+                _ => {}
+            }
+        }
+        Ok(theme)
+    }
+
     /// Parse JSON string value
     ///
     /// # Arguments
@@ -275,9 +833,9 @@ impl Config {
                 start = i + 1;
             }
         }
-        if start < array_content.len() {
-            items.push(&array_content[start..]);
-        }
+        items.push(&array_content[start..]);
+        // Tolerate trailing commas
+        items.retain(|item| !item.trim().is_empty());
         items
     }
 
@@ -293,6 +851,7 @@ impl Config {
         match value.to_lowercase().as_str() {
             "monospace" => Ok(FontFamily::Monospace),
             "proportional" => Ok(FontFamily::Proportional),
+            "custom" => Ok(FontFamily::Custom),
             _ => Ok(FontFamily::default()),
         }
     }
@@ -323,14 +882,57 @@ impl Config {
     fn create_default() -> Self {
         Self {
             recent_files: Vec::new(),
+            max_recent_files: 10,
+            show_hidden_files: false,
+            always_show_zoom: false,
+            datetime_format: crate::editor::DEFAULT_DATETIME_FORMAT.to_string(),
+            file_filter: "Text files (*.txt)".to_string(),
+            last_open_dir: String::new(),
+            last_save_dir: String::new(),
             font_family: "Courier New".to_string(),
             font_family_type: FontFamily::Monospace,
+            font_file: String::new(),
             font_style: FontStyle::Regular,
             font_size: 10.0,
             show_status_bar: false,
+            word_wrap: true,
+            tab_width: 4,
+            indent_with_spaces: false,
+            auto_close_brackets: false,
+            show_line_numbers: false,
+            highlight_current_line: true,
+            syntax_highlighting: true,
+            spell_check: false,
+            markdown_preview: false,
+            preview_split: 0.5,
+            match_case: false,
+            wrap_around: true,
+            use_regex: false,
+            whole_word: false,
+            preserve_case: false,
+            extended_search: false,
+            incremental_find: false,
             dark_mode: true,
+            theme: ThemeSettings::default(),
             window_width: 640.0,
             window_height: 480.0,
+            window_x: None,
+            window_y: None,
+            window_monitor: String::new(),
+            zoom_factor: 1.0,
+            file_zoom: Vec::new(),
+            file_positions: Vec::new(),
+            trim_idle_secs: 300,
+            undo_min_steps: 20,
+            recovery_enabled: true,
+            autosave_enabled: false,
+            autosave_interval_secs: 300,
+            create_backup: false,
+            trim_trailing_on_save: false,
+            default_encoding: ENCODINGS[0].to_string(),
+            legacy_encoding: Encoding::Windows1252.name().to_string(),
+            recovery_dir: String::new(),
+            header_comment: String::new(),
         }
     }
 
@@ -356,69 +958,289 @@ impl Config {
     /// JSON string representation
     fn to_json(&self) -> String {
         use std::fmt::Write;
-        let mut json = String::from("{\n");
+        let mut json = self.header_comment.clone();
+        json.push_str("{\n");
         let _ = writeln!(
             json,
             "  \"recent_files\": {},",
             Self::string_array_to_json(&self.recent_files)
         );
+        let _ = writeln!(json, "  \"max_recent_files\": {},", self.max_recent_files);
+        let _ = writeln!(json, "  \"show_hidden_files\": {},", self.show_hidden_files);
+        let _ = writeln!(json, "  \"always_show_zoom\": {},", self.always_show_zoom);
         let _ = writeln!(
             json,
-            "  \"font_family\": {},",
-            Self::string_to_json(&self.font_family)
+            "  \"datetime_format\": {},",
+            Self::string_to_json(&self.datetime_format)
         );
         let _ = writeln!(
             json,
-            "  \"font_family_type\": {},",
-            Self::font_family_to_json(self.font_family_type)
+            "  \"file_filter\": {},",
+            Self::string_to_json(&self.file_filter)
         );
         let _ = writeln!(
             json,
-            "  \"font_style\": {},",
-            Self::font_style_to_json(self.font_style)
+            "  \"last_open_dir\": {},",
+            Self::string_to_json(&self.last_open_dir)
         );
-        let _ = writeln!(json, "  \"font_size\": {},", self.font_size);
-        let _ = writeln!(json, "  \"show_status_bar\": {},", self.show_status_bar);
+        let _ = writeln!(
+            json,
+            "  \"last_save_dir\": {},",
+            Self::string_to_json(&self.last_save_dir)
+        );
+        self.write_font_settings(&mut json);
+        self.write_view_settings(&mut json);
+        let _ = writeln!(json, "  \"match_case\": {},", self.match_case);
+        let _ = writeln!(json, "  \"wrap_around\": {},", self.wrap_around);
+        let _ = writeln!(json, "  \"use_regex\": {},", self.use_regex);
+        let _ = writeln!(json, "  \"whole_word\": {},", self.whole_word);
+        let _ = writeln!(json, "  \"preserve_case\": {},", self.preserve_case);
+        let _ = writeln!(json, "  \"extended_search\": {},", self.extended_search);
+        let _ = writeln!(json, "  \"incremental_find\": {},", self.incremental_find);
         let _ = writeln!(json, "  \"dark_mode\": {},", self.dark_mode);
-        let _ = writeln!(json, "  \"window_width\": {},", self.window_width);
-        let _ = writeln!(json, "  \"window_height\": {}", self.window_height);
+        let _ = writeln!(json, "  \"theme\": {},", self.theme_to_json());
+        self.write_window_settings(&mut json);
+        let _ = writeln!(json, "  \"zoom_factor\": {},", self.zoom_factor);
+        let _ = writeln!(
+            json,
+            "  \"file_zoom\": {},",
+            Self::number_map_to_json(&self.file_zoom)
+        );
+        let _ = writeln!(
+            json,
+            "  \"file_positions\": {},",
+            Self::position_map_to_json(&self.file_positions)
+        );
+        let _ = writeln!(json, "  \"trim_idle_secs\": {},", self.trim_idle_secs);
+        let _ = writeln!(json, "  \"undo_min_steps\": {},", self.undo_min_steps);
+        let _ = writeln!(json, "  \"recovery_enabled\": {},", self.recovery_enabled);
+        let _ = writeln!(json, "  \"autosave_enabled\": {},", self.autosave_enabled);
+        let _ = writeln!(
+            json,
+            "  \"autosave_interval_secs\": {},",
+            self.autosave_interval_secs
+        );
+        let _ = writeln!(json, "  \"create_backup\": {},", self.create_backup);
+        let _ = writeln!(
+            json,
+            "  \"trim_trailing_on_save\": {},",
+            self.trim_trailing_on_save
+        );
+        let _ = writeln!(
+            json,
+            "  \"default_encoding\": {},",
+            Self::string_to_json(&self.default_encoding)
+        );
+        let _ = writeln!(
+            json,
+            "  \"legacy_encoding\": {},",
+            Self::string_to_json(&self.legacy_encoding)
+        );
+        let _ = writeln!(
+            json,
+            "  \"recovery_dir\": {}",
+            Self::string_to_json(&self.recovery_dir)
+        );
         json.push('}');
         json
     }
 
-    /// Convert string to JSON string value
+    /// Write the editor view fields as JSON
     ///
     /// # Arguments
-    /// * `s` - String to convert
-    ///
-    /// # Returns
-    /// JSON string representation
-    fn string_to_json(s: &str) -> String {
-        format!(
-            "\"{}\"",
-            s.replace('\\', "\\\\")
-                .replace('"', "\\\"")
-                .replace('\n', "\\n")
-                .replace('\r', "\\r")
-                .replace('\t', "\\t")
-        )
+    /// * `json` - JSON being written
+    fn write_view_settings(&self, json: &mut String) {
+        use std::fmt::Write;
+        let _ = writeln!(json, "  \"show_status_bar\": {},", self.show_status_bar);
+        let _ = writeln!(json, "  \"word_wrap\": {},", self.word_wrap);
+        let _ = writeln!(json, "  \"tab_width\": {},", self.tab_width);
+        let _ = writeln!(
+            json,
+            "  \"indent_with_spaces\": {},",
+            self.indent_with_spaces
+        );
+        let _ = writeln!(
+            json,
+            "  \"auto_close_brackets\": {},",
+            self.auto_close_brackets
+        );
+        let _ = writeln!(json, "  \"show_line_numbers\": {},", self.show_line_numbers);
+        let _ = writeln!(
+            json,
+            "  \"highlight_current_line\": {},",
+            self.highlight_current_line
+        );
+        let _ = writeln!(
+            json,
+            "  \"syntax_highlighting\": {},",
+            self.syntax_highlighting
+        );
+        let _ = writeln!(json, "  \"spell_check\": {},", self.spell_check);
+        let _ = writeln!(json, "  \"markdown_preview\": {},", self.markdown_preview);
+        let _ = writeln!(json, "  \"preview_split\": {},", self.preview_split);
     }
 
-    /// Convert string array to JSON array
+    /// Write the font fields as JSON
     ///
     /// # Arguments
-    /// * `arr` - Array of strings
-    ///
-    /// # Returns
-    /// JSON array representation
-    fn string_array_to_json(arr: &[String]) -> String {
-        if arr.is_empty() {
-            return "[]".to_string();
-        }
+    /// * `json` - JSON being written
+    fn write_font_settings(&self, json: &mut String) {
+        use std::fmt::Write;
+        let _ = writeln!(
+            json,
+            "  \"font_family\": {},",
+            Self::string_to_json(&self.font_family)
+        );
+        let _ = writeln!(
+            json,
+            "  \"font_family_type\": {},",
+            Self::font_family_to_json(self.font_family_type)
+        );
+        let _ = writeln!(
+            json,
+            "  \"font_file\": {},",
+            Self::string_to_json(&self.font_file)
+        );
+        let _ = writeln!(
+            json,
+            "  \"font_style\": {},",
+            Self::font_style_to_json(self.font_style)
+        );
+        let _ = writeln!(json, "  \"font_size\": {},", self.font_size);
+    }
+
+    /// Write the window placement fields as JSON
+    ///
+    /// # Arguments
+    /// * `json` - JSON being written
+    fn write_window_settings(&self, json: &mut String) {
+        use std::fmt::Write;
+        let _ = writeln!(json, "  \"window_width\": {},", self.window_width);
+        let _ = writeln!(json, "  \"window_height\": {},", self.window_height);
+        let _ = writeln!(
+            json,
+            "  \"window_x\": {},",
+            Self::optional_number_to_json(self.window_x)
+        );
+        let _ = writeln!(
+            json,
+            "  \"window_y\": {},",
+            Self::optional_number_to_json(self.window_y)
+        );
+        let _ = writeln!(
+            json,
+            "  \"window_monitor\": {},",
+            Self::string_to_json(&self.window_monitor)
+        );
+    }
+
+    /// Convert an optional number to a JSON value
+    ///
+    /// # Arguments
+    /// * `value` - Number to convert
+    ///
+    /// # Returns
+    /// The number, or `null` if there is none
+    fn optional_number_to_json(value: Option<f32>) -> String {
+        value.map_or_else(|| "null".to_string(), |value| value.to_string())
+    }
+
+    /// Convert string to JSON string value
+    ///
+    /// # Arguments
+    /// * `s` - String to convert
+    ///
+    /// # Returns
+    /// JSON string representation
+    fn string_to_json(s: &str) -> String {
+        format!(
+            "\"{}\"",
+            s.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r")
+                .replace('\t', "\\t")
+        )
+    }
+
+    /// Convert string array to JSON array
+    ///
+    /// # Arguments
+    /// * `arr` - Array of strings
+    ///
+    /// # Returns
+    /// JSON array representation
+    fn string_array_to_json(arr: &[String]) -> String {
+        if arr.is_empty() {
+            return "[]".to_string();
+        }
         let items: Vec<String> = arr.iter().map(|s| Self::string_to_json(s)).collect();
         format!("[{}]", items.join(", "))
     }
 
+    /// Convert key-number pairs to a JSON object
+    ///
+    /// # Arguments
+    /// * `map` - Key-number pairs
+    ///
+    /// # Returns
+    /// JSON object representation
+    fn number_map_to_json(map: &[(String, f32)]) -> String {
+        if map.is_empty() {
+            return "{}".to_string();
+        }
+        let items: Vec<String> = map
+            .iter()
+            .map(|(key, number)| format!("{}: {number}", Self::string_to_json(key)))
+            .collect();
+        format!("{{{}}}", items.join(", "))
+    }
+
+    /// Convert path-position pairs to a JSON object
+    ///
+    /// # Arguments
+    /// * `map` - Path-position pairs
+    ///
+    /// # Returns
+    /// JSON object representation
+    fn position_map_to_json(map: &[(String, FilePosition)]) -> String {
+        if map.is_empty() {
+            return "{}".to_string();
+        }
+        let items: Vec<String> = map
+            .iter()
+            .map(|(path, position)| {
+                format!(
+                    "{}: [{}, {}]",
+                    Self::string_to_json(path),
+                    position.cursor,
+                    position.scroll_y
+                )
+            })
+            .collect();
+        format!("{{{}}}", items.join(", "))
+    }
+
+    /// Convert the theme settings to a JSON object
+    ///
+    /// # Returns
+    /// JSON object representation
+    fn theme_to_json(&self) -> String {
+        let theme = &self.theme;
+        let fields = [
+            ("preset", &theme.preset),
+            ("background", &theme.background),
+            ("text", &theme.text),
+            ("selection", &theme.selection),
+            ("caret", &theme.caret),
+        ];
+        let items: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("\"{key}\": {}", Self::string_to_json(value)))
+            .collect();
+        format!("{{{}}}", items.join(", "))
+    }
+
     /// Convert `FontFamily` to JSON string
     ///
     /// # Arguments
@@ -430,6 +1252,7 @@ impl Config {
         let name = match family {
             FontFamily::Monospace => "monospace",
             FontFamily::Proportional => "proportional",
+            FontFamily::Custom => "custom",
         };
         Self::string_to_json(name)
     }
@@ -470,6 +1293,36 @@ impl Config {
         path
     }
 
+    /// Get the user dictionary file of the spell checker
+    ///
+    /// # Returns
+    /// Path to dictionary.txt next to the config file
+    #[must_use]
+    pub fn user_dictionary_path() -> PathBuf {
+        Self::config_path().with_file_name("dictionary.txt")
+    }
+
+    /// Get the encoding of opened files that are not Unicode
+    ///
+    /// # Returns
+    /// The configured legacy encoding, Windows-1252 if it is unknown
+    #[must_use]
+    pub fn legacy(&self) -> Encoding {
+        Encoding::from_name(&self.legacy_encoding).unwrap_or(Encoding::Windows1252)
+    }
+
+    /// Get the directory for recovery files
+    ///
+    /// # Returns
+    /// The configured directory, or `recovery` next to the config file
+    pub fn recovery_dir(&self) -> PathBuf {
+        if self.recovery_dir.is_empty() {
+            Self::config_path().with_file_name("recovery")
+        } else {
+            PathBuf::from(&self.recovery_dir)
+        }
+    }
+
     /// Add file to recent files list
     ///
     /// # Arguments
@@ -477,12 +1330,156 @@ impl Config {
     pub fn add_recent_file(&mut self, file_path: &str) {
         // Remove if already exists
         self.recent_files.retain(|f| f != file_path);
-        // Add to front
-        self.recent_files.insert(0, file_path.to_string());
-        // Limit to 10 recent files
-        if self.recent_files.len() > 10 {
-            self.recent_files.truncate(10);
+        // Add to front, unless the list is turned off
+        if self.max_recent_files > 0 {
+            self.recent_files.insert(0, file_path.to_string());
+        }
+        self.recent_files.truncate(self.max_recent_files);
+        // Per-file settings are only kept for files in the recent list
+        let recent_files = &self.recent_files;
+        self.file_zoom
+            .retain(|(path, _)| recent_files.contains(path));
+    }
+
+    /// Get the window size to open with
+    ///
+    /// # Returns
+    /// The saved inner size, raised to `MIN_WINDOW_SIZE`; an invalid saved
+    /// value falls back to the default size
+    #[must_use]
+    pub fn window_size(&self) -> [f32; 2] {
+        let defaults = Self::create_default();
+        let clamp = |value: f32, default: f32, min: f32| {
+            if value.is_finite() {
+                value.max(min)
+            } else {
+                default
+            }
+        };
+        [
+            clamp(self.window_width, defaults.window_width, MIN_WINDOW_SIZE[0]),
+            clamp(
+                self.window_height,
+                defaults.window_height,
+                MIN_WINDOW_SIZE[1],
+            ),
+        ]
+    }
+
+    /// Remove a file from the recent files list
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to remove
+    pub fn remove_recent_file(&mut self, file_path: &str) {
+        self.recent_files.retain(|f| f != file_path);
+        self.file_zoom.retain(|(path, _)| path != file_path);
+    }
+
+    /// Clear the recent files list
+    pub fn clear_recent_files(&mut self) {
+        self.recent_files.clear();
+        self.file_zoom.clear();
+        self.file_positions.clear();
+    }
+
+    /// Drop recent files that no longer exist
+    ///
+    /// # Arguments
+    /// * `exists` - Returns whether a path still exists
+    ///
+    /// # Returns
+    /// True if any entry was dropped
+    pub fn prune_recent_files(&mut self, exists: impl Fn(&str) -> bool) -> bool {
+        let missing: Vec<String> = self
+            .recent_files
+            .iter()
+            .filter(|path| !exists(path))
+            .cloned()
+            .collect();
+        for path in &missing {
+            self.remove_recent_file(path);
         }
+        !missing.is_empty()
+    }
+
+    /// Get the zoom factor to use for a document
+    ///
+    /// Precedence: the global zoom factor is the default, a zoom remembered
+    /// for the file overrides it, and live changes during the session
+    /// override both (they are applied by the caller, and recorded with
+    /// `remember_zoom`).
+    ///
+    /// # Arguments
+    /// * `file_path` - Path of the document, or None for an untitled one
+    ///
+    /// # Returns
+    /// Zoom factor (1.0 = 100%)
+    #[must_use]
+    pub fn zoom_for_file(&self, file_path: Option<&str>) -> f32 {
+        file_path
+            .and_then(|path| self.file_zoom.iter().find(|(p, _)| p == path))
+            .map_or(self.zoom_factor, |&(_, zoom)| zoom)
+    }
+
+    /// Record a zoom change for the active document
+    ///
+    /// Documents with a path remember their own zoom; untitled documents
+    /// change the global zoom factor.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path of the document, or None for an untitled one
+    /// * `zoom` - New zoom factor
+    pub fn remember_zoom(&mut self, file_path: Option<&str>, zoom: f32) {
+        let Some(path) = file_path else {
+            self.zoom_factor = zoom;
+            return;
+        };
+        self.file_zoom.retain(|(p, _)| p != path);
+        self.file_zoom.insert(0, (path.to_string(), zoom));
+        self.file_zoom.truncate(MAX_FILE_ZOOM);
+    }
+
+    /// Get the remembered position in a file
+    ///
+    /// # Arguments
+    /// * `file_path` - Path of the file
+    ///
+    /// # Returns
+    /// Cursor and scroll position, or None if the file has none
+    #[must_use]
+    pub fn position_for_file(&self, file_path: &str) -> Option<FilePosition> {
+        self.file_positions
+            .iter()
+            .find(|(path, _)| path == file_path)
+            .map(|&(_, position)| position)
+    }
+
+    /// Remember the position in a file, e.g. when it is saved or closed
+    ///
+    /// The least recently remembered files are forgotten beyond
+    /// `MAX_FILE_POSITIONS`.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path of the file
+    /// * `position` - Cursor and scroll position
+    pub fn remember_position(&mut self, file_path: &str, position: FilePosition) {
+        self.file_positions.retain(|(path, _)| path != file_path);
+        self.file_positions
+            .insert(0, (file_path.to_string(), position));
+        self.file_positions.truncate(MAX_FILE_POSITIONS);
+    }
+
+    /// Drop remembered positions of files that no longer exist
+    ///
+    /// # Arguments
+    /// * `exists` - Returns whether a path still exists
+    ///
+    /// # Returns
+    /// True if any entry was dropped
+    pub fn prune_file_positions(&mut self, exists: impl Fn(&str) -> bool) -> bool {
+        let before = self.file_positions.len();
+        self.file_positions.retain(|(path, _)| exists(path));
+        self.file_positions.len() != before
     }
 
     /// Apply format settings from config
@@ -492,8 +1489,13 @@ impl Config {
     pub fn apply_to_format(&self, format_settings: &mut FormatSettings) {
         format_settings.font_family.clone_from(&self.font_family);
         format_settings.font_family_type = self.font_family_type;
+        format_settings.font_file.clone_from(&self.font_file);
         format_settings.font_style = self.font_style;
         format_settings.font_size = self.font_size;
+        format_settings.zoom_factor = self.zoom_factor;
+        format_settings.word_wrap = self.word_wrap;
+        format_settings.tab_width = self.tab_width;
+        format_settings.indent_with_spaces = self.indent_with_spaces;
     }
 
     /// Update config from format settings
@@ -503,8 +1505,88 @@ impl Config {
     pub fn update_from_format(&mut self, format_settings: &FormatSettings) {
         self.font_family.clone_from(&format_settings.font_family);
         self.font_family_type = format_settings.font_family_type;
+        self.font_file.clone_from(&format_settings.font_file);
         self.font_style = format_settings.font_style;
         self.font_size = format_settings.font_size;
+        self.word_wrap = format_settings.word_wrap;
+        self.tab_width = format_settings.tab_width;
+        self.indent_with_spaces = format_settings.indent_with_spaces;
+    }
+}
+
+/// Seconds without further changes after which a deferred save is written
+pub const CONFIG_SAVE_DELAY_SECS: f64 = 1.0;
+
+/// Debounce for saving the config
+///
+/// Changes made in quick succession, e.g. zooming with the mouse wheel,
+/// are written once `CONFIG_SAVE_DELAY_SECS` after the last of them.
+#[derive(Debug, Default)]
+pub struct DeferredSave {
+    /// A change was made since the last `poll`
+    changed: bool,
+    /// Time the config is due to be saved, None when nothing is pending
+    due: Option<f64>,
+    /// Whether the last save failed
+    failed: bool,
+}
+
+impl DeferredSave {
+    /// Note a change to save later
+    pub const fn mark(&mut self) {
+        self.changed = true;
+    }
+
+    /// Check whether a save is waiting
+    ///
+    /// # Returns
+    /// True if changes were made since the last save
+    #[must_use]
+    pub const fn is_pending(&self) -> bool {
+        self.changed || self.due.is_some()
+    }
+
+    /// Advance the debounce to the current time
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    ///
+    /// # Returns
+    /// True if the config should be saved now
+    pub fn poll(&mut self, now: f64) -> bool {
+        if self.changed {
+            self.changed = false;
+            self.due = Some(now + CONFIG_SAVE_DELAY_SECS);
+        }
+        self.due.is_some_and(|due| now >= due)
+    }
+
+    /// Get the time until the pending save
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    ///
+    /// # Returns
+    /// Seconds to wait, or None when nothing is pending
+    #[must_use]
+    pub fn wait(&self, now: f64) -> Option<f64> {
+        self.due.map(|due| (due - now).max(0.0))
+    }
+
+    /// Record the result of a save, which clears the pending changes
+    ///
+    /// # Arguments
+    /// * `result` - Result of `Config::save`
+    ///
+    /// # Returns
+    /// The result, except that a failure following another failure is
+    /// dropped so it is reported only once
+    pub fn record(&mut self, result: Result<(), String>) -> Result<(), String> {
+        self.changed = false;
+        self.due = None;
+        let repeated = self.failed;
+        self.failed = result.is_err();
+        if repeated { Ok(()) } else { result }
     }
 }
 
@@ -512,6 +1594,36 @@ impl Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_deferred_save_waits_for_quiet() {
+        let mut save = DeferredSave::default();
+        assert!(!save.is_pending());
+        assert!(!save.poll(0.0));
+
+        // Each change pushes the save back
+        save.mark();
+        assert!(!save.poll(10.0));
+        save.mark();
+        assert!(!save.poll(10.5));
+        assert!(!save.poll(11.4));
+        assert!((save.wait(11.4).expect("pending") - 0.1).abs() < 1e-9);
+        assert!(save.poll(11.5));
+
+        assert_eq!(save.record(Ok(())), Ok(()));
+        assert!(!save.is_pending());
+        assert_eq!(save.wait(12.0), None);
+    }
+
+    #[test]
+    fn test_deferred_save_reports_failure_once() {
+        let mut save = DeferredSave::default();
+        let failed = || Err("disk full".to_string());
+        assert_eq!(save.record(failed()), failed());
+        assert_eq!(save.record(failed()), Ok(()));
+        assert_eq!(save.record(Ok(())), Ok(()));
+        assert_eq!(save.record(failed()), failed());
+    }
+
     #[test]
     fn test_add_recent_file() {
         let mut config = Config::create_default();
@@ -532,6 +1644,418 @@ mod tests {
         assert_eq!(config.recent_files[0], path2);
     }
 
+    #[test]
+    fn test_line_comments() {
+        let json = r#"{
+            // Editor look
+            "word_wrap": false, // wrap off
+            "font_family": "// not a comment",
+            "recovery_dir": "C:\\Temp\\" // trailing backslash
+        }"#;
+        let parsed = Config::parse_json(json).expect("Failed to parse config");
+        assert!(!parsed.word_wrap);
+        assert_eq!(parsed.font_family, "// not a comment");
+        assert_eq!(parsed.recovery_dir, "C:\\Temp\\");
+    }
+
+    #[test]
+    fn test_block_comments() {
+        let json = r#"{
+            /* Window
+               settings */
+            "window_width": 800,/**/"window_height": 600,
+            "window_monitor": "/* kept */"
+        }"#;
+        let parsed = Config::parse_json(json).expect("Failed to parse config");
+        assert!((parsed.window_width - 800.0).abs() < f32::EPSILON);
+        assert!((parsed.window_height - 600.0).abs() < f32::EPSILON);
+        assert_eq!(parsed.window_monitor, "/* kept */");
+    }
+
+    #[test]
+    fn test_trailing_commas() {
+        let json = r#"{
+            "recent_files": ["a.txt", "b.txt",],
+            "match_case": true,
+        }"#;
+        let parsed = Config::parse_json(json).expect("Failed to parse config");
+        assert_eq!(parsed.recent_files, vec!["a.txt", "b.txt"]);
+        assert!(parsed.match_case);
+    }
+
+    #[test]
+    fn test_header_comment_preserved() {
+        let json = "// My settings\n/* synced */\n{\n  \"word_wrap\": false\n}";
+        let parsed = Config::parse_json(json).expect("Failed to parse config");
+        assert_eq!(parsed.header_comment, "// My settings\n/* synced */\n");
+        let written = parsed.to_json();
+        assert!(written.starts_with("// My settings\n/* synced */\n{"));
+        let reparsed = Config::parse_json(&written).expect("Failed to parse config");
+        assert_eq!(reparsed.header_comment, parsed.header_comment);
+        assert!(!reparsed.word_wrap);
+    }
+
+    #[test]
+    fn test_window_size_clamped() {
+        let mut config = Config::create_default();
+        config.window_width = 1280.0;
+        config.window_height = 20.0;
+        let [width, height] = config.window_size();
+        assert!((width - 1280.0).abs() < f32::EPSILON);
+        assert!((height - MIN_WINDOW_SIZE[1]).abs() < f32::EPSILON);
+        config.window_width = f32::NAN;
+        assert!((config.window_size()[0] - 640.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_prune_and_clear_recent_files() {
+        let mut config = Config::create_default();
+        config.add_recent_file("gone.txt");
+        config.add_recent_file("kept.txt");
+        config.remember_zoom(Some("gone.txt"), 2.0);
+
+        assert!(config.prune_recent_files(|path| path != "gone.txt"));
+        assert_eq!(config.recent_files, vec!["kept.txt".to_string()]);
+        assert!(config.file_zoom.is_empty());
+        assert!(!config.prune_recent_files(|_| true));
+
+        config.clear_recent_files();
+        assert!(config.recent_files.is_empty());
+    }
+
+    #[test]
+    fn test_zoom_precedence() {
+        let mut config = Config::create_default();
+        config.zoom_factor = 1.1;
+        let path = if cfg!(windows) {
+            r"C:\path\to\log.txt"
+        } else {
+            "/path/to/log.txt"
+        };
+        config.add_recent_file(path);
+
+        // Global default applies to untitled documents and unknown files
+        assert!((config.zoom_for_file(None) - 1.1).abs() < f32::EPSILON);
+        assert!((config.zoom_for_file(Some(path)) - 1.1).abs() < f32::EPSILON);
+
+        // A remembered per-file zoom overrides the global default
+        config.remember_zoom(Some(path), 0.9);
+        assert!((config.zoom_for_file(Some(path)) - 0.9).abs() < f32::EPSILON);
+        assert!((config.zoom_for_file(None) - 1.1).abs() < f32::EPSILON);
+
+        // Zoom changes on untitled documents update the global default
+        config.remember_zoom(None, 1.5);
+        assert!((config.zoom_for_file(None) - 1.5).abs() < f32::EPSILON);
+        assert!((config.zoom_for_file(Some(path)) - 0.9).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_file_zoom_dropped_with_recent_entry() {
+        let mut config = Config::create_default();
+        config.add_recent_file("/first.txt");
+        config.remember_zoom(Some("/first.txt"), 1.25);
+        for i in 0..10 {
+            config.add_recent_file(&format!("/other{i}.txt"));
+        }
+        assert!(!config.recent_files.contains(&"/first.txt".to_string()));
+        assert!(config.file_zoom.is_empty());
+    }
+
+    #[test]
+    fn test_file_zoom_bounded() {
+        let mut config = Config::create_default();
+        for i in 0..(MAX_FILE_ZOOM + 10) {
+            config.remember_zoom(Some(&format!("/file{i}.txt")), 1.1);
+        }
+        assert_eq!(config.file_zoom.len(), MAX_FILE_ZOOM);
+        assert_eq!(
+            config.file_zoom[0].0,
+            format!("/file{}.txt", MAX_FILE_ZOOM + 9)
+        );
+    }
+
+    #[test]
+    fn test_file_zoom_round_trip() {
+        let mut config = Config::create_default();
+        config.zoom_factor = 1.1;
+        config.remember_zoom(Some(r"C:\logs\app.log"), 0.9);
+        config.remember_zoom(Some("/home/user/notes: draft.md"), 1.3);
+
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!((parsed.zoom_factor - 1.1).abs() < f32::EPSILON);
+        assert_eq!(parsed.file_zoom, config.file_zoom);
+    }
+
+    #[test]
+    fn test_file_positions_bounded_and_round_trip() {
+        let mut config = Config::create_default();
+        for i in 0..(MAX_FILE_POSITIONS + 10) {
+            config.remember_position(
+                &format!("/file{i}.txt"),
+                FilePosition {
+                    cursor: i,
+                    scroll_y: 12.5,
+                },
+            );
+        }
+        assert_eq!(config.file_positions.len(), MAX_FILE_POSITIONS);
+        let newest = format!("/file{}.txt", MAX_FILE_POSITIONS + 9);
+        assert_eq!(config.file_positions[0].0, newest);
+        assert_eq!(config.position_for_file("/file0.txt"), None);
+
+        config.remember_position(
+            r"C:\logs\app: v2.log",
+            FilePosition {
+                cursor: 7,
+                scroll_y: 0.0,
+            },
+        );
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.file_positions, config.file_positions);
+        assert_eq!(
+            parsed.position_for_file(r"C:\logs\app: v2.log"),
+            Some(FilePosition {
+                cursor: 7,
+                scroll_y: 0.0
+            })
+        );
+
+        assert!(config.prune_file_positions(|path| path == newest));
+        assert_eq!(config.file_positions.len(), 1);
+        assert!(!config.prune_file_positions(|_| true));
+    }
+
+    #[test]
+    fn test_window_position_round_trip() {
+        let mut config = Config::create_default();
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.window_x, None);
+        assert_eq!(parsed.window_y, None);
+
+        config.window_x = Some(-1200.0);
+        config.window_y = Some(40.5);
+        config.window_monitor = "DP-1".to_string();
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.window_x, Some(-1200.0));
+        assert_eq!(parsed.window_y, Some(40.5));
+        assert_eq!(parsed.window_monitor, "DP-1");
+    }
+
+    #[test]
+    fn test_recovery_dir_override() {
+        let mut config = Config::create_default();
+        assert!(config.recovery_dir().ends_with("recovery"));
+        config.recovery_dir = "/mnt/backup/nodepat".to_string();
+        config.recovery_enabled = false;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.recovery_dir(), PathBuf::from("/mnt/backup/nodepat"));
+        assert!(!parsed.recovery_enabled);
+    }
+
+    #[test]
+    fn test_trim_idle_secs_is_at_least_one() {
+        let mut config = Config::create_default();
+        config.trim_idle_secs = 60;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.trim_idle_secs, 60);
+
+        let parsed =
+            Config::parse_json(r#"{"trim_idle_secs": 0}"#).expect("Failed to parse config");
+        assert_eq!(parsed.trim_idle_secs, 1);
+    }
+
+    #[test]
+    fn test_autosave_round_trip() {
+        let mut config = Config::create_default();
+        assert!(!config.autosave_enabled);
+        config.autosave_enabled = true;
+        config.autosave_interval_secs = 120;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!(parsed.autosave_enabled);
+        assert!(parsed.recovery_enabled);
+        assert_eq!(parsed.autosave_interval_secs, 120);
+        assert!(!parsed.create_backup);
+
+        let parsed =
+            Config::parse_json(r#"{"autosave_interval_secs": 0}"#).expect("Failed to parse config");
+        assert_eq!(parsed.autosave_interval_secs, 1);
+    }
+
+    #[test]
+    fn test_create_backup_round_trip() {
+        let mut config = Config::create_default();
+        assert!(!config.create_backup);
+        config.create_backup = true;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!(parsed.create_backup);
+    }
+
+    #[test]
+    fn test_default_encoding_round_trip() {
+        let mut config = Config::create_default();
+        assert_eq!(config.default_encoding, "UTF-8");
+        config.default_encoding = "UTF-16 LE".to_string();
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.default_encoding, "UTF-16 LE");
+
+        // Unknown encodings keep the default
+        let parsed = Config::parse_json(r#"{"default_encoding": "EBCDIC"}"#)
+            .expect("Failed to parse config");
+        assert_eq!(parsed.default_encoding, "UTF-8");
+
+        // Latin1 from older versions means Windows-1252
+        let parsed = Config::parse_json(r#"{"default_encoding": "Latin1"}"#)
+            .expect("Failed to parse config");
+        assert_eq!(parsed.default_encoding, "Windows-1252");
+    }
+
+    #[test]
+    fn test_legacy_encoding_round_trip() {
+        let mut config = Config::create_default();
+        assert_eq!(config.legacy(), Encoding::Windows1252);
+        config.legacy_encoding = "Shift-JIS".to_string();
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.legacy(), Encoding::ShiftJis);
+
+        // Unicode encodings are detected anyway and cannot be the fallback
+        let parsed = Config::parse_json(r#"{"legacy_encoding": "UTF-16 LE"}"#)
+            .expect("Failed to parse config");
+        assert_eq!(parsed.legacy_encoding, "Windows-1252");
+    }
+
+    #[test]
+    fn test_legacy_autosave_enables_recovery() {
+        let parsed =
+            Config::parse_json(r#"{"autosave_enabled": false}"#).expect("Failed to parse config");
+        assert!(!parsed.recovery_enabled);
+        assert!(!parsed.autosave_enabled);
+
+        let parsed =
+            Config::parse_json(r#"{"autosave_enabled": true}"#).expect("Failed to parse config");
+        assert!(parsed.recovery_enabled);
+        assert!(!parsed.autosave_enabled);
+    }
+
+    #[test]
+    fn test_word_wrap_round_trip() {
+        let mut config = Config::create_default();
+        assert!(config.word_wrap);
+        config.word_wrap = false;
+        config.show_line_numbers = true;
+        config.syntax_highlighting = false;
+        config.highlight_current_line = false;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!(!parsed.word_wrap);
+        assert!(parsed.show_line_numbers);
+        assert!(!parsed.syntax_highlighting);
+        assert!(!parsed.highlight_current_line);
+
+        let mut format_settings = FormatSettings::default();
+        parsed.apply_to_format(&mut format_settings);
+        assert!(!format_settings.word_wrap);
+    }
+
+    #[test]
+    fn test_tab_settings_round_trip() {
+        let mut config = Config::create_default();
+        assert_eq!(config.tab_width, 4);
+        assert!(!config.indent_with_spaces);
+        assert!(!config.trim_trailing_on_save);
+        assert!(!config.auto_close_brackets);
+        config.tab_width = 2;
+        config.indent_with_spaces = true;
+        config.trim_trailing_on_save = true;
+        config.auto_close_brackets = true;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.tab_width, 2);
+        assert!(parsed.indent_with_spaces);
+        assert!(parsed.trim_trailing_on_save);
+        assert!(parsed.auto_close_brackets);
+
+        let parsed = Config::parse_json(r#"{"tab_width": 0}"#).expect("Failed to parse config");
+        assert_eq!(parsed.tab_width, 1);
+        let parsed = Config::parse_json(r#"{"tab_width": 99}"#).expect("Failed to parse config");
+        assert_eq!(parsed.tab_width, MAX_TAB_WIDTH);
+    }
+
+    #[test]
+    fn test_spell_check_round_trip() {
+        let mut config = Config::create_default();
+        assert!(!config.spell_check);
+        config.spell_check = true;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!(parsed.spell_check);
+        assert!(Config::user_dictionary_path().ends_with("Nodepat/dictionary.txt"));
+    }
+
+    #[test]
+    fn test_markdown_preview_round_trip() {
+        let mut config = Config::create_default();
+        assert!(!config.markdown_preview);
+        config.markdown_preview = true;
+        config.preview_split = 0.35;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!(parsed.markdown_preview);
+        assert!((parsed.preview_split - 0.35).abs() < f32::EPSILON);
+
+        let parsed = Config::parse_json(r#"{"preview_split": 5}"#).expect("Failed to parse config");
+        assert!((parsed.preview_split - PREVIEW_SPLIT_RANGE.1).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_font_file_round_trip() {
+        let mut config = Config::create_default();
+        assert!(config.font_file.is_empty());
+        config.font_family_type = FontFamily::Custom;
+        config.font_file = r"C:\Fonts\Fira Code.ttf".to_string();
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.font_family_type, FontFamily::Custom);
+        assert_eq!(parsed.font_file, config.font_file);
+
+        let mut format = FormatSettings::default();
+        parsed.apply_to_format(&mut format);
+        assert_eq!(format.font_file, config.font_file);
+    }
+
+    #[test]
+    fn test_theme_round_trip() {
+        let mut config = Config::create_default();
+        assert_eq!(config.theme, ThemeSettings::default());
+        config.theme.preset = "Solarized Dark".to_string();
+        config.theme.caret = "#ff0000".to_string();
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.theme, config.theme);
+
+        // Missing colors stay empty and unknown fields are ignored
+        let parsed = Config::parse_json(r##"{"theme": {"text": "#abc", "font": "x"}}"##)
+            .expect("Failed to parse config");
+        assert_eq!(parsed.theme.text, "#abc");
+        assert!(parsed.theme.background.is_empty());
+    }
+
+    #[test]
+    fn test_search_options_round_trip() {
+        let mut config = Config::create_default();
+        assert!(config.wrap_around);
+        assert!(!config.match_case);
+        assert!(!config.use_regex);
+        config.wrap_around = false;
+        config.match_case = true;
+        config.use_regex = true;
+        config.whole_word = true;
+        config.preserve_case = true;
+        config.incremental_find = true;
+        config.extended_search = true;
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert!(!parsed.wrap_around);
+        assert!(parsed.match_case);
+        assert!(parsed.use_regex);
+        assert!(parsed.whole_word);
+        assert!(parsed.preserve_case);
+        assert!(parsed.incremental_find);
+        assert!(parsed.extended_search);
+    }
+
     #[test]
     fn test_recent_files_limit() {
         let mut config = Config::create_default();
@@ -544,5 +2068,33 @@ mod tests {
             config.add_recent_file(&path);
         }
         assert_eq!(config.recent_files.len(), 10);
+
+        config.max_recent_files = 3;
+        config.add_recent_file("/path/to/new.txt");
+        assert_eq!(config.recent_files.len(), 3);
+        assert_eq!(config.recent_files[0], "/path/to/new.txt");
+    }
+
+    #[test]
+    fn test_recent_files_disabled() {
+        let mut config = Config::create_default();
+        config.add_recent_file("/path/to/old.txt");
+        config.max_recent_files = 0;
+        config.add_recent_file("/path/to/new.txt");
+        assert!(config.recent_files.is_empty());
+    }
+
+    #[test]
+    fn test_max_recent_files_round_trip() {
+        let mut config = Config::create_default();
+        config.max_recent_files = 2;
+        config.recent_files = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let parsed = Config::parse_json(&config.to_json()).expect("Failed to parse config");
+        assert_eq!(parsed.max_recent_files, 2);
+        assert_eq!(parsed.recent_files, vec!["a", "b"]);
+
+        let parsed =
+            Config::parse_json(r#"{"max_recent_files": 500}"#).expect("Failed to parse config");
+        assert_eq!(parsed.max_recent_files, MAX_RECENT_FILES_LIMIT);
     }
 }
@@ -0,0 +1,510 @@
+//! Batch encoding conversion
+//!
+//! This module converts the encoding of every file in a directory that
+//! matches a filename pattern. The work runs on a background thread that
+//! reports progress and can be cancelled from the dialog.
+
+use crate::file_ops::{
+    Encoding, backup_path, can_encode, decode_with, detect_and_decode, encode_with, write_atomic,
+};
+use crate::ui::file_browser::FileBrowser;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Source encoding name that enables detection
+pub const AUTO_ENCODING: &str = "Auto";
+
+/// Options for converting files
+#[derive(Clone)]
+pub struct ConvertOptions {
+    /// Encoding to decode from, or `AUTO_ENCODING` to detect it
+    pub source_encoding: String,
+    /// Encoding detection assumes for files that are not Unicode
+    pub legacy_encoding: Encoding,
+    /// Encoding to write
+    pub target_encoding: String,
+    /// Copy each file to `<name>.bak` before overwriting it
+    pub create_backup: bool,
+    /// Only report what would change
+    pub dry_run: bool,
+    /// Replace characters the target cannot represent instead of skipping
+    pub replace_unmappable: bool,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            source_encoding: AUTO_ENCODING.to_string(),
+            legacy_encoding: Encoding::Windows1252,
+            target_encoding: "UTF-8".to_string(),
+            create_backup: true,
+            dry_run: true,
+            replace_unmappable: false,
+        }
+    }
+}
+
+/// Result of converting a single file
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConvertOutcome {
+    /// The file was converted (or would be, in a dry run) from this encoding
+    Converted(String),
+    /// The file was left alone for this reason
+    Skipped(String),
+    /// The file could not be converted for this reason
+    Failed(String),
+}
+
+/// Convert the encoding of a single file in place
+///
+/// # Arguments
+/// * `path` - File to convert
+/// * `options` - Conversion options
+///
+/// # Returns
+/// What happened to the file
+pub fn convert_file(path: &Path, options: &ConvertOptions) -> ConvertOutcome {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) => return ConvertOutcome::Failed(format!("Failed to read file: {e}")),
+    };
+    let decoded = if options.source_encoding == AUTO_ENCODING {
+        detect_and_decode(&data, options.legacy_encoding)
+            .map(|(text, encoding)| (text, encoding.to_string()))
+    } else {
+        decode_with(&data, &options.source_encoding)
+            .map(|text| (text, options.source_encoding.clone()))
+    };
+    let (text, source) = match decoded {
+        Ok(decoded) => decoded,
+        Err(e) => return ConvertOutcome::Failed(e),
+    };
+
+    let target = &options.target_encoding;
+    // Left untouched rather than failed: nothing went wrong, the file just
+    // needs replacement allowed
+    if !options.replace_unmappable && !can_encode(&text, target) {
+        return ConvertOutcome::Skipped(format!(
+            "contains characters that cannot be represented in {target}"
+        ));
+    }
+    let bytes = encode_with(&text, target);
+    if bytes == data {
+        return ConvertOutcome::Skipped(format!("already {target}"));
+    }
+    if options.dry_run {
+        return ConvertOutcome::Converted(source);
+    }
+
+    if options.create_backup
+        && let Err(e) = fs::copy(path, backup_path(path))
+    {
+        return ConvertOutcome::Failed(format!("Failed to create backup: {e}"));
+    }
+    match write_atomic(path, &bytes) {
+        Ok(()) => ConvertOutcome::Converted(source),
+        Err(e) => ConvertOutcome::Failed(e),
+    }
+}
+
+/// Check whether a file name matches a glob pattern
+///
+/// Supports `*` (any run of characters) and `?` (a single character).
+/// Matching ignores case; an empty pattern matches everything.
+///
+/// # Arguments
+/// * `pattern` - Glob pattern (e.g., "*.txt")
+/// * `name` - File name to check
+///
+/// # Returns
+/// True if the name matches
+#[must_use]
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    if pattern.is_empty() {
+        return true;
+    }
+
+    // Iterative wildcard matching with backtracking to the last '*'
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// List the files in a directory whose names match a pattern
+///
+/// # Arguments
+/// * `dir` - Directory to list (not recursive)
+/// * `pattern` - Glob pattern for file names
+///
+/// # Returns
+/// Sorted list of matching file paths, or error message
+pub fn matching_files(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read directory: {e}"))?;
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| glob_matches(pattern, &name.to_string_lossy()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Progress of a running conversion
+#[derive(Default)]
+pub struct ConvertProgress {
+    /// Number of files processed
+    pub done: usize,
+    /// Number of files to process
+    pub total: usize,
+    /// Name of the file being processed
+    pub current: String,
+    /// Whether the job has stopped (finished, failed, or cancelled)
+    pub finished: bool,
+    /// Per-file report lines
+    report: Vec<String>,
+    /// Number of converted files
+    converted: usize,
+    /// Number of skipped files
+    skipped: usize,
+    /// Number of failed files
+    failed: usize,
+}
+
+impl ConvertProgress {
+    /// Record the outcome for one file
+    ///
+    /// # Arguments
+    /// * `name` - File name
+    /// * `outcome` - What happened to the file
+    /// * `options` - Options the job runs with
+    fn record(&mut self, name: &str, outcome: ConvertOutcome, options: &ConvertOptions) {
+        let line = match outcome {
+            ConvertOutcome::Converted(source) => {
+                self.converted += 1;
+                let verb = if options.dry_run {
+                    "Would convert"
+                } else {
+                    "Converted"
+                };
+                format!("{verb}  {name} ({source} -> {})", options.target_encoding)
+            }
+            ConvertOutcome::Skipped(reason) => {
+                self.skipped += 1;
+                format!("Skipped  {name}: {reason}")
+            }
+            ConvertOutcome::Failed(reason) => {
+                self.failed += 1;
+                format!("Failed  {name}: {reason}")
+            }
+        };
+        self.report.push(line);
+        self.done += 1;
+    }
+}
+
+/// Lock shared progress, recovering from a panicked worker thread
+fn lock_progress(progress: &Mutex<ConvertProgress>) -> MutexGuard<'_, ConvertProgress> {
+    progress.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Conversion running on a background thread
+pub struct ConvertJob {
+    /// Progress shared with the worker thread
+    progress: Arc<Mutex<ConvertProgress>>,
+    /// Set to stop the worker after the current file
+    cancel: Arc<AtomicBool>,
+    /// Whether the job was started as a dry run
+    dry_run: bool,
+}
+
+impl ConvertJob {
+    /// Start converting the matching files of a directory
+    ///
+    /// # Arguments
+    /// * `dir` - Directory containing the files
+    /// * `pattern` - Glob pattern for file names
+    /// * `options` - Conversion options
+    ///
+    /// # Returns
+    /// Handle to the running job
+    #[must_use]
+    pub fn start(dir: PathBuf, pattern: String, options: ConvertOptions) -> Self {
+        let progress = Arc::new(Mutex::new(ConvertProgress::default()));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let job = Self {
+            progress: Arc::clone(&progress),
+            cancel: Arc::clone(&cancel),
+            dry_run: options.dry_run,
+        };
+        std::thread::spawn(move || {
+            match matching_files(&dir, &pattern) {
+                Ok(files) => {
+                    lock_progress(&progress).total = files.len();
+                    for path in files {
+                        if cancel.load(Ordering::Relaxed) {
+                            lock_progress(&progress)
+                                .report
+                                .push("Cancelled".to_string());
+                            break;
+                        }
+                        let name = path
+                            .file_name()
+                            .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+                        lock_progress(&progress).current.clone_from(&name);
+                        let outcome = convert_file(&path, &options);
+                        lock_progress(&progress).record(&name, outcome, &options);
+                    }
+                }
+                Err(e) => lock_progress(&progress).report.push(e),
+            }
+            lock_progress(&progress).finished = true;
+        });
+        job
+    }
+
+    /// Ask the worker to stop after the current file
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Get the current progress
+    ///
+    /// # Returns
+    /// Tuple of (files done, total files, current file name, finished)
+    #[must_use]
+    pub fn status(&self) -> (usize, usize, String, bool) {
+        let progress = lock_progress(&self.progress);
+        (
+            progress.done,
+            progress.total,
+            progress.current.clone(),
+            progress.finished,
+        )
+    }
+
+    /// Build the summary of the job so far
+    ///
+    /// # Returns
+    /// Counts followed by one line per processed file
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let progress = lock_progress(&self.progress);
+        let mut summary = format!(
+            "Converted: {}, Skipped: {}, Failed: {}",
+            progress.converted, progress.skipped, progress.failed
+        );
+        if self.dry_run {
+            summary.push_str(" (dry run, nothing was written)");
+        }
+        summary.push('\n');
+        for line in &progress.report {
+            let _ = write!(summary, "\n{line}");
+        }
+        drop(progress);
+        summary
+    }
+}
+
+/// State of the Convert Files' Encoding dialog
+pub struct ConvertDialogState {
+    /// Directory containing the files
+    pub directory: String,
+    /// Glob pattern for file names
+    pub pattern: String,
+    /// Conversion options
+    pub options: ConvertOptions,
+    /// Directory picker, while open
+    pub browser: Option<FileBrowser>,
+    /// Running or finished job
+    pub job: Option<ConvertJob>,
+}
+
+impl Default for ConvertDialogState {
+    fn default() -> Self {
+        Self {
+            directory: String::new(),
+            pattern: "*.txt".to_string(),
+            options: ConvertOptions::default(),
+            browser: None,
+            job: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty fixture directory in the temp directory
+    fn fixture_dir(name: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Failed to create fixture directory");
+        dir
+    }
+
+    fn options(target: &str, dry_run: bool) -> ConvertOptions {
+        ConvertOptions {
+            source_encoding: AUTO_ENCODING.to_string(),
+            legacy_encoding: Encoding::Windows1252,
+            target_encoding: target.to_string(),
+            create_backup: false,
+            dry_run,
+            replace_unmappable: false,
+        }
+    }
+
+    #[test]
+    fn test_convert_each_encoding_to_utf8() {
+        let dir = fixture_dir("test_Nodepat_convert_each");
+        let text = "Grüße, café";
+        let fixtures = [
+            ("utf16le.txt", encode_with(text, "UTF-16 LE"), "UTF-16 LE"),
+            ("utf16be.txt", encode_with(text, "UTF-16 BE"), "UTF-16 BE"),
+            (
+                "ansi.txt",
+                encode_with(text, "Windows-1252"),
+                "Windows-1252",
+            ),
+        ];
+        for (name, bytes, source) in &fixtures {
+            let path = dir.join(name);
+            fs::write(&path, bytes).expect("Failed to write fixture");
+            assert_eq!(
+                convert_file(&path, &options("UTF-8", false)),
+                ConvertOutcome::Converted((*source).to_string())
+            );
+            assert_eq!(fs::read(&path).expect("Failed to read"), text.as_bytes());
+        }
+
+        // A UTF-8 file is already in the target encoding
+        let path = dir.join("utf8.txt");
+        fs::write(&path, text).expect("Failed to write fixture");
+        assert_eq!(
+            convert_file(&path, &options("UTF-8", false)),
+            ConvertOutcome::Skipped("already UTF-8".to_string())
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_convert_utf8_to_each_encoding() {
+        let dir = fixture_dir("test_Nodepat_convert_targets");
+        let text = "Grüße, café";
+        for target in ["UTF-16 LE", "UTF-16 BE", "Windows-1252"] {
+            let path = dir.join("source.txt");
+            fs::write(&path, text).expect("Failed to write fixture");
+            assert_eq!(
+                convert_file(&path, &options(target, false)),
+                ConvertOutcome::Converted("UTF-8".to_string())
+            );
+            let data = fs::read(&path).expect("Failed to read");
+            assert_eq!(decode_with(&data, target).as_deref(), Ok(text));
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_convert_lossy_dry_run_and_backup() {
+        let dir = fixture_dir("test_Nodepat_convert_lossy");
+        let path = dir.join("yen.txt");
+        fs::write(&path, "price: 5円").expect("Failed to write fixture");
+
+        // Lossy conversions are skipped unless replacement is allowed
+        let mut lossy = options("Windows-1252", false);
+        assert_eq!(
+            convert_file(&path, &lossy),
+            ConvertOutcome::Skipped(
+                "contains characters that cannot be represented in Windows-1252".to_string()
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(&path).ok().as_deref(),
+            Some("price: 5円")
+        );
+
+        // A dry run reports the change without writing
+        lossy.replace_unmappable = true;
+        lossy.dry_run = true;
+        assert_eq!(
+            convert_file(&path, &lossy),
+            ConvertOutcome::Converted("UTF-8".to_string())
+        );
+        assert_eq!(
+            fs::read_to_string(&path).ok().as_deref(),
+            Some("price: 5円")
+        );
+
+        lossy.dry_run = false;
+        lossy.create_backup = true;
+        assert_eq!(
+            convert_file(&path, &lossy),
+            ConvertOutcome::Converted("UTF-8".to_string())
+        );
+        assert_eq!(fs::read(&path).expect("Failed to read"), b"price: 5?");
+        assert_eq!(
+            fs::read_to_string(backup_path(&path)).ok().as_deref(),
+            Some("price: 5円")
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*.txt", "notes.txt"));
+        assert!(glob_matches("*.txt", "NOTES.TXT"));
+        assert!(!glob_matches("*.txt", "notes.txt.bak"));
+        assert!(glob_matches("log?.*", "log1.old"));
+        assert!(!glob_matches("log?.*", "log12.old"));
+        assert!(glob_matches("*a*b*", "xxaxxbxx"));
+        assert!(glob_matches("", "anything"));
+        assert!(glob_matches("*", ""));
+    }
+
+    #[test]
+    fn test_job_reports_summary() {
+        let dir = fixture_dir("test_Nodepat_convert_job");
+        fs::write(dir.join("a.txt"), encode_with("é", "Windows-1252")).expect("write");
+        fs::write(dir.join("b.txt"), "plain").expect("write");
+        fs::write(dir.join("c.log"), encode_with("é", "Windows-1252")).expect("write");
+
+        let job = ConvertJob::start(dir.clone(), "*.txt".to_string(), options("UTF-8", true));
+        while !job.status().3 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let summary = job.summary();
+        assert!(summary.starts_with("Converted: 1, Skipped: 1, Failed: 0"));
+        assert!(summary.contains("Would convert  a.txt (Windows-1252 -> UTF-8)"));
+        assert!(!summary.contains("c.log"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
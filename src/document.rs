@@ -0,0 +1,267 @@
+//! Open documents shown as tabs
+//!
+//! This module holds the state that belongs to one document: its file,
+//! its text with cursor and undo history, and its per-document view
+//! settings. The app keeps every open document in a list; the active one
+//! is edited in place while the others wait here.
+
+use crate::autosave::RecoveryState;
+use crate::editor::EditorState;
+use crate::file_ops::{DiskStamp, FileState};
+use crate::highlight::Language;
+use std::path::Path;
+
+/// One open document
+#[derive(Default)]
+pub struct Document {
+    /// File-related state (path, modified flag, encoding)
+    pub file_state: FileState,
+    /// Editor state (text content, cursor position, undo/redo)
+    pub editor_state: EditorState,
+    /// Language chosen in View > Language; None picks it by file extension
+    pub language_override: Option<Language>,
+    /// Stamp of an external change the user chose to ignore
+    pub ignored_disk_stamp: Option<DiskStamp>,
+    /// Read Only Mode, protecting the document from edits
+    pub read_only_mode: bool,
+    /// Recovery file of the document's unsaved changes
+    pub recovery: RecoveryState,
+}
+
+impl Document {
+    /// Get the name shown on the document's tab
+    ///
+    /// # Returns
+    /// File name, or "Untitled" for a document without a path
+    #[must_use]
+    pub fn name(&self) -> String {
+        display_name(&self.file_state.file_path)
+    }
+}
+
+/// Get the display name of a document
+///
+/// # Arguments
+/// * `file_path` - Path of the document's file, empty for a new document
+///
+/// # Returns
+/// File name, or "Untitled" for a document without a path
+#[must_use]
+pub fn display_name(file_path: &str) -> String {
+    Path::new(file_path).file_name().map_or_else(
+        || "Untitled".to_string(),
+        |n| n.to_string_lossy().to_string(),
+    )
+}
+
+/// Check whether a document is an empty, untouched Untitled document
+///
+/// Opening a file or creating a new one reuses such a document instead of
+/// adding a tab.
+///
+/// # Arguments
+/// * `file_state` - File state of the document
+/// * `editor_state` - Editor state of the document
+///
+/// # Returns
+/// True if the document has no file, no text, and no changes
+#[must_use]
+pub const fn is_blank(file_state: &FileState, editor_state: &EditorState) -> bool {
+    file_state.file_path.is_empty()
+        && !file_state.is_modified
+        && editor_state.text.is_empty()
+        && editor_state.undo_history.is_empty()
+}
+
+/// Get the tab after or before the active one, wrapping around
+///
+/// # Arguments
+/// * `active` - Index of the active tab
+/// * `count` - Number of tabs
+/// * `forward` - Move right (Ctrl+Tab) instead of left (Ctrl+Shift+Tab)
+///
+/// # Returns
+/// Index of the tab to activate
+#[must_use]
+pub const fn cycle_index(active: usize, count: usize, forward: bool) -> usize {
+    if count == 0 {
+        0
+    } else if forward {
+        (active + 1) % count
+    } else {
+        (active + count - 1) % count
+    }
+}
+
+/// Get where a tab ends up when another tab is moved
+///
+/// # Arguments
+/// * `index` - Index of the tab before the move
+/// * `from` - Index of the moved tab before the move
+/// * `to` - Index of the moved tab after the move
+///
+/// # Returns
+/// Index of the tab after the move
+#[must_use]
+pub const fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// Get the index a dragged tab gets when dropped in a gap
+///
+/// # Arguments
+/// * `from` - Index of the dragged tab
+/// * `gap` - Gap between tabs it is dropped in: 0 before the first tab,
+///   the tab count after the last one
+///
+/// # Returns
+/// Index of the tab after the move
+#[must_use]
+pub const fn drop_index(from: usize, gap: usize) -> usize {
+    if gap > from { gap - 1 } else { gap }
+}
+
+/// Tabs closed together from the tab context menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseSet {
+    /// Every tab but the clicked one
+    Others,
+    /// The tabs right of the clicked one
+    ToTheRight,
+}
+
+/// Tabs waiting to be closed one after another
+///
+/// Each tab with unsaved changes asks before it closes, so the tabs are
+/// closed one prompt at a time. They are closed from the right, so closing
+/// one does not move the tabs still waiting.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CloseQueue {
+    /// Indexes of the tabs still to close, in ascending order; the last
+    /// one closes first
+    indexes: Vec<usize>,
+    /// Index of the clicked tab, kept up to date as tabs left of it close
+    keep: usize,
+}
+
+impl CloseQueue {
+    /// Queue the tabs of a context menu command
+    ///
+    /// # Arguments
+    /// * `count` - Number of tabs
+    /// * `clicked` - Index of the tab the menu was opened on
+    /// * `set` - Which tabs to close
+    #[must_use]
+    pub fn new(count: usize, clicked: usize, set: CloseSet) -> Self {
+        let indexes = match set {
+            CloseSet::Others => (0..count).filter(|&index| index != clicked).collect(),
+            CloseSet::ToTheRight => (clicked + 1..count).collect(),
+        };
+        Self {
+            indexes,
+            keep: clicked,
+        }
+    }
+
+    /// Take the next tab to close
+    ///
+    /// # Returns
+    /// Index of the tab, or None when every queued tab was handed out
+    pub fn next(&mut self) -> Option<usize> {
+        let index = self.indexes.pop()?;
+        if index < self.keep {
+            self.keep -= 1;
+        }
+        Some(index)
+    }
+
+    /// Get the index of the clicked tab, given that the tabs handed out
+    /// by `next` were closed
+    #[must_use]
+    pub const fn keep(&self) -> usize {
+        self.keep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_index_wraps() {
+        assert_eq!(cycle_index(0, 3, true), 1);
+        assert_eq!(cycle_index(2, 3, true), 0);
+        assert_eq!(cycle_index(0, 3, false), 2);
+        assert_eq!(cycle_index(1, 3, false), 0);
+        assert_eq!(cycle_index(0, 1, true), 0);
+        assert_eq!(cycle_index(0, 0, false), 0);
+    }
+
+    #[test]
+    fn test_moved_index_follows_the_document() {
+        // Order a b c d e; move b (1) to 3: a c d b e
+        let moved: Vec<usize> = (0..5).map(|index| moved_index(index, 1, 3)).collect();
+        assert_eq!(moved, vec![0, 3, 1, 2, 4]);
+        // Move d (3) to 0: d a b c e
+        let moved: Vec<usize> = (0..5).map(|index| moved_index(index, 3, 0)).collect();
+        assert_eq!(moved, vec![1, 2, 3, 0, 4]);
+        assert_eq!(moved_index(2, 2, 2), 2);
+    }
+
+    #[test]
+    fn test_drop_index() {
+        // Dropping next to itself leaves the tab where it is
+        assert_eq!(drop_index(2, 2), 2);
+        assert_eq!(drop_index(2, 3), 2);
+        assert_eq!(drop_index(2, 0), 0);
+        assert_eq!(drop_index(0, 5), 4);
+        assert_eq!(drop_index(4, 1), 1);
+    }
+
+    #[test]
+    fn test_close_others_keeps_clicked_tab() {
+        let mut queue = CloseQueue::new(5, 2, CloseSet::Others);
+        let mut closed = Vec::new();
+        while let Some(index) = queue.next() {
+            closed.push(index);
+        }
+        // Closing from the right keeps the waiting indexes valid
+        assert_eq!(closed, vec![4, 3, 1, 0]);
+        assert_eq!(queue.keep(), 0);
+    }
+
+    #[test]
+    fn test_close_to_the_right() {
+        let mut queue = CloseQueue::new(4, 1, CloseSet::ToTheRight);
+        assert_eq!(queue.next(), Some(3));
+        assert_eq!(queue.next(), Some(2));
+        assert_eq!(queue.next(), None);
+        assert_eq!(queue.keep(), 1);
+        assert_eq!(CloseQueue::new(4, 3, CloseSet::ToTheRight).next(), None);
+    }
+
+    #[test]
+    fn test_blank_document() {
+        let mut document = Document::default();
+        assert!(is_blank(&document.file_state, &document.editor_state));
+        assert_eq!(document.name(), "Untitled");
+
+        document.editor_state.text = "x".to_string();
+        assert!(!is_blank(&document.file_state, &document.editor_state));
+        document.editor_state.text.clear();
+        document.file_state.file_path = std::env::temp_dir()
+            .join("test_Nodepat_tab.txt")
+            .to_string_lossy()
+            .to_string();
+        assert!(!is_blank(&document.file_state, &document.editor_state));
+        assert_eq!(document.name(), "test_Nodepat_tab.txt");
+    }
+}
@@ -4,180 +4,3351 @@
 //! the text widget, cursor tracking, and basic editing operations.
 
 use crate::app::NodepatApp;
+use crate::carets::{edit_at_carets, move_carets, toggle_caret};
+use crate::highlight::{HighlightCache, Language};
+use crate::links::{find_links, link_at};
+use crate::macros::record_events;
+use crate::memory_trim::{TrimInputs, TrimPlan, TrimPolicy, plan_trim};
+use crate::pairs::{PairEdit, backspace_edit, typed_edit};
+use crate::search::match_ranges;
+use crate::spell::{Misspelling, SpellChecker};
 use eframe::egui;
+use eframe::egui::text_selection::visuals::paint_text_selection;
+use std::sync::Arc;
 
 /// Editor state including text content and undo/redo history
 #[derive(Default)]
 pub struct EditorState {
     /// Current text content
     pub text: String,
-    /// Undo history (previous text states)
-    pub undo_history: Vec<String>,
-    /// Redo history (future text states after undo)
-    pub redo_history: Vec<String>,
+    /// Undo history (changes to revert, oldest first)
+    pub undo_history: Vec<Delta>,
+    /// Redo history (undone changes, next to redo last)
+    pub redo_history: Vec<Delta>,
     /// Current cursor position (line, column)
     pub cursor_line: usize,
     pub cursor_column: usize,
+    /// Discard the `TextEdit` widget state on the next frame
+    reset_widget_state: bool,
+    /// Current selection as a sorted character range (start, end)
+    pub selection: (usize, usize),
+    /// Selection to apply to the `TextEdit` on the next frame
+    pub pending_selection: Option<(usize, usize)>,
+    /// Vertical scroll offset of the editor, in points
+    pub scroll_y: f32,
+    /// Scroll offset to apply on the next frame instead of scrolling to
+    /// the pending selection
+    pending_scroll: Option<f32>,
+    /// Typed characters replace the characters after the cursor (toggled
+    /// with the Insert key)
+    pub overwrite: bool,
+    /// Bookmarked lines (1-indexed, sorted), kept for the session only
+    pub bookmarks: Vec<usize>,
+    /// Extra carets added with Ctrl+click (character indices, sorted),
+    /// besides the cursor; typing applies at all of them
+    pub carets: Vec<usize>,
+    /// Text as of the last recorded undo step
+    committed: String,
+    /// Typing that further keystrokes extend instead of adding undo steps
+    typing: Option<TypingGroup>,
 }
 
-impl EditorState {
-    /// Calculate line and column from character position
+/// One undo step: `removed` at `offset` was replaced by `inserted`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    /// Byte offset of the change
+    pub offset: usize,
+    /// Text before the change
+    pub removed: String,
+    /// Text after the change
+    pub inserted: String,
+}
+
+impl Delta {
+    /// Compute the change between two texts
     ///
     /// # Arguments
-    /// * `pos` - Character position in text
+    /// * `before` - Text before the change
+    /// * `after` - Text after the change
     ///
     /// # Returns
-    /// Tuple of (line, column) where both are 1-indexed
+    /// The change, or None if the texts are equal
     #[must_use]
-    pub fn position_to_line_column(&self, pos: usize) -> (usize, usize) {
-        let text_before = &self.text[..pos.min(self.text.len())];
-        let line = text_before.matches('\n').count() + 1;
-        let last_newline = text_before.rfind('\n').map_or(0, |i| i + 1);
-        let column = pos - last_newline + 1;
-        (line, column)
+    pub fn between(before: &str, after: &str) -> Option<Self> {
+        let (offset, removed_len, inserted) = changed_range(before, after);
+        (removed_len > 0 || !inserted.is_empty()).then(|| Self {
+            offset,
+            removed: before[offset..offset + removed_len].to_string(),
+            inserted: inserted.to_string(),
+        })
     }
 
-    /// Save current state to undo history
-    pub fn save_undo_state(&mut self) {
-        self.undo_history.push(self.text.clone());
-        // Limit undo history to prevent memory issues
-        if self.undo_history.len() > 100 {
-            self.undo_history.remove(0);
-        }
-        // Clear redo history when new edit is made
-        self.redo_history.clear();
+    /// Make the change to the text before it
+    ///
+    /// # Arguments
+    /// * `text` - Text to change
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(
+            self.offset..self.offset + self.removed.len(),
+            &self.inserted,
+        );
     }
 
-    /// Undo last edit
-    pub fn undo(&mut self) -> bool {
-        if let Some(previous) = self.undo_history.pop() {
-            let current = std::mem::replace(&mut self.text, previous);
-            self.redo_history.push(current);
-            true
-        } else {
-            false
+    /// Revert the change in the text after it
+    ///
+    /// # Arguments
+    /// * `text` - Text to change back
+    pub fn revert(&self, text: &mut String) {
+        text.replace_range(
+            self.offset..self.offset + self.inserted.len(),
+            &self.removed,
+        );
+    }
+
+    /// Combine with the change that follows it into one change
+    ///
+    /// # Arguments
+    /// * `next` - Following change
+    /// * `between` - Text after this change and before `next`
+    /// * `after` - Text after `next`
+    fn extend(&mut self, next: &Self, between: &str, after: &str) {
+        let start = self.offset.min(next.offset);
+        let inserted_end = self.offset + self.inserted.len();
+        let end = inserted_end.max(next.offset + next.removed.len());
+        let mut removed = between[start..self.offset].to_string();
+        removed.push_str(&self.removed);
+        removed.push_str(&between[inserted_end..end]);
+        let after_end = end - next.removed.len() + next.inserted.len();
+        self.inserted = after[start..after_end].to_string();
+        self.removed = removed;
+        self.offset = start;
+    }
+
+    /// Get the bytes allocated for the change
+    #[must_use]
+    pub const fn allocated_bytes(&self) -> usize {
+        self.removed.capacity() + self.inserted.capacity()
+    }
+}
+
+/// A burst of typing that is undone as one step
+#[derive(Debug, Clone, Copy)]
+struct TypingGroup {
+    /// Byte offset where the last edit ended
+    end: usize,
+    /// Time of the last edit in seconds
+    last_edit: f64,
+    /// Whether the typing is at several carets, where consecutive edits
+    /// are not next to each other
+    at_carets: bool,
+}
+
+/// Maximum number of undo steps kept
+const MAX_UNDO_STEPS: usize = 100;
+/// Pause in seconds after which typing starts a new undo step
+pub const TYPING_PAUSE_SECS: f64 = 1.0;
+
+/// Id of the editor's `TextEdit` widget
+const EDITOR_ID: &str = "editor_text_edit";
+/// Hint shown when a read-only document rejects an edit
+pub const READ_ONLY_DOCUMENT_HINT: &str = "Read-only document: editing is disabled";
+
+/// Make an offset safe for indexing a text
+///
+/// Offsets restored from widget state or persisted data may point past
+/// the end of the text or into the middle of a multi-byte character.
+///
+/// # Arguments
+/// * `text` - Text the offset indexes into
+/// * `offset` - Byte offset to sanitize
+///
+/// # Returns
+/// The offset clamped to the text length and snapped down to a char boundary
+#[must_use]
+pub fn sanitize_offset(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Get the character index where a line starts
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `line` - Line number (1-indexed); out-of-range values clamp to the last line
+///
+/// # Returns
+/// Character index of the first character of the line
+#[must_use]
+pub fn line_start_char(text: &str, line: usize) -> usize {
+    let mut current = 1;
+    let mut last_start = 0;
+    for (idx, ch) in text.chars().enumerate() {
+        if current >= line {
+            break;
+        }
+        if ch == '\n' {
+            current += 1;
+            last_start = idx + 1;
         }
     }
+    last_start
+}
 
-    /// Redo last undone edit
-    pub fn redo(&mut self) -> bool {
-        if let Some(next) = self.redo_history.pop() {
-            let current = std::mem::replace(&mut self.text, next);
-            self.undo_history.push(current);
-            true
+/// Parse the Go To dialog's input
+///
+/// # Arguments
+/// * `input` - "LINE" or "LINE:COLUMN", both 1-indexed
+///
+/// # Returns
+/// Tuple of (line, column), with column 1 for a plain line, or an error
+/// message for invalid input
+pub fn parse_line_column(input: &str) -> Result<(usize, usize), String> {
+    let input = input.trim();
+    let (line, column) = input
+        .split_once(':')
+        .map_or((input, None), |(line, column)| (line, Some(column)));
+    let parse = |value: &str| value.trim().parse::<usize>().ok().filter(|&n| n > 0);
+    let line = parse(line);
+    let column = column.map_or(Some(1), parse);
+    line.zip(column)
+        .ok_or_else(|| "Enter a line number, or line:column such as 120:35.".to_string())
+}
+
+/// Get the character index of a line and column
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `line` - Line number (1-indexed); out-of-range values clamp to the last line
+/// * `column` - Column in characters (1-indexed); clamps to the end of the line
+///
+/// # Returns
+/// Character index of the position
+#[must_use]
+pub fn line_column_char(text: &str, line: usize, column: usize) -> usize {
+    let start = line_start_char(text, line);
+    let length = text
+        .chars()
+        .skip(start)
+        .take_while(|&c| c != '\n' && c != '\r')
+        .count();
+    start + column.saturating_sub(1).min(length)
+}
+
+/// Move bookmarks along with the lines of an edit
+///
+/// Bookmarks below the edit move by the number of lines it added or
+/// removed, and bookmarks on removed lines join the line the edit starts
+/// on. An edit at the very start of a bookmarked line pushes the
+/// bookmark down with the line's text.
+///
+/// # Arguments
+/// * `bookmarks` - Bookmarked lines (1-indexed, sorted)
+/// * `before` - Text before the edit
+/// * `offset` - Byte offset of the edit
+/// * `removed` - Text the edit removed
+/// * `inserted` - Text the edit inserted
+pub fn shift_bookmarks(
+    bookmarks: &mut Vec<usize>,
+    before: &str,
+    offset: usize,
+    removed: &str,
+    inserted: &str,
+) {
+    let removed_lines = removed.matches('\n').count();
+    let inserted_lines = inserted.matches('\n').count();
+    if bookmarks.is_empty() || (removed_lines == 0 && inserted_lines == 0) {
+        return;
+    }
+    let head = &before[..offset];
+    let line = head.matches('\n').count() + 1;
+    let at_line_start = head.is_empty() || head.ends_with('\n');
+    for bookmark in bookmarks.iter_mut() {
+        *bookmark = if *bookmark < line {
+            *bookmark
+        } else if *bookmark == line {
+            if at_line_start && removed_lines == 0 {
+                line + inserted_lines
+            } else {
+                line
+            }
+        } else if *bookmark <= line + removed_lines {
+            line
         } else {
-            false
+            *bookmark + inserted_lines - removed_lines
+        };
+    }
+    bookmarks.sort_unstable();
+    bookmarks.dedup();
+}
+
+/// Find the bookmark to jump to from a line, wrapping around
+///
+/// # Arguments
+/// * `bookmarks` - Bookmarked lines (1-indexed, sorted)
+/// * `line` - Line of the cursor
+/// * `forward` - Find the next bookmark instead of the previous one
+///
+/// # Returns
+/// Line of the bookmark, or None without bookmarks
+#[must_use]
+pub fn bookmark_from(bookmarks: &[usize], line: usize, forward: bool) -> Option<usize> {
+    if forward {
+        bookmarks
+            .iter()
+            .find(|&&bookmark| bookmark > line)
+            .or_else(|| bookmarks.first())
+            .copied()
+    } else {
+        bookmarks
+            .iter()
+            .rev()
+            .find(|&&bookmark| bookmark < line)
+            .or_else(|| bookmarks.last())
+            .copied()
+    }
+}
+
+/// Find the lines touched by a selection
+///
+/// A selection ending at the start of a line does not include that line.
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `start` - Selection start character index
+/// * `end` - Selection end character index
+///
+/// # Returns
+/// Byte range of the lines, without the final line break
+fn line_block(text: &str, start: usize, end: usize) -> (usize, usize) {
+    let start_byte = char_to_byte(text, start);
+    let mut end_byte = char_to_byte(text, end).max(start_byte);
+    if end_byte > start_byte && text[..end_byte].ends_with('\n') {
+        end_byte -= 1;
+    }
+    let block_start = text[..start_byte].rfind('\n').map_or(0, |i| i + 1);
+    let block_end = text[end_byte..]
+        .find('\n')
+        .map_or(text.len(), |i| end_byte + i);
+    (block_start, block_end)
+}
+
+/// Swap the lines touched by a selection with the line above or below
+///
+/// # Arguments
+/// * `text` - Text to edit
+/// * `start` - Selection start character index
+/// * `end` - Selection end character index
+/// * `up` - Move up instead of down
+///
+/// # Returns
+/// Tuple of (new text, new selection start, new selection end), or None
+/// if the lines are already first or last
+#[must_use]
+pub fn move_lines(
+    text: &str,
+    start: usize,
+    end: usize,
+    up: bool,
+) -> Option<(String, usize, usize)> {
+    let (block_start, block_end) = line_block(text, start, end);
+    let block = &text[block_start..block_end];
+    if up {
+        if block_start == 0 {
+            return None;
         }
+        let above_start = text[..block_start - 1].rfind('\n').map_or(0, |i| i + 1);
+        let above = &text[above_start..block_start - 1];
+        let moved = format!(
+            "{}{block}\n{above}{}",
+            &text[..above_start],
+            &text[block_end..]
+        );
+        let shift = above.chars().count() + 1;
+        Some((moved, start - shift, end - shift))
+    } else {
+        if block_end == text.len() {
+            return None;
+        }
+        let below_end = text[block_end + 1..]
+            .find('\n')
+            .map_or(text.len(), |i| block_end + 1 + i);
+        let below = &text[block_end + 1..below_end];
+        let moved = format!(
+            "{}{below}\n{block}{}",
+            &text[..block_start],
+            &text[below_end..]
+        );
+        let shift = below.chars().count() + 1;
+        Some((moved, start + shift, end + shift))
     }
 }
 
-/// Show the text editor widget
+/// Delete the lines touched by a selection, including their line break
+///
+/// The cursor moves to the start of the following line. When the last
+/// line is deleted, the line break before it goes too and the cursor
+/// moves to the start of the new last line.
+///
+/// # Arguments
+/// * `text` - Text to edit
+/// * `start` - Selection start character index
+/// * `end` - Selection end character index
+///
+/// # Returns
+/// Tuple of (new text, cursor character index)
+#[must_use]
+pub fn delete_lines(text: &str, start: usize, end: usize) -> (String, usize) {
+    let (block_start, block_end) = line_block(text, start, end);
+    let (remove_start, remove_end, cursor_byte) = if block_end < text.len() {
+        (block_start, block_end + 1, block_start)
+    } else if block_start > 0 {
+        let previous_start = text[..block_start - 1].rfind('\n').map_or(0, |i| i + 1);
+        (block_start - 1, block_end, previous_start)
+    } else {
+        (0, block_end, 0)
+    };
+    let mut result = String::with_capacity(text.len() - (remove_end - remove_start));
+    result.push_str(&text[..remove_start]);
+    result.push_str(&text[remove_end..]);
+    let cursor = byte_to_char(&result, cursor_byte);
+    (result, cursor)
+}
+
+/// Duplicate the line containing the cursor
+///
+/// The copy is inserted below the line. A last line without a trailing
+/// newline gets a newline between the line and its copy.
+///
+/// # Arguments
+/// * `text` - Text to edit
+/// * `cursor` - Cursor character index
+///
+/// # Returns
+/// Tuple of (new text, cursor at the same column in the copy)
+#[must_use]
+pub fn duplicate_line(text: &str, cursor: usize) -> (String, usize) {
+    let cursor_byte = char_to_byte(text, cursor);
+    let line_start = text[..cursor_byte].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[cursor_byte..]
+        .find('\n')
+        .map_or(text.len(), |i| cursor_byte + i);
+    let line = &text[line_start..line_end];
+    let mut result = String::with_capacity(text.len() + line.len() + 1);
+    result.push_str(&text[..line_end]);
+    result.push('\n');
+    result.push_str(line);
+    result.push_str(&text[line_end..]);
+    (result, cursor + line.chars().count() + 1)
+}
+
+/// Minimum number of digits the line number gutter has room for
+///
+/// Keeps the gutter from changing width while a document grows to
+/// five-digit line counts.
+const MIN_GUTTER_DIGITS: usize = 5;
+
+/// Get the number of digits the line number gutter needs
+///
+/// # Arguments
+/// * `line_count` - Number of lines in the document
+///
+/// # Returns
+/// Digits to reserve room for
+#[must_use]
+pub fn gutter_digits(line_count: usize) -> usize {
+    line_count.max(1).to_string().len().max(MIN_GUTTER_DIGITS)
+}
+
+/// Number the visual rows of a laid-out text
+///
+/// Only the first visual row of each logical line gets a number, so
+/// wrapped continuation rows stay blank.
+///
+/// # Arguments
+/// * `ends_with_newline` - For each visual row, whether it ends a logical line
+///
+/// # Returns
+/// The 1-based line number to draw next to each row, if any
+pub fn row_line_numbers(ends_with_newline: impl IntoIterator<Item = bool>) -> Vec<Option<usize>> {
+    let mut line = 1;
+    let mut starts_line = true;
+    ends_with_newline
+        .into_iter()
+        .map(|ends_line| {
+            let number = starts_line.then_some(line);
+            if ends_line {
+                line += 1;
+            }
+            starts_line = ends_line;
+            number
+        })
+        .collect()
+}
+
+/// Convert a character index to a byte offset
+///
+/// # Arguments
+/// * `text` - Text the index refers to
+/// * `char_index` - Character index, as used by egui cursors
+///
+/// # Returns
+/// Byte offset, clamped to the text length
+#[must_use]
+pub fn char_to_byte(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// Convert a byte offset to a character index
+///
+/// # Arguments
+/// * `text` - Text the offset refers to
+/// * `byte` - Byte offset
+///
+/// # Returns
+/// Character index, as used by egui cursors
+#[must_use]
+pub fn byte_to_char(text: &str, byte: usize) -> usize {
+    text[..sanitize_offset(text, byte)].chars().count()
+}
+
+/// Replace a character range of a text
+///
+/// # Arguments
+/// * `text` - Text to edit
+/// * `start` - First character index to replace
+/// * `end` - Character index after the replaced range
+/// * `replacement` - Text to insert
+///
+/// # Returns
+/// The text that was replaced
+pub fn splice_chars(text: &mut String, start: usize, end: usize, replacement: &str) -> String {
+    let start = char_to_byte(text, start);
+    let end = char_to_byte(text, end).max(start);
+    let removed = text[start..end].to_string();
+    text.replace_range(start..end, replacement);
+    removed
+}
+
+/// Opacity of the current line highlight
+const CURRENT_LINE_ALPHA: u8 = 14;
+/// Opacity of the background behind incremental search matches
+const SEARCH_MATCH_OPACITY: f32 = 0.3;
+
+/// Get the cursor whose line is highlighted
 ///
 /// # Arguments
 /// * `ui` - egui UI context
 /// * `app` - Application state
-pub fn show_editor(ui: &mut egui::Ui, app: &mut NodepatApp) {
-    // Constants for row calculation
-    const MAX_ROWS: f32 = 1_000_000.0; // Reasonable maximum for UI
+/// * `editor_id` - Id of the editor's `TextEdit`
+///
+/// # Returns
+/// Character index of the cursor, or None if the highlight is turned off
+fn current_line_cursor(ui: &egui::Ui, app: &NodepatApp, editor_id: egui::Id) -> Option<usize> {
+    app.config.highlight_current_line.then(|| {
+        egui::TextEdit::load_state(ui.ctx(), editor_id)
+            .and_then(|state| state.cursor.char_range())
+            .map_or(app.editor_state.selection.1, |range| range.primary.index)
+    })
+}
 
-    // Get the full available height before any widgets
-    let available_height = ui.available_height();
+/// Tint the visual row containing a cursor, behind its text
+///
+/// Only the row the cursor is on is tinted when a line wraps. The tint
+/// spans the whole editor width.
+///
+/// # Arguments
+/// * `galley` - Laid out editor text
+/// * `cursor` - Character index of the cursor
+/// * `width` - Width of the editor
+/// * `color` - Tint color
+fn paint_current_row(
+    galley: &mut Arc<egui::Galley>,
+    cursor: usize,
+    width: f32,
+    color: egui::Color32,
+) {
+    let row_index = galley
+        .layout_from_cursor(egui::text::CCursor::new(cursor))
+        .row;
+    let galley = Arc::make_mut(galley);
+    let Some(placed) = galley.rows.get_mut(row_index) else {
+        return;
+    };
+    let row = Arc::make_mut(&mut placed.row);
+    let width = width.max(row.size.x);
+    let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width, row.size.y));
+    let mesh = &mut row.visuals.mesh;
+    mesh.add_colored_rect(rect, color);
+    // Move the new triangles first, so everything else is painted on top
+    mesh.indices.rotate_right(6);
+    row.visuals.mesh_bounds = mesh.calc_bounds();
+    row.visuals.glyph_index_start += 6;
+    let bounds = row.visuals.mesh_bounds.translate(placed.pos.to_vec2());
+    galley.mesh_bounds = galley.mesh_bounds.union(bounds);
+}
+
+/// Ranges the editor layout marks besides syntax and spelling
+struct LayoutMarks {
+    /// Selected character range to paint while the editor is unfocused
+    inactive_selection: Option<(usize, usize)>,
+    /// Cursor whose row is tinted, if the current line is highlighted
+    current_line: Option<usize>,
+    /// Byte ranges of the incremental search matches
+    search_matches: Vec<std::ops::Range<usize>>,
+}
+
+/// Collect the ranges the editor layout marks this frame
+///
+/// The incremental search matches are updated here, so the search bar
+/// shows the count of what the editor highlights.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `editor_id` - Id of the editor's `TextEdit`
+///
+/// # Returns
+/// The selection, current line and search matches to mark
+fn layout_marks(ui: &egui::Ui, app: &mut NodepatApp, editor_id: egui::Id) -> LayoutMarks {
+    let inactive_selection =
+        (!ui.memory(|m| m.has_focus(editor_id))).then_some(app.editor_state.selection);
+    let current_line = current_line_cursor(ui, app, editor_id);
+    let search_matches = app.search_bar.as_mut().map_or_else(Vec::new, |bar| {
+        bar.matches = match_ranges(&app.search_state, &app.editor_state.text).unwrap_or_default();
+        bar.matches.clone()
+    });
+    LayoutMarks {
+        inactive_selection,
+        current_line,
+        search_matches,
+    }
+}
 
-    // Word wrap is always enabled - only vertical scrolling, text wraps to width
-    egui::ScrollArea::vertical()
-        .auto_shrink([false; 2])
-        .show(ui, |ui| {
-            ui.set_min_height(available_height);
-
-            // Calculate desired rows using clamp (adjust line height based on font size)
-            let font_size = app.format_settings.font_size;
-            let line_height = font_size * 1.2; // Line height is typically 1.2x font size
-            let rows_f32 = (available_height / line_height).clamp(1.0, MAX_ROWS);
-            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            let desired_rows = rows_f32 as usize;
-
-            // Apply font settings locally for the editor
-            // This ensures UI elements like checkboxes aren't affected
-            let font_size = app.format_settings.font_size;
-            let font_id = match app.format_settings.font_family_type {
-                crate::format::FontFamily::Monospace => egui::FontId::monospace(font_size),
-                crate::format::FontFamily::Proportional => egui::FontId::proportional(font_size),
+/// Create the layouter for the editor's `TextEdit`
+///
+/// The `TextEdit` only draws the selection while focused, so an inactive
+/// selection, e.g. a match while the Find dialog has focus, is painted
+/// into the layout instead.
+///
+/// # Arguments
+/// * `font_id` - Editor font
+/// * `word_wrap` - Wrap lines at the editor width
+/// * `tab_width` - Columns a tab advances
+/// * `marks` - Inactive selection, current line and search matches to mark
+/// * `highlight` - Cache and language for syntax highlighting, if enabled
+/// * `spelling` - Spell checker to underline misspelled words, if enabled
+///
+/// # Returns
+/// Layouter closure for `TextEdit::layouter`
+fn editor_layouter<'a>(
+    font_id: egui::FontId,
+    word_wrap: bool,
+    tab_width: u8,
+    marks: LayoutMarks,
+    mut highlight: Option<(&'a mut HighlightCache, Language)>,
+    mut spelling: Option<&'a mut SpellChecker>,
+) -> impl FnMut(&egui::Ui, &dyn egui::TextBuffer, f32) -> Arc<egui::Galley> + 'a {
+    let LayoutMarks {
+        inactive_selection,
+        current_line,
+        search_matches,
+    } = marks;
+    let selection = inactive_selection
+        .filter(|(start, end)| start != end)
+        .map(|(start, end)| {
+            egui::text::CCursorRange::two(
+                egui::text::CCursor::new(start),
+                egui::text::CCursor::new(end),
+            )
+        });
+    move |ui, text, wrap_width| {
+        let color = ui
+            .visuals()
+            .override_text_color
+            .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+        // Without word wrap, lay out lines at their full width
+        let mut job = egui::text::LayoutJob::simple(
+            text.as_str().to_owned(),
+            font_id.clone(),
+            color,
+            if word_wrap { wrap_width } else { f32::INFINITY },
+        );
+        if let Some((cache, language)) = &mut highlight {
+            let spans = cache.spans(&job.text, *language, ui.visuals().dark_mode);
+            set_highlight_colors(&mut job, spans);
+        }
+        if let Some(checker) = &mut spelling {
+            let misspelled = checker.misspelled(&job.text).to_vec();
+            let stroke = egui::Stroke::new(1.0, ui.visuals().error_fg_color);
+            mark_ranges(&mut job, &misspelled, |format| format.underline = stroke);
+        }
+        let links = find_links(&job.text);
+        let stroke = egui::Stroke::new(1.0, ui.visuals().hyperlink_color);
+        mark_ranges(&mut job, &links, |format| format.underline = stroke);
+        let match_color = ui
+            .visuals()
+            .warn_fg_color
+            .gamma_multiply(SEARCH_MATCH_OPACITY);
+        mark_ranges(&mut job, &search_matches, |format| {
+            format.background = match_color;
+        });
+        let tab_width = usize::from(tab_width);
+        if tab_width != egui::text::TAB_SIZE && job.text.contains('\t') {
+            let space_width = ui.fonts_mut(|f| f.glyph_width(&font_id, ' '));
+            #[allow(clippy::cast_precision_loss)]
+            let extra = (tab_width as f32 - egui::text::TAB_SIZE as f32) * space_width;
+            set_tab_spacing(&mut job, extra);
+        }
+        let mut galley = ui.fonts_mut(|f| f.layout_job(job));
+        if let Some(cursor) = current_line {
+            let color = if ui.visuals().dark_mode {
+                egui::Color32::from_white_alpha(CURRENT_LINE_ALPHA)
+            } else {
+                egui::Color32::from_black_alpha(CURRENT_LINE_ALPHA)
             };
+            paint_current_row(&mut galley, cursor, wrap_width, color);
+        }
+        if let Some(range) = &selection {
+            paint_text_selection(&mut galley, ui.visuals(), range, None);
+        }
+        galley
+    }
+}
+
+/// Color the highlighted ranges of a layout job
+///
+/// # Arguments
+/// * `job` - Layout job with a single section
+/// * `spans` - Byte ranges and their colors, in text order
+fn set_highlight_colors(
+    job: &mut egui::text::LayoutJob,
+    spans: &[(std::ops::Range<usize>, egui::Color32)],
+) {
+    let Some(format) = job.sections.first().map(|section| section.format.clone()) else {
+        return;
+    };
+    if spans.is_empty() {
+        return;
+    }
+    let section = |byte_range: std::ops::Range<usize>, color| egui::text::LayoutSection {
+        leading_space: 0.0,
+        byte_range,
+        format: egui::TextFormat {
+            color,
+            ..format.clone()
+        },
+    };
+    let mut sections = Vec::new();
+    let mut plain_start = 0;
+    for (range, color) in spans {
+        if range.start > plain_start {
+            sections.push(section(plain_start..range.start, format.color));
+        }
+        sections.push(section(range.clone(), *color));
+        plain_start = range.end;
+    }
+    if plain_start < job.text.len() {
+        sections.push(section(plain_start..job.text.len(), format.color));
+    }
+    job.sections = sections;
+}
 
-            // Apply font to the editor's UI context only
-            ui.style_mut()
-                .text_styles
-                .insert(egui::TextStyle::Body, font_id.clone());
-            ui.style_mut()
-                .text_styles
-                .insert(egui::TextStyle::Monospace, font_id);
-
-            // Use appropriate text style based on font family
-            let text_style = match app.format_settings.font_family_type {
-                crate::format::FontFamily::Monospace => egui::TextStyle::Monospace,
-                crate::format::FontFamily::Proportional => egui::TextStyle::Body,
+/// Change the format of ranges of a layout job, e.g. to underline
+/// misspelled words
+///
+/// Sections are split at the range boundaries and keep their colors.
+///
+/// # Arguments
+/// * `job` - Layout job
+/// * `ranges` - Byte ranges to mark, in text order
+/// * `mark` - Change applied to the format of the marked pieces
+fn mark_ranges(
+    job: &mut egui::text::LayoutJob,
+    ranges: &[std::ops::Range<usize>],
+    mark: impl Fn(&mut egui::TextFormat),
+) {
+    if ranges.is_empty() {
+        return;
+    }
+    let mut ranges = ranges.iter().peekable();
+    let mut sections = Vec::with_capacity(job.sections.len());
+    for section in std::mem::take(&mut job.sections) {
+        let std::ops::Range { mut start, end } = section.byte_range;
+        while start < end {
+            while ranges.next_if(|range| range.end <= start).is_some() {}
+            let (piece_end, marked) = match ranges.peek() {
+                Some(range) if range.start <= start => (range.end.min(end), true),
+                Some(range) => (range.start.min(end), false),
+                None => (end, false),
             };
-            let text_edit = egui::TextEdit::multiline(&mut app.editor_state.text)
-                .desired_width(f32::INFINITY)
-                .desired_rows(desired_rows)
-                .font(text_style)
-                .show(ui);
-
-            // Update cursor position
-            if let Some(cursor_range) = text_edit.cursor_range {
-                let cursor_pos = cursor_range.primary.index;
-                let (line, column) = app.editor_state.position_to_line_column(cursor_pos);
-                app.editor_state.cursor_line = line;
-                app.editor_state.cursor_column = column;
+            let mut format = section.format.clone();
+            if marked {
+                mark(&mut format);
             }
-        });
-
-    // Handle keyboard shortcuts
-    ui.input(|i| {
-        // Ctrl+Z: Undo
-        if i.key_pressed(egui::Key::Z) && i.modifiers.ctrl && app.editor_state.undo() {
-            app.file_state.is_modified = true;
+            sections.push(egui::text::LayoutSection {
+                leading_space: if start == section.byte_range.start {
+                    section.leading_space
+                } else {
+                    0.0
+                },
+                byte_range: start..piece_end,
+                format,
+            });
+            start = piece_end;
         }
-        // Ctrl+Y: Redo
-        if i.key_pressed(egui::Key::Y) && i.modifiers.ctrl && app.editor_state.redo() {
-            app.file_state.is_modified = true;
+    }
+    job.sections = sections;
+}
+
+/// Widen or narrow the tabs of a layout job
+///
+/// egui draws a tab as `TAB_SIZE` spaces; each tab gets its own section
+/// with leading space to reach the configured width.
+///
+/// # Arguments
+/// * `job` - Layout job
+/// * `extra` - Width added before each tab, in points
+fn set_tab_spacing(job: &mut egui::text::LayoutJob, extra: f32) {
+    let mut sections = Vec::new();
+    for original in std::mem::take(&mut job.sections) {
+        let section =
+            |byte_range: std::ops::Range<usize>, leading_space: f32| egui::text::LayoutSection {
+                leading_space,
+                byte_range,
+                format: original.format.clone(),
+            };
+        let range = original.byte_range.clone();
+        let mut run_start = range.start;
+        for (offset, _) in job.text[range.clone()].match_indices('\t') {
+            let tab = range.start + offset;
+            if tab > run_start {
+                sections.push(section(run_start..tab, 0.0));
+            }
+            sections.push(section(tab..tab + 1, extra));
+            run_start = tab + 1;
         }
-        // F5: Insert Time/Date
-        if i.key_pressed(egui::Key::F5) {
-            insert_time_date(&mut app.editor_state);
-            app.file_state.is_modified = true;
+        if run_start < range.end {
+            sections.push(section(run_start..range.end, 0.0));
         }
-    });
+    }
+    job.sections = sections;
 }
 
-/// Insert current time and date at cursor position
+/// Move the keyboard focus to the editor, e.g. when a dialog closes
 ///
 /// # Arguments
-/// * `editor` - Editor state
-pub fn insert_time_date(editor: &mut EditorState) {
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-
-    let secs = now.as_secs();
-    let datetime = secs % 86400; // Seconds since midnight
-
-    let hours = datetime / 3600;
-    let minutes = (datetime % 3600) / 60;
-    let seconds = datetime % 60;
-
-    // Calculate date (simplified, assumes UTC)
-    let days = secs / 86400;
-    let epoch_days = days + 719_163; // Days since 0000-01-01 (approximate)
-    let year = 1970 + (epoch_days / 365);
-    let day_of_year = epoch_days % 365;
-    let month = (day_of_year / 30) + 1;
-    let day = (day_of_year % 30) + 1;
-
-    let time_str = format!("{hours:02}:{minutes:02}:{seconds:02} {month:02}/{day:02}/{year}");
-    // Note: In a real implementation, we'd need to get cursor position from the text edit widget
-    // For now, append to end
-    editor.text.push_str(&time_str);
+/// * `ctx` - egui context
+pub fn focus_editor(ctx: &egui::Context) {
+    ctx.memory_mut(|m| m.request_focus(egui::Id::new(EDITOR_ID)));
+}
+
+/// Find the part of a text that an edit changed
+///
+/// # Arguments
+/// * `before` - Text before the edit
+/// * `after` - Text after the edit
+///
+/// # Returns
+/// Tuple of (byte offset of the change, removed byte length, inserted text)
+fn changed_range<'a>(before: &str, after: &'a str) -> (usize, usize, &'a str) {
+    let mut prefix = before
+        .bytes()
+        .zip(after.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !before.is_char_boundary(prefix) || !after.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = before.len().min(after.len()) - prefix;
+    let mut suffix = before
+        .bytes()
+        .rev()
+        .zip(after.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !after.is_char_boundary(after.len() - suffix) {
+        suffix -= 1;
+    }
+    (
+        prefix,
+        before.len() - prefix - suffix,
+        &after[prefix..after.len() - suffix],
+    )
+}
+
+/// Set the selection of a `TextEdit`, applied when it is next shown
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `id` - Id of the `TextEdit`
+/// * `start` - First selected character index
+/// * `end` - Character index after the selection, where the cursor goes
+pub fn place_selection(ui: &egui::Ui, id: egui::Id, start: usize, end: usize) {
+    let mut state = egui::text_edit::TextEditState::load(ui.ctx(), id).unwrap_or_default();
+    state
+        .cursor
+        .set_char_range(Some(egui::text::CCursorRange::two(
+            egui::text::CCursor::new(start),
+            egui::text::CCursor::new(end),
+        )));
+    state.store(ui.ctx(), id);
+}
+
+/// Show the editor's `TextEdit` with a line number gutter on its left
+///
+/// The numbers use the editor font and the laid-out row positions, so
+/// they stay aligned when zooming and with wrapped lines.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `text_edit` - Editor widget to show
+/// * `font_id` - Editor font
+/// * `line_count` - Number of lines in the document
+/// * `bookmarks` - Bookmarked lines to mark in the gutter
+///
+/// # Returns
+/// Output of the `TextEdit`
+fn show_with_line_numbers(
+    ui: &mut egui::Ui,
+    text_edit: egui::TextEdit<'_>,
+    font_id: &egui::FontId,
+    line_count: usize,
+    bookmarks: &[usize],
+) -> egui::text_edit::TextEditOutput {
+    // Room around the line numbers in the gutter
+    const GUTTER_PADDING: f32 = 8.0;
+
+    // One more digit's width holds the bookmark markers
+    #[allow(clippy::cast_precision_loss)]
+    let digits = (gutter_digits(line_count) + 1) as f32;
+    let digit_width = ui.fonts_mut(|f| f.glyph_width(font_id, '0'));
+    let gutter_width = digit_width.mul_add(digits, GUTTER_PADDING);
+    let output = ui
+        .horizontal_top(|ui| {
+            ui.add_space(gutter_width);
+            text_edit.show(ui)
+        })
+        .inner;
+    let right_edge =
+        output.response.rect.left() - ui.spacing().item_spacing.x - GUTTER_PADDING / 2.0;
+    paint_line_numbers(ui, &output, font_id, right_edge, bookmarks);
+    output
+}
+
+/// Paint line numbers to the left of the editor text
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `output` - Output of the editor's `TextEdit`
+/// * `font_id` - Editor font
+/// * `right_edge` - X position the numbers are right-aligned to
+/// * `bookmarks` - Bookmarked lines, marked left of their numbers
+fn paint_line_numbers(
+    ui: &egui::Ui,
+    output: &egui::text_edit::TextEditOutput,
+    font_id: &egui::FontId,
+    right_edge: f32,
+    bookmarks: &[usize],
+) {
+    let clip = ui.clip_rect();
+    let color = ui.visuals().weak_text_color();
+    let marker_color = ui.visuals().selection.bg_fill;
+    let marker_x = ui.min_rect().left() + font_id.size / 2.0;
+    let numbers = row_line_numbers(output.galley.rows.iter().map(|row| row.ends_with_newline));
+    for (row, number) in output.galley.rows.iter().zip(numbers) {
+        let Some(number) = number else {
+            continue;
+        };
+        let rect = row.rect().translate(output.galley_pos.to_vec2());
+        if rect.bottom() < clip.top() || rect.top() > clip.bottom() {
+            continue;
+        }
+        ui.painter().text(
+            egui::pos2(right_edge, rect.top()),
+            egui::Align2::RIGHT_TOP,
+            number.to_string(),
+            font_id.clone(),
+            color,
+        );
+        if bookmarks.binary_search(&number).is_ok() {
+            let center = egui::pos2(marker_x, rect.center().y);
+            ui.painter()
+                .circle_filled(center, font_id.size / 4.0, marker_color);
+        }
+    }
+}
+
+impl EditorState {
+    /// Replace the whole document, e.g. after loading a file
+    ///
+    /// Clears the undo/redo history and the `TextEdit` widget state so no
+    /// stale cursor from the previous document is restored.
+    ///
+    /// # Arguments
+    /// * `text` - New document content
+    pub fn set_document(&mut self, text: String) {
+        self.committed.clone_from(&text);
+        self.text = text;
+        self.undo_history.clear();
+        self.redo_history.clear();
+        self.typing = None;
+        self.cursor_line = 1;
+        self.cursor_column = 1;
+        self.reset_widget_state = true;
+        self.selection = (0, 0);
+        self.pending_selection = None;
+        self.scroll_y = 0.0;
+        self.pending_scroll = Some(0.0);
+        self.overwrite = false;
+        self.bookmarks.clear();
+        self.carets.clear();
+    }
+
+    /// Add or remove a bookmark on the cursor's line
+    pub fn toggle_bookmark(&mut self) {
+        let line = self.cursor_line.max(1);
+        match self.bookmarks.binary_search(&line) {
+            Ok(index) => {
+                self.bookmarks.remove(index);
+            }
+            Err(index) => self.bookmarks.insert(index, line),
+        }
+    }
+
+    /// Show this document's cursor, selection, and scroll position again
+    /// on the next frame
+    ///
+    /// For a document that becomes active after another one was edited
+    /// in the same widget.
+    pub const fn reactivate(&mut self) {
+        self.reset_widget_state = true;
+        self.pending_selection = Some(self.selection);
+        self.pending_scroll = Some(self.scroll_y);
+    }
+
+    /// Place the cursor and scroll the editor to a remembered position
+    ///
+    /// # Arguments
+    /// * `cursor` - Character index of the cursor
+    /// * `scroll_y` - Vertical scroll offset, in points
+    pub const fn restore_position(&mut self, cursor: usize, scroll_y: f32) {
+        self.select(cursor, cursor);
+        self.scroll_y = scroll_y;
+        self.pending_scroll = Some(scroll_y);
+    }
+
+    /// Select a character range in the editor on the next frame
+    ///
+    /// # Arguments
+    /// * `start` - First selected character index
+    /// * `end` - Character index after the selection
+    pub const fn select(&mut self, start: usize, end: usize) {
+        self.selection = (start, end);
+        self.pending_selection = Some((start, end));
+    }
+
+    /// Insert text at the cursor, replacing the selection
+    ///
+    /// The insertion is one undo step and the cursor moves to the end of
+    /// the inserted text.
+    ///
+    /// # Arguments
+    /// * `inserted` - Text to insert
+    ///
+    /// # Returns
+    /// The selected text that was replaced
+    pub fn insert_at_cursor(&mut self, inserted: &str) -> String {
+        let (start, end) = self.selection;
+        self.save_undo_state();
+        let replaced = splice_chars(&mut self.text, start, end, inserted);
+        self.save_undo_state();
+        let cursor = start + inserted.chars().count();
+        self.select(cursor, cursor);
+        replaced
+    }
+
+    /// Update cursor position and selection from the text edit
+    ///
+    /// # Arguments
+    /// * `cursor_range` - Cursor range reported by the text edit
+    fn update_cursor(&mut self, cursor_range: egui::text::CCursorRange) {
+        let range = cursor_range.as_sorted_char_range();
+        self.selection = (range.start, range.end);
+        let (line, column) = self.position_to_line_column(cursor_range.primary.index);
+        self.cursor_line = line;
+        self.cursor_column = column;
+    }
+
+    /// Remove the selected text
+    ///
+    /// # Returns
+    /// The removed text, empty if nothing is selected
+    pub fn cut(&mut self) -> String {
+        let (start, end) = self.selection;
+        if start == end {
+            return String::new();
+        }
+        self.insert_at_cursor("")
+    }
+
+    /// Delete the selection, or the character after the cursor
+    ///
+    /// # Returns
+    /// True if text was deleted
+    pub fn delete(&mut self) -> bool {
+        let (start, end) = self.selection;
+        if start == end {
+            if start >= self.text.chars().count() {
+                return false;
+            }
+            self.selection = (start, start + 1);
+        }
+        self.insert_at_cursor("");
+        true
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.select(0, self.text.chars().count());
+    }
+
+    /// Duplicate the current line, or the selection if there is one
+    ///
+    /// The duplication is one undo step. A duplicated line is inserted
+    /// below with the cursor moving along; a duplicated selection is
+    /// inserted after it and selected.
+    pub fn duplicate(&mut self) {
+        let (start, end) = self.selection;
+        self.save_undo_state();
+        if start == end {
+            let (text, cursor) = duplicate_line(&self.text, start);
+            self.text = text;
+            self.select(cursor, cursor);
+        } else {
+            let selected = self
+                .text
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<String>();
+            splice_chars(&mut self.text, end, end, &selected);
+            self.select(end, end + (end - start));
+        }
+        self.save_undo_state();
+    }
+
+    /// Move the current or selected lines up or down by one line
+    ///
+    /// The move is one undo step and the selection moves with the lines.
+    ///
+    /// # Arguments
+    /// * `up` - Move up instead of down
+    ///
+    /// # Returns
+    /// True if the lines moved; false at the start or end of the text
+    pub fn move_lines(&mut self, up: bool) -> bool {
+        let (start, end) = self.selection;
+        let Some((text, start, end)) = move_lines(&self.text, start, end, up) else {
+            return false;
+        };
+        self.save_undo_state();
+        self.text = text;
+        self.save_undo_state();
+        self.select(start, end);
+        true
+    }
+
+    /// Delete the current line, or all lines the selection touches
+    ///
+    /// The deletion is one undo step.
+    pub fn delete_lines(&mut self) {
+        let (start, end) = self.selection;
+        let (text, cursor) = delete_lines(&self.text, start, end);
+        if text == self.text {
+            return;
+        }
+        self.save_undo_state();
+        self.text = text;
+        self.save_undo_state();
+        self.select(cursor, cursor);
+    }
+
+    /// Rewrite the selected lines, or the whole document without a selection
+    ///
+    /// The rewrite is one undo step. A selection grows to cover the
+    /// rewritten lines.
+    ///
+    /// # Arguments
+    /// * `transform` - Returns the new text of the lines, e.g. sorted
+    pub fn transform_lines(&mut self, transform: impl Fn(&str) -> String) {
+        let (start, end) = self.selection;
+        let (region_start, region_end) = if start == end {
+            (0, self.text.len())
+        } else {
+            line_block(&self.text, start, end)
+        };
+        let transformed = transform(&self.text[region_start..region_end]);
+        if transformed == self.text[region_start..region_end] {
+            return;
+        }
+        self.save_undo_state();
+        self.text
+            .replace_range(region_start..region_end, &transformed);
+        self.save_undo_state();
+        if start != end {
+            let region_start = byte_to_char(&self.text, region_start);
+            self.select(region_start, region_start + transformed.chars().count());
+        }
+    }
+
+    /// Convert the case of the selection, or of the whole document
+    ///
+    /// The conversion is one undo step, and the converted selection stays
+    /// selected so another conversion can follow.
+    ///
+    /// # Arguments
+    /// * `conversion` - Case to convert to
+    pub fn convert_case(&mut self, conversion: crate::case::CaseConversion) {
+        let (start, end) = self.selection;
+        let (start, end) = if start == end {
+            (0, self.text.chars().count())
+        } else {
+            (start, end)
+        };
+        let range = char_to_byte(&self.text, start)..char_to_byte(&self.text, end);
+        let converted = crate::case::convert_case(&self.text[range.clone()], conversion);
+        if converted == self.text[range.clone()] {
+            return;
+        }
+        self.save_undo_state();
+        self.text.replace_range(range, &converted);
+        self.save_undo_state();
+        if self.selection.0 != self.selection.1 {
+            self.select(start, start + converted.chars().count());
+        }
+    }
+
+    /// Remove spaces and tabs at the end of every line
+    ///
+    /// The trim is one undo step.
+    ///
+    /// # Returns
+    /// Number of lines changed
+    pub fn trim_trailing_whitespace(&mut self) -> usize {
+        let (text, changed) = crate::lines::trim_trailing_whitespace(&self.text);
+        if changed == 0 {
+            return 0;
+        }
+        self.save_undo_state();
+        self.text = text;
+        self.save_undo_state();
+        let len = self.text.chars().count();
+        let (start, end) = self.selection;
+        self.select(start.min(len), end.min(len));
+        changed
+    }
+
+    /// Remove the characters that typed text is about to overwrite
+    ///
+    /// Each typed character replaces the character after the cursor. Line
+    /// breaks and the end of the text are never overwritten, and typing
+    /// over a selection only replaces the selection. The cursor does not
+    /// move, so the editor widget inserts the typed text in their place.
+    ///
+    /// # Arguments
+    /// * `typed` - Number of characters about to be typed
+    ///
+    /// # Returns
+    /// The removed text
+    pub fn overwrite_ahead(&mut self, typed: usize) -> String {
+        let (start, end) = self.selection;
+        if start != end {
+            return String::new();
+        }
+        let count = self
+            .text
+            .chars()
+            .skip(start)
+            .take(typed)
+            .take_while(|&c| c != '\n' && c != '\r')
+            .count();
+        splice_chars(&mut self.text, start, start + count, "")
+    }
+
+    /// Apply an auto-closing edit in place of a keystroke
+    ///
+    /// The edit joins the current burst of typing, so typing a bracket,
+    /// its contents and the step over its partner are undone together.
+    ///
+    /// # Arguments
+    /// * `edit` - Edit replacing the keystroke
+    /// * `now` - Current time in seconds
+    pub fn apply_pair_edit(&mut self, edit: &PairEdit, now: f64) {
+        let cursor = char_to_byte(&self.text, self.selection.1);
+        let edit_end = char_to_byte(&self.text, edit.end);
+        // Backspace removes the closing character after the cursor too
+        if let Some(group) = self.typing.as_mut().filter(|group| group.end == cursor) {
+            group.end = edit_end;
+        }
+        splice_chars(&mut self.text, edit.start, edit.end, &edit.replacement);
+        self.record_typing(now, false);
+        let (start, end) = edit.selection;
+        self.selection = (start, end);
+        // Typing continues at the cursor, not after the closing character
+        if let Some(group) = self.typing.as_mut() {
+            group.end = char_to_byte(&self.text, end);
+        }
+    }
+
+    /// Indent the selected lines, or insert an indent at the cursor
+    ///
+    /// # Arguments
+    /// * `unit` - Text of one indentation level
+    pub fn indent(&mut self, unit: &str) {
+        let (start, end) = self.selection;
+        if start == end {
+            self.insert_at_cursor(unit);
+        } else {
+            self.reindent_lines(|line| Some(format!("{unit}{line}")));
+        }
+    }
+
+    /// Remove one level of indentation from the current or selected lines
+    ///
+    /// A leading tab or up to `tab_width` leading spaces are removed.
+    ///
+    /// # Arguments
+    /// * `tab_width` - Width of a tab in spaces
+    pub fn unindent(&mut self, tab_width: usize) {
+        self.reindent_lines(|line| {
+            let removed = if line.starts_with('\t') {
+                1
+            } else {
+                line.bytes()
+                    .take(tab_width)
+                    .take_while(|&b| b == b' ')
+                    .count()
+            };
+            (removed > 0).then(|| line[removed..].to_string())
+        });
+    }
+
+    /// Rewrite the lines touched by the selection as one undo step
+    ///
+    /// A selection grows to cover the rewritten lines; a cursor stays
+    /// next to the character it was at.
+    ///
+    /// # Arguments
+    /// * `edit` - Returns the new line, or None to keep it
+    fn reindent_lines(&mut self, edit: impl Fn(&str) -> Option<String>) {
+        let (start, end) = self.selection;
+        let (block_start, block_end) = line_block(&self.text, start, end);
+        let block = &self.text[block_start..block_end];
+
+        let mut changed = false;
+        let mut lines = Vec::new();
+        for line in block.split('\n') {
+            if let Some(new_line) = edit(line) {
+                changed = true;
+                lines.push(new_line);
+            } else {
+                lines.push(line.to_string());
+            }
+        }
+        if !changed {
+            return;
+        }
+        let old_first = block
+            .split('\n')
+            .next()
+            .map_or(0, |line| line.chars().count());
+        let new_first = lines.first().map_or(0, |line| line.chars().count());
+        let new_block = lines.join("\n");
+        let block_start_char = byte_to_char(&self.text, block_start);
+
+        self.save_undo_state();
+        self.text.replace_range(block_start..block_end, &new_block);
+        self.save_undo_state();
+        if start == end {
+            let cursor = if new_first >= old_first {
+                start + (new_first - old_first)
+            } else {
+                start
+                    .saturating_sub(old_first - new_first)
+                    .max(block_start_char)
+            };
+            self.select(cursor, cursor);
+        } else {
+            let block_end_char = block_start_char + new_block.chars().count();
+            self.select(block_start_char, block_end_char);
+        }
+    }
+
+    /// Get the selected text
+    ///
+    /// # Returns
+    /// The text of the current selection, empty if nothing is selected
+    #[must_use]
+    pub fn selected_text(&self) -> &str {
+        let (start, end) = self.selection;
+        let start = char_to_byte(&self.text, start);
+        let end = char_to_byte(&self.text, end).max(start);
+        &self.text[start..end]
+    }
+
+    /// Calculate line and column from character position
+    ///
+    /// The column counts characters, so "😀x" has 'x' in column 2.
+    ///
+    /// # Arguments
+    /// * `pos` - Character index in text; past the end clamps to the end
+    ///
+    /// # Returns
+    /// Tuple of (line, column) where both are 1-indexed
+    #[must_use]
+    pub fn position_to_line_column(&self, pos: usize) -> (usize, usize) {
+        let mut line = 1;
+        let mut column = 1;
+        for c in self.text.chars().take(pos) {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+
+    /// Trim the history and buffers if the policy asks for it
+    ///
+    /// # Arguments
+    /// * `policy` - Trim limits
+    /// * `idle_secs` - Seconds the window has been minimized or unfocused
+    ///
+    /// # Returns
+    /// The plan that was applied, or None if nothing was trimmed
+    pub fn trim(&mut self, policy: &TrimPolicy, idle_secs: f64) -> Option<TrimPlan> {
+        // While in use, only undo steps above the floor can be dropped
+        if idle_secs < policy.idle_after_secs && self.undo_history.len() <= policy.min_undo_steps {
+            return None;
+        }
+        let undo_step_bytes: Vec<usize> = self
+            .undo_history
+            .iter()
+            .map(Delta::allocated_bytes)
+            .collect();
+        let inputs = TrimInputs {
+            undo_step_bytes: &undo_step_bytes,
+            redo_steps: self.redo_history.len(),
+            idle_secs,
+        };
+        let plan = plan_trim(policy, &inputs)?;
+        self.apply_trim(&plan);
+        Some(plan)
+    }
+
+    /// Release history and buffer capacity according to a trim plan
+    ///
+    /// The current text is never changed.
+    ///
+    /// # Arguments
+    /// * `plan` - What to release
+    ///
+    /// # Returns
+    /// Approximate number of bytes freed
+    pub fn apply_trim(&mut self, plan: &TrimPlan) -> usize {
+        let before = self.allocated_bytes();
+        let drop_undo = plan.drop_undo_steps.min(self.undo_history.len());
+        self.undo_history.drain(..drop_undo);
+        // The redo entry applied next is at the end, so drop from the front
+        let drop_redo = plan.drop_redo_steps.min(self.redo_history.len());
+        self.redo_history.drain(..drop_redo);
+        if plan.shrink_buffers {
+            self.text.shrink_to_fit();
+            self.committed.shrink_to_fit();
+            for delta in self.undo_history.iter_mut().chain(&mut self.redo_history) {
+                delta.removed.shrink_to_fit();
+                delta.inserted.shrink_to_fit();
+            }
+            self.undo_history.shrink_to_fit();
+            self.redo_history.shrink_to_fit();
+        }
+        before.saturating_sub(self.allocated_bytes())
+    }
+
+    /// Get the bytes allocated for the text and its history
+    fn allocated_bytes(&self) -> usize {
+        self.text.capacity()
+            + self.committed.capacity()
+            + self
+                .undo_history
+                .iter()
+                .chain(&self.redo_history)
+                .map(Delta::allocated_bytes)
+                .sum::<usize>()
+    }
+
+    /// Mark the current text as an undo point before an edit
+    ///
+    /// Changes since the last undo point become an undo step of their own.
+    pub fn save_undo_state(&mut self) {
+        self.commit_changes();
+        self.typing = None;
+    }
+
+    /// Check whether there is anything to undo
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo_history.is_empty() || self.committed != self.text
+    }
+
+    /// Check whether there is anything to redo
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        // Uncommitted changes clear the redo history when they are recorded
+        !self.redo_history.is_empty() && self.committed == self.text
+    }
+
+    /// Record changes made since the last undo point as an undo step
+    fn commit_changes(&mut self) {
+        if let Some(delta) = Delta::between(&self.committed, &self.text) {
+            // Carets do not follow edits made by commands
+            self.carets.clear();
+            self.shift_bookmarks(&delta);
+            delta.apply(&mut self.committed);
+            self.push_undo(delta);
+        }
+    }
+
+    /// Move the bookmarks along with a change to the committed text
+    ///
+    /// # Arguments
+    /// * `delta` - Change about to be applied to the committed text
+    fn shift_bookmarks(&mut self, delta: &Delta) {
+        shift_bookmarks(
+            &mut self.bookmarks,
+            &self.committed,
+            delta.offset,
+            &delta.removed,
+            &delta.inserted,
+        );
+    }
+
+    /// Add an undo step and end the current burst of typing
+    ///
+    /// # Arguments
+    /// * `delta` - Change the undo step reverts
+    fn push_undo(&mut self, delta: Delta) {
+        self.undo_history.push(delta);
+        // Limit undo history to prevent memory issues
+        if self.undo_history.len() > MAX_UNDO_STEPS {
+            self.undo_history.remove(0);
+        }
+        // Clear redo history when new edit is made
+        self.redo_history.clear();
+        self.typing = None;
+    }
+
+    /// Record edits made outside of typing before the `TextEdit` handles input
+    ///
+    /// Call every frame before showing the editor, so edits made by menus,
+    /// search or other commands are not mistaken for typing.
+    pub fn prepare_typing(&mut self) {
+        self.commit_changes();
+    }
+
+    /// Record text typed into the editor since `prepare_typing`
+    ///
+    /// Consecutive keystrokes share one undo step. A new step starts after
+    /// a pause of `TYPING_PAUSE_SECS`, when the edit location jumps, after
+    /// a newline and for pasted or cut text.
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    /// * `separate` - Give this edit its own undo step, e.g. for a paste
+    pub fn record_typing(&mut self, now: f64, separate: bool) {
+        let Some(delta) = Delta::between(&self.committed, &self.text) else {
+            return;
+        };
+        // Typing that reaches the text edit is at the cursor only
+        self.carets.clear();
+        self.shift_bookmarks(&delta);
+        let inserted_newline = delta.inserted.contains('\n');
+        let end = delta.offset + delta.inserted.len();
+        let extends_typing = self.typing.is_some_and(|group| {
+            !group.at_carets
+                && now - group.last_edit < TYPING_PAUSE_SECS
+                // Typing and Delete continue at the end, Backspace before it
+                && (delta.offset == group.end
+                    || (delta.inserted.is_empty()
+                        && delta.offset + delta.removed.len() == group.end))
+        });
+        if !separate
+            && extends_typing
+            && let Some(step) = self.undo_history.last_mut()
+        {
+            step.extend(&delta, &self.committed, &self.text);
+            delta.apply(&mut self.committed);
+        } else {
+            delta.apply(&mut self.committed);
+            self.push_undo(delta);
+        }
+        self.typing = (!separate && !inserted_newline).then_some(TypingGroup {
+            end,
+            last_edit: now,
+            at_carets: false,
+        });
+    }
+
+    /// Make the same edit at the cursor and at every extra caret
+    ///
+    /// Keystrokes at the carets share one undo step until a pause of
+    /// `TYPING_PAUSE_SECS`. Carets that meet are merged.
+    ///
+    /// # Arguments
+    /// * `remove_before` - Characters to remove before each caret
+    /// * `remove_after` - Characters to remove after each caret
+    /// * `inserted` - Text to insert at each caret
+    /// * `now` - Current time in seconds
+    pub fn edit_at_carets(
+        &mut self,
+        remove_before: usize,
+        remove_after: usize,
+        inserted: &str,
+        now: f64,
+    ) {
+        let cursor = self.selection.1;
+        let mut carets = self.carets.clone();
+        if let Err(index) = carets.binary_search(&cursor) {
+            carets.insert(index, cursor);
+        }
+        let (text, moved) =
+            edit_at_carets(&self.text, &carets, remove_before, remove_after, inserted);
+        let cursor_index = carets.binary_search(&cursor).unwrap_or_default();
+        let cursor = moved[cursor_index];
+        self.text = text;
+        self.carets = moved;
+        self.carets.dedup();
+        self.carets.retain(|&caret| caret != cursor);
+        self.selection = (cursor, cursor);
+        self.record_caret_edit(now);
+    }
+
+    /// Record an edit made at several carets
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    fn record_caret_edit(&mut self, now: f64) {
+        let Some(delta) = Delta::between(&self.committed, &self.text) else {
+            return;
+        };
+        self.shift_bookmarks(&delta);
+        let extends_typing = self
+            .typing
+            .is_some_and(|group| group.at_carets && now - group.last_edit < TYPING_PAUSE_SECS);
+        if extends_typing && let Some(step) = self.undo_history.last_mut() {
+            step.extend(&delta, &self.committed, &self.text);
+            delta.apply(&mut self.committed);
+        } else {
+            delta.apply(&mut self.committed);
+            self.push_undo(delta);
+        }
+        self.typing = Some(TypingGroup {
+            end: 0,
+            last_edit: now,
+            at_carets: true,
+        });
+    }
+
+    /// Undo last edit
+    pub fn undo(&mut self) -> bool {
+        self.commit_changes();
+        self.typing = None;
+        self.carets.clear();
+        if let Some(delta) = self.undo_history.pop() {
+            shift_bookmarks(
+                &mut self.bookmarks,
+                &self.text,
+                delta.offset,
+                &delta.inserted,
+                &delta.removed,
+            );
+            delta.revert(&mut self.text);
+            delta.revert(&mut self.committed);
+            self.redo_history.push(delta);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Redo last undone edit
+    pub fn redo(&mut self) -> bool {
+        self.commit_changes();
+        self.typing = None;
+        self.carets.clear();
+        if let Some(delta) = self.redo_history.pop() {
+            self.shift_bookmarks(&delta);
+            delta.apply(&mut self.text);
+            delta.apply(&mut self.committed);
+            self.undo_history.push(delta);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Get the area to center in the view to show a selection's end
+///
+/// The line is centered vertically. Without word wrap, the view only moves
+/// sideways when the cursor is outside the visible columns, so moving the
+/// cursor along a visible line never jumps horizontally.
+///
+/// # Arguments
+/// * `cursor_rect` - Cursor position in screen coordinates
+/// * `visible` - Visible part of the editor in screen coordinates
+///
+/// # Returns
+/// Rectangle to pass to `scroll_to_rect` with centered alignment
+fn selection_scroll_target(cursor_rect: egui::Rect, visible: egui::Rect) -> egui::Rect {
+    if visible.x_range().contains(cursor_rect.center().x) {
+        egui::Rect::from_x_y_ranges(visible.x_range(), cursor_rect.y_range())
+    } else {
+        cursor_rect
+    }
+}
+
+/// Move the editor's cursor where a command asked for it
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `editor_id` - Id of the editor's `TextEdit`
+///
+/// # Returns
+/// The selection that was applied, if any
+fn apply_pending_selection(
+    ui: &egui::Ui,
+    app: &mut NodepatApp,
+    editor_id: egui::Id,
+) -> Option<(usize, usize)> {
+    // Move the cursor to a requested line, e.g. from Go To or +LINE
+    if let Some(line) = app.pending_goto_line.take() {
+        let index = line_start_char(&app.editor_state.text, line);
+        app.editor_state.select(index, index);
+    }
+    // Apply a requested selection, e.g. a search match
+    let pending_selection = app.editor_state.pending_selection.take();
+    if let Some((start, end)) = pending_selection {
+        place_selection(ui, editor_id, start, end);
+    }
+    pending_selection
+}
+
+/// Create the editor's scroll area
+///
+/// # Arguments
+/// * `app` - Application state
+///
+/// # Returns
+/// The scroll area, and the scroll offset it restores this frame, if any
+fn editor_scroll_area(app: &mut NodepatApp) -> (egui::ScrollArea, Option<f32>) {
+    // With word wrap, text wraps to the width and only scrolls vertically
+    let scroll_area = if app.format_settings.word_wrap {
+        egui::ScrollArea::vertical()
+    } else {
+        egui::ScrollArea::both()
+    };
+    // Go To moves the cursor into view instead of restoring the scroll
+    if app.pending_goto_line.is_some() {
+        app.editor_state.pending_scroll = None;
+    }
+    let restored_scroll = app.editor_state.pending_scroll.take();
+    match restored_scroll {
+        Some(offset) => (scroll_area.vertical_scroll_offset(offset), restored_scroll),
+        None => (scroll_area, None),
+    }
+}
+
+/// Show the text editor widget
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+pub fn show_editor(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    // Constants for row calculation
+    const MAX_ROWS: f32 = 1_000_000.0; // Reasonable maximum for UI
+
+    // Get the full available height before any widgets
+    let available_height = ui.available_height();
+
+    app.editor_state.prepare_typing();
+    let read_only = app.is_read_only();
+    handle_input(ui, app, read_only);
+    // Pasted and cut text gets its own undo step
+    let (now, separate_edit) = ui.input(|i| {
+        let clipboard_edit = i
+            .events
+            .iter()
+            .any(|event| matches!(event, egui::Event::Paste(_) | egui::Event::Cut));
+        (i.time, clipboard_edit)
+    });
+
+    let selection_before = app.editor_state.selection;
+    let (scroll_area, restored_scroll) = editor_scroll_area(app);
+    let output = scroll_area.auto_shrink([false; 2]).show(ui, |ui| {
+        ui.set_min_height(available_height);
+
+        // Calculate desired rows using clamp (adjust line height based on font size)
+        let font_size = app.format_settings.editor_font_size();
+        let line_height = font_size * 1.2; // Line height is typically 1.2x font size
+        let rows_f32 = (available_height / line_height).clamp(1.0, MAX_ROWS);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let desired_rows = rows_f32 as usize;
+
+        let (font_id, text_style) = apply_editor_font(ui, &app.format_settings);
+        let gutter_font = font_id.clone();
+        let editor_id = egui::Id::new(EDITOR_ID);
+        if std::mem::take(&mut app.editor_state.reset_widget_state) {
+            ui.data_mut(|d| d.remove::<egui::text_edit::TextEditState>(editor_id));
+        }
+        let pending_selection = apply_pending_selection(ui, app, editor_id);
+        let marks = layout_marks(ui, app, editor_id);
+        app.load_spell_checker();
+        let language = app.document_language();
+        let highlight = (app.config.syntax_highlighting && language != Language::Plain)
+            .then_some((&mut app.highlight_cache, language));
+        let spelling = app
+            .spell_checker
+            .as_mut()
+            .filter(|_| app.config.spell_check);
+        let mut layouter = editor_layouter(
+            font_id,
+            app.format_settings.word_wrap,
+            app.format_settings.tab_width,
+            marks,
+            highlight,
+            spelling,
+        );
+        let line_count = app.editor_state.text.matches('\n').count() + 1;
+        // A read-only document still lets the user select and copy
+        let mut shown: &str;
+        let buffer: &mut dyn egui::TextBuffer = if read_only {
+            shown = &app.editor_state.text;
+            &mut shown
+        } else {
+            &mut app.editor_state.text
+        };
+        let text_edit = egui::TextEdit::multiline(buffer)
+            .id(editor_id)
+            .desired_width(f32::INFINITY)
+            .desired_rows(desired_rows)
+            .font(text_style)
+            .layouter(&mut layouter);
+        let text_edit = if app.show_line_numbers {
+            let bookmarks = &app.editor_state.bookmarks;
+            show_with_line_numbers(ui, text_edit, &gutter_font, line_count, bookmarks)
+        } else {
+            text_edit.show(ui)
+        };
+        if text_edit.response.changed() {
+            app.editor_state.record_typing(now, separate_edit);
+            app.file_state.update_modified(&app.editor_state.text);
+        }
+        if let Some((_, end)) = pending_selection {
+            if restored_scroll.is_none() {
+                let cursor_rect = text_edit
+                    .galley
+                    .pos_from_cursor(egui::text::CCursor::new(end))
+                    .translate(text_edit.galley_pos.to_vec2());
+                let target = selection_scroll_target(cursor_rect, ui.clip_rect());
+                ui.scroll_to_rect(target, Some(egui::Align::Center));
+            }
+            // Keep the focus in an open search dialog so Enter finds again
+            if !app.show_find_dialog && !app.show_replace_dialog {
+                text_edit.response.request_focus();
+            }
+        }
+
+        if let Some(cursor_range) = text_edit.cursor_range {
+            app.editor_state.update_cursor(cursor_range);
+        }
+        drop(layouter);
+        paint_carets(ui, &text_edit, &app.editor_state.carets);
+        if !open_clicked_link(ui, app, &text_edit) && !read_only {
+            handle_caret_click(ui, app, &text_edit, selection_before);
+        }
+        show_context_menu(app, &text_edit, read_only);
+    });
+    app.editor_state.scroll_y = output.state.offset.y;
+}
+
+/// Draw the extra carets of multi-caret editing
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `output` - Output of the editor's `TextEdit`
+/// * `carets` - Extra carets as character indices
+fn paint_carets(ui: &egui::Ui, output: &egui::text_edit::TextEditOutput, carets: &[usize]) {
+    let stroke = ui.visuals().text_cursor.stroke;
+    for &caret in carets {
+        let rect = output
+            .galley
+            .pos_from_cursor(egui::text::CCursor::new(caret))
+            .translate(output.galley_pos.to_vec2());
+        ui.painter()
+            .line_segment([rect.center_top(), rect.center_bottom()], stroke);
+    }
+}
+
+/// Add or remove a caret on Ctrl+click, and drop the extra carets on a
+/// plain click
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `output` - Output of the editor's `TextEdit`
+/// * `before` - Selection before the click
+fn handle_caret_click(
+    ui: &egui::Ui,
+    app: &mut NodepatApp,
+    output: &egui::text_edit::TextEditOutput,
+    before: (usize, usize),
+) {
+    let editor = &mut app.editor_state;
+    if !output.response.clicked() {
+        return;
+    }
+    if !ui.input(|i| i.modifiers.command) {
+        editor.carets.clear();
+        return;
+    }
+    let Some(clicked) = output.cursor_range.map(|range| range.primary.index) else {
+        return;
+    };
+    // Ctrl+click on a caret removes it and keeps the cursor where it was
+    if editor.carets.contains(&clicked) {
+        toggle_caret(&mut editor.carets, clicked);
+        editor.selection = before;
+        place_selection(ui, output.response.id, before.0, before.1);
+        return;
+    }
+    if before.0 == before.1 && before.1 != clicked {
+        toggle_caret(&mut editor.carets, before.1);
+    }
+}
+
+/// Type at every caret while there are extra carets
+///
+/// Typed and pasted text, Enter, Tab, Backspace and Delete are taken
+/// before the text edit sees them and applied at all carets. Left and
+/// Right move the extra carets along with the cursor; Escape, a
+/// selection, and the other navigation keys leave only the cursor.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn handle_carets(ui: &egui::Ui, app: &mut NodepatApp) {
+    let editor_id = egui::Id::new(EDITOR_ID);
+    let editor = &mut app.editor_state;
+    if editor.carets.is_empty() || !ui.memory(|m| m.has_focus(editor_id)) {
+        return;
+    }
+    if editor.selection.0 != editor.selection.1 {
+        editor.carets.clear();
+        return;
+    }
+    let unit = app.format_settings.indent_unit();
+    let mut edited = false;
+    ui.input_mut(|i| {
+        let now = i.time;
+        i.events.retain(|event| {
+            if editor.carets.is_empty() {
+                return true;
+            }
+            let (before, after, inserted) = match event {
+                egui::Event::Text(text) | egui::Event::Paste(text) => (0, 0, text.as_str()),
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if !modifiers.command && !modifiers.alt => match key {
+                    egui::Key::Enter => (0, 0, "\n"),
+                    egui::Key::Tab if !modifiers.shift => (0, 0, unit.as_str()),
+                    egui::Key::Backspace => (1, 0, ""),
+                    egui::Key::Delete => (0, 1, ""),
+                    egui::Key::ArrowLeft | egui::Key::ArrowRight if !modifiers.shift => {
+                        let right = *key == egui::Key::ArrowRight;
+                        move_carets(&mut editor.carets, &editor.text, right);
+                        return true;
+                    }
+                    egui::Key::Escape => {
+                        editor.carets.clear();
+                        return false;
+                    }
+                    egui::Key::ArrowLeft
+                    | egui::Key::ArrowRight
+                    | egui::Key::ArrowUp
+                    | egui::Key::ArrowDown
+                    | egui::Key::Home
+                    | egui::Key::End
+                    | egui::Key::PageUp
+                    | egui::Key::PageDown => {
+                        editor.carets.clear();
+                        return true;
+                    }
+                    _ => return true,
+                },
+                _ => return true,
+            };
+            editor.edit_at_carets(before, after, inserted, now);
+            edited = true;
+            false
+        });
+    });
+    if edited {
+        let (start, end) = app.editor_state.selection;
+        place_selection(ui, editor_id, start, end);
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Get the byte offset of the text under the pointer
+///
+/// # Arguments
+/// * `text` - Editor text
+/// * `output` - Output of the editor's `TextEdit`
+///
+/// # Returns
+/// Byte offset of the character the pointer last interacted with, if any
+fn pointer_byte(text: &str, output: &egui::text_edit::TextEditOutput) -> Option<usize> {
+    let pos = output.response.interact_pointer_pos()?;
+    let cursor = output.galley.cursor_from_pos(pos - output.galley_pos);
+    Some(char_to_byte(text, cursor.index))
+}
+
+/// Open the link under the pointer on Ctrl+click
+///
+/// A plain click only places the cursor. While Ctrl is held, the pointer
+/// turns into a hand over links.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `output` - Output of the editor's `TextEdit`
+///
+/// # Returns
+/// True if a link was opened
+fn open_clicked_link(
+    ui: &egui::Ui,
+    app: &NodepatApp,
+    output: &egui::text_edit::TextEditOutput,
+) -> bool {
+    if !ui.input(|i| i.modifiers.command) {
+        return false;
+    }
+    let text = &app.editor_state.text;
+    let response = &output.response;
+    if let Some(pos) = response.hover_pos() {
+        let cursor = output.galley.cursor_from_pos(pos - output.galley_pos);
+        if link_at(text, char_to_byte(text, cursor.index)).is_some() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+        }
+    }
+    if !response.clicked() {
+        return false;
+    }
+    let Some(link) = pointer_byte(text, output).and_then(|byte| link_at(text, byte)) else {
+        return false;
+    };
+    ui.ctx().open_url(egui::OpenUrl::new_tab(&text[link]));
+    true
+}
+
+/// Show the editor's context menu when a link or a misspelled word is
+/// right-clicked
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `output` - Output of the editor's `TextEdit`
+/// * `read_only` - Whether the document is read-only; only adding the word
+///   to the dictionary is offered then
+fn show_context_menu(
+    app: &mut NodepatApp,
+    output: &egui::text_edit::TextEditOutput,
+    read_only: bool,
+) {
+    let response = &output.response;
+    if response.secondary_clicked() {
+        let text = &app.editor_state.text;
+        let byte = pointer_byte(text, output);
+        app.spelling_menu = app
+            .spell_checker
+            .as_mut()
+            .filter(|_| app.config.spell_check)
+            .zip(byte)
+            .and_then(|(checker, byte)| checker.misspelling_at(text, byte));
+        app.link_menu = byte
+            .and_then(|byte| link_at(text, byte))
+            .map(|link| text[link].to_string());
+    }
+    if app.spelling_menu.is_none() && app.link_menu.is_none() {
+        return;
+    }
+    let menu = response.context_menu(|ui| {
+        if let Some(link) = app.link_menu.clone() {
+            show_link_items(ui, &link);
+        }
+        if let Some(misspelling) = app.spelling_menu.clone() {
+            if app.link_menu.is_some() {
+                ui.separator();
+            }
+            show_spelling_items(ui, app, &misspelling, read_only);
+        }
+    });
+    if menu.is_none() {
+        app.spelling_menu = None;
+        app.link_menu = None;
+    }
+}
+
+/// Show the context menu items of a right-clicked link
+///
+/// # Arguments
+/// * `ui` - egui UI context of the menu
+/// * `link` - URL of the link
+fn show_link_items(ui: &mut egui::Ui, link: &str) {
+    if ui.button("Open Link").clicked() {
+        ui.ctx().open_url(egui::OpenUrl::new_tab(link));
+        ui.close();
+    }
+    if ui.button("Copy Link").clicked() {
+        ui.ctx().copy_text(link.to_string());
+        ui.close();
+    }
+}
+
+/// Show the corrections of a right-clicked misspelled word
+///
+/// # Arguments
+/// * `ui` - egui UI context of the menu
+/// * `app` - Application state
+/// * `misspelling` - The misspelled word
+/// * `read_only` - Whether the document is read-only; only adding the word
+///   to the dictionary is offered then
+fn show_spelling_items(
+    ui: &mut egui::Ui,
+    app: &mut NodepatApp,
+    misspelling: &Misspelling,
+    read_only: bool,
+) {
+    if !read_only {
+        for suggestion in &misspelling.suggestions {
+            if ui.button(suggestion).clicked() {
+                let text = &app.editor_state.text;
+                let start = byte_to_char(text, misspelling.range.start);
+                let end = byte_to_char(text, misspelling.range.end);
+                app.editor_state.select(start, end);
+                app.editor_state.insert_at_cursor(suggestion);
+                app.file_state.update_modified(&app.editor_state.text);
+                ui.close();
+            }
+        }
+        if misspelling.suggestions.is_empty() {
+            ui.add_enabled(false, egui::Button::new("No suggestions"));
+        }
+        ui.separator();
+    }
+    if ui.button("Add to Dictionary").clicked() {
+        if let Some(checker) = &mut app.spell_checker
+            && let Err(e) = checker.add_word(&misspelling.word)
+        {
+            app.messages.error(e);
+        }
+        ui.close();
+    }
+}
+
+/// Apply the font settings to the editor's UI only
+///
+/// This ensures UI elements like checkboxes aren't affected.
+///
+/// # Arguments
+/// * `ui` - egui UI context of the editor
+/// * `format` - Format settings
+///
+/// # Returns
+/// Tuple of (editor font, text style using it)
+fn apply_editor_font(
+    ui: &mut egui::Ui,
+    format: &crate::format::FormatSettings,
+) -> (egui::FontId, egui::TextStyle) {
+    let font_size = format.editor_font_size();
+    let font_id = crate::fonts::editor_font(ui.ctx(), format.font_family_type, font_size);
+    let text_style = match format.font_family_type {
+        crate::format::FontFamily::Proportional => egui::TextStyle::Body,
+        crate::format::FontFamily::Monospace | crate::format::FontFamily::Custom => {
+            egui::TextStyle::Monospace
+        }
+    };
+    ui.style_mut()
+        .text_styles
+        .insert(egui::TextStyle::Body, font_id.clone());
+    ui.style_mut()
+        .text_styles
+        .insert(egui::TextStyle::Monospace, font_id.clone());
+    (font_id, text_style)
+}
+
+/// Indent with Tab and unindent with Shift+Tab
+///
+/// The keys are taken before the text edit sees them, so Tab inserts
+/// the configured indent instead of a tab character.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn handle_indent_keys(ui: &egui::Ui, app: &mut NodepatApp) {
+    if !ui.memory(|m| m.has_focus(egui::Id::new(EDITOR_ID))) {
+        return;
+    }
+    // Shift+Tab first, since the plain Tab check also matches it
+    let (unindent, indent) = ui.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+        )
+    });
+    if unindent {
+        let tab_width = usize::from(app.format_settings.tab_width);
+        app.editor_state.unindent(tab_width);
+    } else if indent {
+        let unit = app.format_settings.indent_unit();
+        app.editor_state.indent(&unit);
+    } else {
+        return;
+    }
+    app.file_state.update_modified(&app.editor_state.text);
+}
+
+/// Handle the input the editor's `TextEdit` must not handle on its own
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `read_only` - Whether the document is read-only
+fn handle_input(ui: &egui::Ui, app: &mut NodepatApp, read_only: bool) {
+    record_clipboard_history(ui, app);
+    if read_only {
+        reject_typing(ui, app);
+        return;
+    }
+    record_macro_input(ui, app);
+    handle_carets(ui, app);
+    handle_indent_keys(ui, app);
+    handle_auto_pairs(ui, app);
+    handle_overwrite(ui, app);
+}
+
+/// Keep the text the editor is about to cut or copy in the clipboard
+/// history
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn record_clipboard_history(ui: &egui::Ui, app: &mut NodepatApp) {
+    if !ui.memory(|m| m.has_focus(egui::Id::new(EDITOR_ID))) {
+        return;
+    }
+    let copies = ui.input(|i| {
+        i.events
+            .iter()
+            .any(|event| matches!(event, egui::Event::Copy | egui::Event::Cut))
+    });
+    if copies {
+        let selected = app.editor_state.selected_text().to_string();
+        app.clipboard_history.push(selected);
+    }
+}
+
+/// Record the editor's input while a keyboard macro is being recorded
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn record_macro_input(ui: &egui::Ui, app: &mut NodepatApp) {
+    if let Some(steps) = &mut app.macro_recording
+        && ui.memory(|m| m.has_focus(egui::Id::new(EDITOR_ID)))
+    {
+        ui.input(|i| record_events(&i.events, steps));
+    }
+}
+
+/// Auto-close brackets and quotes as they are typed
+///
+/// Typed brackets and quotes, and Backspace between an empty pair, are
+/// taken before the text edit sees them and applied as pair edits.
+/// Handling stops at the first other edit, so keystrokes stay in order.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn handle_auto_pairs(ui: &egui::Ui, app: &mut NodepatApp) {
+    let editor_id = egui::Id::new(EDITOR_ID);
+    if !app.config.auto_close_brackets || !ui.memory(|m| m.has_focus(editor_id)) {
+        return;
+    }
+    let editor = &mut app.editor_state;
+    let mut handled = false;
+    let mut stopped = false;
+    ui.input_mut(|i| {
+        let now = i.time;
+        i.events.retain(|event| {
+            if stopped {
+                return true;
+            }
+            let edit = match event {
+                egui::Event::Text(text) => {
+                    let mut chars = text.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(typed), None) => typed_edit(&editor.text, editor.selection, typed),
+                        _ => None,
+                    }
+                }
+                egui::Event::Key {
+                    key: egui::Key::Backspace,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if modifiers.is_none() => backspace_edit(&editor.text, editor.selection),
+                _ => None,
+            };
+            let Some(edit) = edit else {
+                stopped = is_edit_event(event);
+                return true;
+            };
+            editor.apply_pair_edit(&edit, now);
+            handled = true;
+            false
+        });
+    });
+    if handled {
+        let (start, end) = app.editor_state.selection;
+        place_selection(ui, editor_id, start, end);
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Toggle overwrite mode with the Insert key, and make room for text
+/// typed in overwrite mode
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn handle_overwrite(ui: &egui::Ui, app: &mut NodepatApp) {
+    if !ui.memory(|m| m.has_focus(egui::Id::new(EDITOR_ID))) {
+        return;
+    }
+    if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Insert)) {
+        app.editor_state.overwrite = !app.editor_state.overwrite;
+    }
+    if !app.editor_state.overwrite {
+        return;
+    }
+    let typed = ui.input(|i| {
+        i.events
+            .iter()
+            .map(|event| match event {
+                egui::Event::Text(text) => text.chars().count(),
+                _ => 0,
+            })
+            .sum()
+    });
+    if typed > 0 {
+        app.editor_state.overwrite_ahead(typed);
+    }
+}
+
+/// Flash the read-only hint when the user types into a read-only document
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn reject_typing(ui: &egui::Ui, app: &mut NodepatApp) {
+    if !ui.memory(|m| m.has_focus(egui::Id::new(EDITOR_ID))) {
+        return;
+    }
+    let (typed, now) = ui.input(|i| (i.events.iter().any(is_edit_event), i.time));
+    if typed {
+        app.reject_edit(now);
+    }
+}
+
+/// Check whether an input event would edit the text
+///
+/// # Arguments
+/// * `event` - Input event
+///
+/// # Returns
+/// True for typed text, paste, cut and the editing keys
+const fn is_edit_event(event: &egui::Event) -> bool {
+    match event {
+        egui::Event::Text(_) | egui::Event::Paste(_) | egui::Event::Cut => true,
+        egui::Event::Key {
+            key, pressed: true, ..
+        } => matches!(
+            key,
+            egui::Key::Backspace | egui::Key::Delete | egui::Key::Enter | egui::Key::Tab
+        ),
+        _ => false,
+    }
+}
+
+/// Time/Date format matching Notepad, e.g. "1:05 PM 2/29/2024"
+pub const DEFAULT_DATETIME_FORMAT: &str = "%-I:%M %p %-m/%-d/%Y";
+
+/// Insert current time and date at cursor position
+///
+/// The local time is used, like in Notepad. A selection is replaced.
+///
+/// # Arguments
+/// * `editor` - Editor state
+/// * `format` - Timestamp format, see `format_time_date`
+pub fn insert_time_date(editor: &mut EditorState, format: &str) {
+    let time_str = format_time_date(chrono::Local::now().naive_local(), format);
+    editor.insert_at_cursor(&time_str);
+}
+
+/// Format a time for Time/Date
+///
+/// Supports %Y %y %m %d %H %I %M %S %p and %%. A '-' after the '%'
+/// drops the leading zero, as in %-d. Other specifiers are kept as typed.
+///
+/// # Arguments
+/// * `time` - Time in local time
+/// * `format` - Timestamp format
+///
+/// # Returns
+/// The formatted time
+#[must_use]
+pub fn format_time_date(time: chrono::NaiveDateTime, format: &str) -> String {
+    use chrono::{Datelike, Timelike};
+
+    let mut result = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        let unpadded = chars.next_if_eq(&'-').is_some();
+        let number = |value: u32| {
+            if unpadded {
+                value.to_string()
+            } else {
+                format!("{value:02}")
+            }
+        };
+        let spec = chars.next();
+        let text = match spec {
+            Some('Y') => time.year().to_string(),
+            Some('y') => number(time.year().rem_euclid(100).unsigned_abs()),
+            Some('m') => number(time.month()),
+            Some('d') => number(time.day()),
+            Some('H') => number(time.hour()),
+            Some('I') => number(time.hour12().1),
+            Some('M') => number(time.minute()),
+            Some('S') => number(time.second()),
+            Some('p') => if time.hour12().0 { "PM" } else { "AM" }.to_string(),
+            Some('%') => "%".to_string(),
+            _ => {
+                // Unknown specifier: keep it as typed
+                let mut literal = String::from("%");
+                if unpadded {
+                    literal.push('-');
+                }
+                literal.extend(spec);
+                literal
+            }
+        };
+        result.push_str(&text);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(secs: i64) -> chrono::NaiveDateTime {
+        chrono::DateTime::from_timestamp(secs, 0)
+            .expect("Valid timestamp")
+            .naive_utc()
+    }
+
+    #[test]
+    fn test_insert_at_cursor_replaces_selection() {
+        let mut editor = EditorState::default();
+        editor.set_document("héllo wörld".to_string());
+        editor.select(6, 11);
+        assert_eq!(editor.insert_at_cursor("thére"), "wörld");
+        assert_eq!(editor.text, "héllo thére");
+        assert_eq!(editor.selection, (11, 11));
+
+        editor.select(2, 2);
+        editor.insert_at_cursor("ü");
+        assert_eq!(editor.text, "héüllo thére");
+        assert_eq!(editor.pending_selection, Some((3, 3)));
+
+        assert!(editor.undo());
+        assert_eq!(editor.text, "héllo thére");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "héllo wörld");
+    }
+
+    #[test]
+    fn test_overwrite_stops_at_line_end() {
+        let mut editor = EditorState {
+            overwrite: true,
+            ..Default::default()
+        };
+        editor.set_document("aé€b\r\nnext".to_string());
+        assert!(!editor.overwrite);
+
+        editor.select(1, 1);
+        assert_eq!(editor.overwrite_ahead(2), "é€");
+        assert_eq!(editor.text, "ab\r\nnext");
+        // The line break is not overwritten
+        editor.select(2, 2);
+        assert_eq!(editor.overwrite_ahead(3), "");
+        editor.select(4, 4);
+        assert_eq!(editor.overwrite_ahead(9), "next");
+        assert_eq!(editor.text, "ab\r\n");
+
+        // Typing over a selection only replaces the selection
+        editor.select(0, 1);
+        assert_eq!(editor.overwrite_ahead(1), "");
+        assert_eq!(editor.text, "ab\r\n");
+    }
+
+    #[test]
+    fn test_pair_edits_join_typing_undo_step() {
+        let mut editor = EditorState::default();
+        editor.set_document(String::new());
+        editor.prepare_typing();
+        editor.text.push('f');
+        editor.record_typing(0.0, false);
+        editor.selection = (1, 1);
+
+        let open = typed_edit(&editor.text, editor.selection, '(').expect("Expected an edit");
+        editor.apply_pair_edit(&open, 0.1);
+        assert_eq!((editor.text.as_str(), editor.selection), ("f()", (2, 2)));
+        editor.prepare_typing();
+        editor.text.insert(2, 'x');
+        editor.record_typing(0.2, false);
+        editor.selection = (3, 3);
+        let close = typed_edit(&editor.text, editor.selection, ')').expect("Expected an edit");
+        editor.apply_pair_edit(&close, 0.3);
+        assert_eq!((editor.text.as_str(), editor.selection), ("f(x)", (4, 4)));
+        assert!(editor.undo());
+        assert_eq!(editor.text, "");
+
+        // Deleting an empty pair continues the burst as well
+        editor.set_document("f".to_string());
+        editor.selection = (1, 1);
+        editor.prepare_typing();
+        let open = typed_edit(&editor.text, editor.selection, '[').expect("Expected an edit");
+        editor.apply_pair_edit(&open, 1.0);
+        let delete = backspace_edit(&editor.text, editor.selection).expect("Expected an edit");
+        editor.apply_pair_edit(&delete, 1.1);
+        assert_eq!((editor.text.as_str(), editor.selection), ("f", (1, 1)));
+        assert!(editor.undo());
+        assert_eq!(editor.text, "f");
+        assert!(!editor.can_undo());
+    }
+
+    #[test]
+    fn test_indent_and_unindent_lines() {
+        let mut editor = EditorState::default();
+        editor.set_document("a\nbé\nc\n".to_string());
+        // The selection ends at the start of "c", so "c" is not indented
+        editor.select(1, 5);
+        editor.indent("\t");
+        assert_eq!(editor.text, "\ta\n\tbé\nc\n");
+        assert_eq!(editor.selection, (0, 6));
+
+        editor.unindent(4);
+        assert_eq!(editor.text, "a\nbé\nc\n");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "\ta\n\tbé\nc\n");
+    }
+
+    #[test]
+    fn test_indent_at_cursor_and_unindent_spaces() {
+        let mut editor = EditorState::default();
+        editor.set_document("x\n      yé".to_string());
+        editor.select(9, 9);
+        editor.unindent(4);
+        assert_eq!(editor.text, "x\n  yé");
+        assert_eq!(editor.selection, (5, 5));
+        editor.unindent(4);
+        assert_eq!(editor.text, "x\nyé");
+        assert_eq!(editor.selection, (3, 3));
+        editor.unindent(4);
+        assert_eq!(editor.text, "x\nyé");
+
+        editor.indent("    ");
+        assert_eq!(editor.text, "x\ny    é");
+        assert_eq!(editor.selection, (7, 7));
+    }
+
+    #[test]
+    fn test_move_lines() {
+        let text = "one\ntwö\nthree";
+        // The cursor keeps its column within the moved line
+        assert_eq!(
+            move_lines(text, 6, 6, true),
+            Some(("twö\none\nthree".to_string(), 2, 2))
+        );
+        // The last line has no trailing newline
+        assert_eq!(
+            move_lines(text, 6, 6, false),
+            Some(("one\nthree\ntwö".to_string(), 12, 12))
+        );
+        assert_eq!(
+            move_lines(text, 12, 12, true),
+            Some(("one\nthree\ntwö".to_string(), 8, 8))
+        );
+        assert_eq!(move_lines(text, 1, 1, true), None);
+        assert_eq!(move_lines(text, 10, 10, false), None);
+    }
+
+    #[test]
+    fn test_move_lines_block() {
+        let mut editor = EditorState::default();
+        editor.set_document("a\nb\nc\nd\n".to_string());
+        // The selection ends at the start of "c", so only "a" and "b" move
+        editor.select(0, 4);
+        assert!(editor.move_lines(false));
+        assert_eq!(editor.text, "c\na\nb\nd\n");
+        assert_eq!(editor.selection, (2, 6));
+        assert!(editor.move_lines(false));
+        assert_eq!(editor.text, "c\nd\na\nb\n");
+        // The empty line after the final newline is the last line
+        assert!(editor.move_lines(false));
+        assert_eq!(editor.text, "c\nd\n\na\nb");
+        assert!(!editor.move_lines(false));
+        assert!(editor.undo());
+        assert_eq!(editor.text, "c\nd\na\nb\n");
+    }
+
+    #[test]
+    fn test_delete_lines() {
+        let text = "föo\nbar\nbaz";
+        assert_eq!(delete_lines(text, 1, 1), ("bar\nbaz".to_string(), 0));
+        assert_eq!(delete_lines(text, 5, 5), ("föo\nbaz".to_string(), 4));
+        // The last line has no final newline
+        assert_eq!(delete_lines(text, 9, 9), ("föo\nbar".to_string(), 4));
+        assert_eq!(
+            delete_lines("föo\nbar\n", 9, 9),
+            ("föo\nbar".to_string(), 4)
+        );
+        // A selection deletes every line it touches
+        assert_eq!(delete_lines(text, 2, 6), ("baz".to_string(), 0));
+        assert_eq!(delete_lines(text, 0, 11), (String::new(), 0));
+        assert_eq!(delete_lines("only", 2, 2), (String::new(), 0));
+    }
+
+    #[test]
+    fn test_sort_selected_lines() {
+        use crate::lines::{SortMode, sort_lines};
+
+        let mut editor = EditorState::default();
+        editor.set_document("z\nc\nb\na\n".to_string());
+        editor.select(3, 7);
+        editor.transform_lines(|text| sort_lines(text, SortMode::Ascending));
+        assert_eq!(editor.text, "z\na\nb\nc\n");
+        assert_eq!(editor.selection, (2, 7));
+
+        editor.select(0, 0);
+        editor.transform_lines(|text| sort_lines(text, SortMode::Descending));
+        assert_eq!(editor.text, "z\nc\nb\na\n");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "z\na\nb\nc\n");
+    }
+
+    #[test]
+    fn test_convert_case_keeps_selection() {
+        use crate::case::CaseConversion;
+
+        let mut editor = EditorState::default();
+        editor.set_document("a straße b".to_string());
+        editor.select(2, 8);
+        editor.convert_case(CaseConversion::Upper);
+        assert_eq!(editor.text, "a STRASSE b");
+        assert_eq!(editor.selection, (2, 9));
+        editor.convert_case(CaseConversion::Title);
+        assert_eq!(editor.text, "a Strasse b");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "a STRASSE b");
+
+        editor.select(0, 0);
+        editor.convert_case(CaseConversion::Lower);
+        assert_eq!(editor.text, "a strasse b");
+    }
+
+    #[test]
+    fn test_cut_delete_and_select_all() {
+        let mut editor = EditorState::default();
+        editor.set_document("añb".to_string());
+        editor.select(1, 1);
+        assert_eq!(editor.cut(), "");
+        assert!(editor.delete());
+        assert_eq!(editor.text, "ab");
+        assert_eq!(editor.selection, (1, 1));
+
+        editor.select_all();
+        assert_eq!(editor.selected_text(), "ab");
+        assert_eq!(editor.cut(), "ab");
+        assert_eq!(editor.text, "");
+        assert!(!editor.delete());
+
+        assert!(editor.undo());
+        assert_eq!(editor.text, "ab");
+        assert!(editor.undo());
+        assert_eq!(editor.text, "añb");
+    }
+
+    #[test]
+    fn test_duplicate_line() {
+        assert_eq!(
+            duplicate_line("one\ntwö\nthree", 5),
+            ("one\ntwö\ntwö\nthree".to_string(), 9)
+        );
+        // The last line has no trailing newline
+        assert_eq!(
+            duplicate_line("one\ntwo", 7),
+            ("one\ntwo\ntwo".to_string(), 11)
+        );
+        assert_eq!(duplicate_line("", 0), ("\n".to_string(), 1));
+        assert_eq!(duplicate_line("a\n", 2), ("a\n\n".to_string(), 3));
+    }
+
+    #[test]
+    fn test_duplicate_selection_is_one_undo_step() {
+        let mut editor = EditorState::default();
+        editor.set_document("héllo".to_string());
+        editor.select(1, 3);
+        editor.duplicate();
+        assert_eq!(editor.text, "héléllo");
+        assert_eq!(editor.selection, (3, 5));
+        assert!(editor.undo());
+        assert_eq!(editor.text, "héllo");
+    }
+
+    #[test]
+    fn test_format_time_date() {
+        let notepad = |secs| format_time_date(utc(secs), DEFAULT_DATETIME_FORMAT);
+        assert_eq!(notepad(0), "12:00 AM 1/1/1970");
+        // Leap day
+        assert_eq!(notepad(1_709_213_100), "1:25 PM 2/29/2024");
+        assert_eq!(notepad(951_825_600), "12:00 PM 2/29/2000");
+        // Last minute of a leap year
+        assert_eq!(notepad(1_735_689_540), "11:59 PM 12/31/2024");
+        assert_eq!(notepad(1_704_067_199), "11:59 PM 12/31/2023");
+    }
+
+    #[test]
+    fn test_format_time_date_formats() {
+        // 2024-02-29 13:25:07 UTC
+        let time = utc(1_709_213_107);
+        assert_eq!(
+            format_time_date(time, "%Y-%m-%dT%H:%M:%S"),
+            "2024-02-29T13:25:07"
+        );
+        assert_eq!(format_time_date(time, "%d.%m.%Y %H:%M"), "29.02.2024 13:25");
+        assert_eq!(format_time_date(time, "%Y%m%d-%H%M%S"), "20240229-132507");
+        assert_eq!(format_time_date(time, "%y %I%p 100%%"), "24 01PM 100%");
+        // Unknown specifiers are kept as typed
+        assert_eq!(format_time_date(time, "%Q %-Q %Y%"), "%Q %-Q 2024%");
+    }
+
+    /// Deterministic pseudo-random numbers (64-bit LCG)
+    fn next_random(seed: &mut u64) -> u64 {
+        *seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        *seed >> 33
+    }
+
+    #[test]
+    fn test_sanitize_offset_random() {
+        let text = "aé€😀\nçà 中文\r\n👍🏽 end";
+        let mut seed = 42;
+        for _ in 0..10_000 {
+            let offset = usize::try_from(next_random(&mut seed)).unwrap_or(0) % (text.len() * 2);
+            let sanitized = sanitize_offset(text, offset);
+            assert!(sanitized <= text.len());
+            assert!(sanitized <= offset);
+            assert!(text.is_char_boundary(sanitized));
+        }
+        assert_eq!(sanitize_offset(text, usize::MAX), text.len());
+        assert_eq!(sanitize_offset("", 5), 0);
+        // Inside 'é' (bytes 1..3) snaps back to its start
+        assert_eq!(sanitize_offset(text, 2), 1);
+    }
+
+    #[test]
+    fn test_position_to_line_column_never_panics() {
+        let editor = EditorState {
+            text: "😀😀\n中文中文\néé".to_string(),
+            ..Default::default()
+        };
+        let mut seed = 7;
+        for _ in 0..10_000 {
+            let offset = usize::try_from(next_random(&mut seed)).unwrap_or(0) % 64;
+            let (line, column) = editor.position_to_line_column(offset);
+            assert!((1..=3).contains(&line));
+            assert!(column >= 1);
+        }
+    }
+
+    #[test]
+    fn test_position_to_line_column_counts_chars() {
+        let editor = EditorState {
+            text: "😀x
+中文ab
+"
+            .to_string(),
+            ..Default::default()
+        };
+        assert_eq!(editor.position_to_line_column(0), (1, 1));
+        assert_eq!(editor.position_to_line_column(1), (1, 2));
+        assert_eq!(editor.position_to_line_column(2), (1, 3));
+        assert_eq!(editor.position_to_line_column(3), (2, 1));
+        assert_eq!(editor.position_to_line_column(5), (2, 3));
+        // The very end is the start of the empty last line
+        assert_eq!(editor.position_to_line_column(8), (3, 1));
+        assert_eq!(editor.position_to_line_column(100), (3, 1));
+    }
+
+    #[test]
+    fn test_line_start_char() {
+        let text = "één\ntwo\nthree";
+        assert_eq!(line_start_char(text, 0), 0);
+        assert_eq!(line_start_char(text, 1), 0);
+        assert_eq!(line_start_char(text, 2), 4);
+        assert_eq!(line_start_char(text, 3), 8);
+        // Out-of-range lines clamp to the last line
+        assert_eq!(line_start_char(text, 99), 8);
+        assert_eq!(line_start_char("", 5), 0);
+    }
+
+    #[test]
+    fn test_parse_line_column() {
+        assert_eq!(parse_line_column("120"), Ok((120, 1)));
+        assert_eq!(parse_line_column(" 120:35 "), Ok((120, 35)));
+        assert_eq!(parse_line_column("7 : 2"), Ok((7, 2)));
+        for invalid in ["", "0", "12:0", "12:", ":5", "x", "1:2:3", "-4"] {
+            assert!(parse_line_column(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_line_column_char_counts_characters() {
+        let text = "één\r\ntwo\nthree";
+        assert_eq!(line_column_char(text, 1, 1), 0);
+        assert_eq!(line_column_char(text, 1, 3), 2);
+        // Columns past the end stop before the line break
+        assert_eq!(line_column_char(text, 1, 99), 3);
+        assert_eq!(line_column_char(text, 2, 2), 6);
+        assert_eq!(line_column_char(text, 3, 99), 14);
+        // Out-of-range lines clamp to the last line
+        assert_eq!(line_column_char(text, 99, 2), 10);
+    }
+
+    #[test]
+    fn test_shift_bookmarks() {
+        let text = "one\ntwo\nthree\nfour";
+        let shifted = |offset, removed, inserted| {
+            let mut bookmarks = vec![2, 3, 4];
+            shift_bookmarks(&mut bookmarks, text, offset, removed, inserted);
+            bookmarks
+        };
+        // Lines added in line 1 push every bookmark down
+        assert_eq!(shifted(1, "", "x\ny\n"), vec![4, 5, 6]);
+        // Enter at the start of a bookmarked line moves it with its text,
+        // Enter at its end keeps it
+        assert_eq!(shifted(4, "", "\n"), vec![3, 4, 5]);
+        assert_eq!(shifted(7, "", "\n"), vec![2, 4, 5]);
+        // Joining lines 2 and 3 joins their bookmarks at line 2
+        assert_eq!(shifted(5, "wo\nthr", ""), vec![2, 3]);
+        // Edits within a line change nothing
+        assert_eq!(shifted(9, "thr", "é"), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_bookmark_from_wraps() {
+        assert_eq!(bookmark_from(&[3, 8], 3, true), Some(8));
+        assert_eq!(bookmark_from(&[3, 8], 8, true), Some(3));
+        assert_eq!(bookmark_from(&[3, 8], 5, false), Some(3));
+        assert_eq!(bookmark_from(&[3, 8], 2, false), Some(8));
+        assert_eq!(bookmark_from(&[], 2, true), None);
+    }
+
+    #[test]
+    fn test_bookmarks_follow_edits_and_undo() {
+        let mut editor = EditorState::default();
+        editor.set_document("a\nb\nc".to_string());
+        editor.cursor_line = 3;
+        editor.toggle_bookmark();
+        assert_eq!(editor.bookmarks, vec![3]);
+
+        editor.prepare_typing();
+        editor.text.insert_str(0, "new\n");
+        editor.record_typing(0.0, false);
+        assert_eq!(editor.bookmarks, vec![4]);
+        editor.text.insert_str(0, "x\n");
+        editor.save_undo_state();
+        assert_eq!(editor.bookmarks, vec![5]);
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert_eq!(editor.bookmarks, vec![3]);
+        assert!(editor.redo());
+        assert_eq!(editor.bookmarks, vec![4]);
+
+        editor.cursor_line = 4;
+        editor.toggle_bookmark();
+        assert!(editor.bookmarks.is_empty());
+        editor.toggle_bookmark();
+        editor.set_document(String::new());
+        assert!(editor.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn test_typing_at_carets_is_one_undo_step() {
+        let mut editor = EditorState::default();
+        editor.set_document("é1\né2\né3".to_string());
+        editor.carets = vec![1, 7];
+        editor.select(4, 4);
+        editor.edit_at_carets(0, 0, "x", 0.0);
+        editor.edit_at_carets(0, 0, "y", 0.2);
+        assert_eq!(editor.text, "éxy1\néxy2\néxy3");
+        assert_eq!(editor.selection, (8, 8));
+        assert_eq!(editor.carets, vec![3, 13]);
+        editor.edit_at_carets(1, 0, "", 0.4);
+        assert_eq!(editor.text, "éx1\néx2\néx3");
+        assert_eq!(editor.undo_history.len(), 1);
+
+        // A new undo step after a pause
+        editor.edit_at_carets(2, 0, "", 3.0);
+        assert_eq!(editor.text, "1\n2\n3");
+        assert_eq!(
+            (editor.selection, editor.carets.clone()),
+            ((2, 2), vec![0, 4])
+        );
+        assert_eq!(editor.undo_history.len(), 2);
+        assert!(editor.undo());
+        assert!(editor.carets.is_empty());
+        assert!(editor.undo());
+        assert_eq!(editor.text, "é1\né2\né3");
+    }
+
+    #[test]
+    fn test_row_line_numbers_skip_wrapped_rows() {
+        // Line 1 wraps onto a second row, line 2 fits, line 3 is empty
+        let numbers = row_line_numbers([false, true, true, false]);
+        assert_eq!(numbers, vec![Some(1), None, Some(2), Some(3)]);
+        assert_eq!(row_line_numbers([false]), vec![Some(1)]);
+    }
+
+    #[test]
+    fn test_selection_scroll_keeps_visible_columns() {
+        let visible = egui::Rect::from_min_max(egui::pos2(100.0, 0.0), egui::pos2(500.0, 300.0));
+        let cursor = egui::Rect::from_min_max(egui::pos2(120.0, 900.0), egui::pos2(121.0, 915.0));
+        let target = selection_scroll_target(cursor, visible);
+        assert_eq!(target.x_range(), visible.x_range());
+        assert_eq!(target.y_range(), cursor.y_range());
+
+        // A cursor left of the view, e.g. at the start of a long line
+        let cursor = cursor.translate(egui::vec2(-110.0, 0.0));
+        assert_eq!(selection_scroll_target(cursor, visible), cursor);
+    }
+
+    #[test]
+    fn test_current_row_tint_on_cursor_row_only() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            let text = "a line that wraps\nlast";
+            let job = egui::text::LayoutJob::simple(
+                text.to_string(),
+                egui::FontId::monospace(14.0),
+                egui::Color32::WHITE,
+                60.0,
+            );
+            let galley = ctx.fonts_mut(|f| f.layout_job(job));
+            assert!(galley.rows.len() > 2);
+            let indices = |galley: &egui::Galley| -> Vec<usize> {
+                galley
+                    .rows
+                    .iter()
+                    .map(|row| row.visuals.mesh.indices.len())
+                    .collect()
+            };
+            let untinted = indices(&galley);
+
+            for (cursor, row) in [(1, 0), (text.chars().count(), galley.rows.len() - 1)] {
+                let mut tinted = galley.clone();
+                paint_current_row(&mut tinted, cursor, 200.0, egui::Color32::RED);
+                let mut expected = untinted.clone();
+                expected[row] += 6;
+                assert_eq!(indices(&tinted), expected);
+                let visuals = &tinted.rows[row].visuals;
+                assert!(visuals.mesh_bounds.width() >= 200.0);
+                assert_eq!(
+                    visuals.glyph_index_start,
+                    galley.rows[row].visuals.glyph_index_start + 6
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn test_gutter_digits_stay_stable() {
+        assert_eq!(gutter_digits(0), MIN_GUTTER_DIGITS);
+        assert_eq!(gutter_digits(999), MIN_GUTTER_DIGITS);
+        assert_eq!(gutter_digits(99_999), MIN_GUTTER_DIGITS);
+        assert_eq!(gutter_digits(100_000), 6);
+    }
+
+    #[test]
+    fn test_char_byte_conversion() {
+        let text = "añb€c";
+        assert_eq!(char_to_byte(text, 0), 0);
+        assert_eq!(char_to_byte(text, 2), 3);
+        assert_eq!(char_to_byte(text, 4), 7);
+        assert_eq!(char_to_byte(text, 99), text.len());
+        assert_eq!(byte_to_char(text, 3), 2);
+        assert_eq!(byte_to_char(text, 7), 4);
+        // Offsets inside a character snap back to its start
+        assert_eq!(byte_to_char(text, 5), 3);
+        assert_eq!(byte_to_char(text, 99), 5);
+    }
+
+    #[test]
+    fn test_splice_chars() {
+        let mut text = "héllo wörld".to_string();
+        assert_eq!(splice_chars(&mut text, 6, 11, "there"), "wörld");
+        assert_eq!(text, "héllo there");
+        // An empty range inserts
+        assert_eq!(splice_chars(&mut text, 5, 5, ","), "");
+        assert_eq!(text, "héllo, there");
+        // An empty replacement removes
+        assert_eq!(splice_chars(&mut text, 0, 7, ""), "héllo, ");
+        assert_eq!(text, "there");
+        // Out-of-range indices clamp to the end
+        assert_eq!(splice_chars(&mut text, 3, 99, "!"), "re");
+        assert_eq!(text, "the!");
+    }
+
+    #[test]
+    fn test_apply_trim_keeps_text_and_newest_history() {
+        let mut editor = EditorState::default();
+        editor.set_document("state 0".to_string());
+        for i in 1..10 {
+            editor.save_undo_state();
+            editor.text = format!("state {i}");
+        }
+        editor.save_undo_state();
+        editor.text = "current".to_string();
+        for _ in 0..4 {
+            editor.undo();
+        }
+        assert_eq!(editor.redo_history.len(), 4);
+
+        let plan = TrimPlan {
+            drop_undo_steps: 2,
+            drop_redo_steps: 3,
+            shrink_buffers: true,
+        };
+        editor.apply_trim(&plan);
+        assert_eq!(editor.text, "state 6");
+        assert_eq!(editor.undo_history.len(), 4);
+        // The remaining redo step is the one that would be applied next
+        assert_eq!(editor.redo_history.len(), 1);
+        assert!(editor.redo());
+        assert_eq!(editor.text, "state 7");
+        // The oldest steps were dropped
+        while editor.undo() {}
+        assert_eq!(editor.text, "state 2");
+    }
+
+    /// Type text at a byte offset like the `TextEdit` would
+    fn type_at(editor: &mut EditorState, pos: usize, text: &str, now: f64) {
+        editor.prepare_typing();
+        editor.text.insert_str(pos, text);
+        editor.record_typing(now, false);
+    }
+
+    #[test]
+    fn test_typing_is_coalesced_into_one_undo_step() {
+        let mut editor = EditorState::default();
+        let mut now = 0.0;
+        for (i, c) in "hello".chars().enumerate() {
+            type_at(&mut editor, i, &c.to_string(), now);
+            now += 0.1;
+        }
+        assert_eq!(
+            editor.undo_history,
+            vec![Delta {
+                offset: 0,
+                removed: String::new(),
+                inserted: "hello".to_string(),
+            }]
+        );
+        assert!(editor.undo());
+        assert_eq!(editor.text, "");
+        assert!(editor.redo());
+        assert_eq!(editor.text, "hello");
+    }
+
+    #[test]
+    fn test_typing_breaks_on_pause_newline_and_jump() {
+        let mut editor = EditorState::default();
+        type_at(&mut editor, 0, "a", 0.0);
+        type_at(&mut editor, 1, "b", 0.5);
+        // Pause
+        type_at(&mut editor, 2, "c", 0.5 + TYPING_PAUSE_SECS);
+        // Newline ends the step it is typed in
+        type_at(&mut editor, 3, "\n", 2.0);
+        type_at(&mut editor, 4, "d", 2.1);
+        // Jump back to the start
+        type_at(&mut editor, 0, "e", 2.2);
+        assert_eq!(editor.text, "eabc\nd");
+        let steps: Vec<(usize, &str)> = editor
+            .undo_history
+            .iter()
+            .map(|delta| (delta.offset, delta.inserted.as_str()))
+            .collect();
+        assert_eq!(steps, vec![(0, "ab"), (2, "c\n"), (4, "d"), (0, "e")]);
+
+        // Pasted text gets its own step even while typing continues
+        editor.prepare_typing();
+        editor.text.push_str("pasted");
+        editor.record_typing(2.3, true);
+        let end = editor.text.len();
+        type_at(&mut editor, end, "f", 2.4);
+        assert_eq!(editor.undo_history.len(), 6);
+    }
+
+    #[test]
+    fn test_backspace_continues_typing_step() {
+        let mut editor = EditorState::default();
+        type_at(&mut editor, 0, "héllo", 0.0);
+        for now in [0.1, 0.2] {
+            editor.prepare_typing();
+            let end = editor.text.len();
+            let last = editor.text[..end]
+                .chars()
+                .next_back()
+                .map_or(0, char::len_utf8);
+            editor.text.truncate(end - last);
+            editor.record_typing(now, false);
+        }
+        let end = editor.text.len();
+        type_at(&mut editor, end, "p!", 0.3);
+        assert_eq!(editor.text, "hélp!");
+        assert_eq!(editor.undo_history.len(), 1);
+        // Edits outside of typing start a new step
+        editor.save_undo_state();
+        editor.text.push('?');
+        let end = editor.text.len();
+        type_at(&mut editor, end, "!", 0.4);
+        assert_eq!(editor.undo_history.len(), 3);
+    }
+
+    #[test]
+    fn test_undo_to_saved_content_clears_modified() {
+        let mut editor = EditorState::default();
+        let mut file = crate::file_ops::FileState::default();
+        editor.set_document("saved".to_string());
+        file.mark_saved("saved");
+
+        type_at(&mut editor, 5, "!", 0.0);
+        file.update_modified(&editor.text);
+        assert!(file.is_modified);
+        assert!(editor.undo());
+        file.update_modified(&editor.text);
+        assert!(!file.is_modified);
+        assert!(editor.redo());
+        file.update_modified(&editor.text);
+        assert!(file.is_modified);
+    }
+
+    #[test]
+    fn test_typing_after_save_sets_modified() {
+        let path = std::env::temp_dir().join("test_Nodepat_modified.txt");
+        let path = path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        let mut editor = EditorState::default();
+        let mut file = crate::file_ops::FileState::default();
+
+        type_at(&mut editor, 0, "draft", 0.0);
+        file.update_modified(&editor.text);
+        assert!(file.is_modified);
+        file.save_file(path, &editor.text)
+            .expect("Failed to save test file");
+        assert!(!file.is_modified);
+
+        type_at(&mut editor, 5, "!", 5.0);
+        file.update_modified(&editor.text);
+        assert!(file.is_modified);
+        // Undoing past the save point modifies the document again
+        assert!(editor.undo());
+        file.update_modified(&editor.text);
+        assert!(!file.is_modified);
+        assert!(editor.undo());
+        file.update_modified(&editor.text);
+        assert!(file.is_modified);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_delta_extend() {
+        // Typing, Delete past the typed text and Backspace before it
+        let mut delta = Delta::between("abcdef", "abXYcdef").expect("No change");
+        let next = Delta::between("abXYcdef", "abXYdef").expect("No change");
+        delta.extend(&next, "abXYcdef", "abXYdef");
+        assert_eq!((delta.offset, delta.removed.as_str()), (2, "c"));
+        assert_eq!(delta.inserted, "XY");
+        let next = Delta::between("abXYdef", "aXYdef").expect("No change");
+        delta.extend(&next, "abXYdef", "aXYdef");
+        let mut text = "aXYdef".to_string();
+        delta.revert(&mut text);
+        assert_eq!(text, "abcdef");
+        delta.apply(&mut text);
+        assert_eq!(text, "aXYdef");
+    }
+
+    #[test]
+    fn test_random_edits_undo_to_original() {
+        const ALPHABET: [&str; 6] = ["a", "é", "\n", " ", "xy", "😀"];
+        // Small deterministic generator so failures are reproducible
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move |bound: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            usize::try_from(seed % bound as u64).expect("Bound fits usize")
+        };
+        for round in 0..50 {
+            let original = "héllo wörld\nsecond line".to_string();
+            let mut editor = EditorState::default();
+            editor.set_document(original.clone());
+            let mut now = 0.0;
+            for _ in 0..40 {
+                editor.prepare_typing();
+                let chars = editor.text.chars().count();
+                let start = char_to_byte(&editor.text, next(chars + 1));
+                let end = char_to_byte(&editor.text, next(chars + 1)).max(start);
+                let insert = if next(3) == 0 {
+                    ""
+                } else {
+                    ALPHABET[next(ALPHABET.len())]
+                };
+                editor.text.replace_range(start..end, insert);
+                editor.record_typing(now, next(5) == 0);
+                now += if next(4) == 0 { 2.0 } else { 0.1 };
+            }
+            let edited = editor.text.clone();
+            while editor.undo() {}
+            assert_eq!(editor.text, original, "round {round}");
+            while editor.redo() {}
+            assert_eq!(editor.text, edited, "round {round}");
+        }
+    }
+
+    #[test]
+    fn test_changed_range() {
+        assert_eq!(changed_range("abc", "abXc"), (2, 0, "X"));
+        assert_eq!(changed_range("aaa", "aa"), (2, 1, ""));
+        assert_eq!(changed_range("héllo", "hallo"), (1, 2, "a"));
+        assert_eq!(changed_range("same", "same"), (4, 0, ""));
+    }
+
+    #[test]
+    fn test_set_document_resets_state() {
+        let mut editor = EditorState {
+            text: "old".to_string(),
+            ..Default::default()
+        };
+        editor.save_undo_state();
+        editor.set_document("new".to_string());
+        assert_eq!(editor.text, "new");
+        assert!(editor.undo_history.is_empty());
+        assert!(editor.reset_widget_state);
+    }
+
+    #[test]
+    fn test_highlight_sections_keep_tab_spacing() {
+        let mut job = egui::text::LayoutJob::simple(
+            "fn\tx".to_string(),
+            egui::FontId::monospace(12.0),
+            egui::Color32::WHITE,
+            f32::INFINITY,
+        );
+        set_highlight_colors(&mut job, &[(0..2, egui::Color32::BLUE)]);
+        set_tab_spacing(&mut job, 5.0);
+        let sections: Vec<_> = job
+            .sections
+            .iter()
+            .map(|s| (s.byte_range.clone(), s.format.color, s.leading_space))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (0..2, egui::Color32::BLUE, 0.0),
+                (2..3, egui::Color32::WHITE, 5.0),
+                (3..4, egui::Color32::WHITE, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marked_ranges_split_sections() {
+        let mut job = egui::text::LayoutJob::simple(
+            "fn teh x".to_string(),
+            egui::FontId::monospace(12.0),
+            egui::Color32::WHITE,
+            f32::INFINITY,
+        );
+        set_highlight_colors(&mut job, &[(0..4, egui::Color32::BLUE)]);
+        let stroke = egui::Stroke::new(1.0, egui::Color32::RED);
+        mark_ranges(&mut job, &[3..6, 7..8], |format| format.underline = stroke);
+        let sections: Vec<_> = job
+            .sections
+            .iter()
+            .map(|s| (s.byte_range.clone(), s.format.color, s.format.underline))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (0..3, egui::Color32::BLUE, egui::Stroke::NONE),
+                (3..4, egui::Color32::BLUE, stroke),
+                (4..6, egui::Color32::WHITE, stroke),
+                (6..7, egui::Color32::WHITE, egui::Stroke::NONE),
+                (7..8, egui::Color32::WHITE, stroke),
+            ]
+        );
+    }
 }
@@ -4,9 +4,40 @@
 //! encoding detection and conversion, and recent files management.
 
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::Path;
+
+/// Size and modification time of a file on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskStamp {
+    /// Last modification time, if the file system reports one
+    modified: Option<std::time::SystemTime>,
+    /// File size in bytes
+    len: u64,
+}
+
+impl DiskStamp {
+    /// Read the stamp of a file
+    ///
+    /// # Arguments
+    /// * `path` - File path
+    ///
+    /// # Returns
+    /// The stamp, or None if the file cannot be read
+    #[must_use]
+    pub fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
 
 /// File state including path, modified flag, and encoding
 #[derive(Default)]
+#[allow(clippy::struct_excessive_bools)] // Independent facts about the file
 pub struct FileState {
     /// Current file path
     pub file_path: String,
@@ -14,73 +45,128 @@ pub struct FileState {
     pub is_modified: bool,
     /// Current encoding
     pub encoding: String,
+    /// Whether the UTF-8 file starts with a byte order mark, written again
+    /// on save
+    pub had_bom: bool,
+    /// Encoding of opened files that are not Unicode
+    pub legacy_encoding: Encoding,
+    /// Whether the file is read-only on disk
+    pub read_only: bool,
+    /// Whether saving over an existing file keeps its old content in a .bak file
+    pub create_backup: bool,
+    /// Why the backup of the last save failed, if it did
+    pub backup_warning: Option<String>,
+    /// Hash of the content as last loaded or saved, None for a new document
+    saved_hash: Option<u64>,
+    /// Stamp of the file as last loaded or saved
+    disk_stamp: Option<DiskStamp>,
 }
 
 impl FileState {
     /// Load file from path
     ///
+    /// Binary files are refused with `BINARY_FILE_ERROR`, since decoding
+    /// and saving them as text would damage them.
+    ///
     /// # Arguments
     /// * `path` - File path to load
     ///
     /// # Returns
     /// Result containing the file content as String, or error message
     pub fn load_file(&mut self, path: &str) -> Result<String, String> {
-        let file_data = fs::read(path).map_err(|e| format!("Failed to read file: {e}"))?;
-
-        // Check file size
-        if file_data.len() > 60_000 {
-            return Err(
-                "File is too large. Nodepat can only handle files up to ~58KB.".to_string(),
-            );
+        let file_data = read_editor_file(path)?;
+        if looks_binary(&file_data) {
+            return Err(BINARY_FILE_ERROR.to_string());
         }
 
         // Detect encoding
-        let (text, encoding_used) = if file_data.starts_with(&[0xFF, 0xFE]) {
-            // UTF-16 LE BOM
-            let utf16_data = &file_data[2..];
-            let decoded = decode_utf16_le(utf16_data)?;
-            (decoded, "UTF-16 LE")
-        } else if file_data.starts_with(&[0xFE, 0xFF]) {
-            // UTF-16 BE BOM
-            let utf16_data = &file_data[2..];
-            let decoded = decode_utf16_be(utf16_data)?;
-            (decoded, "UTF-16 BE")
-        } else if file_data.starts_with(&[0xEF, 0xBB, 0xBF]) {
-            // UTF-8 BOM
-            let decoded = String::from_utf8_lossy(&file_data[3..]).to_string();
-            (decoded, "UTF-8")
-        } else {
-            // Try UTF-8 first, fallback to ANSI/Latin1
-            String::from_utf8(file_data.clone()).map_or_else(
-                |_| {
-                    // Fallback to Latin1 (ANSI)
-                    let decoded = decode_latin1(&file_data);
-                    (decoded, "Latin1")
-                },
-                |text| (text, "UTF-8"),
-            )
-        };
+        let (text, encoding_used) = detect_and_decode(&file_data, self.legacy_encoding)?;
 
-        self.file_path = path.to_string();
-        self.encoding = encoding_used.to_string();
-        self.is_modified = false;
+        self.loaded(path, &file_data, &text, encoding_used);
+        Ok(text)
+    }
 
+    /// Read the current file again, decoding it with a chosen encoding
+    ///
+    /// For files whose encoding was detected wrongly. Nothing changes if
+    /// the file cannot be read or decoded.
+    ///
+    /// # Arguments
+    /// * `encoding` - Encoding name (one of `ENCODINGS`)
+    ///
+    /// # Returns
+    /// Result containing the file content as String, or error message
+    pub fn reload_with_encoding(&mut self, encoding: &str) -> Result<String, String> {
+        if self.file_path.is_empty() {
+            return Err("The document has not been saved to a file.".to_string());
+        }
+        let path = self.file_path.clone();
+        let file_data = read_editor_file(&path)?;
+        let text = decode_with(&file_data, encoding)
+            .map_err(|e| format!("The file cannot be read as {encoding}: {e}"))?;
+        self.loaded(&path, &file_data, &text, encoding);
         Ok(text)
     }
 
+    /// Remember a file that was just loaded
+    ///
+    /// # Arguments
+    /// * `path` - File path
+    /// * `data` - Raw file bytes
+    /// * `text` - Decoded content
+    /// * `encoding` - Encoding the content was decoded from
+    fn loaded(&mut self, path: &str, data: &[u8], text: &str, encoding: &str) {
+        self.file_path = path.to_string();
+        self.encoding = encoding.to_string();
+        self.had_bom = encoding == "UTF-8" && data.starts_with(&UTF8_BOM);
+        self.mark_saved(text);
+        self.disk_stamp = DiskStamp::read(Path::new(path));
+        self.read_only = fs::metadata(path).is_ok_and(|m| m.permissions().readonly());
+    }
+
+    /// Check whether saving writes a UTF-8 byte order mark
+    ///
+    /// # Returns
+    /// True if the encoding is UTF-8 and `had_bom` is set
+    #[must_use]
+    pub fn has_utf8_bom(&self) -> bool {
+        self.had_bom && self.encoding == "UTF-8"
+    }
+
+    /// Get the encoding as shown to the user
+    ///
+    /// # Returns
+    /// The encoding name, "UTF-8 BOM" for UTF-8 with a byte order mark
+    #[must_use]
+    pub fn encoding_label(&self) -> String {
+        if self.has_utf8_bom() {
+            UTF8_BOM_LABEL.to_string()
+        } else {
+            self.encoding.clone()
+        }
+    }
+
     /// Add file to recent files in config
     ///
     /// # Arguments
     /// * `config` - Configuration to update
-    pub fn add_to_recent_files(&self, config: &mut crate::config::Config) {
-        if !self.file_path.is_empty() {
-            config.add_recent_file(&self.file_path);
-            let _ = config.save();
+    ///
+    /// # Returns
+    /// Result of saving the configuration
+    pub fn add_to_recent_files(&self, config: &mut crate::config::Config) -> Result<(), String> {
+        if self.file_path.is_empty() {
+            return Ok(());
         }
+        config.add_recent_file(&self.file_path);
+        config.save()
     }
 
     /// Save file to path
     ///
+    /// With `create_backup` set, an existing file is first copied to
+    /// `<name>.bak`. A failed backup does not fail the save; it is left in
+    /// `backup_warning` instead.
+    ///
     /// # Arguments
     /// * `path` - File path to save to
     /// * `content` - Content to save
@@ -88,28 +174,635 @@ impl FileState {
     /// # Returns
     /// Result indicating success or error message
     pub fn save_file(&mut self, path: &str, content: &str) -> Result<(), String> {
-        let bytes = match self.encoding.as_str() {
-            "UTF-16 LE" => {
-                let mut bytes = vec![0xFF, 0xFE]; // BOM
-                bytes.extend(encode_utf16_le(content));
-                bytes
-            }
-            "UTF-16 BE" => {
-                let mut bytes = vec![0xFE, 0xFF]; // BOM
-                bytes.extend(encode_utf16_be(content));
-                bytes
+        let mut bytes = encode_with(content, &self.encoding);
+        if self.has_utf8_bom() {
+            bytes.splice(0..0, UTF8_BOM);
+        }
+
+        self.backup_warning = None;
+        if self.create_backup && Path::new(path).is_file() {
+            let backup = backup_path(Path::new(path));
+            if let Err(e) = fs::copy(path, &backup) {
+                self.backup_warning = Some(format!(
+                    "The file was saved, but the backup {} could not be written: {e}",
+                    backup.display()
+                ));
             }
-            "ANSI" | "Latin1" => encode_latin1(content),
-            _ => content.as_bytes().to_vec(), // UTF-8 or unknown
-        };
+        }
 
-        fs::write(path, bytes).map_err(|e| format!("Failed to write file: {e}"))?;
+        write_replacing(Path::new(path), &bytes)?;
 
         self.file_path = path.to_string();
-        self.is_modified = false;
+        self.mark_saved(content);
+        self.disk_stamp = DiskStamp::read(Path::new(path));
+        self.read_only = false;
 
         Ok(())
     }
+
+    /// Check whether another program changed the file since it was loaded or saved
+    ///
+    /// # Returns
+    /// The current stamp of the file if it differs, otherwise None. A file
+    /// that was deleted or cannot be read is not reported.
+    #[must_use]
+    pub fn changed_on_disk(&self) -> Option<DiskStamp> {
+        if self.file_path.is_empty() {
+            return None;
+        }
+        let current = DiskStamp::read(Path::new(&self.file_path))?;
+        (self.disk_stamp != Some(current)).then_some(current)
+    }
+
+    /// Remember content as saved and clear the modified flag
+    ///
+    /// # Arguments
+    /// * `content` - Content as it is on disk
+    pub fn mark_saved(&mut self, content: &str) {
+        self.saved_hash = Some(content_hash(content));
+        self.is_modified = false;
+    }
+
+    /// Update the modified flag after the content changed
+    ///
+    /// The flag clears when the content is back to what was last saved,
+    /// e.g. after undoing all changes. A new document counts as saved
+    /// while it is empty.
+    ///
+    /// # Arguments
+    /// * `content` - Current content
+    pub fn update_modified(&mut self, content: &str) {
+        let saved = self.saved_hash.unwrap_or_else(|| content_hash(""));
+        self.is_modified = content_hash(content) != saved;
+    }
+}
+
+/// Read a file for the editor
+///
+/// # Arguments
+/// * `path` - File path
+///
+/// # Returns
+/// The raw bytes, or error message if the file cannot be read or is too
+/// large for the editor
+fn read_editor_file(path: &str) -> Result<Vec<u8>, String> {
+    let file_data = fs::read(path).map_err(|e| format!("Failed to read file: {e}"))?;
+    if file_data.len() > MAX_EDITOR_FILE_SIZE {
+        return Err(
+            "File is too large. Nodepat can only handle files up to ~58KB. \
+             Use File > Open as Viewer... to view larger files."
+                .to_string(),
+        );
+    }
+    Ok(file_data)
+}
+
+/// Hash document content for comparing it with the saved content
+///
+/// # Arguments
+/// * `content` - Content to hash
+///
+/// # Returns
+/// Hash of the content
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Largest file, in bytes, the editor loads; larger files need the viewer
+pub const MAX_EDITOR_FILE_SIZE: usize = 60_000;
+
+/// Number of bytes `looks_binary` inspects
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// Data is binary when more than one in this many inspected bytes is a
+/// control character
+const BINARY_CONTROL_DIVISOR: usize = 10;
+
+/// Error of `FileState::load_file` for data that `looks_binary`
+pub const BINARY_FILE_ERROR: &str = "The file looks like a binary file.";
+
+/// Check whether file data looks like a binary file rather than text
+///
+/// Text files do not contain NUL bytes, except UTF-16 files, which are
+/// recognized by their byte order mark, and hardly any control characters
+/// other than whitespace and escape. Only the start of the data is
+/// inspected.
+///
+/// # Arguments
+/// * `file_data` - Raw file bytes
+///
+/// # Returns
+/// True if the data has no UTF-16 byte order mark and contains a NUL byte
+/// or many control characters
+#[must_use]
+pub fn looks_binary(file_data: &[u8]) -> bool {
+    if file_data.starts_with(&[0xFF, 0xFE]) || file_data.starts_with(&[0xFE, 0xFF]) {
+        return false;
+    }
+    let sample = &file_data[..file_data.len().min(BINARY_SNIFF_LEN)];
+    if sample.contains(&0) {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&byte| {
+            (byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B)) || byte == 0x7F
+        })
+        .count();
+    control * BINARY_CONTROL_DIVISOR > sample.len()
+}
+
+/// Text encoding of a file
+///
+/// Every encoding has a `Codec`; adding an encoding means adding a variant
+/// here and returning its codec from `Encoding::codec`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Western European "ANSI"
+    Windows1252,
+    /// Japanese
+    ShiftJis,
+    /// Korean
+    EucKr,
+    /// Simplified Chinese
+    Gbk,
+    /// Russian
+    Koi8R,
+}
+
+impl Encoding {
+    /// Every supported encoding, in menu order
+    pub const ALL: [Self; 8] = [
+        Self::Utf8,
+        Self::Utf16Le,
+        Self::Utf16Be,
+        Self::Windows1252,
+        Self::ShiftJis,
+        Self::EucKr,
+        Self::Gbk,
+        Self::Koi8R,
+    ];
+
+    /// Get the name shown to the user and stored in the config
+    ///
+    /// # Returns
+    /// The encoding name, e.g. "UTF-16 LE"
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16 LE",
+            Self::Utf16Be => "UTF-16 BE",
+            Self::Windows1252 => "Windows-1252",
+            Self::ShiftJis => "Shift-JIS",
+            Self::EucKr => "EUC-KR",
+            Self::Gbk => "GBK",
+            Self::Koi8R => "KOI8-R",
+        }
+    }
+
+    /// Look up an encoding by name
+    ///
+    /// "Latin1" and "ANSI", the names of older versions, mean Windows-1252.
+    ///
+    /// # Arguments
+    /// * `name` - Encoding name
+    ///
+    /// # Returns
+    /// The encoding, or None for an unknown name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Latin1" | "ANSI" => Some(Self::Windows1252),
+            _ => Self::ALL
+                .into_iter()
+                .find(|encoding| encoding.name() == name),
+        }
+    }
+
+    /// Check whether this is a Unicode encoding
+    ///
+    /// # Returns
+    /// True for UTF-8 and UTF-16; false for the legacy code pages
+    #[must_use]
+    pub const fn is_unicode(self) -> bool {
+        matches!(self, Self::Utf8 | Self::Utf16Le | Self::Utf16Be)
+    }
+
+    /// Get the decoder and encoder of this encoding
+    ///
+    /// # Returns
+    /// The codec
+    #[must_use]
+    pub fn codec(self) -> &'static dyn Codec {
+        static UTF_16_LE: Utf16Codec = Utf16Codec { big_endian: false };
+        static UTF_16_BE: Utf16Codec = Utf16Codec { big_endian: true };
+        static SHIFT_JIS: LegacyCodec = LegacyCodec(&encoding_rs::SHIFT_JIS_INIT);
+        static EUC_KR: LegacyCodec = LegacyCodec(&encoding_rs::EUC_KR_INIT);
+        static GBK: LegacyCodec = LegacyCodec(&encoding_rs::GBK_INIT);
+        static KOI8_R: LegacyCodec = LegacyCodec(&encoding_rs::KOI8_R_INIT);
+        match self {
+            Self::Utf8 => &Utf8Codec,
+            Self::Utf16Le => &UTF_16_LE,
+            Self::Utf16Be => &UTF_16_BE,
+            Self::Windows1252 => &Windows1252Codec,
+            Self::ShiftJis => &SHIFT_JIS,
+            Self::EucKr => &EUC_KR,
+            Self::Gbk => &GBK,
+            Self::Koi8R => &KOI8_R,
+        }
+    }
+}
+
+/// Decoder and encoder of one encoding
+pub trait Codec: Sync {
+    /// Get the byte order mark written at the start of a file
+    ///
+    /// # Returns
+    /// The byte order mark, empty if the encoding has none
+    fn bom(&self) -> &'static [u8] {
+        &[]
+    }
+
+    /// Decode bytes without a byte order mark
+    ///
+    /// # Arguments
+    /// * `bytes` - Encoded bytes
+    ///
+    /// # Returns
+    /// Decoded text, or error message if the bytes are not valid
+    fn decode(&self, bytes: &[u8]) -> Result<String, String>;
+
+    /// Encode text without a byte order mark
+    ///
+    /// # Arguments
+    /// * `text` - Text to encode
+    ///
+    /// # Returns
+    /// Encoded bytes; unrepresentable characters become '?'
+    fn encode(&self, text: &str) -> Vec<u8>;
+
+    /// Check whether every character of a text can be encoded
+    ///
+    /// # Arguments
+    /// * `text` - Text to check
+    ///
+    /// # Returns
+    /// True if encoding the text loses nothing
+    fn can_encode(&self, _text: &str) -> bool {
+        true
+    }
+}
+
+/// UTF-8
+struct Utf8Codec;
+
+impl Codec for Utf8Codec {
+    fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        String::from_utf8(bytes.to_vec()).map_err(|e| format!("Invalid UTF-8: {e}"))
+    }
+
+    fn encode(&self, text: &str) -> Vec<u8> {
+        text.as_bytes().to_vec()
+    }
+}
+
+/// UTF-16 in either byte order
+struct Utf16Codec {
+    /// Most significant byte first
+    big_endian: bool,
+}
+
+impl Codec for Utf16Codec {
+    fn bom(&self) -> &'static [u8] {
+        if self.big_endian {
+            &[0xFE, 0xFF]
+        } else {
+            &[0xFF, 0xFE]
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        if self.big_endian {
+            decode_utf16_be(bytes)
+        } else {
+            decode_utf16_le(bytes)
+        }
+    }
+
+    fn encode(&self, text: &str) -> Vec<u8> {
+        if self.big_endian {
+            encode_utf16_be(text)
+        } else {
+            encode_utf16_le(text)
+        }
+    }
+}
+
+/// Windows-1252, which can decode any bytes
+struct Windows1252Codec;
+
+impl Codec for Windows1252Codec {
+    fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        Ok(decode_windows_1252(bytes))
+    }
+
+    fn encode(&self, text: &str) -> Vec<u8> {
+        encode_windows_1252(text)
+    }
+
+    fn can_encode(&self, text: &str) -> bool {
+        text.chars().all(|c| windows_1252_byte(c).is_some())
+    }
+}
+
+/// A legacy code page provided by `encoding_rs`
+struct LegacyCodec(&'static encoding_rs::Encoding);
+
+impl LegacyCodec {
+    /// Encode text, replacing unrepresentable characters with '?'
+    ///
+    /// # Arguments
+    /// * `text` - Text to encode
+    ///
+    /// # Returns
+    /// Tuple of (encoded bytes, whether any character was replaced)
+    fn encode_replacing(&self, text: &str) -> (Vec<u8>, bool) {
+        let mut encoder = self.0.new_encoder();
+        let mut bytes = Vec::with_capacity(text.len());
+        let mut rest = text;
+        let mut replaced = false;
+        loop {
+            let (result, read) =
+                encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut bytes, true);
+            rest = &rest[read..];
+            match result {
+                encoding_rs::EncoderResult::InputEmpty => return (bytes, replaced),
+                encoding_rs::EncoderResult::OutputFull => bytes.reserve(rest.len() + 16),
+                encoding_rs::EncoderResult::Unmappable(_) => {
+                    bytes.push(b'?');
+                    replaced = true;
+                }
+            }
+        }
+    }
+}
+
+impl Codec for LegacyCodec {
+    fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        self.0
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(std::borrow::Cow::into_owned)
+            .ok_or_else(|| format!("Invalid {} data", self.0.name()))
+    }
+
+    fn encode(&self, text: &str) -> Vec<u8> {
+        self.encode_replacing(text).0
+    }
+
+    fn can_encode(&self, text: &str) -> bool {
+        !self.encode_replacing(text).1
+    }
+}
+
+/// Byte order mark that some UTF-8 files start with
+pub const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// Label of UTF-8 with a byte order mark in the status bar and menus
+pub const UTF8_BOM_LABEL: &str = "UTF-8 BOM";
+
+/// Names of the encodings that files can be decoded from and encoded to
+pub const ENCODINGS: [&str; Encoding::ALL.len()] = {
+    let mut names = [""; Encoding::ALL.len()];
+    let mut index = 0;
+    while index < names.len() {
+        names[index] = Encoding::ALL[index].name();
+        index += 1;
+    }
+    names
+};
+
+/// Look up an encoding name
+///
+/// # Arguments
+/// * `name` - Encoding name, possibly from an older version
+///
+/// # Returns
+/// The entry of `ENCODINGS`, or None for an unknown encoding
+#[must_use]
+pub fn encoding_by_name(name: &str) -> Option<&'static str> {
+    Encoding::from_name(name).map(Encoding::name)
+}
+
+/// Detect the encoding of file data and decode it
+///
+/// Checks for a byte order mark first, then UTF-8 validity, then tries
+/// the legacy encoding, and finally falls back to Windows-1252, which
+/// decodes any bytes.
+///
+/// # Arguments
+/// * `file_data` - Raw file bytes
+/// * `legacy` - Encoding of files that are not Unicode
+///
+/// # Returns
+/// Tuple of (decoded text, encoding name) or error message
+pub fn detect_and_decode(
+    file_data: &[u8],
+    legacy: Encoding,
+) -> Result<(String, &'static str), String> {
+    if file_data.starts_with(&[0xFF, 0xFE]) {
+        Ok((decode_utf16_le(&file_data[2..])?, "UTF-16 LE"))
+    } else if file_data.starts_with(&[0xFE, 0xFF]) {
+        Ok((decode_utf16_be(&file_data[2..])?, "UTF-16 BE"))
+    } else if file_data.starts_with(&UTF8_BOM) {
+        let decoded = String::from_utf8_lossy(&file_data[3..]).to_string();
+        Ok((decoded, "UTF-8"))
+    } else if let Ok(text) = std::str::from_utf8(file_data) {
+        Ok((text.to_string(), "UTF-8"))
+    } else if let Ok(text) = legacy.codec().decode(file_data) {
+        Ok((text, legacy.name()))
+    } else {
+        Ok((decode_windows_1252(file_data), "Windows-1252"))
+    }
+}
+
+/// Decode file data with an explicitly chosen encoding
+///
+/// A byte order mark matching the encoding is skipped.
+///
+/// # Arguments
+/// * `file_data` - Raw file bytes
+/// * `encoding` - Encoding name (one of `ENCODINGS`)
+///
+/// # Returns
+/// Decoded text or error message
+pub fn decode_with(file_data: &[u8], encoding: &str) -> Result<String, String> {
+    let encoding = Encoding::from_name(encoding).unwrap_or_default();
+    // A UTF-8 file may start with a byte order mark although none is written
+    let bom: &[u8] = if encoding == Encoding::Utf8 {
+        &UTF8_BOM
+    } else {
+        encoding.codec().bom()
+    };
+    let data = file_data.strip_prefix(bom).unwrap_or(file_data);
+    encoding.codec().decode(data)
+}
+
+/// Encode text with the given encoding
+///
+/// UTF-16 output starts with a byte order mark. Characters that the
+/// encoding cannot represent are replaced with '?'.
+///
+/// # Arguments
+/// * `text` - Text to encode
+/// * `encoding` - Encoding name (one of `ENCODINGS`); unknown names mean
+///   UTF-8
+///
+/// # Returns
+/// Encoded bytes
+#[must_use]
+pub fn encode_with(text: &str, encoding: &str) -> Vec<u8> {
+    let codec = Encoding::from_name(encoding).unwrap_or_default().codec();
+    let mut bytes = codec.bom().to_vec();
+    bytes.extend(codec.encode(text));
+    bytes
+}
+
+/// Check whether an encoding can represent every character of a text
+///
+/// # Arguments
+/// * `text` - Text to check
+/// * `encoding` - Encoding name (one of `ENCODINGS`)
+///
+/// # Returns
+/// True if encoding the text loses nothing
+#[must_use]
+pub fn can_encode(text: &str, encoding: &str) -> bool {
+    Encoding::from_name(encoding)
+        .unwrap_or_default()
+        .codec()
+        .can_encode(text)
+}
+
+/// Get the backup path for a file (`<name>.bak` next to it)
+///
+/// # Arguments
+/// * `path` - File path
+///
+/// # Returns
+/// Path of the backup copy
+#[must_use]
+pub fn backup_path(path: &Path) -> std::path::PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Write bytes to a file atomically
+///
+/// The data is written to a temporary file in the same directory, flushed
+/// to disk, and renamed over the target so a crash never leaves a
+/// truncated file behind.
+///
+/// # Arguments
+/// * `path` - Target file path
+/// * `bytes` - Data to write
+///
+/// # Returns
+/// Result indicating success or error message
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let temp_path = temp_file_path(path)?;
+    if let Err(e) = write_temp(&temp_path, path, bytes).and_then(|()| fs::rename(&temp_path, path))
+    {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write file: {e}"));
+    }
+    Ok(())
+}
+
+/// Write bytes to a file, atomically where the file system allows it
+///
+/// Like `write_atomic`, but falls back to writing the file in place when
+/// the temporary file cannot be renamed over the target, or cannot be
+/// created because the directory is not writable. `fs::rename` replaces
+/// an existing target on Windows as well. A failed write of the temporary
+/// file never touches the target.
+///
+/// # Arguments
+/// * `path` - Target file path
+/// * `bytes` - Data to write
+///
+/// # Returns
+/// Result indicating success, or an error message naming the write that failed
+pub fn write_replacing(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let temp_path = temp_file_path(path)?;
+    let fallback_reason = match write_temp(&temp_path, path, bytes) {
+        Ok(()) => match fs::rename(&temp_path, path) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                format!("renaming the temporary file failed: {e}")
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let _ = fs::remove_file(&temp_path);
+            format!("creating the temporary file failed: {e}")
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path);
+            return Err(format!(
+                "Failed to write temporary file {}: {e}",
+                temp_path.display()
+            ));
+        }
+    };
+    fs::write(path, bytes)
+        .map_err(|e| format!("Failed to write file directly after {fallback_reason}: {e}"))
+}
+
+/// Get the temporary file used while saving a file
+///
+/// # Arguments
+/// * `path` - Target file path
+///
+/// # Returns
+/// A hidden file next to the target, so renaming stays on one file system
+fn temp_file_path(path: &Path) -> Result<std::path::PathBuf, String> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| "Invalid file path".to_string())?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".nodepat-tmp");
+    Ok(path.with_file_name(temp_name))
+}
+
+/// Write and flush the temporary file of a save
+///
+/// The temporary file gets the permissions of an existing target, so
+/// renaming it does not change them.
+///
+/// # Arguments
+/// * `temp_path` - Temporary file path
+/// * `target` - File that will be replaced
+/// * `bytes` - Data to write
+///
+/// # Returns
+/// Result indicating success or I/O error
+fn write_temp(temp_path: &Path, target: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::File::create(temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    if let Ok(metadata) = fs::metadata(target) {
+        let _ = fs::set_permissions(temp_path, metadata.permissions());
+    }
+    Ok(())
 }
 
 /// Decode UTF-16 LE bytes to string
@@ -178,38 +871,64 @@ fn encode_utf16_be(text: &str) -> Vec<u8> {
         .collect()
 }
 
-/// Decode Latin1 (ISO-8859-1) bytes to string
+/// Characters of the Windows-1252 bytes 0x80-0x9F
 ///
-/// Latin1 maps directly: byte 0x00-0xFF maps to Unicode U+0000-U+00FF
+/// Everywhere else Windows-1252 matches Latin1 (ISO-8859-1), where byte
+/// 0xNN is U+00NN. The five bytes Windows-1252 leaves undefined keep their
+/// Latin1 control characters, so any file survives a round trip.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Decode Windows-1252 (ANSI) bytes to string
 ///
 /// # Arguments
-/// * `bytes` - Latin1 encoded bytes
+/// * `bytes` - Windows-1252 encoded bytes
 ///
 /// # Returns
 /// Decoded string
-fn decode_latin1(bytes: &[u8]) -> String {
-    bytes.iter().map(|&b| char::from(b)).collect()
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(b - 0x80)],
+            _ => char::from(b),
+        })
+        .collect()
+}
+
+/// Get the Windows-1252 byte of a character
+///
+/// # Arguments
+/// * `c` - Character to encode
+///
+/// # Returns
+/// The byte, or None if Windows-1252 cannot represent the character
+fn windows_1252_byte(c: char) -> Option<u8> {
+    if let Some(index) = WINDOWS_1252_HIGH.iter().position(|&high| high == c) {
+        return u8::try_from(index).ok().map(|index| 0x80 + index);
+    }
+    match u32::from(c) {
+        0x80..=0x9F => None,
+        code => u8::try_from(code).ok(),
+    }
 }
 
-/// Encode string to Latin1 (ISO-8859-1) bytes
+/// Encode string to Windows-1252 (ANSI) bytes
 ///
-/// Characters outside Latin1 range (U+0100 and above) are replaced with '?'
+/// Characters Windows-1252 cannot represent are replaced with '?'
 ///
 /// # Arguments
 /// * `text` - Text to encode
 ///
 /// # Returns
 /// Encoded bytes
-fn encode_latin1(text: &str) -> Vec<u8> {
+fn encode_windows_1252(text: &str) -> Vec<u8> {
     text.chars()
-        .map(|c| {
-            let code = u32::from(c);
-            if code <= 0xFF {
-                u8::try_from(code).unwrap_or(b'?')
-            } else {
-                b'?' // Replacement character for out-of-range chars
-            }
-        })
+        .map(|c| windows_1252_byte(c).unwrap_or(b'?'))
         .collect()
 }
 
@@ -243,6 +962,401 @@ mod tests {
         let _ = fs::remove_file(&temp_path);
     }
 
+    #[test]
+    fn test_utf8_bom_kept_added_or_removed() {
+        let path = std::env::temp_dir().join("test_Nodepat_utf8_bom.txt");
+        let path_str = path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        let text = "bom?";
+
+        // (file starts with a BOM, BOM wanted on save)
+        for (loaded_bom, saved_bom) in [(false, false), (false, true), (true, false), (true, true)]
+        {
+            let mut data = if loaded_bom {
+                UTF8_BOM.to_vec()
+            } else {
+                Vec::new()
+            };
+            data.extend_from_slice(text.as_bytes());
+            fs::write(&path, &data).expect("Failed to write test file");
+
+            let mut file_state = FileState::default();
+            let loaded = file_state
+                .load_file(path_str)
+                .expect("Failed to load test file");
+            assert_eq!(loaded, text);
+            assert_eq!(file_state.had_bom, loaded_bom);
+            let label = if loaded_bom { "UTF-8 BOM" } else { "UTF-8" };
+            assert_eq!(file_state.encoding_label(), label);
+
+            // Saving unchanged keeps the BOM as loaded
+            file_state
+                .save_file(path_str, text)
+                .expect("Failed to save");
+            let saved = fs::read(&path).expect("Failed to read saved file");
+            assert_eq!(saved[..3] == UTF8_BOM, loaded_bom);
+
+            file_state.had_bom = saved_bom;
+            file_state
+                .save_file(path_str, text)
+                .expect("Failed to save");
+            let saved = fs::read(&path).expect("Failed to read saved file");
+            assert_eq!(
+                saved[..3] == UTF8_BOM,
+                saved_bom,
+                "{loaded_bom} {saved_bom}"
+            );
+            assert_eq!(saved.len(), text.len() + if saved_bom { 3 } else { 0 });
+        }
+
+        // Other encodings never get a UTF-8 BOM
+        let mut file_state = FileState {
+            encoding: "Windows-1252".to_string(),
+            had_bom: true,
+            ..FileState::default()
+        };
+        file_state
+            .save_file(path_str, text)
+            .expect("Failed to save");
+        assert_eq!(
+            fs::read(&path).expect("Failed to read saved file"),
+            text.as_bytes()
+        );
+        assert_eq!(file_state.encoding_label(), "Windows-1252");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_save_keeps_previous_version_in_backup() {
+        let mut file_state = FileState {
+            create_backup: true,
+            ..FileState::default()
+        };
+        let temp_path = std::env::temp_dir().join("test_Nodepat_backup.txt");
+        let temp_path_str = temp_path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        let backup = backup_path(&temp_path);
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_file(&backup);
+
+        file_state
+            .save_file(temp_path_str, "first")
+            .expect("Failed to save test file");
+        assert!(!backup.exists());
+        file_state
+            .save_file(temp_path_str, "second")
+            .expect("Failed to save test file");
+        assert_eq!(file_state.backup_warning, None);
+        assert_eq!(
+            fs::read_to_string(&backup).expect("Failed to read backup"),
+            "first"
+        );
+        assert_eq!(
+            fs::read_to_string(&temp_path).expect("Failed to read test file"),
+            "second"
+        );
+
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_file(&backup);
+    }
+
+    #[test]
+    fn test_failed_backup_does_not_fail_save() {
+        let mut file_state = FileState {
+            create_backup: true,
+            ..FileState::default()
+        };
+        let temp_path = std::env::temp_dir().join("test_Nodepat_backup_blocked.txt");
+        let temp_path_str = temp_path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        let backup = backup_path(&temp_path);
+        fs::write(&temp_path, "first").expect("Failed to write test file");
+        // A directory in place of the backup file makes the copy fail
+        let _ = fs::remove_file(&backup);
+        fs::create_dir_all(&backup).expect("Failed to create directory");
+
+        file_state
+            .save_file(temp_path_str, "second")
+            .expect("Failed to save test file");
+        assert!(file_state.backup_warning.is_some());
+        assert_eq!(
+            fs::read_to_string(&temp_path).expect("Failed to read test file"),
+            "second"
+        );
+
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_dir(&backup);
+    }
+
+    #[test]
+    fn test_save_replaces_file_without_leftovers() {
+        let mut file_state = FileState::default();
+        let temp_path = std::env::temp_dir().join("test_Nodepat_atomic.txt");
+        let temp_path_str = temp_path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        fs::write(&temp_path, "old content").expect("Failed to write test file");
+
+        file_state
+            .save_file(temp_path_str, "new content")
+            .expect("Failed to save test file");
+        assert_eq!(
+            fs::read_to_string(&temp_path).expect("Failed to read test file"),
+            "new content"
+        );
+        assert!(
+            !temp_file_path(&temp_path)
+                .expect("Failed to get temporary path")
+                .exists(),
+            "temporary file left behind"
+        );
+
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    #[test]
+    fn test_failed_temp_write_keeps_target() {
+        let mut file_state = FileState::default();
+        let temp_path = std::env::temp_dir().join("test_Nodepat_atomic_blocked.txt");
+        let temp_path_str = temp_path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        fs::write(&temp_path, "old content").expect("Failed to write test file");
+        // A directory in place of the temporary file makes its write fail
+        let blocked = temp_file_path(&temp_path).expect("Failed to get temporary path");
+        fs::create_dir_all(&blocked).expect("Failed to create directory");
+
+        let error = file_state
+            .save_file(temp_path_str, "new content")
+            .expect_err("Save should fail");
+        assert!(error.contains("temporary file"), "{error}");
+        assert_eq!(
+            fs::read_to_string(&temp_path).expect("Failed to read test file"),
+            "old content"
+        );
+
+        let _ = fs::remove_dir(&blocked);
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    #[test]
+    fn test_detects_external_change() {
+        let mut file_state = FileState::default();
+        let temp_path = std::env::temp_dir().join("test_Nodepat_external.txt");
+        let temp_path_str = temp_path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        fs::write(&temp_path, "original").expect("Failed to write test file");
+
+        file_state
+            .load_file(temp_path_str)
+            .expect("Failed to load test file");
+        assert_eq!(file_state.changed_on_disk(), None);
+
+        fs::write(&temp_path, "changed elsewhere").expect("Failed to write test file");
+        let stamp = file_state.changed_on_disk().expect("Change not detected");
+        assert_eq!(
+            Some(stamp),
+            DiskStamp::read(&temp_path),
+            "stamp of the changed file"
+        );
+
+        // Our own save is not an external change
+        file_state
+            .save_file(temp_path_str, "saved here")
+            .expect("Failed to save test file");
+        assert_eq!(file_state.changed_on_disk(), None);
+
+        let _ = fs::remove_file(&temp_path);
+        assert_eq!(file_state.changed_on_disk(), None);
+    }
+
+    #[test]
+    fn test_read_only_file_detected() {
+        let mut file_state = FileState::default();
+        let temp_path = std::env::temp_dir().join("test_Nodepat_read_only.txt");
+        let temp_path_str = temp_path
+            .to_str()
+            .expect("Failed to convert temp path to string");
+        let saved_as = std::env::temp_dir().join("test_Nodepat_read_only_copy.txt");
+        let _ = fs::remove_file(&temp_path);
+        fs::write(&temp_path, "protected").expect("Failed to write test file");
+        let mut permissions = fs::metadata(&temp_path)
+            .expect("Failed to read metadata")
+            .permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&temp_path, permissions).expect("Failed to set permissions");
+
+        file_state
+            .load_file(temp_path_str)
+            .expect("Failed to load test file");
+        assert!(file_state.read_only);
+
+        // Saving under another name gives a writable file
+        file_state
+            .save_file(
+                saved_as.to_str().expect("Failed to convert path"),
+                "protected",
+            )
+            .expect("Failed to save test file");
+        assert!(!file_state.read_only);
+
+        let mut permissions = fs::metadata(&temp_path)
+            .expect("Failed to read metadata")
+            .permissions();
+        #[allow(clippy::permissions_set_readonly_false)] // Only to delete the test file
+        permissions.set_readonly(false);
+        let _ = fs::set_permissions(&temp_path, permissions);
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_file(&saved_as);
+    }
+
+    #[test]
+    fn test_windows_1252_round_trip() {
+        let bytes = [
+            0x80, b' ', 0x93, b'q', 0x94, b' ', 0x91, b's', 0x92, 0x85, 0xE9,
+        ];
+        let text = "\u{20AC} \u{201C}q\u{201D} \u{2018}s\u{2019}\u{2026}\u{E9}";
+        assert_eq!(decode_with(&bytes, "Windows-1252").expect("decode"), text);
+        assert_eq!(encode_with(text, "Windows-1252"), bytes);
+        assert!(can_encode(text, "Windows-1252"));
+
+        // Invalid UTF-8 falls back to Windows-1252
+        assert_eq!(
+            detect_and_decode(&bytes, Encoding::Windows1252).expect("decode"),
+            (text.to_string(), "Windows-1252")
+        );
+
+        // Every byte survives a round trip, including the undefined ones
+        let all: Vec<u8> = (0..=255).collect();
+        let decoded = decode_with(&all, "Windows-1252").expect("decode");
+        assert_eq!(encode_with(&decoded, "Windows-1252"), all);
+
+        // Characters outside Windows-1252 are replaced
+        assert!(!can_encode("\u{0080}\u{0100}", "Windows-1252"));
+        assert_eq!(encode_with("\u{0080}\u{0100}", "Windows-1252"), b"??");
+        assert_eq!(encode_with("\u{20AC}", "Latin1"), [0x80]);
+    }
+
+    #[test]
+    fn test_every_encoding_round_trips() {
+        let text = "Line one\r\nLine two ?\n";
+        for encoding in Encoding::ALL {
+            let bytes = encode_with(text, encoding.name());
+            assert!(bytes.starts_with(encoding.codec().bom()), "{encoding:?}");
+            assert_eq!(
+                decode_with(&bytes, encoding.name()).expect("decode"),
+                text,
+                "{encoding:?}"
+            );
+            assert_eq!(Encoding::from_name(encoding.name()), Some(encoding));
+        }
+    }
+
+    #[test]
+    fn test_legacy_code_page_round_trips() {
+        let samples: [(Encoding, &str, &[u8]); 4] = [
+            (
+                Encoding::ShiftJis,
+                "日本語",
+                &[0x93, 0xFA, 0x96, 0x7B, 0x8C, 0xEA],
+            ),
+            (
+                Encoding::EucKr,
+                "한국어",
+                &[0xC7, 0xD1, 0xB1, 0xB9, 0xBE, 0xEE],
+            ),
+            (Encoding::Gbk, "中文", &[0xD6, 0xD0, 0xCE, 0xC4]),
+            (
+                Encoding::Koi8R,
+                "Привет",
+                &[0xF0, 0xD2, 0xC9, 0xD7, 0xC5, 0xD4],
+            ),
+        ];
+        for (encoding, text, bytes) in samples {
+            assert_eq!(encode_with(text, encoding.name()), bytes, "{encoding:?}");
+            assert_eq!(
+                decode_with(bytes, encoding.name()).expect("decode"),
+                text,
+                "{encoding:?}"
+            );
+            assert!(can_encode(text, encoding.name()), "{encoding:?}");
+            assert!(!encoding.is_unicode());
+        }
+
+        // Characters outside the code page are replaced
+        assert!(!can_encode("Привет 日本", "KOI8-R"));
+        assert_eq!(encode_with("ab日", "KOI8-R"), b"ab?");
+        // Truncated multibyte sequences are not valid
+        assert!(decode_with(&[0x93], "Shift-JIS").is_err());
+    }
+
+    #[test]
+    fn test_detection_falls_back_to_legacy_encoding() {
+        let shift_jis = encode_with("日本語テキスト", "Shift-JIS");
+        assert_eq!(
+            detect_and_decode(&shift_jis, Encoding::ShiftJis).expect("decode"),
+            ("日本語テキスト".to_string(), "Shift-JIS")
+        );
+
+        // Byte order marks and valid UTF-8 win over the legacy encoding
+        let utf16 = encode_with("日本", "UTF-16 BE");
+        assert_eq!(
+            detect_and_decode(&utf16, Encoding::ShiftJis).expect("decode"),
+            ("日本".to_string(), "UTF-16 BE")
+        );
+        assert_eq!(
+            detect_and_decode("日本".as_bytes(), Encoding::ShiftJis).expect("decode"),
+            ("日本".to_string(), "UTF-8")
+        );
+
+        // Data that is not valid in the legacy encoding either is read as
+        // Windows-1252
+        assert_eq!(
+            detect_and_decode(&[b'a', 0x93], Encoding::ShiftJis).expect("decode"),
+            ("a\u{201C}".to_string(), "Windows-1252")
+        );
+    }
+
+    #[test]
+    fn test_encoding_by_name() {
+        assert_eq!(encoding_by_name("UTF-16 BE"), Some("UTF-16 BE"));
+        assert_eq!(encoding_by_name("ANSI"), Some("Windows-1252"));
+        assert_eq!(encoding_by_name("EBCDIC"), None);
+    }
+
+    #[test]
+    fn test_reload_with_encoding() {
+        let temp_path = std::env::temp_dir().join("test_Nodepat_reload_encoding.txt");
+        // Valid UTF-8 that was meant as Windows-1252
+        fs::write(&temp_path, "caf\u{e9}").expect("Failed to write test file");
+        let path = temp_path.to_string_lossy().to_string();
+
+        let mut file_state = FileState::default();
+        assert_eq!(file_state.load_file(&path).expect("Failed to load"), "café");
+        assert_eq!(file_state.encoding, "UTF-8");
+        file_state.is_modified = true;
+
+        let text = file_state
+            .reload_with_encoding("Windows-1252")
+            .expect("Failed to reload");
+        assert_eq!(text, "cafÃ©");
+        assert_eq!(file_state.encoding, "Windows-1252");
+        assert!(!file_state.is_modified);
+
+        // A failed reload keeps the current encoding
+        fs::write(&temp_path, [b'a', 0xE9]).expect("Failed to write test file");
+        assert!(file_state.reload_with_encoding("UTF-8").is_err());
+        assert_eq!(file_state.encoding, "Windows-1252");
+
+        assert!(FileState::default().reload_with_encoding("UTF-8").is_err());
+        let _ = fs::remove_file(&temp_path);
+    }
+
     #[test]
     fn test_file_too_large() {
         let mut file_state = FileState::default();
@@ -265,4 +1379,41 @@ mod tests {
         // Cleanup
         let _ = fs::remove_file(&temp_path);
     }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b"plain text\n"));
+        assert!(!looks_binary(&encode_with("text", "UTF-16 LE")));
+        assert!(looks_binary(b"\x7fELF\x02\x01\x00\x00"));
+        // Only the start of the data is inspected
+        let mut late_nul = vec![b'x'; BINARY_SNIFF_LEN];
+        late_nul.push(0);
+        assert!(!looks_binary(&late_nul));
+
+        // Control characters other than whitespace mark binary data
+        assert!(looks_binary(b"\x89PNG\r\n\x1a\n\x01\x02\x03\x04IHDR"));
+        assert!(!looks_binary(b"tab\tform feed\x0c \x1b[1mbold\x1b[0m\r\n"));
+        assert!(!looks_binary(&encode_with(
+            "Grüße aus Köln",
+            "Windows-1252"
+        )));
+        let mut mostly_text = vec![b'x'; 100];
+        mostly_text[..9].fill(0x01);
+        assert!(!looks_binary(&mostly_text));
+        mostly_text[..11].fill(0x01);
+        assert!(looks_binary(&mostly_text));
+    }
+
+    #[test]
+    fn test_load_refuses_binary_file() {
+        let path = std::env::temp_dir().join("test_Nodepat_binary.png");
+        fs::write(&path, b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR").expect("write");
+        let mut file_state = FileState::default();
+        let error = file_state
+            .load_file(path.to_str().expect("path"))
+            .expect_err("Binary file was loaded");
+        assert_eq!(error, BINARY_FILE_ERROR);
+        assert!(file_state.file_path.is_empty());
+        let _ = fs::remove_file(&path);
+    }
 }
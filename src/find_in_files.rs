@@ -0,0 +1,474 @@
+//! Find in Files
+//!
+//! This module searches every matching file below a directory for a text
+//! and lists the matching lines. The directory is walked and searched on a
+//! background thread that sends its results over a channel; the app drains
+//! the channel each frame, so the window stays responsive.
+
+use crate::convert::glob_matches;
+use crate::file_ops::{Encoding, MAX_EDITOR_FILE_SIZE, detect_and_decode, looks_binary};
+use crate::search::SearchState;
+use crate::ui::file_browser::FileBrowser;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+/// Matching lines listed before the search stops
+pub const MAX_RESULTS: usize = 5000;
+
+/// Characters of a matching line shown in the results
+const MAX_PREVIEW_CHARS: usize = 200;
+
+/// A line that contains the search text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatch {
+    /// File containing the line
+    pub path: PathBuf,
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Text of the line, trimmed and shortened
+    pub preview: String,
+}
+
+impl FileMatch {
+    /// Get the text shown for the match in the results list
+    ///
+    /// # Returns
+    /// The match as "path:line: preview"
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!("{}:{}: {}", self.path.display(), self.line, self.preview)
+    }
+}
+
+/// Message from the search thread
+#[derive(Debug)]
+pub enum FindEvent {
+    /// A matching line was found
+    Match(FileMatch),
+    /// The search stopped (finished, cancelled, or hit `MAX_RESULTS`)
+    Finished {
+        /// Number of files searched
+        searched: usize,
+        /// Number of files skipped as binary, too large, or unreadable
+        skipped: usize,
+    },
+}
+
+/// Collect the files below a directory whose names match a glob pattern
+///
+/// Subdirectories are searched recursively. Symbolic links to directories
+/// are not followed, so links cannot make the walk loop. Unreadable
+/// directories are skipped.
+///
+/// # Arguments
+/// * `dir` - Directory to walk
+/// * `pattern` - Glob pattern for file names
+/// * `cancel` - Stops the walk when set
+/// * `files` - Receives the matching file paths
+pub fn collect_files(dir: &Path, pattern: &str, cancel: &AtomicBool, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<fs::DirEntry> = entries.flatten().collect();
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_files(&path, pattern, cancel, files);
+        } else if path.is_file() && glob_matches(pattern, &entry.file_name().to_string_lossy()) {
+            files.push(path);
+        }
+    }
+}
+
+/// Find the lines of a file that match a pattern
+///
+/// Files too large for the editor and binary files are skipped.
+///
+/// # Arguments
+/// * `path` - File to search
+/// * `regex` - Pattern to find
+/// * `legacy` - Encoding of files that are not Unicode
+///
+/// # Returns
+/// The matching lines, or None if the file was skipped
+#[must_use]
+pub fn search_file(path: &Path, regex: &Regex, legacy: Encoding) -> Option<Vec<FileMatch>> {
+    let data = fs::read(path).ok()?;
+    if data.len() > MAX_EDITOR_FILE_SIZE || looks_binary(&data) {
+        return None;
+    }
+    let (text, _) = detect_and_decode(&data, legacy).ok()?;
+    let matches = text
+        .split('\n')
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .map(|(index, line)| FileMatch {
+            path: path.to_path_buf(),
+            line: index + 1,
+            preview: preview(line),
+        })
+        .collect();
+    Some(matches)
+}
+
+/// Shorten a matching line for the results list
+///
+/// # Arguments
+/// * `line` - Line text
+///
+/// # Returns
+/// The line without surrounding whitespace, cut after `MAX_PREVIEW_CHARS`
+fn preview(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Walk a directory and send the matching lines of its files
+///
+/// # Arguments
+/// * `dir` - Directory to search
+/// * `pattern` - Glob pattern for file names
+/// * `regex` - Pattern to find
+/// * `legacy` - Encoding of files that are not Unicode
+/// * `cancel` - Stops the search when set
+/// * `sender` - Receives the results
+fn run_search(
+    dir: &Path,
+    pattern: &str,
+    regex: &Regex,
+    legacy: Encoding,
+    cancel: &AtomicBool,
+    sender: &Sender<FindEvent>,
+) {
+    let mut files = Vec::new();
+    collect_files(dir, pattern, cancel, &mut files);
+    let (mut searched, mut skipped, mut found) = (0, 0, 0);
+    for path in files {
+        if cancel.load(Ordering::Relaxed) || found >= MAX_RESULTS {
+            break;
+        }
+        let Some(matches) = search_file(&path, regex, legacy) else {
+            skipped += 1;
+            continue;
+        };
+        searched += 1;
+        for file_match in matches.into_iter().take(MAX_RESULTS - found) {
+            found += 1;
+            // The dialog was closed; nobody is listening
+            if sender.send(FindEvent::Match(file_match)).is_err() {
+                return;
+            }
+        }
+    }
+    let _ = sender.send(FindEvent::Finished { searched, skipped });
+}
+
+/// Search running on a background thread
+pub struct FindJob {
+    /// Results sent by the search thread
+    receiver: Receiver<FindEvent>,
+    /// Set to stop the search thread
+    cancel: Arc<AtomicBool>,
+}
+
+impl FindJob {
+    /// Start searching the matching files below a directory
+    ///
+    /// # Arguments
+    /// * `dir` - Directory to search
+    /// * `pattern` - Glob pattern for file names
+    /// * `regex` - Pattern to find
+    /// * `legacy` - Encoding of files that are not Unicode
+    ///
+    /// # Returns
+    /// Handle to the running search
+    #[must_use]
+    pub fn start(dir: PathBuf, pattern: String, regex: Regex, legacy: Encoding) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let job = Self {
+            receiver,
+            cancel: Arc::clone(&cancel),
+        };
+        std::thread::spawn(move || run_search(&dir, &pattern, &regex, legacy, &cancel, &sender));
+        job
+    }
+
+    /// Ask the search thread to stop
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Take the results sent since the last call
+    ///
+    /// # Returns
+    /// Tuple of (new events, whether the search thread has stopped)
+    #[must_use]
+    pub fn poll(&self) -> (Vec<FindEvent>, bool) {
+        let mut events = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => return (events, false),
+                Err(TryRecvError::Disconnected) => return (events, true),
+            }
+        }
+    }
+}
+
+/// State of the Find in Files dialog
+pub struct FindInFilesState {
+    /// Directory to search
+    pub directory: String,
+    /// Glob pattern for file names
+    pub pattern: String,
+    /// Text to find
+    pub find_text: String,
+    /// Case sensitive search
+    pub case_sensitive: bool,
+    /// Only match whole words
+    pub whole_word: bool,
+    /// Directory picker, while open
+    pub browser: Option<FileBrowser>,
+    /// Running search
+    pub job: Option<FindJob>,
+    /// Matching lines found so far
+    pub results: Vec<FileMatch>,
+    /// Summary of the last search
+    pub status: String,
+}
+
+impl Default for FindInFilesState {
+    fn default() -> Self {
+        Self {
+            directory: String::new(),
+            pattern: "*.txt".to_string(),
+            find_text: String::new(),
+            case_sensitive: false,
+            whole_word: false,
+            browser: None,
+            job: None,
+            results: Vec::new(),
+            status: String::new(),
+        }
+    }
+}
+
+impl FindInFilesState {
+    /// Start a search with the dialog's settings
+    ///
+    /// Any running search is cancelled and the results are cleared.
+    ///
+    /// # Arguments
+    /// * `legacy` - Encoding of files that are not Unicode
+    pub fn start(&mut self, legacy: Encoding) {
+        self.cancel();
+        self.results.clear();
+        let search = SearchState {
+            find_text: self.find_text.clone(),
+            case_sensitive: self.case_sensitive,
+            whole_word: self.whole_word,
+            ..SearchState::default()
+        };
+        match search.regex() {
+            Ok(regex) => {
+                self.status = "Searching...".to_string();
+                self.job = Some(FindJob::start(
+                    PathBuf::from(&self.directory),
+                    self.pattern.clone(),
+                    regex,
+                    legacy,
+                ));
+            }
+            Err(e) => self.status = e,
+        }
+    }
+
+    /// Stop the running search, keeping the results found so far
+    pub fn cancel(&mut self) {
+        if let Some(job) = self.job.take() {
+            job.cancel();
+            self.status = format!("Cancelled, {} matching lines", self.results.len());
+        }
+    }
+
+    /// Check whether a search is running
+    ///
+    /// # Returns
+    /// True until the search thread has stopped and been polled
+    #[must_use]
+    pub const fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Collect the results of the running search
+    ///
+    /// Called every frame; does nothing without a running search.
+    pub fn poll(&mut self) {
+        let Some(job) = &self.job else {
+            return;
+        };
+        let (events, stopped) = job.poll();
+        for event in events {
+            match event {
+                FindEvent::Match(file_match) => self.results.push(file_match),
+                FindEvent::Finished { searched, skipped } => {
+                    let limit = if self.results.len() >= MAX_RESULTS {
+                        " (limit reached)"
+                    } else {
+                        ""
+                    };
+                    self.status = format!(
+                        "{} matching lines{limit} in {searched} files, {skipped} skipped",
+                        self.results.len()
+                    );
+                }
+            }
+        }
+        if stopped {
+            self.job = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty fixture directory in the temp directory
+    fn fixture_dir(name: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Failed to create fixture directory");
+        dir
+    }
+
+    fn regex(find_text: &str, case_sensitive: bool, whole_word: bool) -> Regex {
+        SearchState {
+            find_text: find_text.to_string(),
+            case_sensitive,
+            whole_word,
+            ..SearchState::default()
+        }
+        .regex()
+        .expect("Failed to build pattern")
+    }
+
+    #[test]
+    fn test_collect_files_recurses_and_filters() {
+        let dir = fixture_dir("test_Nodepat_find_walk");
+        fs::create_dir_all(dir.join("sub/deeper")).expect("Failed to create directories");
+        fs::write(dir.join("a.txt"), "").expect("write");
+        fs::write(dir.join("b.log"), "").expect("write");
+        fs::write(dir.join("sub/deeper/c.txt"), "").expect("write");
+
+        let mut files = Vec::new();
+        collect_files(&dir, "*.txt", &AtomicBool::new(false), &mut files);
+        assert_eq!(files, vec![dir.join("a.txt"), dir.join("sub/deeper/c.txt")]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_search_file_lists_matching_lines() {
+        let dir = fixture_dir("test_Nodepat_find_lines");
+        let path = dir.join("notes.txt");
+        fs::write(&path, "Color\r\n  the colors  \ncolor\n").expect("write");
+
+        let lines = |regex: &Regex| {
+            search_file(&path, regex, Encoding::Windows1252)
+                .expect("File was skipped")
+                .iter()
+                .map(|m| (m.line, m.preview.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lines(&regex("color", false, false)),
+            vec![
+                (1, "Color".to_string()),
+                (2, "the colors".to_string()),
+                (3, "color".to_string())
+            ]
+        );
+        assert_eq!(
+            lines(&regex("color", true, true)),
+            vec![(3, "color".to_string())]
+        );
+        let found = search_file(&path, &regex("Color", true, false), Encoding::Windows1252)
+            .expect("File was skipped");
+        assert_eq!(found[0].label(), format!("{}:1: Color", path.display()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_search_file_skips_binary_and_large_files() {
+        let dir = fixture_dir("test_Nodepat_find_skip");
+        let binary = dir.join("image.txt");
+        fs::write(&binary, b"color\x00\x01").expect("write");
+        let large = dir.join("large.txt");
+        fs::write(&large, "color\n".repeat(MAX_EDITOR_FILE_SIZE)).expect("write");
+
+        let pattern = regex("color", false, false);
+        assert_eq!(search_file(&binary, &pattern, Encoding::Windows1252), None);
+        assert_eq!(search_file(&large, &pattern, Encoding::Windows1252), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_job_sends_results() {
+        let dir = fixture_dir("test_Nodepat_find_job");
+        fs::create_dir_all(dir.join("sub")).expect("Failed to create directory");
+        fs::write(dir.join("a.txt"), "one\ntwo").expect("write");
+        fs::write(dir.join("sub/b.txt"), "two\nthree").expect("write");
+        fs::write(dir.join("c.bin"), b"two\x00").expect("write");
+
+        let mut state = FindInFilesState {
+            directory: dir.to_string_lossy().to_string(),
+            pattern: "*".to_string(),
+            find_text: "two".to_string(),
+            ..FindInFilesState::default()
+        };
+        state.start(Encoding::Windows1252);
+        while state.is_running() {
+            state.poll();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let found: Vec<(PathBuf, usize)> = state
+            .results
+            .iter()
+            .map(|m| (m.path.clone(), m.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(dir.join("a.txt"), 2), (dir.join("sub/b.txt"), 1)]
+        );
+        assert_eq!(state.status, "2 matching lines in 2 files, 1 skipped");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_preview_is_shortened() {
+        let line = format!("  {}", "é".repeat(MAX_PREVIEW_CHARS + 5));
+        let shown = preview(&line);
+        assert_eq!(shown.chars().count(), MAX_PREVIEW_CHARS + 1);
+        assert!(shown.ends_with('…'));
+    }
+}
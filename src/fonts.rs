@@ -0,0 +1,145 @@
+//! Custom editor fonts
+//!
+//! This module loads a TrueType or OpenType font file chosen by the user
+//! and registers it with egui as a font family of its own. egui's built-in
+//! monospace fonts stay behind it for characters the file has no glyphs
+//! for. Font files are checked before egui sees them, as egui panics on a
+//! file it cannot parse.
+
+use crate::format::FontFamily;
+use eframe::egui;
+use std::path::Path;
+
+/// Name of the custom font and its family in egui's font definitions
+pub const CUSTOM_FONT_NAME: &str = "nodepat-custom";
+
+/// Get egui's font family of the custom font
+#[must_use]
+pub fn custom_family() -> egui::FontFamily {
+    egui::FontFamily::Name(CUSTOM_FONT_NAME.into())
+}
+
+/// Check that data is a font egui can use
+///
+/// # Arguments
+/// * `data` - Contents of a font file
+///
+/// # Returns
+/// Ok, or an error describing what is wrong with the font
+pub fn check_font_data(data: &[u8]) -> Result<(), String> {
+    use ab_glyph::Font;
+    let font = ab_glyph::FontRef::try_from_slice(data)
+        .map_err(|_| "The file is not a TrueType or OpenType font.".to_string())?;
+    if font.units_per_em().is_none() {
+        return Err("The font's units per em are out of range.".to_string());
+    }
+    Ok(())
+}
+
+/// Read and check a font file
+///
+/// # Arguments
+/// * `path` - Path of a .ttf or .otf file
+///
+/// # Returns
+/// The file's contents, or an error message
+pub fn read_font(path: &Path) -> Result<Vec<u8>, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    check_font_data(&data)?;
+    Ok(data)
+}
+
+/// Get egui's default fonts plus a custom font
+///
+/// # Arguments
+/// * `data` - Contents of a font file checked with `check_font_data`
+///
+/// # Returns
+/// Font definitions with the custom font family registered
+#[must_use]
+pub fn font_definitions(data: Vec<u8>) -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+    fonts.font_data.insert(
+        CUSTOM_FONT_NAME.to_string(),
+        std::sync::Arc::new(egui::FontData::from_owned(data)),
+    );
+    let mut family = vec![CUSTOM_FONT_NAME.to_string()];
+    family.extend(
+        fonts
+            .families
+            .get(&egui::FontFamily::Monospace)
+            .cloned()
+            .unwrap_or_default(),
+    );
+    fonts.families.insert(custom_family(), family);
+    fonts
+}
+
+/// Get the font the editor uses
+///
+/// The custom font is only used once egui has it, which is the frame
+/// after it was loaded; until then, and if it could not be loaded, the
+/// built-in monospace font is used.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `family` - Font family setting
+/// * `size` - Font size in points
+///
+/// # Returns
+/// The editor font
+#[must_use]
+pub fn editor_font(ctx: &egui::Context, family: FontFamily, size: f32) -> egui::FontId {
+    match family {
+        FontFamily::Monospace => egui::FontId::monospace(size),
+        FontFamily::Proportional => egui::FontId::proportional(size),
+        FontFamily::Custom => {
+            let custom = custom_family();
+            if ctx.fonts(|fonts| fonts.families().contains(&custom)) {
+                egui::FontId::new(size, custom)
+            } else {
+                egui::FontId::monospace(size)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the data of one of egui's bundled fonts
+    fn bundled_font() -> Vec<u8> {
+        let fonts = egui::FontDefinitions::default();
+        let name = fonts.families[&egui::FontFamily::Monospace][0].clone();
+        fonts.font_data[&name].font.to_vec()
+    }
+
+    #[test]
+    fn test_check_font_data() {
+        assert!(check_font_data(&bundled_font()).is_ok());
+        assert!(check_font_data(b"").is_err());
+        assert!(check_font_data(b"not a font at all, just some text").is_err());
+    }
+
+    #[test]
+    fn test_read_font_rejects_missing_and_invalid_files() {
+        let path = std::env::temp_dir().join("test_Nodepat_font.ttf");
+        std::fs::write(&path, "plain text").expect("Failed to write font file");
+        assert!(read_font(&path).is_err());
+        std::fs::remove_file(&path).expect("Failed to remove font file");
+        assert!(read_font(&path).is_err());
+    }
+
+    #[test]
+    fn test_custom_family_falls_back_to_monospace() {
+        let fonts = font_definitions(bundled_font());
+        let family = &fonts.families[&custom_family()];
+        assert_eq!(family[0], CUSTOM_FONT_NAME);
+        assert_eq!(
+            family[1..],
+            fonts.families[&egui::FontFamily::Monospace][..]
+        );
+        assert!(fonts.font_data.contains_key(CUSTOM_FONT_NAME));
+    }
+}
@@ -6,6 +6,7 @@
 ///
 /// Represents the available font families in egui.
 /// Monospace fonts are fixed-width, Proportional fonts are variable-width.
+/// Custom is a font file loaded by the user.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FontFamily {
     /// Monospace (fixed-width) font family
@@ -13,6 +14,8 @@ pub enum FontFamily {
     Monospace,
     /// Proportional (variable-width) font family
     Proportional,
+    /// Font loaded from the file in `FormatSettings::font_file`
+    Custom,
 }
 
 impl FontFamily {
@@ -25,13 +28,14 @@ impl FontFamily {
         match self {
             Self::Monospace => "Monospace",
             Self::Proportional => "Proportional",
+            Self::Custom => "Custom font",
         }
     }
 
-    /// Get all available font families
+    /// Get the built-in font families
     ///
     /// # Returns
-    /// Vector of all font family variants
+    /// Vector of the font families that need no font file
     #[must_use]
     pub fn all() -> Vec<Self> {
         vec![Self::Monospace, Self::Proportional]
@@ -82,18 +86,134 @@ impl FontStyle {
     }
 }
 
+/// Zoom presets offered in the View menu
+pub const ZOOM_PRESETS: [f32; 5] = [0.9, 1.0, 1.1, 1.25, 1.5];
+
 /// Format settings including font preferences
 #[allow(clippy::struct_field_names)] // Font-related fields naturally share prefix
 #[derive(Default)]
 pub struct FormatSettings {
     /// Font family name (kept for backward compatibility with config)
     pub font_family: String,
-    /// Font family selection (Monospace or Proportional)
+    /// Font family selection (Monospace, Proportional or Custom)
     pub font_family_type: FontFamily,
+    /// Path of the custom font file; empty for none
+    pub font_file: String,
     /// Font style (Regular, Bold, Italic, `BoldItalic`)
     pub font_style: FontStyle,
     /// Font size in points
     pub font_size: f32,
+    /// Zoom factor of the active document (1.0 = 100%)
+    pub zoom_factor: f32,
+    /// Wrap long lines to the window width
+    pub word_wrap: bool,
+    /// Width of a tab in spaces
+    pub tab_width: u8,
+    /// Tab inserts spaces instead of a tab character
+    pub indent_with_spaces: bool,
+}
+
+impl FormatSettings {
+    /// Get the font size used by the editor after applying zoom
+    ///
+    /// # Returns
+    /// Zoomed font size in points, clamped to 8–72
+    #[must_use]
+    pub fn editor_font_size(&self) -> f32 {
+        (self.font_size * self.zoom_factor).clamp(8.0, 72.0)
+    }
+
+    /// Get the zoom relative to the configured font size
+    ///
+    /// The clamp of the editor font size is taken into account, so the
+    /// percentage matches what is shown.
+    ///
+    /// # Returns
+    /// The zoom as a percentage, e.g. 1.2 for 120%
+    #[must_use]
+    pub fn zoom(&self) -> f32 {
+        if self.font_size > 0.0 {
+            self.editor_font_size() / self.font_size
+        } else {
+            self.zoom_factor
+        }
+    }
+
+    /// Get the zoom for display
+    ///
+    /// # Returns
+    /// The zoom as a rounded percentage, e.g. "120%"
+    #[must_use]
+    pub fn zoom_label(&self) -> String {
+        format!("{:.0}%", self.zoom() * 100.0)
+    }
+
+    /// Check whether the configured font size is shown unzoomed
+    ///
+    /// # Returns
+    /// True if the zoom rounds to 100%
+    #[must_use]
+    pub fn is_default_zoom(&self) -> bool {
+        (self.zoom() - 1.0).abs() < 0.005
+    }
+
+    /// Get the text inserted by the Tab key
+    ///
+    /// # Returns
+    /// `tab_width` spaces, or a tab character
+    #[must_use]
+    pub fn indent_unit(&self) -> String {
+        if self.indent_with_spaces {
+            " ".repeat(usize::from(self.tab_width.max(1)))
+        } else {
+            "\t".to_string()
+        }
+    }
+
+    /// Reset the zoom to 100%
+    pub const fn reset_zoom(&mut self) {
+        self.zoom_factor = 1.0;
+    }
 }
 
-impl FormatSettings {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(font_size: f32, zoom_factor: f32) -> FormatSettings {
+        FormatSettings {
+            font_size,
+            zoom_factor,
+            ..FormatSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_zoom_label() {
+        assert_eq!(settings(12.0, 1.0).zoom_label(), "100%");
+        assert_eq!(settings(10.0, 1.2).zoom_label(), "120%");
+        assert_eq!(settings(12.0, 0.9).zoom_label(), "90%");
+        // The editor font size stops at 72 points
+        assert_eq!(settings(40.0, 3.0).zoom_label(), "180%");
+    }
+
+    #[test]
+    fn test_reset_zoom() {
+        let mut format = settings(14.0, 1.5);
+        assert!(!format.is_default_zoom());
+        format.reset_zoom();
+        assert!(format.is_default_zoom());
+        assert!((format.editor_font_size() - 14.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_indent_unit() {
+        let mut format = FormatSettings {
+            tab_width: 2,
+            ..FormatSettings::default()
+        };
+        assert_eq!(format.indent_unit(), "\t");
+        format.indent_with_spaces = true;
+        assert_eq!(format.indent_unit(), "  ");
+    }
+}
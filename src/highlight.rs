@@ -0,0 +1,674 @@
+//! Syntax highlighting
+//!
+//! This module splits a document into keywords, strings, comments, and
+//! numbers with simple per-language rules, chosen by the file extension.
+//! The rules do not parse the language, so unusual constructs may be
+//! colored wrongly, but they are fast and never fail.
+
+use eframe::egui::Color32;
+use std::ops::Range;
+use std::path::Path;
+
+/// Language whose rules color a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    /// No highlighting
+    #[default]
+    Plain,
+    /// Rust source code
+    Rust,
+    /// Python source code
+    Python,
+    /// JSON data
+    Json,
+    /// Markdown text
+    Markdown,
+    /// INI configuration files
+    Ini,
+}
+
+impl Language {
+    /// Choose the language of a file by its extension
+    ///
+    /// # Arguments
+    /// * `path` - File path
+    ///
+    /// # Returns
+    /// The language, or `Plain` for unknown extensions
+    #[must_use]
+    pub fn from_path(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "rs" => Self::Rust,
+            "py" | "pyw" => Self::Python,
+            "json" => Self::Json,
+            "md" | "markdown" => Self::Markdown,
+            "ini" | "cfg" | "conf" => Self::Ini,
+            _ => Self::Plain,
+        }
+    }
+
+    /// Get display name for the language
+    ///
+    /// # Returns
+    /// Menu label of the language
+    #[must_use]
+    pub const fn display_name(self) -> &'static str {
+        match self {
+            Self::Plain => "Plain Text",
+            Self::Rust => "Rust",
+            Self::Python => "Python",
+            Self::Json => "JSON",
+            Self::Markdown => "Markdown",
+            Self::Ini => "INI",
+        }
+    }
+
+    /// Get all languages
+    ///
+    /// # Returns
+    /// Languages in menu order
+    #[must_use]
+    pub const fn all() -> [Self; 6] {
+        [
+            Self::Plain,
+            Self::Rust,
+            Self::Python,
+            Self::Json,
+            Self::Markdown,
+            Self::Ini,
+        ]
+    }
+}
+
+/// Kind of a highlighted piece of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Keyword, JSON key, Markdown heading, or INI section
+    Keyword,
+    /// String or character literal, or Markdown code
+    String,
+    /// Comment, or Markdown quote
+    Comment,
+    /// Number literal
+    Number,
+}
+
+impl TokenKind {
+    /// Get the color of the token kind
+    ///
+    /// # Arguments
+    /// * `dark_mode` - Color for the dark theme instead of the light one
+    ///
+    /// # Returns
+    /// Text color
+    #[must_use]
+    pub const fn color(self, dark_mode: bool) -> Color32 {
+        match (self, dark_mode) {
+            (Self::Keyword, true) => Color32::from_rgb(86, 156, 214),
+            (Self::Keyword, false) => Color32::from_rgb(0, 0, 255),
+            (Self::String, true) => Color32::from_rgb(206, 145, 120),
+            (Self::String, false) => Color32::from_rgb(163, 21, 21),
+            (Self::Comment, true) => Color32::from_rgb(106, 153, 85),
+            (Self::Comment, false) => Color32::from_rgb(0, 128, 0),
+            (Self::Number, true) => Color32::from_rgb(181, 206, 168),
+            (Self::Number, false) => Color32::from_rgb(9, 134, 88),
+        }
+    }
+}
+
+/// A highlighted piece of text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// Byte range in the text
+    pub range: Range<usize>,
+    /// What the text is
+    pub kind: TokenKind,
+}
+
+/// Rules of a programming language or data format
+#[allow(clippy::struct_excessive_bools)] // Independent language features
+struct CodeRules {
+    /// Words shown as keywords
+    keywords: &'static [&'static str],
+    /// Start of a comment that runs to the end of the line
+    line_comment: Option<&'static str>,
+    /// Start and end of a comment that may span lines
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Characters that start and end a string
+    quotes: &'static [u8],
+    /// Strings may span lines
+    multiline_strings: bool,
+    /// Three quotes start a string that may span lines, as in Python
+    triple_quotes: bool,
+    /// `'` starts a character literal or, as in Rust, a lifetime
+    char_literals: bool,
+    /// A string followed by ':' is a key, as in JSON
+    keys: bool,
+}
+
+/// Rules for Rust
+const RUST: CodeRules = CodeRules {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"",
+    multiline_strings: true,
+    triple_quotes: false,
+    char_literals: true,
+    keys: false,
+};
+
+/// Rules for Python
+const PYTHON: CodeRules = CodeRules {
+    keywords: &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: b"\"'",
+    multiline_strings: false,
+    triple_quotes: true,
+    char_literals: false,
+    keys: false,
+};
+
+/// Rules for JSON
+const JSON: CodeRules = CodeRules {
+    keywords: &["true", "false", "null"],
+    line_comment: None,
+    block_comment: None,
+    quotes: b"\"",
+    multiline_strings: false,
+    triple_quotes: false,
+    char_literals: false,
+    keys: true,
+};
+
+/// Split a text into highlighted tokens
+///
+/// # Arguments
+/// * `text` - Text to highlight
+/// * `language` - Rules to apply
+///
+/// # Returns
+/// Tokens in text order, not overlapping; text between them is plain
+#[must_use]
+pub fn tokenize(text: &str, language: Language) -> Vec<Token> {
+    match language {
+        Language::Plain => Vec::new(),
+        Language::Rust => tokenize_code(text, &RUST),
+        Language::Python => tokenize_code(text, &PYTHON),
+        Language::Json => tokenize_code(text, &JSON),
+        Language::Markdown => tokenize_markdown(text),
+        Language::Ini => tokenize_ini(text),
+    }
+}
+
+/// Check whether a byte continues a word
+///
+/// Bytes of non-ASCII characters count as word bytes, so words never end
+/// inside a character.
+///
+/// # Arguments
+/// * `byte` - Byte of the text
+///
+/// # Returns
+/// True for ASCII letters, digits, '_', and non-ASCII bytes
+const fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// Find the end of the line containing a position
+///
+/// # Arguments
+/// * `text` - Text
+/// * `position` - Byte offset in the line
+///
+/// # Returns
+/// Byte offset of the line break, or the end of the text
+fn line_end(text: &str, position: usize) -> usize {
+    text[position..]
+        .find('\n')
+        .map_or(text.len(), |offset| position + offset)
+}
+
+/// Find the end of a string literal
+///
+/// # Arguments
+/// * `bytes` - Text bytes
+/// * `start` - Byte offset of the opening quote
+/// * `multiline` - Whether the string may continue past a line break
+///
+/// # Returns
+/// Byte offset after the closing quote; an unterminated string ends at the
+/// line break (or the end of the text)
+fn string_end(bytes: &[u8], start: usize, multiline: bool) -> usize {
+    let quote = bytes[start];
+    let mut index = start + 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'\n' if !multiline => return index,
+            byte if byte == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Find the end of a character literal like `'a'` or `'\n'`
+///
+/// # Arguments
+/// * `text` - Text
+/// * `start` - Byte offset of the opening `'`
+///
+/// # Returns
+/// Byte offset after the closing `'`, or None for a lifetime like `'a`
+fn char_literal_end(text: &str, start: usize) -> Option<usize> {
+    let rest = &text[start + 1..];
+    if let Some(escape) = rest.strip_prefix('\\') {
+        // Escapes like '\u{1F600}' are short and stay on one line
+        let close = escape
+            .char_indices()
+            .take(12)
+            .take_while(|&(_, c)| c != '\n')
+            .find(|&(_, c)| c == '\'')?;
+        return Some(start + 2 + close.0 + 1);
+    }
+    let c = rest.chars().next()?;
+    rest[c.len_utf8()..]
+        .starts_with('\'')
+        .then(|| start + 1 + c.len_utf8() + 1)
+}
+
+/// Split source code into tokens
+///
+/// # Arguments
+/// * `text` - Source code
+/// * `rules` - Rules of the language
+///
+/// # Returns
+/// Tokens in text order
+fn tokenize_code(text: &str, rules: &CodeRules) -> Vec<Token> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &text[index..];
+        let byte = bytes[index];
+        let (end, kind) = if let Some(prefix) = rules.line_comment
+            && rest.starts_with(prefix)
+        {
+            (line_end(text, index), Some(TokenKind::Comment))
+        } else if let Some((open, close)) = rules.block_comment
+            && rest.starts_with(open)
+        {
+            let body = index + open.len();
+            let end = text[body..]
+                .find(close)
+                .map_or(text.len(), |offset| body + offset + close.len());
+            (end, Some(TokenKind::Comment))
+        } else if rules.triple_quotes && (rest.starts_with("\"\"\"") || rest.starts_with("'''")) {
+            let end = text[index + 3..]
+                .find(&rest[..3])
+                .map_or(text.len(), |offset| index + 3 + offset + 3);
+            (end, Some(TokenKind::String))
+        } else if rules.quotes.contains(&byte) {
+            let end = string_end(bytes, index, rules.multiline_strings);
+            let is_key = rules.keys && text[end..].trim_start().starts_with(':');
+            let kind = if is_key {
+                TokenKind::Keyword
+            } else {
+                TokenKind::String
+            };
+            (end, Some(kind))
+        } else if rules.char_literals && byte == b'\'' {
+            char_literal_end(text, index)
+                .map_or((index + 1, None), |end| (end, Some(TokenKind::String)))
+        } else if byte.is_ascii_digit() {
+            let mut end = index + 1;
+            while end < bytes.len()
+                && (bytes[end].is_ascii_alphanumeric()
+                    || bytes[end] == b'_'
+                    // A point only continues a number before a digit, so
+                    // ranges like 1..10 are two numbers
+                    || (bytes[end] == b'.' && bytes.get(end + 1).is_some_and(u8::is_ascii_digit)))
+            {
+                end += 1;
+            }
+            (end, Some(TokenKind::Number))
+        } else if is_word_byte(byte) {
+            let mut end = index + 1;
+            while end < bytes.len() && is_word_byte(bytes[end]) {
+                end += 1;
+            }
+            let keyword = rules.keywords.contains(&&text[index..end]);
+            (end, keyword.then_some(TokenKind::Keyword))
+        } else {
+            (index + 1, None)
+        };
+        if let Some(kind) = kind {
+            tokens.push(Token {
+                range: index..end,
+                kind,
+            });
+        }
+        index = end;
+    }
+    tokens
+}
+
+/// Iterate over the lines of a text with their offsets
+///
+/// # Arguments
+/// * `text` - Text
+///
+/// # Returns
+/// Iterator over (byte offset of the line, line without its line break)
+fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some((start, line.strip_suffix('\r').unwrap_or(line)))
+    })
+}
+
+/// Split Markdown into tokens
+///
+/// Headings are keywords, code blocks and `code` spans are strings, and
+/// quotes are comments.
+///
+/// # Arguments
+/// * `text` - Markdown text
+///
+/// # Returns
+/// Tokens in text order
+fn tokenize_markdown(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut in_fence = false;
+    for (start, line) in lines_with_offsets(text) {
+        let whole_line = start..start + line.len();
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            tokens.push(Token {
+                range: whole_line,
+                kind: TokenKind::String,
+            });
+        } else if in_fence {
+            if !line.is_empty() {
+                tokens.push(Token {
+                    range: whole_line,
+                    kind: TokenKind::String,
+                });
+            }
+        } else if indent < 4 && trimmed.starts_with('#') {
+            tokens.push(Token {
+                range: whole_line,
+                kind: TokenKind::Keyword,
+            });
+        } else if indent < 4 && trimmed.starts_with('>') {
+            tokens.push(Token {
+                range: whole_line,
+                kind: TokenKind::Comment,
+            });
+        } else {
+            let mut search = 0;
+            while let Some(open) = line[search..].find('`').map(|offset| search + offset) {
+                let Some(close) = line[open + 1..].find('`').map(|offset| open + 1 + offset) else {
+                    break;
+                };
+                tokens.push(Token {
+                    range: start + open..start + close + 1,
+                    kind: TokenKind::String,
+                });
+                search = close + 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// Split an INI file into tokens
+///
+/// Section headers are keywords, and `;` or `#` lines are comments. A
+/// value is a number or, in quotes, a string.
+///
+/// # Arguments
+/// * `text` - INI text
+///
+/// # Returns
+/// Tokens in text order
+fn tokenize_ini(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (start, line) in lines_with_offsets(text) {
+        let trimmed = line.trim_start();
+        let indent = start + line.len() - trimmed.len();
+        let kind = if trimmed.starts_with([';', '#']) {
+            TokenKind::Comment
+        } else if trimmed.starts_with('[') {
+            TokenKind::Keyword
+        } else if let Some(separator) = line.find(['=', ':']) {
+            let value = line[separator + 1..].trim();
+            let value_start =
+                start + separator + 1 + line[separator + 1..].find(value).unwrap_or(0);
+            let quoted = value.len() >= 2
+                && ((value.starts_with('"') && value.ends_with('"'))
+                    || (value.starts_with('\'') && value.ends_with('\'')));
+            let kind = if quoted {
+                TokenKind::String
+            } else if value.parse::<f64>().is_ok() {
+                TokenKind::Number
+            } else {
+                continue;
+            };
+            tokens.push(Token {
+                range: value_start..value_start + value.len(),
+                kind,
+            });
+            continue;
+        } else {
+            continue;
+        };
+        tokens.push(Token {
+            range: indent..start + line.trim_end().len(),
+            kind,
+        });
+    }
+    tokens
+}
+
+/// Colored text ranges, kept until the text, language, or theme changes
+///
+/// The editor lays out its text every frame; tokenizing only after a
+/// change keeps highlighting cheap while nothing is typed.
+#[derive(Default)]
+pub struct HighlightCache {
+    /// Text, language, and dark mode the spans were computed for
+    key: Option<(String, Language, bool)>,
+    /// Byte ranges and their colors, in text order
+    spans: Vec<(Range<usize>, Color32)>,
+}
+
+impl HighlightCache {
+    /// Get the colored ranges of a text
+    ///
+    /// # Arguments
+    /// * `text` - Text to highlight
+    /// * `language` - Rules to apply
+    /// * `dark_mode` - Use the dark theme colors
+    ///
+    /// # Returns
+    /// Byte ranges and their colors, in text order
+    pub fn spans(
+        &mut self,
+        text: &str,
+        language: Language,
+        dark_mode: bool,
+    ) -> &[(Range<usize>, Color32)] {
+        let current = self
+            .key
+            .as_ref()
+            .is_some_and(|(cached, cached_language, cached_dark)| {
+                *cached_language == language && *cached_dark == dark_mode && cached == text
+            });
+        if !current {
+            self.spans = tokenize(text, language)
+                .into_iter()
+                .map(|token| (token.range, token.kind.color(dark_mode)))
+                .collect();
+            self.key = Some((text.to_string(), language, dark_mode));
+        }
+        &self.spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tokenize and return the text and kind of each token
+    fn tokens(text: &str, language: Language) -> Vec<(&str, TokenKind)> {
+        tokenize(text, language)
+            .into_iter()
+            .map(|token| (&text[token.range], token.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(Language::from_path("/src/main.rs"), Language::Rust);
+        assert_eq!(Language::from_path("script.PY"), Language::Python);
+        assert_eq!(Language::from_path("README.md"), Language::Markdown);
+        assert_eq!(Language::from_path("setup.cfg"), Language::Ini);
+        assert_eq!(Language::from_path("notes.txt"), Language::Plain);
+        assert_eq!(Language::from_path(""), Language::Plain);
+    }
+
+    #[test]
+    fn test_rust_tokens() {
+        let text = "fn main<'a>() { let c = 'é'; // note\n/* x */ \"s\\\"t\" 1..10 }";
+        assert_eq!(
+            tokens(text, Language::Rust),
+            vec![
+                ("fn", TokenKind::Keyword),
+                ("let", TokenKind::Keyword),
+                ("'é'", TokenKind::String),
+                ("// note", TokenKind::Comment),
+                ("/* x */", TokenKind::Comment),
+                ("\"s\\\"t\"", TokenKind::String),
+                ("1", TokenKind::Number),
+                ("10", TokenKind::Number),
+            ]
+        );
+        assert_eq!(
+            tokens("'\\n' 0x1F 2.5", Language::Rust),
+            vec![
+                ("'\\n'", TokenKind::String),
+                ("0x1F", TokenKind::Number),
+                ("2.5", TokenKind::Number),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_tokens() {
+        let text = "def f(x):  # add\n    return 'a' + \"\"\"b\nc\"\"\" + 3\nfor_x = None";
+        assert_eq!(
+            tokens(text, Language::Python),
+            vec![
+                ("def", TokenKind::Keyword),
+                ("# add", TokenKind::Comment),
+                ("return", TokenKind::Keyword),
+                ("'a'", TokenKind::String),
+                ("\"\"\"b\nc\"\"\"", TokenKind::String),
+                ("3", TokenKind::Number),
+                ("None", TokenKind::Keyword),
+            ]
+        );
+        // An unterminated string stops at the end of its line
+        assert_eq!(
+            tokens("'open\nif", Language::Python),
+            vec![("'open", TokenKind::String), ("if", TokenKind::Keyword)]
+        );
+    }
+
+    #[test]
+    fn test_json_tokens() {
+        let text = "{\"name\" : \"Nodepat\", \"size\": -1.5e3, \"ok\": [true, null]}";
+        assert_eq!(
+            tokens(text, Language::Json),
+            vec![
+                ("\"name\"", TokenKind::Keyword),
+                ("\"Nodepat\"", TokenKind::String),
+                ("\"size\"", TokenKind::Keyword),
+                ("1.5e3", TokenKind::Number),
+                ("\"ok\"", TokenKind::Keyword),
+                ("true", TokenKind::Keyword),
+                ("null", TokenKind::Keyword),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_tokens() {
+        let text = "# Title\nUse `cargo` and `x`.\n> quote\n```\n# not a heading\n```\nend";
+        assert_eq!(
+            tokens(text, Language::Markdown),
+            vec![
+                ("# Title", TokenKind::Keyword),
+                ("`cargo`", TokenKind::String),
+                ("`x`", TokenKind::String),
+                ("> quote", TokenKind::Comment),
+                ("```", TokenKind::String),
+                ("# not a heading", TokenKind::String),
+                ("```", TokenKind::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ini_tokens() {
+        let text = "; comment\r\n[section]\nname = \"Nodepat\"\nsize=12\nmode = fast\n  # note";
+        assert_eq!(
+            tokens(text, Language::Ini),
+            vec![
+                ("; comment", TokenKind::Comment),
+                ("[section]", TokenKind::Keyword),
+                ("\"Nodepat\"", TokenKind::String),
+                ("12", TokenKind::Number),
+                ("# note", TokenKind::Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_text_has_no_tokens() {
+        assert!(tokenize("fn main() {}", Language::Plain).is_empty());
+    }
+
+    #[test]
+    fn test_cache_follows_text_and_theme() {
+        let mut cache = HighlightCache::default();
+        let dark = cache.spans("fn", Language::Rust, true).to_vec();
+        assert_eq!(dark, vec![(0..2, TokenKind::Keyword.color(true))]);
+        let light = cache.spans("fn", Language::Rust, false).to_vec();
+        assert_eq!(light, vec![(0..2, TokenKind::Keyword.color(false))]);
+        assert!(cache.spans("fn", Language::Plain, false).is_empty());
+        assert_eq!(cache.spans("x fn", Language::Rust, false).len(), 1);
+    }
+}
@@ -0,0 +1,347 @@
+//! Keyboard shortcuts for menu commands
+//!
+//! This module maps commands to their keyboard shortcuts, so the key
+//! handling and the shortcuts shown in the menus always agree.
+
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+/// A command that can be triggered by a keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// File > New
+    New,
+    /// File > New Window
+    NewWindow,
+    /// File > Open
+    Open,
+    /// File > Save
+    Save,
+    /// File > Close Tab
+    CloseTab,
+    /// Activate the tab to the right
+    NextTab,
+    /// Activate the tab to the left
+    PreviousTab,
+    /// Edit > Undo
+    Undo,
+    /// Edit > Redo
+    Redo,
+    /// Edit > Find
+    Find,
+    /// Edit > Find Next
+    FindNext,
+    /// Edit > Incremental Search
+    IncrementalSearch,
+    /// Edit > Replace
+    Replace,
+    /// Edit > Find in Files
+    FindInFiles,
+    /// Edit > Go To
+    GoTo,
+    /// Edit > Bookmarks > Toggle Bookmark
+    ToggleBookmark,
+    /// Edit > Bookmarks > Next Bookmark
+    NextBookmark,
+    /// Edit > Bookmarks > Previous Bookmark
+    PreviousBookmark,
+    /// Edit > Macro > Start Recording / Stop Recording
+    ToggleMacroRecording,
+    /// Edit > Macro > Playback
+    PlayMacro,
+    /// Edit > Time/Date
+    TimeDate,
+    /// Edit > Duplicate
+    Duplicate,
+    /// Edit > Delete Line
+    DeleteLine,
+    /// Edit > Lines > Trim Trailing Whitespace
+    TrimTrailingWhitespace,
+    /// Edit > Move Line Up
+    MoveLineUp,
+    /// Edit > Move Line Down
+    MoveLineDown,
+    /// View > Zoom > Zoom In
+    ZoomIn,
+    /// View > Zoom > Zoom Out
+    ZoomOut,
+    /// View > Zoom > Restore Default Zoom
+    ResetZoom,
+}
+
+/// Shortcuts bound to commands
+pub struct Keymap {
+    /// Bindings in priority order; the first binding of a command is shown
+    /// in the menus
+    bindings: Vec<(Action, KeyboardShortcut)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let command = |key| KeyboardShortcut::new(Modifiers::COMMAND, key);
+        Self {
+            bindings: vec![
+                (Action::New, command(Key::N)),
+                (
+                    Action::NewWindow,
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::N),
+                ),
+                (Action::Open, command(Key::O)),
+                (Action::Save, command(Key::S)),
+                (Action::CloseTab, command(Key::W)),
+                (
+                    Action::NextTab,
+                    KeyboardShortcut::new(Modifiers::CTRL, Key::Tab),
+                ),
+                (
+                    Action::PreviousTab,
+                    KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::Tab),
+                ),
+                (Action::Undo, command(Key::Z)),
+                (Action::Redo, command(Key::Y)),
+                (
+                    Action::Redo,
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z),
+                ),
+                (Action::Find, command(Key::F)),
+                (
+                    Action::FindNext,
+                    KeyboardShortcut::new(Modifiers::NONE, Key::F3),
+                ),
+                (Action::IncrementalSearch, command(Key::I)),
+                (Action::Replace, command(Key::H)),
+                (
+                    Action::FindInFiles,
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::F),
+                ),
+                (Action::GoTo, command(Key::G)),
+                (Action::ToggleBookmark, command(Key::F2)),
+                (
+                    Action::NextBookmark,
+                    KeyboardShortcut::new(Modifiers::NONE, Key::F2),
+                ),
+                (
+                    Action::PreviousBookmark,
+                    KeyboardShortcut::new(Modifiers::SHIFT, Key::F2),
+                ),
+                (
+                    Action::ToggleMacroRecording,
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::R),
+                ),
+                (
+                    Action::PlayMacro,
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::P),
+                ),
+                (
+                    Action::TimeDate,
+                    KeyboardShortcut::new(Modifiers::NONE, Key::F5),
+                ),
+                (Action::Duplicate, command(Key::D)),
+                (
+                    Action::DeleteLine,
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::K),
+                ),
+                (
+                    Action::MoveLineUp,
+                    KeyboardShortcut::new(Modifiers::ALT, Key::ArrowUp),
+                ),
+                (
+                    Action::MoveLineDown,
+                    KeyboardShortcut::new(Modifiers::ALT, Key::ArrowDown),
+                ),
+                // Numpad plus and minus arrive as Plus and Minus
+                (Action::ZoomIn, command(Key::Plus)),
+                (Action::ZoomIn, command(Key::Equals)),
+                (Action::ZoomOut, command(Key::Minus)),
+                (Action::ResetZoom, command(Key::Num0)),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// Get the shortcut shown for a command
+    ///
+    /// # Arguments
+    /// * `action` - Command to look up
+    ///
+    /// # Returns
+    /// The first shortcut bound to the command, if any
+    #[must_use]
+    pub fn shortcut(&self, action: Action) -> Option<KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, shortcut)| *shortcut)
+    }
+
+    /// Get a menu label with the command's shortcut
+    ///
+    /// # Arguments
+    /// * `ctx` - egui context, for platform specific key names
+    /// * `name` - Menu item name
+    /// * `action` - Command of the menu item
+    ///
+    /// # Returns
+    /// The name followed by the shortcut, e.g. "Undo\tCtrl+Z"
+    #[must_use]
+    pub fn menu_label(&self, ctx: &egui::Context, name: &str, action: Action) -> String {
+        self.shortcut(action).map_or_else(
+            || name.to_string(),
+            |shortcut| format!("{name}\t{}", ctx.format_shortcut(&shortcut)),
+        )
+    }
+
+    /// Take the pressed shortcuts out of the input
+    ///
+    /// Shortcuts with more modifiers are checked first, so Ctrl+Shift+Z is
+    /// not mistaken for Ctrl+Z. Consumed keys do not reach the editor.
+    ///
+    /// # Arguments
+    /// * `input` - Input of the current frame
+    ///
+    /// # Returns
+    /// Commands triggered this frame
+    pub fn consume_pressed(&self, input: &mut egui::InputState) -> Vec<Action> {
+        let mut bindings: Vec<&(Action, KeyboardShortcut)> = self.bindings.iter().collect();
+        bindings.sort_by_key(|(_, shortcut)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+        let mut actions = Vec::new();
+        for (action, shortcut) in bindings {
+            if input.consume_shortcut(shortcut) && !actions.contains(action) {
+                actions.push(*action);
+            }
+        }
+        actions
+    }
+}
+
+/// Count the modifiers of a shortcut
+///
+/// # Arguments
+/// * `modifiers` - Modifiers to count
+///
+/// # Returns
+/// Number of modifier keys, counting Ctrl/Cmd once
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [
+        modifiers.alt,
+        modifiers.shift,
+        modifiers.command || modifiers.ctrl || modifiers.mac_cmd,
+    ]
+    .into_iter()
+    .filter(|&held| held)
+    .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(modifiers: Modifiers, key: Key) -> egui::InputState {
+        let mut input = egui::InputState::default();
+        input.modifiers = modifiers;
+        input.events.push(egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        });
+        input
+    }
+
+    #[test]
+    fn test_redo_bindings() {
+        let keymap = Keymap::default();
+        let mut input = press(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::Redo]);
+        assert!(input.events.is_empty());
+
+        let mut input = press(Modifiers::COMMAND, Key::Y);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::Redo]);
+        let mut input = press(Modifiers::COMMAND, Key::Z);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::Undo]);
+    }
+
+    #[test]
+    fn test_new_window_binding() {
+        let keymap = Keymap::default();
+        let mut input = press(Modifiers::COMMAND | Modifiers::SHIFT, Key::N);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::NewWindow]);
+        let mut input = press(Modifiers::COMMAND, Key::N);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::New]);
+    }
+
+    #[test]
+    fn test_zoom_bindings() {
+        let keymap = Keymap::default();
+        for (key, action) in [
+            (Key::Plus, Action::ZoomIn),
+            (Key::Equals, Action::ZoomIn),
+            (Key::Minus, Action::ZoomOut),
+            (Key::Num0, Action::ResetZoom),
+        ] {
+            let mut input = press(Modifiers::COMMAND, key);
+            assert_eq!(keymap.consume_pressed(&mut input), vec![action]);
+        }
+    }
+
+    #[test]
+    fn test_tab_bindings() {
+        let keymap = Keymap::default();
+        let mut input = press(Modifiers::CTRL | Modifiers::SHIFT, Key::Tab);
+        assert_eq!(
+            keymap.consume_pressed(&mut input),
+            vec![Action::PreviousTab]
+        );
+        let mut input = press(Modifiers::CTRL, Key::Tab);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::NextTab]);
+        let mut input = press(Modifiers::COMMAND, Key::W);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::CloseTab]);
+    }
+
+    #[test]
+    fn test_search_bindings() {
+        let keymap = Keymap::default();
+        let mut input = press(Modifiers::COMMAND, Key::I);
+        assert_eq!(
+            keymap.consume_pressed(&mut input),
+            vec![Action::IncrementalSearch]
+        );
+        let mut input = press(Modifiers::COMMAND, Key::F);
+        assert_eq!(keymap.consume_pressed(&mut input), vec![Action::Find]);
+    }
+
+    #[test]
+    fn test_bookmark_bindings() {
+        let keymap = Keymap::default();
+        for (modifiers, action) in [
+            (Modifiers::COMMAND, Action::ToggleBookmark),
+            (Modifiers::NONE, Action::NextBookmark),
+            (Modifiers::SHIFT, Action::PreviousBookmark),
+        ] {
+            let mut input = press(modifiers, Key::F2);
+            assert_eq!(keymap.consume_pressed(&mut input), vec![action]);
+        }
+    }
+
+    #[test]
+    fn test_unbound_keys_are_kept() {
+        let keymap = Keymap::default();
+        let mut input = press(Modifiers::COMMAND, Key::A);
+        assert!(keymap.consume_pressed(&mut input).is_empty());
+        assert_eq!(input.events.len(), 1);
+    }
+
+    #[test]
+    fn test_menu_shortcut_is_first_binding() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.shortcut(Action::Redo),
+            Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::Y))
+        );
+        assert_eq!(
+            keymap.shortcut(Action::FindNext),
+            Some(KeyboardShortcut::new(Modifiers::NONE, Key::F3))
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Line operations (Edit > Lines)
+//!
+//! This module holds the text transformations that work on whole lines,
+//! such as sorting. They operate on logical lines, independent of how
+//! the editor wraps them.
+
+use std::cmp::Ordering;
+
+/// How Sort Lines orders lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    /// A to Z, by character code
+    Ascending,
+    /// Z to A, by character code
+    Descending,
+    /// A to Z, ignoring case
+    CaseInsensitive,
+    /// By the number at the start of each line, e.g. "2" before "10"
+    Numeric,
+}
+
+impl SortMode {
+    /// Get display name for the sort mode
+    ///
+    /// # Returns
+    /// Menu label of the sort mode
+    #[must_use]
+    pub const fn display_name(self) -> &'static str {
+        match self {
+            Self::Ascending => "Ascending",
+            Self::Descending => "Descending",
+            Self::CaseInsensitive => "Case Insensitive",
+            Self::Numeric => "Numeric",
+        }
+    }
+
+    /// Get all sort modes
+    ///
+    /// # Returns
+    /// Sort modes in menu order
+    #[must_use]
+    pub const fn all() -> [Self; 4] {
+        [
+            Self::Ascending,
+            Self::Descending,
+            Self::CaseInsensitive,
+            Self::Numeric,
+        ]
+    }
+
+    /// Compare two lines
+    ///
+    /// # Arguments
+    /// * `a` - First line
+    /// * `b` - Second line
+    ///
+    /// # Returns
+    /// Ordering of the lines
+    fn compare(self, a: &str, b: &str) -> Ordering {
+        match self {
+            Self::Ascending => a.cmp(b),
+            Self::Descending => b.cmp(a),
+            Self::CaseInsensitive => a.to_lowercase().cmp(&b.to_lowercase()),
+            Self::Numeric => match (leading_number(a), leading_number(b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        }
+    }
+}
+
+/// Parse the number at the start of a line
+///
+/// Leading spaces are skipped, and a sign and decimal point are allowed,
+/// so "  -1.5 apples" gives -1.5.
+///
+/// # Arguments
+/// * `line` - Line to parse
+///
+/// # Returns
+/// The number, or None if the line does not start with one
+fn leading_number(line: &str) -> Option<f64> {
+    let line = line.trim_start();
+    let mut end = 0;
+    let mut seen_point = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '-' | '+' if index == 0 => {}
+            '.' if !seen_point => seen_point = true,
+            '0'..='9' => {}
+            _ => break,
+        }
+        end = index + c.len_utf8();
+    }
+    line[..end].parse().ok()
+}
+
+/// Sort the lines of a text
+///
+/// The sort is stable, so equal lines keep their order. A final line
+/// break stays at the end instead of being sorted as an empty line.
+/// Lines are compared without their line ending and joined with CRLF if
+/// the text uses it anywhere, so a CRLF document stays CRLF.
+///
+/// # Arguments
+/// * `text` - Lines to sort
+/// * `mode` - How to order the lines
+///
+/// # Returns
+/// The sorted text
+#[must_use]
+pub fn sort_lines(text: &str, mode: SortMode) -> String {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines: Vec<&str> = text
+        .split_inclusive('\n')
+        .map(|line| {
+            line.strip_suffix('\n')
+                .map_or(line, |line| line.strip_suffix('\r').unwrap_or(line))
+        })
+        .collect();
+    lines.sort_by(|a, b| mode.compare(a, b));
+    let mut sorted = lines.join(newline);
+    if text.ends_with('\n') {
+        sorted.push_str(newline);
+    }
+    sorted
+}
+
+/// Remove spaces and tabs at the end of each line
+///
+/// Line breaks are kept as they are, including a final line break and
+/// the "\r" of CRLF line endings.
+///
+/// # Arguments
+/// * `text` - Text to trim
+///
+/// # Returns
+/// Tuple of (trimmed text, number of lines changed)
+#[must_use]
+pub fn trim_trailing_whitespace(text: &str) -> (String, usize) {
+    let mut trimmed = String::with_capacity(text.len());
+    let mut changed = 0;
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            trimmed.push('\n');
+        }
+        let (body, ending) = line
+            .strip_suffix('\r')
+            .map_or((line, ""), |body| (body, "\r"));
+        let kept = body.trim_end_matches([' ', '\t']);
+        if kept.len() != body.len() {
+            changed += 1;
+        }
+        trimmed.push_str(kept);
+        trimmed.push_str(ending);
+    }
+    (trimmed, changed)
+}
+
+/// Expand tabs to spaces up to the next tab stop
+///
+/// # Arguments
+/// * `text` - Text to convert
+/// * `tab_width` - Columns between tab stops
+///
+/// # Returns
+/// The text without tabs
+#[must_use]
+pub fn tabs_to_spaces(text: &str, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut converted = String::with_capacity(text.len());
+    let mut column = 0;
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let spaces = tab_width - column % tab_width;
+                converted.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\n' => {
+                converted.push(c);
+                column = 0;
+            }
+            _ => {
+                converted.push(c);
+                column += 1;
+            }
+        }
+    }
+    converted
+}
+
+/// Replace the leading indentation of each line with tabs
+///
+/// Indentation narrower than a tab stays as spaces. Spaces after the
+/// first other character, e.g. for alignment, are kept.
+///
+/// # Arguments
+/// * `text` - Text to convert
+/// * `tab_width` - Columns between tab stops
+///
+/// # Returns
+/// The text indented with tabs
+#[must_use]
+pub fn spaces_to_tabs(text: &str, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut lines = Vec::new();
+    for line in text.split('\n') {
+        let body = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - body.len()];
+        let width = indent.chars().fold(0, |column, c| {
+            if c == '\t' {
+                column + tab_width - column % tab_width
+            } else {
+                column + 1
+            }
+        });
+        let mut converted = "\t".repeat(width / tab_width);
+        converted.push_str(&" ".repeat(width % tab_width));
+        converted.push_str(body);
+        lines.push(converted);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_lines_keeps_final_newline() {
+        assert_eq!(sort_lines("b\na\nc\n", SortMode::Ascending), "a\nb\nc\n");
+        assert_eq!(sort_lines("b\na\nc", SortMode::Descending), "c\nb\na");
+        // Blank lines sort like any other line
+        assert_eq!(sort_lines("b\n\na", SortMode::Ascending), "\na\nb");
+        assert_eq!(sort_lines("", SortMode::Ascending), "");
+    }
+
+    #[test]
+    fn test_sort_lines_keeps_crlf_line_endings() {
+        assert_eq!(sort_lines("b\r\na", SortMode::Ascending), "a\r\nb");
+        assert_eq!(
+            sort_lines("b\r\nc\r\na\r\n", SortMode::Ascending),
+            "a\r\nb\r\nc\r\n"
+        );
+        // "\r" is not part of what is compared, so "a" sorts before "a\t"
+        assert_eq!(sort_lines("a\tx\r\na", SortMode::Ascending), "a\r\na\tx");
+    }
+
+    #[test]
+    fn test_sort_lines_is_stable() {
+        assert_eq!(
+            sort_lines("b\nB\na\nA", SortMode::CaseInsensitive),
+            "a\nA\nb\nB"
+        );
+        assert_eq!(sort_lines("1 x\n1 a\n0", SortMode::Numeric), "0\n1 x\n1 a");
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        assert_eq!(
+            trim_trailing_whitespace("a \t\nb\t\n  \nc\n"),
+            ("a\nb\n\nc\n".to_string(), 3)
+        );
+        assert_eq!(
+            trim_trailing_whitespace("  keep indent"),
+            ("  keep indent".to_string(), 0)
+        );
+        // Only the spaces before CRLF go
+        assert_eq!(
+            trim_trailing_whitespace("a  \r\nb\r\n \t\r\n"),
+            ("a\r\nb\r\n\r\n".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_tabs_to_spaces_uses_tab_stops() {
+        assert_eq!(tabs_to_spaces("\ta\tb", 4), "    a   b");
+        assert_eq!(tabs_to_spaces("abcd\tx\nab\tx", 4), "abcd    x\nab  x");
+        assert_eq!(tabs_to_spaces("é\tx", 2), "é x");
+    }
+
+    #[test]
+    fn test_spaces_to_tabs_converts_indentation_only() {
+        assert_eq!(spaces_to_tabs("        a  = 1\n", 4), "\t\ta  = 1\n");
+        assert_eq!(spaces_to_tabs("      b\n  \tc", 4), "\t  b\n\tc");
+        assert_eq!(spaces_to_tabs("x    y", 4), "x    y");
+    }
+
+    #[test]
+    fn test_numeric_sort() {
+        assert_eq!(sort_lines("10\n2\n1", SortMode::Ascending), "1\n10\n2");
+        assert_eq!(sort_lines("10\n2\n1", SortMode::Numeric), "1\n2\n10");
+        assert_eq!(
+            sort_lines("2.5 kg\nnone\n-3\n 10", SortMode::Numeric),
+            "none\n-3\n2.5 kg\n 10"
+        );
+    }
+}
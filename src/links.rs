@@ -0,0 +1,149 @@
+//! Links in the document
+//!
+//! This module finds http(s):// and file:// URLs in text, so the editor can
+//! underline them and open them with Ctrl+click.
+
+use std::ops::Range;
+
+/// URL schemes that are recognized as links
+const SCHEMES: [&str; 3] = ["https://", "http://", "file://"];
+
+/// Find the links in a text
+///
+/// A link starts with one of the schemes at a word boundary and ends at
+/// whitespace or a character that cannot be part of a URL. Trailing
+/// punctuation and unbalanced closing brackets are left out, so a link at
+/// the end of a sentence or in parentheses does not take them along.
+///
+/// # Arguments
+/// * `text` - Text to scan
+///
+/// # Returns
+/// Byte ranges of the links, in text order
+#[must_use]
+pub fn find_links(text: &str) -> Vec<Range<usize>> {
+    let mut links = Vec::new();
+    let mut from = 0;
+    while let Some(found) = text[from..].find("://") {
+        let separator = from + found;
+        from = separator + 3;
+        let Some(scheme) = SCHEMES
+            .iter()
+            .find(|scheme| text[..from].ends_with(*scheme))
+        else {
+            continue;
+        };
+        let start = from - scheme.len();
+        let at_boundary = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        if !at_boundary {
+            continue;
+        }
+        let end = link_end(text, from);
+        if end > from {
+            links.push(start..end);
+            from = end;
+        }
+    }
+    links
+}
+
+/// Find the link at a position
+///
+/// # Arguments
+/// * `text` - Text to scan
+/// * `byte` - Byte offset, e.g. of a clicked character
+///
+/// # Returns
+/// Byte range of the link containing the position, if any
+#[must_use]
+pub fn link_at(text: &str, byte: usize) -> Option<Range<usize>> {
+    find_links(text)
+        .into_iter()
+        .find(|link| link.contains(&byte))
+}
+
+/// Find where a link ends
+///
+/// # Arguments
+/// * `text` - Text containing the link
+/// * `after_scheme` - Byte offset just past the scheme
+///
+/// # Returns
+/// Byte offset just past the link
+fn link_end(text: &str, after_scheme: usize) -> usize {
+    let rest = &text[after_scheme..];
+    let raw_end = rest
+        .find(|c: char| c.is_whitespace() || c.is_control() || "<>\"'`{}|\\^".contains(c))
+        .unwrap_or(rest.len());
+    let mut link = &rest[..raw_end];
+    loop {
+        let trimmed = link.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let trimmed = match trimmed.chars().next_back() {
+            Some(')') if trimmed.matches('(').count() < trimmed.matches(')').count() => {
+                &trimmed[..trimmed.len() - 1]
+            }
+            Some(']') if trimmed.matches('[').count() < trimmed.matches(']').count() => {
+                &trimmed[..trimmed.len() - 1]
+            }
+            _ => trimmed,
+        };
+        if trimmed.len() == link.len() {
+            break;
+        }
+        link = trimmed;
+    }
+    after_scheme + link.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(text: &str) -> Vec<&str> {
+        find_links(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_find_links_in_prose() {
+        let text = "See https://example.com/a?b=1#c, or http://x.org. Also file:///tmp/notes.txt!";
+        assert_eq!(
+            links(text),
+            vec![
+                "https://example.com/a?b=1#c",
+                "http://x.org",
+                "file:///tmp/notes.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_brackets_and_quotes_end_links() {
+        assert_eq!(
+            links("(see https://en.wikipedia.org/wiki/Rust_(language))"),
+            vec!["https://en.wikipedia.org/wiki/Rust_(language)"]
+        );
+        assert_eq!(links("[https://a.b/c]"), vec!["https://a.b/c"]);
+        assert_eq!(links("<a href=\"https://a.b/é\">"), vec!["https://a.b/é"]);
+    }
+
+    #[test]
+    fn test_only_known_schemes_at_word_start() {
+        assert!(links("ftp://a.b mailto://x xhttps://a.b https:// done").is_empty());
+        assert_eq!(links("«https://a.b"), vec!["https://a.b"]);
+    }
+
+    #[test]
+    fn test_link_at_position() {
+        let text = "go to https://a.b now";
+        assert_eq!(link_at(text, 6), Some(6..17));
+        assert_eq!(link_at(text, 16), Some(6..17));
+        assert_eq!(link_at(text, 17), None);
+        assert_eq!(link_at(text, 2), None);
+    }
+}
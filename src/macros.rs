@@ -0,0 +1,487 @@
+//! Keyboard macros
+//!
+//! This module holds the steps of a recorded keyboard macro and replays
+//! them. Recording turns the editor's input events and line commands into
+//! steps. Playback runs the steps on a copy of the document starting at
+//! the cursor, so the editor applies each run as one undo step.
+
+use crate::editor::{EditorState, char_to_byte, splice_chars};
+use crate::pairs::{PairEdit, backspace_edit, typed_edit};
+use eframe::egui;
+
+/// Most runs the Run Macro Multiple Times dialog allows
+pub const MAX_MACRO_RUNS: usize = 10_000;
+
+/// Cursor movement within the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    /// One character left
+    Left,
+    /// One character right
+    Right,
+    /// Same column on the line above
+    Up,
+    /// Same column on the line below
+    Down,
+    /// Start of the line
+    Home,
+    /// End of the line
+    End,
+}
+
+/// One recorded command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroStep {
+    /// Typed text, auto-closing brackets and quotes like typing does
+    Type(String),
+    /// Pasted or entered text replacing the selection; empty for Cut
+    Insert(String),
+    /// Backspace
+    Backspace,
+    /// Delete
+    Delete,
+    /// Tab
+    Indent,
+    /// Shift+Tab
+    Unindent,
+    /// Arrow, Home or End key
+    Move {
+        /// Where the cursor goes
+        motion: Motion,
+        /// Whether Shift extends the selection
+        select: bool,
+    },
+    /// Edit > Lines > Duplicate
+    Duplicate,
+    /// Edit > Lines > Delete Line
+    DeleteLines,
+    /// Edit > Lines > Move Line Up / Down
+    MoveLines {
+        /// Move up instead of down
+        up: bool,
+    },
+}
+
+/// Editor settings the steps are replayed with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaybackSettings {
+    /// Text of one indentation level
+    pub indent_unit: String,
+    /// Width of a tab in spaces
+    pub tab_width: usize,
+    /// Auto-close brackets and quotes
+    pub auto_close: bool,
+}
+
+/// Turn the editor's input events into macro steps
+///
+/// Keys held with Ctrl, Cmd or Alt are not recorded; the line commands
+/// they trigger are recorded by the menu instead.
+///
+/// # Arguments
+/// * `events` - Input events of the frame, before the editor handles them
+/// * `steps` - Recorded steps to add to
+pub fn record_events(events: &[egui::Event], steps: &mut Vec<MacroStep>) {
+    steps.extend(events.iter().filter_map(|event| match event {
+        egui::Event::Text(text) => Some(MacroStep::Type(text.clone())),
+        egui::Event::Paste(text) => Some(MacroStep::Insert(text.clone())),
+        egui::Event::Cut => Some(MacroStep::Insert(String::new())),
+        egui::Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } if !modifiers.command && !modifiers.ctrl && !modifiers.alt => {
+            key_step(*key, modifiers.shift)
+        }
+        _ => None,
+    }));
+}
+
+/// Get the step of a key pressed without Ctrl, Cmd or Alt
+///
+/// # Arguments
+/// * `key` - Pressed key
+/// * `shift` - Whether Shift is held
+///
+/// # Returns
+/// The step, or None for keys that neither edit nor move the cursor
+fn key_step(key: egui::Key, shift: bool) -> Option<MacroStep> {
+    let motion = match key {
+        egui::Key::Enter => return Some(MacroStep::Insert("\n".to_string())),
+        egui::Key::Backspace => return Some(MacroStep::Backspace),
+        egui::Key::Delete => return Some(MacroStep::Delete),
+        egui::Key::Tab if shift => return Some(MacroStep::Unindent),
+        egui::Key::Tab => return Some(MacroStep::Indent),
+        egui::Key::ArrowLeft => Motion::Left,
+        egui::Key::ArrowRight => Motion::Right,
+        egui::Key::ArrowUp => Motion::Up,
+        egui::Key::ArrowDown => Motion::Down,
+        egui::Key::Home => Motion::Home,
+        egui::Key::End => Motion::End,
+        _ => return None,
+    };
+    Some(MacroStep::Move {
+        motion,
+        select: shift,
+    })
+}
+
+/// Get the first character index of the line containing an index
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `index` - Character index
+///
+/// # Returns
+/// Character index of the line start
+fn line_start(text: &str, index: usize) -> usize {
+    let byte = char_to_byte(text, index);
+    let start = text[..byte].rfind('\n').map_or(0, |newline| newline + 1);
+    index - text[start..byte].chars().count()
+}
+
+/// Get the character index at the end of the line containing an index
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `index` - Character index
+///
+/// # Returns
+/// Character index of the line's newline, or of the end of the text
+fn line_end(text: &str, index: usize) -> usize {
+    let byte = char_to_byte(text, index);
+    let end = text[byte..]
+        .find('\n')
+        .map_or(text.len(), |newline| byte + newline);
+    index + text[byte..end].chars().count()
+}
+
+/// Get where a motion moves the cursor
+///
+/// Up and Down keep the column within the lines of the text, ignoring
+/// word wrap.
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `cursor` - Character index of the cursor
+/// * `motion` - Cursor movement
+///
+/// # Returns
+/// Character index of the cursor afterwards
+fn move_cursor(text: &str, cursor: usize, motion: Motion) -> usize {
+    let start = line_start(text, cursor);
+    match motion {
+        Motion::Left => cursor.saturating_sub(1),
+        Motion::Right => (cursor + 1).min(text.chars().count()),
+        Motion::Home => start,
+        Motion::End => line_end(text, cursor),
+        Motion::Up if start == 0 => 0,
+        Motion::Up => {
+            let above = line_start(text, start - 1);
+            above + (cursor - start).min(start - 1 - above)
+        }
+        Motion::Down => {
+            let end = line_end(text, cursor);
+            if end >= text.chars().count() {
+                return end;
+            }
+            (end + 1 + cursor - start).min(line_end(text, end + 1))
+        }
+    }
+}
+
+/// Document copy the steps of a run are replayed on
+struct Playback {
+    /// Scratch editor holding the copy
+    editor: EditorState,
+    /// Character index where the selection started
+    anchor: usize,
+    /// Character index of the cursor
+    cursor: usize,
+}
+
+impl Playback {
+    /// Select the range between the anchor and the cursor
+    fn select(&mut self, anchor: usize, cursor: usize) {
+        self.anchor = anchor;
+        self.cursor = cursor;
+        self.editor.select(anchor.min(cursor), anchor.max(cursor));
+    }
+
+    /// Take the selection an editor command left, with the cursor at its end
+    const fn sync(&mut self) {
+        let (start, end) = self.editor.selection;
+        self.anchor = start;
+        self.cursor = end;
+    }
+
+    /// Apply an auto-closing edit
+    fn apply_pair_edit(&mut self, edit: &PairEdit) {
+        splice_chars(
+            &mut self.editor.text,
+            edit.start,
+            edit.end,
+            &edit.replacement,
+        );
+        self.select(edit.selection.0, edit.selection.1);
+    }
+
+    /// Replay one step
+    ///
+    /// # Arguments
+    /// * `step` - Step to replay
+    /// * `settings` - Editor settings to replay with
+    fn run(&mut self, step: &MacroStep, settings: &PlaybackSettings) {
+        let editor = &mut self.editor;
+        let selection = editor.selection;
+        match step {
+            MacroStep::Type(typed) => {
+                let mut chars = typed.chars();
+                let pair = match (chars.next(), chars.next()) {
+                    (Some(c), None) if settings.auto_close => {
+                        typed_edit(&editor.text, selection, c)
+                    }
+                    _ => None,
+                };
+                if let Some(edit) = pair {
+                    self.apply_pair_edit(&edit);
+                    return;
+                }
+                editor.insert_at_cursor(typed);
+            }
+            MacroStep::Insert(inserted) => {
+                editor.insert_at_cursor(inserted);
+            }
+            MacroStep::Backspace => {
+                let pair = backspace_edit(&editor.text, selection).filter(|_| settings.auto_close);
+                if let Some(edit) = pair {
+                    self.apply_pair_edit(&edit);
+                    return;
+                }
+                if selection.0 == selection.1 {
+                    if selection.0 == 0 {
+                        return;
+                    }
+                    editor.selection = (selection.0 - 1, selection.0);
+                }
+                editor.insert_at_cursor("");
+            }
+            MacroStep::Delete => {
+                editor.delete();
+            }
+            MacroStep::Indent => editor.indent(&settings.indent_unit),
+            MacroStep::Unindent => editor.unindent(settings.tab_width),
+            MacroStep::Duplicate => editor.duplicate(),
+            MacroStep::DeleteLines => editor.delete_lines(),
+            MacroStep::MoveLines { up } => {
+                editor.move_lines(*up);
+            }
+            MacroStep::Move { motion, select } => {
+                let (start, end) = selection;
+                let cursor = match motion {
+                    // Left and Right collapse a selection to its side
+                    Motion::Left if !select && start != end => start,
+                    Motion::Right if !select && start != end => end,
+                    _ => move_cursor(&editor.text, self.cursor, *motion),
+                };
+                let anchor = if *select { self.anchor } else { cursor };
+                self.select(anchor, cursor);
+                return;
+            }
+        }
+        self.sync();
+    }
+}
+
+/// Replay macro steps on a text
+///
+/// # Arguments
+/// * `steps` - Recorded steps
+/// * `text` - Document text
+/// * `selection` - Selection as sorted (start, end) character indices;
+///   the cursor is at its end
+/// * `settings` - Editor settings to replay with
+///
+/// # Returns
+/// Tuple of (text, selection) after the steps
+#[must_use]
+pub fn play(
+    steps: &[MacroStep],
+    text: &str,
+    selection: (usize, usize),
+    settings: &PlaybackSettings,
+) -> (String, (usize, usize)) {
+    let mut playback = Playback {
+        editor: EditorState::default(),
+        anchor: 0,
+        cursor: 0,
+    };
+    playback.editor.set_document(text.to_string());
+    playback.select(selection.0, selection.1);
+    for step in steps {
+        playback.run(step, settings);
+    }
+    (playback.editor.text, playback.editor.selection)
+}
+
+/// Run a macro in the editor, starting at the cursor
+///
+/// Each run is one undo step.
+///
+/// # Arguments
+/// * `editor` - Editor state
+/// * `steps` - Recorded steps
+/// * `settings` - Editor settings to replay with
+/// * `runs` - Number of times to run the macro
+///
+/// # Returns
+/// True if the text changed
+pub fn run_macro(
+    editor: &mut EditorState,
+    steps: &[MacroStep],
+    settings: &PlaybackSettings,
+    runs: usize,
+) -> bool {
+    let mut changed = false;
+    for _ in 0..runs {
+        let (text, (start, end)) = play(steps, &editor.text, editor.selection, settings);
+        if text != editor.text {
+            editor.save_undo_state();
+            editor.text = text;
+            editor.save_undo_state();
+            changed = true;
+        }
+        editor.select(start, end);
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> PlaybackSettings {
+        PlaybackSettings {
+            indent_unit: "    ".to_string(),
+            tab_width: 4,
+            auto_close: false,
+        }
+    }
+
+    fn key(key: egui::Key, modifiers: egui::Modifiers) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn test_record_events() {
+        let mut steps = Vec::new();
+        let events = [
+            egui::Event::Text("a".to_string()),
+            key(egui::Key::Enter, egui::Modifiers::NONE),
+            key(egui::Key::ArrowLeft, egui::Modifiers::SHIFT),
+            key(egui::Key::ArrowLeft, egui::Modifiers::COMMAND),
+            key(egui::Key::A, egui::Modifiers::NONE),
+            egui::Event::Paste("p".to_string()),
+            egui::Event::Cut,
+        ];
+        record_events(&events, &mut steps);
+        assert_eq!(
+            steps,
+            vec![
+                MacroStep::Type("a".to_string()),
+                MacroStep::Insert("\n".to_string()),
+                MacroStep::Move {
+                    motion: Motion::Left,
+                    select: true
+                },
+                MacroStep::Insert("p".to_string()),
+                MacroStep::Insert(String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_play_recorded_sequence_on_fresh_buffer() {
+        // Turn "key value" lines into "key = value;" lines, one per run
+        let mut steps = Vec::new();
+        let events = [
+            key(egui::Key::Home, egui::Modifiers::NONE),
+            key(egui::Key::ArrowRight, egui::Modifiers::NONE),
+            key(egui::Key::ArrowRight, egui::Modifiers::NONE),
+            key(egui::Key::ArrowRight, egui::Modifiers::SHIFT),
+            egui::Event::Text(" = ".to_string()),
+            key(egui::Key::End, egui::Modifiers::NONE),
+            egui::Event::Text(";".to_string()),
+            key(egui::Key::ArrowDown, egui::Modifiers::NONE),
+        ];
+        record_events(&events, &mut steps);
+        let mut editor = EditorState::default();
+        editor.set_document("ab cd\nxy zé\n".to_string());
+        assert!(run_macro(&mut editor, &steps, &settings(), 2));
+        assert_eq!(editor.text, "ab = cd;\nxy = zé;\n");
+        // One undo step per run
+        assert_eq!(editor.undo_history.len(), 2);
+        assert!(editor.undo());
+        assert_eq!(editor.text, "ab = cd;\nxy zé\n");
+    }
+
+    #[test]
+    fn test_play_edits_and_commands() {
+        let steps = [
+            MacroStep::Type("x".to_string()),
+            MacroStep::Backspace,
+            MacroStep::Backspace,
+            MacroStep::Duplicate,
+            MacroStep::Move {
+                motion: Motion::Up,
+                select: false,
+            },
+            MacroStep::Move {
+                motion: Motion::Home,
+                select: false,
+            },
+            MacroStep::Indent,
+        ];
+        let (text, selection) = play(&steps, "one\ntwo", (3, 3), &settings());
+        assert_eq!(text, "    on\non\ntwo");
+        assert_eq!(selection, (4, 4));
+    }
+
+    #[test]
+    fn test_play_auto_closes_like_typing() {
+        let settings = PlaybackSettings {
+            auto_close: true,
+            ..settings()
+        };
+        let steps = [
+            MacroStep::Type("(".to_string()),
+            MacroStep::Type("a".to_string()),
+            MacroStep::Type(")".to_string()),
+            MacroStep::Type("[".to_string()),
+            MacroStep::Backspace,
+        ];
+        assert_eq!(
+            play(&steps, "f", (1, 1), &settings),
+            ("f(a)".to_string(), (4, 4))
+        );
+    }
+
+    #[test]
+    fn test_move_cursor_keeps_column() {
+        let text = "abcd\nx\nwxyz";
+        assert_eq!(move_cursor(text, 3, Motion::Down), 6);
+        assert_eq!(move_cursor(text, 6, Motion::Down), 8);
+        assert_eq!(move_cursor(text, 11, Motion::Up), 6);
+        assert_eq!(move_cursor(text, 2, Motion::Up), 0);
+        assert_eq!(move_cursor(text, 9, Motion::Down), 11);
+        assert_eq!(move_cursor(text, 7, Motion::End), 11);
+        assert_eq!(move_cursor(text, 7, Motion::Home), 7);
+    }
+}
@@ -5,29 +5,73 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod autosave;
+mod carets;
+mod case;
+mod cli;
+mod clipboard_history;
 mod config;
+mod convert;
+mod document;
 mod editor;
 mod file_ops;
+mod find_in_files;
+mod fonts;
 mod format;
+mod highlight;
+mod keymap;
+mod lines;
+mod links;
+mod macros;
+mod markdown;
+mod memory_trim;
 mod menu;
+mod messages;
+mod pairs;
+mod pdf;
+mod placement;
 mod search;
+mod spell;
+mod stats;
+mod theme;
 mod ui;
+mod viewer;
 
 use app::NodepatApp;
 use eframe::egui;
 
 fn main() -> eframe::Result<()> {
+    // A file passed on the command line, e.g. from "Open with"
+    let startup = cli::StartupArgs::parse(std::env::args_os().skip(1));
+    let config = config::Config::load();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("Untitled - Nodepat")
-            .with_inner_size([640.0, 480.0])
-            .with_min_inner_size([400.0, 300.0]),
+            .with_inner_size(config.window_size())
+            .with_min_inner_size(config::MIN_WINDOW_SIZE),
         ..Default::default()
     };
 
-    eframe::run_native(
+    // Run our own event loop so the connected monitors can be probed for
+    // restoring the window position
+    let event_loop =
+        winit::event_loop::EventLoop::<eframe::UserEvent>::with_user_event().build()?;
+    let monitors = placement::SharedMonitors::default();
+    let app_monitors = std::sync::Arc::clone(&monitors);
+    let app = eframe::create_native(
         "Nodepat",
         options,
-        Box::new(|_cc| Ok(Box::<NodepatApp>::default())),
-    )
+        Box::new(move |cc| {
+            // Ctrl+Plus/Minus zoom the editor font, not the whole UI
+            cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
+            let mut app = NodepatApp::with_startup(&startup);
+            app.install_custom_font(&cc.egui_ctx);
+            app.monitors = app_monitors;
+            Ok(Box::new(app))
+        }),
+        &event_loop,
+    );
+    event_loop.run_app(&mut placement::MonitorProbe::new(app, monitors))?;
+    Ok(())
 }
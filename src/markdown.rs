@@ -0,0 +1,474 @@
+//! Markdown preview parsing
+//!
+//! This module turns Markdown into a list of blocks for the preview pane.
+//! It understands headings, paragraphs, lists, code blocks, quotes,
+//! horizontal rules, and inline emphasis, code, and links. Anything else
+//! is shown as plain text; this is not a full `CommonMark` parser.
+
+/// Seconds the preview waits after an edit before rendering again
+pub const PREVIEW_DEBOUNCE_SECS: f64 = 0.3;
+
+/// A run of text with one style
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    /// Text of the run
+    pub text: String,
+    /// **Bold** text
+    pub bold: bool,
+    /// *Italic* text
+    pub italic: bool,
+    /// `Code` text
+    pub code: bool,
+    /// Target of a [link](url)
+    pub link: Option<String>,
+}
+
+/// A block of a Markdown document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    /// A heading; level 1 is the largest
+    Heading(usize, Vec<Span>),
+    /// Consecutive lines of text
+    Paragraph(Vec<Span>),
+    /// A list item with its marker ("•" or e.g. "1.") and nesting depth
+    ListItem {
+        /// Bullet or number shown before the item
+        marker: String,
+        /// Nesting depth, 0 for top-level items
+        depth: usize,
+        /// Text of the item
+        spans: Vec<Span>,
+    },
+    /// A fenced or indented code block
+    Code(String),
+    /// A quoted paragraph
+    Quote(Vec<Span>),
+    /// A horizontal rule
+    Rule,
+}
+
+/// Parse a Markdown document
+///
+/// # Arguments
+/// * `text` - Markdown text
+///
+/// # Returns
+/// The blocks of the document in order
+#[must_use]
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut quote: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if let Some(lines) = &mut code {
+            if is_fence(line) {
+                blocks.push(Block::Code(lines.join("\n")));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if let Some(body) = trimmed.strip_prefix('>') {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            quote.push(body.trim());
+            continue;
+        }
+        if !quote.is_empty() {
+            blocks.push(Block::Quote(parse_inline(&quote.join(" "))));
+            quote.clear();
+        }
+        let block = if is_fence(line) {
+            code = Some(Vec::new());
+            None
+        } else if trimmed.is_empty() {
+            None
+        } else if let Some(heading) = heading(trimmed) {
+            Some(heading)
+        } else if is_rule(trimmed) {
+            Some(Block::Rule)
+        } else if let Some(item) = list_item(line) {
+            Some(item)
+        } else {
+            paragraph.push(trimmed.trim_end());
+            continue;
+        };
+        flush_paragraph(&mut blocks, &mut paragraph);
+        blocks.extend(block);
+    }
+    flush_paragraph(&mut blocks, &mut paragraph);
+    if !quote.is_empty() {
+        blocks.push(Block::Quote(parse_inline(&quote.join(" "))));
+    }
+    // An unclosed code block runs to the end of the document
+    if let Some(lines) = code {
+        blocks.push(Block::Code(lines.join("\n")));
+    }
+    blocks
+}
+
+/// Add the collected paragraph lines as a block
+///
+/// # Arguments
+/// * `blocks` - Blocks parsed so far
+/// * `paragraph` - Lines of the paragraph; cleared
+fn flush_paragraph(blocks: &mut Vec<Block>, paragraph: &mut Vec<&str>) {
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph(parse_inline(&paragraph.join(" "))));
+        paragraph.clear();
+    }
+}
+
+/// Check whether a line opens or closes a fenced code block
+///
+/// # Arguments
+/// * `line` - Line to check
+///
+/// # Returns
+/// True for lines starting with three backticks or tildes
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// Parse a heading line like "## Title"
+///
+/// # Arguments
+/// * `line` - Line without leading whitespace
+///
+/// # Returns
+/// The heading block, or None if the line is not a heading
+fn heading(line: &str) -> Option<Block> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    Some(Block::Heading(level, parse_inline(title)))
+}
+
+/// Check whether a line is a horizontal rule like "---" or "* * *"
+///
+/// # Arguments
+/// * `line` - Line without leading whitespace
+///
+/// # Returns
+/// True for three or more '-', '*', or '_' and nothing else but spaces
+fn is_rule(line: &str) -> bool {
+    let marks: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3
+        && ["-", "*", "_"]
+            .iter()
+            .any(|mark| marks.chars().all(|c| c.to_string() == *mark))
+}
+
+/// Parse a list item like "- item", "* item", or "2. item"
+///
+/// # Arguments
+/// * `line` - Line including its indentation
+///
+/// # Returns
+/// The list item block, or None if the line is not a list item
+fn list_item(line: &str) -> Option<Block> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let depth = indent / 2;
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = trimmed.strip_prefix(bullet) {
+            return Some(Block::ListItem {
+                marker: "•".to_string(),
+                depth,
+                spans: parse_inline(text.trim()),
+            });
+        }
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let text = trimmed[digits..]
+        .strip_prefix(". ")
+        .or_else(|| trimmed[digits..].strip_prefix(") "))?;
+    (digits > 0).then(|| Block::ListItem {
+        marker: format!("{}.", &trimmed[..digits]),
+        depth,
+        spans: parse_inline(text.trim()),
+    })
+}
+
+/// Parse inline emphasis, code, and links
+///
+/// `**bold**`, `__bold__`, `*italic*`, `_italic_`, `` `code` `` and
+/// `[text](url)` are recognized. A '*' before a space and a '_' inside a
+/// word are kept as text, so "2 * 3" and `snake_case` are not emphasis.
+///
+/// # Arguments
+/// * `text` - Text of a block
+///
+/// # Returns
+/// Runs of text with their styles
+#[must_use]
+pub fn parse_inline(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut current = Span::default();
+    let mut index = 0;
+    let flush = |spans: &mut Vec<Span>, current: &mut Span| {
+        if !current.text.is_empty() {
+            let style = Span {
+                text: String::new(),
+                ..current.clone()
+            };
+            spans.push(std::mem::replace(current, style));
+        }
+    };
+    while index < chars.len() {
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+        let previous = index.checked_sub(1).map(|i| chars[i]);
+        if c == '`'
+            && let Some(close) = find_char(&chars, index + 1, '`')
+        {
+            flush(&mut spans, &mut current);
+            spans.push(Span {
+                text: chars[index + 1..close].iter().collect(),
+                code: true,
+                ..current.clone()
+            });
+            index = close + 1;
+        } else if c == '['
+            && let Some((label, url, end)) = link(&chars, index)
+        {
+            flush(&mut spans, &mut current);
+            for mut span in parse_inline(&label) {
+                span.link = Some(url.clone());
+                span.bold |= current.bold;
+                span.italic |= current.italic;
+                spans.push(span);
+            }
+            index = end;
+        } else if (c == '*' || c == '_') && next == Some(c) {
+            flush(&mut spans, &mut current);
+            current.bold = !current.bold;
+            index += 2;
+        } else if (c == '*' && (current.italic || next.is_some_and(|n| !n.is_whitespace())))
+            || (c == '_'
+                && !previous.is_some_and(char::is_alphanumeric)
+                && next.is_some_and(|n| !n.is_whitespace()))
+            || (c == '_' && current.italic && !next.is_some_and(char::is_alphanumeric))
+        {
+            flush(&mut spans, &mut current);
+            current.italic = !current.italic;
+            index += 1;
+        } else {
+            current.text.push(c);
+            index += 1;
+        }
+    }
+    flush(&mut spans, &mut current);
+    spans
+}
+
+/// Find a character from a position
+///
+/// # Arguments
+/// * `chars` - Characters to search
+/// * `from` - Index to start at
+/// * `target` - Character to find
+///
+/// # Returns
+/// Index of the character, if found
+fn find_char(chars: &[char], from: usize, target: char) -> Option<usize> {
+    chars
+        .get(from..)?
+        .iter()
+        .position(|&c| c == target)
+        .map(|offset| from + offset)
+}
+
+/// Parse a link like "[text](url)"
+///
+/// # Arguments
+/// * `chars` - Characters of the text
+/// * `start` - Index of the '['
+///
+/// # Returns
+/// Tuple of (link text, target, index after the link), or None
+fn link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close = find_char(chars, start + 1, ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = find_char(chars, close + 2, ')')?;
+    let label = chars[start + 1..close].iter().collect();
+    let url = chars[close + 2..end].iter().collect::<String>();
+    Some((label, url.trim().to_string(), end + 1))
+}
+
+/// Parsed preview of a document, refreshed shortly after edits stop
+#[derive(Default)]
+pub struct MarkdownPreview {
+    /// Text the blocks were parsed from
+    source: Option<String>,
+    /// Parsed blocks of `source`
+    blocks: Vec<Block>,
+    /// Time the text was first seen to differ from `source`
+    changed_at: Option<f64>,
+}
+
+impl MarkdownPreview {
+    /// Parse the text again if it changed and the debounce delay passed
+    ///
+    /// The first text is parsed at once; later changes wait until
+    /// `PREVIEW_DEBOUNCE_SECS` after they were first seen.
+    ///
+    /// # Arguments
+    /// * `text` - Current document text
+    /// * `now` - Current time in seconds
+    ///
+    /// # Returns
+    /// Seconds until the preview should be refreshed, if it is out of date
+    pub fn update(&mut self, text: &str, now: f64) -> Option<f64> {
+        if self.source.as_deref() == Some(text) {
+            self.changed_at = None;
+            return None;
+        }
+        let changed_at = *self.changed_at.get_or_insert(now);
+        let waited = now - changed_at;
+        if self.source.is_some() && waited < PREVIEW_DEBOUNCE_SECS {
+            return Some(PREVIEW_DEBOUNCE_SECS - waited);
+        }
+        self.blocks = parse(text);
+        self.source = Some(text.to_string());
+        self.changed_at = None;
+        None
+    }
+
+    /// Get the parsed blocks
+    ///
+    /// # Returns
+    /// Blocks of the last parsed text
+    #[must_use]
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> Span {
+        Span {
+            text: text.to_string(),
+            ..Span::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let text = "# Title\n\nFirst line\nsecond line\n\n- one\n  * nested\n3. three\n\n\
+                    ```rust\nlet x = 1;\n\n```\n> quoted\n> more\n---\n####### not a heading";
+        assert_eq!(
+            parse(text),
+            vec![
+                Block::Heading(1, vec![plain("Title")]),
+                Block::Paragraph(vec![plain("First line second line")]),
+                Block::ListItem {
+                    marker: "•".to_string(),
+                    depth: 0,
+                    spans: vec![plain("one")]
+                },
+                Block::ListItem {
+                    marker: "•".to_string(),
+                    depth: 1,
+                    spans: vec![plain("nested")]
+                },
+                Block::ListItem {
+                    marker: "3.".to_string(),
+                    depth: 0,
+                    spans: vec![plain("three")]
+                },
+                Block::Code("let x = 1;\n".to_string()),
+                Block::Quote(vec![plain("quoted more")]),
+                Block::Rule,
+                Block::Paragraph(vec![plain("####### not a heading")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_unclosed_code_block() {
+        assert_eq!(
+            parse("text\n```\ncode"),
+            vec![
+                Block::Paragraph(vec![plain("text")]),
+                Block::Code("code".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_styles() {
+        let spans = parse_inline("a **bold** _it_ `x*y` [site](https://example.com)");
+        assert_eq!(
+            spans,
+            vec![
+                plain("a "),
+                Span {
+                    text: "bold".to_string(),
+                    bold: true,
+                    ..Span::default()
+                },
+                plain(" "),
+                Span {
+                    text: "it".to_string(),
+                    italic: true,
+                    ..Span::default()
+                },
+                plain(" "),
+                Span {
+                    text: "x*y".to_string(),
+                    code: true,
+                    ..Span::default()
+                },
+                plain(" "),
+                Span {
+                    text: "site".to_string(),
+                    link: Some("https://example.com".to_string()),
+                    ..Span::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_keeps_literal_marks() {
+        assert_eq!(
+            parse_inline("2 * 3 = snake_case_name"),
+            vec![plain("2 * 3 = snake_case_name")]
+        );
+        assert_eq!(
+            parse_inline("[not a link] `open"),
+            vec![plain("[not a link] `open")]
+        );
+    }
+
+    #[test]
+    fn test_preview_debounces_changes() {
+        let mut preview = MarkdownPreview::default();
+        assert_eq!(preview.update("# A", 0.0), None);
+        assert_eq!(preview.blocks().len(), 1);
+
+        // Changes wait for the debounce delay
+        let wait = preview.update("# A\n\nB", 1.0).expect("Should wait");
+        assert!((wait - PREVIEW_DEBOUNCE_SECS).abs() < f64::EPSILON);
+        assert_eq!(preview.blocks().len(), 1);
+        assert!(preview.update("# A\n\nBC", 1.1).is_some());
+        assert_eq!(
+            preview.update("# A\n\nBC", 1.0 + PREVIEW_DEBOUNCE_SECS),
+            None
+        );
+        assert_eq!(preview.blocks().len(), 2);
+    }
+}
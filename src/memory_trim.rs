@@ -0,0 +1,173 @@
+//! Memory trimming for long-running sessions
+//!
+//! This module decides how much of the undo/redo history and spare buffer
+//! capacity can be released when the window has been idle (minimized or
+//! unfocused) for a while, or when the undo history outgrows its budget.
+//! Unsaved text is never touched and a minimum number of undo steps is
+//! always kept.
+
+/// Undo history size that triggers a trim even while the window is in use
+pub const UNDO_BYTE_BUDGET: usize = 32 * 1024 * 1024;
+/// Number of redo steps kept when trimming
+pub const KEEP_REDO_STEPS: usize = 5;
+
+/// Limits that control when and how much to trim
+#[derive(Debug, Clone, Copy)]
+pub struct TrimPolicy {
+    /// Seconds the window must be idle before trimming
+    pub idle_after_secs: f64,
+    /// Maximum bytes held by the undo history
+    pub undo_byte_budget: usize,
+    /// Undo steps that are never dropped
+    pub min_undo_steps: usize,
+    /// Redo steps kept when trimming
+    pub keep_redo_steps: usize,
+}
+
+/// Current cache sizes the trim decision is based on
+#[derive(Debug, Clone, Copy)]
+pub struct TrimInputs<'a> {
+    /// Size of each undo step in bytes, oldest first
+    pub undo_step_bytes: &'a [usize],
+    /// Number of redo steps
+    pub redo_steps: usize,
+    /// Seconds the window has been minimized or unfocused
+    pub idle_secs: f64,
+}
+
+/// What to release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrimPlan {
+    /// Number of oldest undo steps to drop
+    pub drop_undo_steps: usize,
+    /// Number of redo steps to drop, farthest from the current state first
+    pub drop_redo_steps: usize,
+    /// Release spare capacity of the text buffers
+    pub shrink_buffers: bool,
+}
+
+/// Decide what to trim
+///
+/// Trimming happens when the window has been idle long enough or the
+/// undo history exceeds its byte budget. Redo history is cut down to a
+/// few steps; undo steps are only dropped (oldest first) while over
+/// budget, and never below the minimum depth. Over budget with every step
+/// protected by the minimum depth, nothing is trimmed until the window
+/// is idle, so the buffers are not shrunk on every frame.
+///
+/// # Arguments
+/// * `policy` - Trim limits
+/// * `inputs` - Current cache sizes
+///
+/// # Returns
+/// The trim plan, or None if nothing should be trimmed
+#[must_use]
+pub fn plan_trim(policy: &TrimPolicy, inputs: &TrimInputs<'_>) -> Option<TrimPlan> {
+    let undo_bytes: usize = inputs.undo_step_bytes.iter().sum();
+    let idle = inputs.idle_secs >= policy.idle_after_secs;
+    let over_budget = undo_bytes > policy.undo_byte_budget;
+    if !idle && !over_budget {
+        return None;
+    }
+
+    let mut drop_undo_steps = 0;
+    if over_budget {
+        let droppable = inputs
+            .undo_step_bytes
+            .len()
+            .saturating_sub(policy.min_undo_steps);
+        let mut remaining = undo_bytes;
+        for &bytes in &inputs.undo_step_bytes[..droppable] {
+            if remaining <= policy.undo_byte_budget {
+                break;
+            }
+            remaining -= bytes;
+            drop_undo_steps += 1;
+        }
+    }
+
+    if !idle && drop_undo_steps == 0 {
+        return None;
+    }
+
+    Some(TrimPlan {
+        drop_undo_steps,
+        drop_redo_steps: inputs.redo_steps.saturating_sub(policy.keep_redo_steps),
+        shrink_buffers: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: TrimPolicy = TrimPolicy {
+        idle_after_secs: 300.0,
+        undo_byte_budget: 100,
+        min_undo_steps: 3,
+        keep_redo_steps: 2,
+    };
+
+    #[test]
+    fn test_no_trim_while_active_and_within_budget() {
+        let inputs = TrimInputs {
+            undo_step_bytes: &[10, 20, 30],
+            redo_steps: 10,
+            idle_secs: 10.0,
+        };
+        assert_eq!(plan_trim(&POLICY, &inputs), None);
+    }
+
+    #[test]
+    fn test_idle_trims_redo_and_buffers_only() {
+        let inputs = TrimInputs {
+            undo_step_bytes: &[10, 20, 30],
+            redo_steps: 10,
+            idle_secs: 301.0,
+        };
+        let plan = plan_trim(&POLICY, &inputs).expect("Expected a trim");
+        assert_eq!(plan.drop_undo_steps, 0);
+        assert_eq!(plan.drop_redo_steps, 8);
+        assert!(plan.shrink_buffers);
+    }
+
+    #[test]
+    fn test_over_budget_drops_oldest_undo_steps() {
+        let inputs = TrimInputs {
+            undo_step_bytes: &[50, 50, 10, 10, 10, 10],
+            redo_steps: 1,
+            idle_secs: 0.0,
+        };
+        let plan = plan_trim(&POLICY, &inputs).expect("Expected a trim");
+        // 140 bytes: dropping the first 50-byte step brings it to 90
+        assert_eq!(plan.drop_undo_steps, 1);
+        assert_eq!(plan.drop_redo_steps, 0);
+    }
+
+    #[test]
+    fn test_undo_floor_is_respected() {
+        let inputs = TrimInputs {
+            undo_step_bytes: &[500, 500, 500, 500],
+            redo_steps: 0,
+            idle_secs: 0.0,
+        };
+        let plan = plan_trim(&POLICY, &inputs).expect("Expected a trim");
+        // Still over budget, but only one step is above the floor of 3
+        assert_eq!(plan.drop_undo_steps, 1);
+    }
+
+    #[test]
+    fn test_over_budget_within_floor_waits_for_idle() {
+        let mut inputs = TrimInputs {
+            undo_step_bytes: &[500, 500, 500],
+            redo_steps: 4,
+            idle_secs: 0.0,
+        };
+        assert_eq!(plan_trim(&POLICY, &inputs), None);
+
+        inputs.idle_secs = 301.0;
+        let plan = plan_trim(&POLICY, &inputs).expect("Expected a trim");
+        assert_eq!(plan.drop_undo_steps, 0);
+        assert_eq!(plan.drop_redo_steps, 2);
+    }
+}
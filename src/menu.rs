@@ -1,9 +1,18 @@
 //! Menu bar implementation
 //!
 //! This module implements the menu bar with File, Edit, Format,
-//! View, and Help menus.
+//! View, Tools, and Help menus.
 
-use crate::app::NodepatApp;
+use crate::app::{NodepatApp, PendingAction};
+use crate::case::CaseConversion;
+use crate::file_ops::UTF8_BOM_LABEL;
+use crate::format::ZOOM_PRESETS;
+use crate::highlight::Language;
+use crate::keymap::Action;
+use crate::lines::SortMode;
+use crate::macros::MacroStep;
+use crate::theme::ThemePreset;
+use crate::viewer::ViewerState;
 use eframe::egui;
 
 /// Show the menu bar
@@ -12,46 +21,263 @@ use eframe::egui;
 /// * `ui` - egui UI context
 /// * `app` - Application state
 pub fn show_menu_bar(ui: &mut egui::Ui, app: &mut NodepatApp) {
-    // Handle keyboard shortcuts
-    ui.input(|i| {
-        // Ctrl+N: New
-        if i.key_pressed(egui::Key::N) && i.modifiers.ctrl {
-            handle_new_file(app);
-        }
-        // Ctrl+O: Open
-        if i.key_pressed(egui::Key::O) && i.modifiers.ctrl {
-            app.show_open_dialog = true;
-        }
-        // Ctrl+S: Save
-        if i.key_pressed(egui::Key::S) && i.modifiers.ctrl {
-            handle_save(app);
-        }
-        // Ctrl+F: Find
-        if i.key_pressed(egui::Key::F) && i.modifiers.ctrl {
-            app.show_find_dialog = true;
-        }
-        // Ctrl+H: Replace
-        if i.key_pressed(egui::Key::H) && i.modifiers.ctrl {
-            app.show_replace_dialog = true;
-        }
-        // Ctrl+G: Go To
-        if i.key_pressed(egui::Key::G) && i.modifiers.ctrl {
-            app.show_goto_dialog = true;
-        }
-        // F3: Find Next
-        if i.key_pressed(egui::Key::F3) {
-            crate::search::find_next(app);
-        }
-    });
+    // Ctrl+Shift+X: Swap with Clipboard. The platform turns Ctrl+X into a
+    // cut event, so take it before the editor cuts the selection.
+    let swap = app.is_editable()
+        && ui.input_mut(|i| {
+            let shifted = i.modifiers.command && i.modifiers.shift;
+            let before = i.events.len();
+            i.events
+                .retain(|event| !(shifted && matches!(event, egui::Event::Cut)));
+            i.events.len() != before
+        });
+    if swap {
+        handle_swap_with_clipboard(app, ui.ctx());
+    }
+    // Ctrl+Shift+V: Paste from History, which the platform also turns
+    // into a paste event. Without a history to choose from, the paste
+    // goes through.
+    let paste_from_history = app.can_paste_from_history()
+        && ui.input_mut(|i| {
+            let shifted = i.modifiers.command && i.modifiers.shift;
+            let before = i.events.len();
+            i.events
+                .retain(|event| !(shifted && matches!(event, egui::Event::Paste(_))));
+            i.events.len() != before
+        });
+    if paste_from_history {
+        app.open_paste_history();
+    }
+
+    // Handle keyboard shortcuts before the editor sees the keys
+    let (actions, now) = ui.input_mut(|i| (app.keymap.consume_pressed(i), i.time));
+    for action in actions {
+        run_action(app, action, now);
+    }
     egui::MenuBar::new().ui(ui, |ui| {
         show_file_menu(ui, app);
         show_edit_menu(ui, app);
         show_format_menu(ui, app);
         show_view_menu(ui, app);
+        show_tools_menu(ui, app);
         show_help_menu(ui, app);
     });
 }
 
+/// Run a command triggered by its keyboard shortcut
+///
+/// Editing commands on a read-only document flash the read-only hint.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `action` - Command to run
+/// * `now` - Current time in seconds
+fn run_action(app: &mut NodepatApp, action: Action, now: f64) {
+    let edits = matches!(
+        action,
+        Action::Undo
+            | Action::Redo
+            | Action::Replace
+            | Action::TimeDate
+            | Action::Duplicate
+            | Action::DeleteLine
+            | Action::MoveLineUp
+            | Action::MoveLineDown
+            | Action::TrimTrailingWhitespace
+            | Action::PlayMacro
+    );
+    if edits && !app.is_editable() {
+        if let Some(viewer) = &mut app.viewer {
+            viewer.status = crate::viewer::READ_ONLY_HINT.to_string();
+        } else {
+            app.reject_edit(now);
+        }
+        return;
+    }
+    match action {
+        Action::New => handle_new_file(app),
+        Action::NewWindow => app.open_new_window(),
+        Action::Open => app.request_action(PendingAction::OpenDialog),
+        Action::Save => handle_save(app, now),
+        Action::CloseTab => {
+            app.viewer = None;
+            app.request_action(PendingAction::CloseTab);
+        }
+        Action::NextTab => app.cycle_tab(true),
+        Action::PreviousTab => app.cycle_tab(false),
+        Action::Undo => handle_undo(app),
+        Action::Redo => handle_redo(app),
+        Action::Find if app.config.incremental_find => app.open_search_bar(),
+        Action::Find => app.open_find_dialog(),
+        Action::FindNext => {
+            crate::search::find_next(app);
+        }
+        Action::IncrementalSearch => app.toggle_search_bar(),
+        Action::Replace => app.open_replace_dialog(),
+        Action::FindInFiles => app.open_find_in_files_dialog(),
+        Action::GoTo => app.open_goto_dialog(),
+        Action::ToggleBookmark => app.toggle_bookmark(),
+        Action::NextBookmark => app.jump_to_bookmark(true),
+        Action::PreviousBookmark => app.jump_to_bookmark(false),
+        Action::ToggleMacroRecording => app.toggle_macro_recording(),
+        Action::PlayMacro => app.play_macro(1),
+        Action::TimeDate => handle_time_date(app),
+        Action::Duplicate => {
+            handle_duplicate(app);
+            app.record_macro_step(MacroStep::Duplicate);
+        }
+        Action::DeleteLine => {
+            handle_delete_lines(app);
+            app.record_macro_step(MacroStep::DeleteLines);
+        }
+        Action::MoveLineUp => {
+            handle_move_lines(app, true);
+            app.record_macro_step(MacroStep::MoveLines { up: true });
+        }
+        Action::MoveLineDown => {
+            handle_move_lines(app, false);
+            app.record_macro_step(MacroStep::MoveLines { up: false });
+        }
+        Action::TrimTrailingWhitespace => handle_trim_trailing_whitespace(app, now, true),
+        Action::ZoomIn => app.zoom_by(1.0),
+        Action::ZoomOut => app.zoom_by(-1.0),
+        Action::ResetZoom => app.reset_zoom(),
+    }
+}
+
+/// Recent files beyond this count move into a "Recent Files" submenu
+const RECENT_SUBMENU_THRESHOLD: usize = 8;
+
+/// Get the recent files shown in the File menu
+///
+/// Files that no longer exist are skipped, so the numbering matches
+/// what is displayed.
+///
+/// # Arguments
+/// * `recent_files` - Recent file paths, most recent first
+/// * `limit` - Maximum number of entries to show
+/// * `exists` - Returns whether a path still exists
+///
+/// # Returns
+/// Up to `limit` displayed entries, in menu order
+fn visible_recent_files(
+    recent_files: &[String],
+    limit: usize,
+    exists: impl Fn(&str) -> bool,
+) -> Vec<&String> {
+    recent_files
+        .iter()
+        .filter(|path| exists(path))
+        .take(limit)
+        .collect()
+}
+
+/// Show the recent files section of the File menu
+///
+/// Entries are numbered 1-9 and 0 like classic Notepad. Long lists are
+/// moved into a "Recent Files" submenu to keep the File menu short.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_recent_files(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let recent = visible_recent_files(
+        &app.config.recent_files,
+        app.config.max_recent_files,
+        |path| std::path::Path::new(path).exists(),
+    );
+    if recent.is_empty() {
+        return;
+    }
+    ui.separator();
+    let mut clicked_recent = recent_digit_pressed(ui)
+        .and_then(|digit| recent_entry_for_digit(&recent, digit))
+        .cloned();
+    let mut clear = false;
+    let mut show_entries = |ui: &mut egui::Ui| {
+        for (idx, recent_file) in recent.iter().enumerate() {
+            let label = if recent_file.chars().count() > 50 {
+                format!("{}...", recent_file.chars().take(50).collect::<String>())
+            } else {
+                (*recent_file).clone()
+            };
+            let label = if idx < 10 {
+                format!("{} {label}", (idx + 1) % 10)
+            } else {
+                label
+            };
+            if ui.button(label).on_hover_text(*recent_file).clicked() {
+                clicked_recent = Some((*recent_file).clone());
+            }
+        }
+        clear = ui.button("Clear Recent Files").clicked();
+    };
+    if recent.len() > RECENT_SUBMENU_THRESHOLD {
+        ui.menu_button("Recent Files", show_entries);
+    } else {
+        show_entries(ui);
+    }
+    if clear {
+        app.config.clear_recent_files();
+        app.save_config_later();
+        ui.close();
+    }
+    if let Some(path) = clicked_recent {
+        ui.close();
+        app.request_action(PendingAction::OpenFile(path));
+    }
+}
+
+/// Map a digit key to a displayed recent file entry
+///
+/// Digits 1-9 select the first nine entries and 0 selects the tenth.
+///
+/// # Arguments
+/// * `entries` - Displayed recent file entries
+/// * `digit` - Pressed digit (0-9)
+///
+/// # Returns
+/// The selected entry, or None if there is no entry for the digit
+fn recent_entry_for_digit<'a>(entries: &[&'a String], digit: u8) -> Option<&'a String> {
+    let index = match digit {
+        0 => 9,
+        1..=9 => usize::from(digit) - 1,
+        _ => return None,
+    };
+    entries.get(index).copied()
+}
+
+/// Consume a digit key pressed without modifiers
+///
+/// Consuming the key keeps it from being typed into the editor while
+/// the File menu is open.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+///
+/// # Returns
+/// The pressed digit, if any
+fn recent_digit_pressed(ui: &egui::Ui) -> Option<u8> {
+    const DIGIT_KEYS: [egui::Key; 10] = [
+        egui::Key::Num0,
+        egui::Key::Num1,
+        egui::Key::Num2,
+        egui::Key::Num3,
+        egui::Key::Num4,
+        egui::Key::Num5,
+        egui::Key::Num6,
+        egui::Key::Num7,
+        egui::Key::Num8,
+        egui::Key::Num9,
+    ];
+    ui.input_mut(|i| {
+        (0u8..)
+            .zip(DIGIT_KEYS)
+            .find(|&(_, key)| i.consume_key(egui::Modifiers::NONE, key))
+            .map(|(digit, _)| digit)
+    })
+}
+
 /// Show File menu
 ///
 /// # Arguments
@@ -59,53 +285,153 @@ pub fn show_menu_bar(ui: &mut egui::Ui, app: &mut NodepatApp) {
 /// * `app` - Application state
 fn show_file_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
     ui.menu_button("File", |ui| {
-        if ui.button("New\tCtrl+N").clicked() {
+        let ctx = ui.ctx().clone();
+        if ui
+            .button(app.keymap.menu_label(&ctx, "New", Action::New))
+            .clicked()
+        {
             handle_new_file(app);
             ui.close();
         }
-        if ui.button("Open...\tCtrl+O").clicked() {
+        if ui
+            .button(app.keymap.menu_label(&ctx, "New Window", Action::NewWindow))
+            .clicked()
+        {
+            app.open_new_window();
+            ui.close();
+        }
+        if ui
+            .button(app.keymap.menu_label(&ctx, "Open...", Action::Open))
+            .clicked()
+        {
+            app.request_action(PendingAction::OpenDialog);
+            ui.close();
+        }
+        if ui.button("Open as Viewer...").clicked() {
+            app.open_as_viewer = true;
             app.show_open_dialog = true;
             ui.close();
         }
-        // Show recent files
-        if !app.config.recent_files.is_empty() {
-            ui.separator();
-            for (idx, recent_file) in app.config.recent_files.iter().take(5).enumerate() {
-                let label = if recent_file.len() > 50 {
-                    format!("{}...", &recent_file[..50])
-                } else {
-                    recent_file.clone()
-                };
-                if ui.button(format!("{} {label}", idx + 1)).clicked() {
-                    if let Ok(content) = app.file_state.load_file(recent_file) {
-                        app.editor_state.text = content;
-                        app.editor_state.undo_history.clear();
-                        app.editor_state.redo_history.clear();
-                    }
-                    ui.close();
-                }
-            }
+        if app.viewer.is_some() && ui.button("Close Viewer").clicked() {
+            app.viewer = None;
+            ui.close();
+        }
+        if ui
+            .button(app.keymap.menu_label(&ctx, "Close Tab", Action::CloseTab))
+            .clicked()
+        {
+            app.viewer = None;
+            app.request_action(PendingAction::CloseTab);
+            ui.close();
         }
+        show_recent_files(ui, app);
+        let reopenable = app.viewer.is_none() && !app.file_state.file_path.is_empty();
+        ui.add_enabled_ui(reopenable, |ui| {
+            ui.menu_button("Reopen with Encoding", |ui| {
+                show_reopen_encoding_items(ui, app);
+            });
+        });
         ui.separator();
-        if ui.button("Save\tCtrl+S").clicked() {
-            handle_save(app);
+        // Saving a read-only document goes to Save As
+        let editable = app.viewer.is_none();
+        if ui
+            .add_enabled(
+                editable,
+                egui::Button::new(app.keymap.menu_label(&ctx, "Save", Action::Save)),
+            )
+            .on_disabled_hover_text(crate::viewer::READ_ONLY_HINT)
+            .clicked()
+        {
+            handle_save(app, ui.input(|i| i.time));
             ui.close();
         }
-        if ui.button("Save As...").clicked() {
+        if ui
+            .add_enabled(editable, egui::Button::new("Save As..."))
+            .on_disabled_hover_text(crate::viewer::READ_ONLY_HINT)
+            .clicked()
+        {
             app.show_save_dialog = true;
             ui.close();
         }
+        ui.add_enabled_ui(editable, |ui| {
+            ui.menu_button("Save with Encoding", |ui| {
+                show_save_encoding_items(ui, app);
+            });
+        });
+        ui.menu_button("Export", |ui| {
+            if ui
+                .add_enabled(editable, egui::Button::new("PDF..."))
+                .on_disabled_hover_text(crate::viewer::READ_ONLY_HINT)
+                .clicked()
+            {
+                app.show_export_pdf_dialog = true;
+                ui.close();
+            }
+        });
+        show_save_settings(ui, app);
         ui.separator();
         if ui.button("Exit").clicked() {
-            // Close the application
-            // Note: In a full implementation, we would check for unsaved changes
-            // and prompt the user to save before exiting
+            // Close the application; the close request is intercepted in
+            // `NodepatApp::update` to ask about unsaved changes
             ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
             ui.close();
         }
     });
 }
 
+/// Show the backup, autosave and recovery file settings of the File menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_save_settings(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    if ui
+        .checkbox(&mut app.config.create_backup, "Back Up on Save")
+        .on_hover_text("Keep the previous version of a file as <name>.bak")
+        .changed()
+    {
+        app.file_state.create_backup = app.config.create_backup;
+        app.save_config_later();
+    }
+    let mut changed = ui
+        .checkbox(&mut app.config.autosave_enabled, "Autosave")
+        .on_hover_text("Save documents that have a file name periodically")
+        .changed();
+    if changed {
+        app.file_autosave = crate::autosave::FileAutosave::default();
+    }
+    ui.add_enabled_ui(app.config.autosave_enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Every");
+            let mut minutes = (app.config.autosave_interval_secs / 60).max(1);
+            if ui
+                .add(
+                    egui::DragValue::new(&mut minutes)
+                        .range(1..=120)
+                        .suffix(" min"),
+                )
+                .changed()
+            {
+                app.config.autosave_interval_secs = minutes * 60;
+                changed = true;
+            }
+        });
+    });
+    if ui
+        .checkbox(&mut app.config.recovery_enabled, "Recovery Files")
+        .on_hover_text("Write unsaved changes to recovery files")
+        .changed()
+    {
+        if !app.config.recovery_enabled {
+            app.autosave = crate::autosave::Autosave::default();
+        }
+        changed = true;
+    }
+    if changed {
+        app.save_config_later();
+    }
+}
+
 /// Show Edit menu
 ///
 /// # Arguments
@@ -113,110 +439,601 @@ fn show_file_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
 /// * `app` - Application state
 fn show_edit_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
     ui.menu_button("Edit", |ui| {
-        let can_undo = !app.editor_state.undo_history.is_empty();
+        let editable = app.is_editable();
+        let hint = app.read_only_hint();
+        let ctx = ui.ctx().clone();
+        let label = |name, action| app.keymap.menu_label(&ctx, name, action);
+        let (undo, redo, time_date) = (
+            label("Undo", Action::Undo),
+            label("Redo", Action::Redo),
+            label("Time/Date", Action::TimeDate),
+        );
+        let can_undo = editable && app.editor_state.can_undo();
+        if ui.add_enabled(can_undo, egui::Button::new(undo)).clicked() {
+            handle_undo(app);
+            ui.close();
+        }
+        let can_redo = editable && app.editor_state.can_redo();
+        if ui.add_enabled(can_redo, egui::Button::new(redo)).clicked() {
+            handle_redo(app);
+            ui.close();
+        }
+        ui.separator();
+        show_clipboard_items(ui, app, editable, hint);
+        ui.separator();
+        show_search_items(ui, app, editable, hint);
+        ui.separator();
+        if ui.button("Select All\tCtrl+A").clicked() {
+            handle_select_all(app);
+            ui.close();
+        }
         if ui
-            .add_enabled(can_undo, egui::Button::new("Undo\tCtrl+Z"))
+            .add_enabled(editable, egui::Button::new(time_date))
+            .on_disabled_hover_text(hint)
             .clicked()
         {
-            if app.editor_state.undo() {
-                app.file_state.is_modified = true;
-            }
+            handle_time_date(app);
             ui.close();
         }
-        let can_redo = !app.editor_state.redo_history.is_empty();
+        ui.separator();
+        ui.menu_button("Lines", |ui| show_line_items(ui, app, editable, hint));
+        ui.menu_button("Macro", |ui| show_macro_items(ui, app, hint));
+        ui.add_enabled_ui(editable, |ui| {
+            ui.menu_button("Indentation", |ui| {
+                for (name, to_spaces) in [
+                    ("Convert Tabs to Spaces", true),
+                    ("Convert Spaces to Tabs", false),
+                ] {
+                    if ui.button(name).clicked() {
+                        handle_convert_indentation(app, to_spaces);
+                        ui.close();
+                    }
+                }
+            })
+        })
+        .response
+        .on_disabled_hover_text(hint);
+        ui.add_enabled_ui(editable, |ui| {
+            ui.menu_button("Convert Case", |ui| {
+                for conversion in CaseConversion::all() {
+                    if ui.button(conversion.display_name()).clicked() {
+                        handle_convert_case(app, conversion);
+                        ui.close();
+                    }
+                }
+            })
+        })
+        .response
+        .on_disabled_hover_text(hint);
+        ui.separator();
+        if ui.button("Preferences...").clicked() {
+            app.open_preferences();
+            ui.close();
+        }
+    });
+}
+
+/// Show the search and navigation items of the Edit menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `editable` - Whether the document can be edited
+/// * `hint` - Tooltip of the disabled Replace item
+fn show_search_items(ui: &mut egui::Ui, app: &mut NodepatApp, editable: bool, hint: &str) {
+    let ctx = ui.ctx().clone();
+    let label = |name, action| app.keymap.menu_label(&ctx, name, action);
+    let (find, find_next, incremental, replace, find_in_files, go_to) = (
+        label("Find...", Action::Find),
+        label("Find Next", Action::FindNext),
+        label("Incremental Search", Action::IncrementalSearch),
+        label("Replace...", Action::Replace),
+        label("Find in Files...", Action::FindInFiles),
+        label("Go To...", Action::GoTo),
+    );
+    if ui.button(find).clicked() {
+        app.open_find_dialog();
+        ui.close();
+    }
+    if ui.button(find_next).clicked() {
+        crate::search::find_next(app);
+        ui.close();
+    }
+    if ui
+        .add(egui::Button::new(incremental).selected(app.search_bar.is_some()))
+        .clicked()
+    {
+        app.toggle_search_bar();
+        ui.close();
+    }
+    if ui
+        .add_enabled(editable, egui::Button::new(replace))
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        app.open_replace_dialog();
+        ui.close();
+    }
+    if ui.button(find_in_files).clicked() {
+        app.open_find_in_files_dialog();
+        ui.close();
+    }
+    if ui.button(go_to).clicked() {
+        app.open_goto_dialog();
+        ui.close();
+    }
+    ui.add_enabled_ui(app.viewer.is_none(), |ui| {
+        ui.menu_button("Bookmarks", |ui| show_bookmark_items(ui, app));
+    });
+}
+
+/// Show the Edit > Bookmarks items
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_bookmark_items(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let ctx = ui.ctx().clone();
+    let label = |name, action| app.keymap.menu_label(&ctx, name, action);
+    let (toggle, next, previous) = (
+        label("Toggle Bookmark", Action::ToggleBookmark),
+        label("Next Bookmark", Action::NextBookmark),
+        label("Previous Bookmark", Action::PreviousBookmark),
+    );
+    let any = !app.editor_state.bookmarks.is_empty();
+    if ui.button(toggle).clicked() {
+        app.toggle_bookmark();
+        ui.close();
+    }
+    if ui.add_enabled(any, egui::Button::new(next)).clicked() {
+        app.jump_to_bookmark(true);
+        ui.close();
+    }
+    if ui.add_enabled(any, egui::Button::new(previous)).clicked() {
+        app.jump_to_bookmark(false);
+        ui.close();
+    }
+    ui.separator();
+    if ui
+        .add_enabled(any, egui::Button::new("Clear All"))
+        .clicked()
+    {
+        app.editor_state.bookmarks.clear();
+        ui.close();
+    }
+}
+
+/// Show the Edit > Macro items
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `hint` - Hover text of the disabled playback items
+fn show_macro_items(ui: &mut egui::Ui, app: &mut NodepatApp, hint: &str) {
+    let ctx = ui.ctx().clone();
+    let label = |name, action| app.keymap.menu_label(&ctx, name, action);
+    let (start, stop, playback) = (
+        label("Start Recording", Action::ToggleMacroRecording),
+        label("Stop Recording", Action::ToggleMacroRecording),
+        label("Playback", Action::PlayMacro),
+    );
+    let recording = app.macro_recording.is_some();
+    if ui
+        .add_enabled(!recording, egui::Button::new(start))
+        .clicked()
+    {
+        app.toggle_macro_recording();
+        ui.close();
+    }
+    if ui.add_enabled(recording, egui::Button::new(stop)).clicked() {
+        app.toggle_macro_recording();
+        ui.close();
+    }
+    ui.separator();
+    let hint = if app.is_editable() {
+        "Record a macro first"
+    } else {
+        hint
+    };
+    let playable = app.can_play_macro();
+    if ui
+        .add_enabled(playable, egui::Button::new(playback))
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        app.play_macro(1);
+        ui.close();
+    }
+    if ui
+        .add_enabled(playable, egui::Button::new("Run Macro Multiple Times..."))
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        app.open_macro_dialog();
+        ui.close();
+    }
+}
+
+/// Show the line editing items of the Edit menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `editable` - Whether the document can be edited
+/// * `hint` - Hover text of disabled items
+fn show_line_items(ui: &mut egui::Ui, app: &mut NodepatApp, editable: bool, hint: &str) {
+    let ctx = ui.ctx().clone();
+    for (name, action) in [
+        ("Duplicate", Action::Duplicate),
+        ("Delete Line", Action::DeleteLine),
+        ("Move Line Up", Action::MoveLineUp),
+        ("Move Line Down", Action::MoveLineDown),
+        ("Trim Trailing Whitespace", Action::TrimTrailingWhitespace),
+    ] {
+        let label = app.keymap.menu_label(&ctx, name, action);
         if ui
-            .add_enabled(can_redo, egui::Button::new("Redo\tCtrl+Y"))
+            .add_enabled(editable, egui::Button::new(label))
+            .on_disabled_hover_text(hint)
             .clicked()
         {
-            if app.editor_state.redo() {
-                app.file_state.is_modified = true;
+            run_action(app, action, ui.input(|i| i.time));
+            ui.close();
+        }
+    }
+    ui.separator();
+    ui.add_enabled_ui(editable, |ui| {
+        ui.menu_button("Sort", |ui| {
+            for mode in SortMode::all() {
+                if ui.button(mode.display_name()).clicked() {
+                    handle_sort_lines(app, mode);
+                    ui.close();
+                }
             }
+        })
+    })
+    .response
+    .on_disabled_hover_text(hint);
+}
+
+/// Show the clipboard items of the Edit menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+/// * `editable` - Whether the document can be edited
+/// * `hint` - Hover text of disabled items
+fn show_clipboard_items(ui: &mut egui::Ui, app: &mut NodepatApp, editable: bool, hint: &str) {
+    if ui
+        .add_enabled(editable, egui::Button::new("Cut\tCtrl+X"))
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        handle_cut(app, ui.ctx());
+        ui.close();
+    }
+    if ui.button("Copy\tCtrl+C").clicked() {
+        handle_copy(app, ui.ctx());
+        ui.close();
+    }
+    if ui
+        .add_enabled(editable, egui::Button::new("Paste\tCtrl+V"))
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        handle_paste(app);
+        ui.close();
+    }
+    let has_history = app.can_paste_from_history();
+    if ui
+        .add_enabled(
+            has_history,
+            egui::Button::new("Paste from History...\tCtrl+Shift+V"),
+        )
+        .on_disabled_hover_text(if editable {
+            "Nothing was cut or copied yet"
+        } else {
+            hint
+        })
+        .clicked()
+    {
+        app.open_paste_history();
+        ui.close();
+    }
+    if ui
+        .add_enabled(
+            editable,
+            egui::Button::new("Swap with Clipboard\tCtrl+Shift+X"),
+        )
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        handle_swap_with_clipboard(app, ui.ctx());
+        ui.close();
+    }
+    if ui
+        .add_enabled(editable, egui::Button::new("Delete\tDel"))
+        .on_disabled_hover_text(hint)
+        .clicked()
+    {
+        handle_delete(app);
+        ui.close();
+    }
+}
+
+/// Show Format menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_format_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    ui.menu_button("Format", |ui| {
+        if ui
+            .checkbox(&mut app.format_settings.word_wrap, "Word Wrap")
+            .clicked()
+        {
+            app.config.update_from_format(&app.format_settings);
+            app.save_config_later();
             ui.close();
         }
-        ui.separator();
-        if ui.button("Cut\tCtrl+X").clicked() {
-            handle_cut(app, ui.ctx());
+        if ui.button("Font...").clicked() {
+            app.show_font_dialog = true;
             ui.close();
         }
-        if ui.button("Copy\tCtrl+C").clicked() {
-            handle_copy(app, ui.ctx());
+    });
+}
+
+/// Show View menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_view_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    ui.menu_button("View", |ui| {
+        ui.menu_button("Zoom", |ui| show_zoom_items(ui, app));
+        ui.separator();
+        ui.menu_button("Theme", |ui| show_theme_items(ui, app));
+        // A preset decides between light and dark itself
+        let no_preset = ThemePreset::from_name(&app.config.theme.preset).is_none();
+        if ui
+            .add_enabled(
+                no_preset,
+                egui::Checkbox::new(&mut app.dark_mode, "Dark Mode"),
+            )
+            .clicked()
+        {
+            app.config.dark_mode = app.dark_mode;
+            app.save_config_later();
             ui.close();
         }
-        if ui.button("Paste\tCtrl+V").clicked() {
-            handle_paste(app, ui.ctx());
+        ui.separator();
+        if ui
+            .checkbox(&mut app.show_status_bar, "Status Bar")
+            .clicked()
+        {
+            app.config.show_status_bar = app.show_status_bar;
+            app.save_config_later();
             ui.close();
         }
-        if ui.button("Delete\tDel").clicked() {
-            handle_delete(app);
+        if ui
+            .checkbox(&mut app.show_line_numbers, "Line Numbers")
+            .clicked()
+        {
+            app.config.show_line_numbers = app.show_line_numbers;
+            app.save_config_later();
             ui.close();
         }
-        ui.separator();
-        if ui.button("Find...\tCtrl+F").clicked() {
-            app.show_find_dialog = true;
+        if ui
+            .checkbox(
+                &mut app.config.highlight_current_line,
+                "Highlight Current Line",
+            )
+            .clicked()
+        {
+            app.save_config_later();
             ui.close();
         }
-        if ui.button("Find Next\tF3").clicked() {
-            crate::search::find_next(app);
+        if ui
+            .checkbox(&mut app.config.syntax_highlighting, "Syntax Highlighting")
+            .clicked()
+        {
+            app.save_config_later();
             ui.close();
         }
-        if ui.button("Replace...\tCtrl+H").clicked() {
-            app.show_replace_dialog = true;
+        ui.add_enabled_ui(app.config.syntax_highlighting, |ui| {
+            ui.menu_button("Language", |ui| show_language_items(ui, app));
+        });
+        if ui
+            .checkbox(&mut app.config.spell_check, "Spell Check")
+            .clicked()
+        {
+            app.save_config_later();
             ui.close();
         }
-        if ui.button("Go To...\tCtrl+G").clicked() {
-            app.show_goto_dialog = true;
+        if ui
+            .add_enabled(
+                app.can_preview_markdown(),
+                egui::Checkbox::new(&mut app.config.markdown_preview, "Markdown Preview"),
+            )
+            .on_disabled_hover_text("Available for Markdown documents")
+            .clicked()
+        {
+            app.save_config_later();
             ui.close();
         }
         ui.separator();
-        if ui.button("Select All\tCtrl+A").clicked() {
-            handle_select_all(app);
-            // TextEdit handles Ctrl+A internally
+        if ui
+            .add_enabled(
+                app.viewer.is_none(),
+                egui::Checkbox::new(&mut app.read_only_mode, "Read Only Mode"),
+            )
+            .on_hover_text("Protect the document from accidental edits")
+            .clicked()
+        {
             ui.close();
         }
-        if ui.button("Time/Date\tF5").clicked() {
-            crate::editor::insert_time_date(&mut app.editor_state);
-            app.file_state.is_modified = true;
+        ui.separator();
+        if ui
+            .add_enabled(app.viewer.is_none(), egui::Button::new("Statistics..."))
+            .clicked()
+        {
+            app.show_statistics_dialog = true;
             ui.close();
         }
     });
 }
 
-/// Show Format menu
+/// Show the File > Reopen with Encoding submenu
 ///
 /// # Arguments
 /// * `ui` - egui UI context
 /// * `app` - Application state
-fn show_format_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
-    ui.menu_button("Format", |ui| {
-        if ui.button("Font...").clicked() {
-            app.show_font_dialog = true;
+fn show_reopen_encoding_items(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    for encoding in crate::file_ops::ENCODINGS {
+        if ui
+            .radio(app.file_state.encoding == encoding, encoding)
+            .clicked()
+        {
+            app.request_action(PendingAction::ReopenWithEncoding(encoding.to_string()));
             ui.close();
         }
-    });
+    }
 }
 
-/// Show View menu
+/// Show the File > Save with Encoding submenu
 ///
 /// # Arguments
 /// * `ui` - egui UI context
 /// * `app` - Application state
-fn show_view_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
-    ui.menu_button("View", |ui| {
-        if ui.checkbox(&mut app.dark_mode, "Dark Mode").clicked() {
-            app.config.dark_mode = app.dark_mode;
-            let _ = app.config.save();
+fn show_save_encoding_items(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let label = app.file_state.encoding_label();
+    for encoding in crate::file_ops::ENCODINGS {
+        if ui.radio(label == encoding, encoding).clicked() {
+            save_with_encoding(app, encoding, false, ui.input(|i| i.time));
             ui.close();
         }
-        ui.separator();
+        if encoding == "UTF-8"
+            && ui
+                .radio(label == UTF8_BOM_LABEL, UTF8_BOM_LABEL)
+                .on_hover_text("UTF-8 starting with a byte order mark")
+                .clicked()
+        {
+            save_with_encoding(app, encoding, true, ui.input(|i| i.time));
+            ui.close();
+        }
+    }
+}
+
+/// Change the encoding of the document and save it
+///
+/// Nothing changes if the encoding cannot represent the whole text.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `encoding` - Encoding name (one of `ENCODINGS`)
+/// * `bom` - Start a UTF-8 file with a byte order mark
+/// * `now` - Current time in seconds
+fn save_with_encoding(app: &mut NodepatApp, encoding: &str, bom: bool, now: f64) {
+    if !crate::file_ops::can_encode(&app.editor_state.text, encoding) {
+        app.messages.error(format!(
+            "The document contains characters that cannot be saved in {encoding}."
+        ));
+        return;
+    }
+    app.file_state.encoding = encoding.to_string();
+    app.file_state.had_bom = bom;
+    handle_save(app, now);
+}
+
+/// Show the View > Language submenu
+///
+/// "Auto" picks the language by the file extension; the others override
+/// it for the current document.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_language_items(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let detected = Language::from_path(&app.file_state.file_path);
+    let auto = format!("Auto ({})", detected.display_name());
+    if ui.radio(app.language_override.is_none(), auto).clicked() {
+        app.language_override = None;
+        ui.close();
+    }
+    ui.separator();
+    for language in Language::all() {
+        let selected = app.language_override == Some(language);
+        if ui.radio(selected, language.display_name()).clicked() {
+            app.language_override = Some(language);
+            ui.close();
+        }
+    }
+}
+
+/// Show Tools menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_tools_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    ui.menu_button("Tools", |ui| {
+        if ui.button("Convert Files' Encoding...").clicked() {
+            app.show_convert_dialog = true;
+            ui.close();
+        }
+    });
+}
+
+/// Show the items of the View > Theme submenu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_theme_items(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let current = ThemePreset::from_name(&app.config.theme.preset);
+    let mut chosen = None;
+    for (name, preset) in std::iter::once(("Default", None))
+        .chain(ThemePreset::all().map(|preset| (preset.name(), Some(preset))))
+    {
+        if ui.radio(current == preset, name).clicked() {
+            chosen = Some(preset.map_or("", ThemePreset::name));
+        }
+    }
+    if let Some(preset) = chosen {
+        app.config.theme.preset = preset.to_string();
+        app.save_config_later();
+        ui.close();
+    }
+}
+
+/// Show the items of the View > Zoom submenu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_zoom_items(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let ctx = ui.ctx().clone();
+    for (name, action) in [
+        ("Zoom In", Action::ZoomIn),
+        ("Zoom Out", Action::ZoomOut),
+        ("Restore Default Zoom", Action::ResetZoom),
+    ] {
         if ui
-            .checkbox(&mut app.show_status_bar, "Status Bar")
+            .button(app.keymap.menu_label(&ctx, name, action))
             .clicked()
         {
-            app.config.show_status_bar = app.show_status_bar;
-            let _ = app.config.save();
+            run_action(app, action, ctx.input(|i| i.time));
             ui.close();
         }
-    });
+    }
+    ui.separator();
+    for preset in ZOOM_PRESETS {
+        let selected = (app.format_settings.zoom_factor - preset).abs() < 0.005;
+        let label = format!("{:.0}%", preset * 100.0);
+        if ui.radio(selected, label).clicked() {
+            app.set_zoom(preset);
+            ui.close();
+        }
+    }
 }
 
 /// Show Help menu
@@ -238,28 +1055,198 @@ fn show_help_menu(ui: &mut egui::Ui, app: &mut NodepatApp) {
 /// # Arguments
 /// * `app` - Application state
 fn handle_new_file(app: &mut NodepatApp) {
-    // TODO: Check if file needs saving
-    app.editor_state.text.clear();
-    app.editor_state.undo_history.clear();
-    app.editor_state.redo_history.clear();
-    app.file_state.file_path.clear();
-    app.file_state.is_modified = false;
+    app.request_action(PendingAction::NewFile);
 }
 
 /// Handle Save action
 ///
 /// # Arguments
 /// * `app` - Application state
-fn handle_save(app: &mut NodepatApp) {
-    if app.file_state.file_path.is_empty() {
+/// * `now` - Current time in seconds
+fn handle_save(app: &mut NodepatApp, now: f64) {
+    if let Some(viewer) = &mut app.viewer {
+        viewer.status = crate::viewer::READ_ONLY_HINT.to_string();
+    } else if app.file_state.file_path.is_empty() || app.is_read_only() {
         app.show_save_dialog = true;
+    } else if app.file_state.changed_on_disk().is_some() {
+        app.show_overwrite_dialog = true;
     } else {
-        let file_path = app.file_state.file_path.clone();
-        let content = app.editor_state.text.clone();
-        if let Err(e) = app.file_state.save_file(&file_path, &content) {
-            // Show error dialog
-            eprintln!("Save error: {e}");
-        }
+        save_to_current_path(app, now);
+    }
+}
+
+/// Save the document to its current file path
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `now` - Current time in seconds
+///
+/// # Returns
+/// True if the document was saved
+pub fn save_to_current_path(app: &mut NodepatApp, now: f64) -> bool {
+    trim_on_save(app, now);
+    let file_path = app.file_state.file_path.clone();
+    let content = app.editor_state.text.clone();
+    if let Err(e) = app.file_state.save_file(&file_path, &content) {
+        app.messages
+            .error(format!("{file_path} could not be saved.\n{e}"));
+        return false;
+    }
+    if let Some(warning) = app.file_state.backup_warning.take() {
+        app.messages.warning(warning);
+    }
+    app.remember_position();
+    true
+}
+
+/// Trim trailing whitespace before saving, if enabled in the settings
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `now` - Current time in seconds
+pub fn trim_on_save(app: &mut NodepatApp, now: f64) {
+    if app.config.trim_trailing_on_save {
+        handle_trim_trailing_whitespace(app, now, false);
+    }
+}
+
+/// Handle Trim Trailing Whitespace action
+///
+/// The number of changed lines is shown in the status bar.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `now` - Current time in seconds
+/// * `report_unchanged` - Also report when no line had trailing whitespace
+fn handle_trim_trailing_whitespace(app: &mut NodepatApp, now: f64, report_unchanged: bool) {
+    if !app.is_editable() {
+        return;
+    }
+    let notice = match app.editor_state.trim_trailing_whitespace() {
+        0 if report_unchanged => "No trailing whitespace found".to_string(),
+        0 => return,
+        1 => "Trimmed trailing whitespace on 1 line".to_string(),
+        changed => format!("Trimmed trailing whitespace on {changed} lines"),
+    };
+    app.file_state.update_modified(&app.editor_state.text);
+    app.file_autosave.set_notice(now, notice);
+}
+
+/// Handle Undo action
+///
+/// # Arguments
+/// * `app` - Application state
+fn handle_undo(app: &mut NodepatApp) {
+    if app.is_editable() && app.editor_state.undo() {
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Redo action
+///
+/// # Arguments
+/// * `app` - Application state
+fn handle_redo(app: &mut NodepatApp) {
+    if app.is_editable() && app.editor_state.redo() {
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Time/Date action
+///
+/// # Arguments
+/// * `app` - Application state
+fn handle_time_date(app: &mut NodepatApp) {
+    if app.is_editable() {
+        crate::editor::insert_time_date(&mut app.editor_state, &app.config.datetime_format);
+        app.file_state.is_modified = true;
+    }
+}
+
+/// Handle Duplicate action
+///
+/// Duplicates the current line, or the selection if there is one.
+///
+/// # Arguments
+/// * `app` - Application state
+fn handle_duplicate(app: &mut NodepatApp) {
+    if app.is_editable() {
+        app.editor_state.duplicate();
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Delete Line action
+///
+/// Deletes the current line, or all lines the selection touches.
+///
+/// # Arguments
+/// * `app` - Application state
+fn handle_delete_lines(app: &mut NodepatApp) {
+    if app.is_editable() {
+        app.editor_state.delete_lines();
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Convert Case actions
+///
+/// Converts the selection, or the whole document without a selection.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `conversion` - Case to convert to
+fn handle_convert_case(app: &mut NodepatApp, conversion: CaseConversion) {
+    if app.is_editable() {
+        app.editor_state.convert_case(conversion);
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Convert Tabs to Spaces and Convert Spaces to Tabs actions
+///
+/// Converts the selected lines, or the whole document without a selection.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `to_spaces` - Convert tabs to spaces instead of spaces to tabs
+fn handle_convert_indentation(app: &mut NodepatApp, to_spaces: bool) {
+    if app.is_editable() {
+        let tab_width = usize::from(app.format_settings.tab_width);
+        app.editor_state.transform_lines(|text| {
+            if to_spaces {
+                crate::lines::tabs_to_spaces(text, tab_width)
+            } else {
+                crate::lines::spaces_to_tabs(text, tab_width)
+            }
+        });
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Sort Lines actions
+///
+/// Sorts the selected lines, or the whole document without a selection.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `mode` - How to order the lines
+fn handle_sort_lines(app: &mut NodepatApp, mode: SortMode) {
+    if app.is_editable() {
+        app.editor_state
+            .transform_lines(|text| crate::lines::sort_lines(text, mode));
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Move Line Up and Move Line Down actions
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `up` - Move up instead of down
+fn handle_move_lines(app: &mut NodepatApp, up: bool) {
+    if app.is_editable() && app.editor_state.move_lines(up) {
+        app.file_state.update_modified(&app.editor_state.text);
     }
 }
 
@@ -268,33 +1255,78 @@ fn handle_save(app: &mut NodepatApp) {
 /// # Arguments
 /// * `app` - Application state
 /// * `ctx` - egui context for clipboard access
-fn handle_cut(app: &mut NodepatApp, _ctx: &egui::Context) {
-    // TextEdit handles cut internally via Ctrl+X
-    // We just mark as modified when cut happens
-    app.editor_state.save_undo_state();
-    app.file_state.is_modified = true;
+fn handle_cut(app: &mut NodepatApp, ctx: &egui::Context) {
+    if !app.is_editable() {
+        return;
+    }
+    let selected = app.editor_state.cut();
+    if !selected.is_empty() {
+        app.copy_text(ctx, selected);
+        app.file_state.update_modified(&app.editor_state.text);
+    }
 }
 
 /// Handle Copy action
 ///
 /// # Arguments
-/// * `_app` - Application state
-/// * `_ctx` - egui context (`TextEdit` handles copy internally)
-#[allow(clippy::missing_const_for_fn)] // Cannot be const: takes &mut
-fn handle_copy(_app: &mut NodepatApp, _ctx: &egui::Context) {
-    // TextEdit handles copy internally via Ctrl+C
+/// * `app` - Application state
+/// * `ctx` - egui context for clipboard access
+fn handle_copy(app: &mut NodepatApp, ctx: &egui::Context) {
+    // The viewer copies its selected lines
+    let selected = if app.viewer.is_some() {
+        app.viewer.as_mut().and_then(ViewerState::selected_text)
+    } else {
+        Some(app.editor_state.selected_text().to_string())
+    };
+    if let Some(text) = selected.filter(|text| !text.is_empty()) {
+        app.copy_text(ctx, text);
+    }
 }
 
 /// Handle Paste action
 ///
+/// Replaces the selection with the clipboard text.
+///
 /// # Arguments
 /// * `app` - Application state
-/// * `_ctx` - egui context (`TextEdit` handles paste internally)
-fn handle_paste(app: &mut NodepatApp, _ctx: &egui::Context) {
-    // TextEdit handles paste internally via Ctrl+V
-    // We just mark as modified when paste happens
-    app.editor_state.save_undo_state();
-    app.file_state.is_modified = true;
+fn handle_paste(app: &mut NodepatApp) {
+    if !app.is_editable() {
+        return;
+    }
+    let clipboard_text = app
+        .clipboard
+        .as_mut()
+        .and_then(egui_winit::clipboard::Clipboard::get)
+        .unwrap_or_default();
+    if !clipboard_text.is_empty() {
+        app.editor_state.insert_at_cursor(&clipboard_text);
+        app.file_state.update_modified(&app.editor_state.text);
+    }
+}
+
+/// Handle Swap with Clipboard action
+///
+/// Replaces the selection with the clipboard text and puts the selection
+/// on the clipboard, as one undoable edit. Without a selection this is a
+/// plain paste; with an empty clipboard it is a cut.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `ctx` - egui context for clipboard access
+fn handle_swap_with_clipboard(app: &mut NodepatApp, ctx: &egui::Context) {
+    if !app.is_editable() {
+        return;
+    }
+    let clipboard_text = app
+        .clipboard
+        .as_mut()
+        .and_then(egui_winit::clipboard::Clipboard::get)
+        .unwrap_or_default();
+    let selected = app.editor_state.insert_at_cursor(&clipboard_text);
+    app.file_state.update_modified(&app.editor_state.text);
+    if !selected.is_empty() {
+        app.copy_text(ctx, selected);
+    }
 }
 
 /// Handle Delete action
@@ -302,17 +1334,81 @@ fn handle_paste(app: &mut NodepatApp, _ctx: &egui::Context) {
 /// # Arguments
 /// * `app` - Application state
 fn handle_delete(app: &mut NodepatApp) {
-    // TextEdit handles delete internally
-    app.editor_state.save_undo_state();
-    app.file_state.is_modified = true;
+    if app.is_editable() && app.editor_state.delete() {
+        app.file_state.update_modified(&app.editor_state.text);
+    }
 }
 
 /// Handle Select All action
 ///
 /// # Arguments
-/// * `_app` - Application state
-#[allow(clippy::missing_const_for_fn)] // Cannot be const: takes &mut
-fn handle_select_all(_app: &mut NodepatApp) {
-    // TextEdit handles select all with Ctrl+A internally
-    // This function is kept for menu consistency
+/// * `app` - Application state
+fn handle_select_all(app: &mut NodepatApp) {
+    app.editor_state.select_all();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recent(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("file{i}.txt")).collect()
+    }
+
+    #[test]
+    fn test_recent_digit_mapping() {
+        let files = recent(12);
+        let entries = visible_recent_files(&files, 20, |_| true);
+        assert_eq!(entries.len(), 12);
+        assert_eq!(
+            recent_entry_for_digit(&entries, 1).map(String::as_str),
+            Some("file1.txt")
+        );
+        assert_eq!(
+            recent_entry_for_digit(&entries, 9).map(String::as_str),
+            Some("file9.txt")
+        );
+        assert_eq!(
+            recent_entry_for_digit(&entries, 0).map(String::as_str),
+            Some("file10.txt")
+        );
+        assert_eq!(recent_entry_for_digit(&entries, 10), None);
+    }
+
+    #[test]
+    fn test_visible_recent_files_honors_limit() {
+        let files = recent(12);
+        assert_eq!(visible_recent_files(&files, 3, |_| true).len(), 3);
+        assert!(visible_recent_files(&files, 0, |_| true).is_empty());
+    }
+
+    #[test]
+    fn test_recent_digit_skips_missing_files() {
+        let files = recent(4);
+        let entries = visible_recent_files(&files, 10, |path| path != "file2.txt");
+        // The second displayed entry is the third file
+        assert_eq!(
+            recent_entry_for_digit(&entries, 2).map(String::as_str),
+            Some("file3.txt")
+        );
+        assert_eq!(recent_entry_for_digit(&entries, 4), None);
+        assert_eq!(recent_entry_for_digit(&entries, 0), None);
+    }
+
+    #[test]
+    fn test_read_only_mode_blocks_edits() {
+        let mut app = NodepatApp::default();
+        app.file_state.file_path = "notes.txt".to_string();
+        app.editor_state.set_document("text".to_string());
+        app.read_only_mode = true;
+
+        run_action(&mut app, Action::TimeDate, 10.0);
+        assert_eq!(app.editor_state.text, "text");
+        assert!(app.edit_rejected(11.0));
+        assert!(!app.edit_rejected(10.0 + crate::autosave::NOTICE_SECS));
+
+        // Saving goes to Save As instead of overwriting the file
+        run_action(&mut app, Action::Save, 10.0);
+        assert!(app.show_save_dialog);
+    }
 }
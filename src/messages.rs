@@ -0,0 +1,140 @@
+//! Messages for the message dialog
+//!
+//! Errors, warnings, and notifications such as search results are queued
+//! here and shown one at a time, so failures reach the user instead of
+//! stderr.
+
+use std::collections::VecDeque;
+
+/// How a message is presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Notification, such as a search result
+    Info,
+    /// Something worked, but not completely
+    Warning,
+    /// Something failed
+    Error,
+}
+
+/// A message waiting to be shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// Window title
+    pub title: String,
+    /// Message text
+    pub text: String,
+    /// How the message is presented
+    pub kind: MessageKind,
+}
+
+/// Queue of messages shown one at a time
+#[derive(Debug, Default)]
+pub struct MessageQueue {
+    /// Pending messages, oldest first
+    queue: VecDeque<Message>,
+}
+
+impl MessageQueue {
+    /// Queue a message
+    ///
+    /// A message equal to the last queued one is dropped, so repeating
+    /// an action does not stack identical dialogs.
+    ///
+    /// # Arguments
+    /// * `title` - Window title
+    /// * `text` - Message text
+    /// * `kind` - How the message is presented
+    pub fn push(&mut self, title: &str, text: impl Into<String>, kind: MessageKind) {
+        let message = Message {
+            title: title.to_string(),
+            text: text.into(),
+            kind,
+        };
+        if self.queue.back() != Some(&message) {
+            self.queue.push_back(message);
+        }
+    }
+
+    /// Queue a notification
+    ///
+    /// # Arguments
+    /// * `text` - Message text
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push("Nodepat", text, MessageKind::Info);
+    }
+
+    /// Queue a warning
+    ///
+    /// # Arguments
+    /// * `text` - Message text
+    pub fn warning(&mut self, text: impl Into<String>) {
+        self.push("Nodepat", text, MessageKind::Warning);
+    }
+
+    /// Queue an error
+    ///
+    /// # Arguments
+    /// * `text` - Message text
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push("Error", text, MessageKind::Error);
+    }
+
+    /// Queue an error if saving the settings failed
+    ///
+    /// # Arguments
+    /// * `result` - Result of `Config::save`
+    pub fn report_config_save(&mut self, result: Result<(), String>) {
+        if let Err(e) = result {
+            self.error(format!("Settings could not be saved.\n{e}"));
+        }
+    }
+
+    /// Get the message to show
+    ///
+    /// # Returns
+    /// The oldest pending message, if any
+    #[must_use]
+    pub fn front(&self) -> Option<&Message> {
+        self.queue.front()
+    }
+
+    /// Dismiss the message being shown
+    pub fn pop(&mut self) {
+        self.queue.pop_front();
+    }
+
+    /// Check whether no messages are pending
+    ///
+    /// # Returns
+    /// True if there is nothing to show
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_show_in_order_without_repeats() {
+        let mut messages = MessageQueue::default();
+        messages.info("Cannot find \"x\"");
+        messages.info("Cannot find \"x\"");
+        messages.error("Disk full");
+        messages.report_config_save(Ok(()));
+
+        let first = messages.front().expect("Expected a message");
+        assert_eq!(first.kind, MessageKind::Info);
+        assert_eq!(first.title, "Nodepat");
+        messages.pop();
+        assert_eq!(messages.front().map(|m| m.text.as_str()), Some("Disk full"));
+        messages.pop();
+        assert!(messages.is_empty());
+
+        messages.report_config_save(Err("Failed to write config".to_string()));
+        assert_eq!(messages.front().map(|m| m.kind), Some(MessageKind::Error));
+    }
+}
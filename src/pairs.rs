@@ -0,0 +1,178 @@
+//! Auto-closing of brackets and quotes
+//!
+//! This module decides how typed brackets and quotes change the text when
+//! auto-closing is on: an opening character gets its closing partner, a
+//! typed closing character steps over the one already there, a selection
+//! is wrapped, and Backspace between an empty pair deletes both. The
+//! functions work on character indices and leave the text untouched, so
+//! the editor applies their result as one edit.
+
+/// Opening and closing characters that are auto-closed
+const PAIRS: [(char, char); 5] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+
+/// A change to make instead of the plain keystroke
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairEdit {
+    /// First character index to replace
+    pub start: usize,
+    /// Character index after the replaced range
+    pub end: usize,
+    /// Text to put in place of the range
+    pub replacement: String,
+    /// Selection afterwards as (start, end) character indices
+    pub selection: (usize, usize),
+}
+
+/// Get the closing partner of an opening character
+///
+/// # Arguments
+/// * `open` - Typed character
+///
+/// # Returns
+/// The closing character, or None if `open` does not start a pair
+fn closing_of(open: char) -> Option<char> {
+    PAIRS
+        .iter()
+        .find(|(opening, _)| *opening == open)
+        .map(|&(_, closing)| closing)
+}
+
+/// Decide what typing a character does
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `selection` - Selection as sorted (start, end) character indices
+/// * `typed` - Typed character
+///
+/// # Returns
+/// The edit to make instead of inserting `typed`, or None to insert it
+/// normally
+#[must_use]
+pub fn typed_edit(text: &str, selection: (usize, usize), typed: char) -> Option<PairEdit> {
+    let (start, end) = selection;
+    let before = start.checked_sub(1).and_then(|i| text.chars().nth(i));
+    let after = text.chars().nth(end);
+    let is_closing = PAIRS.iter().any(|&(_, closing)| closing == typed);
+
+    // Step over the closing character that is already there
+    if start == end && is_closing && after == Some(typed) {
+        return Some(PairEdit {
+            start,
+            end,
+            replacement: String::new(),
+            selection: (start + 1, start + 1),
+        });
+    }
+    let closing = closing_of(typed)?;
+    if start != end {
+        let selected: String = text.chars().skip(start).take(end - start).collect();
+        return Some(PairEdit {
+            start,
+            end,
+            replacement: format!("{typed}{selected}{closing}"),
+            selection: (start + 1, end + 1),
+        });
+    }
+    // Only close before whitespace, punctuation, or the end of the text,
+    // and not for a quote typed as an apostrophe, e.g. in "don't"
+    let quote = typed == closing;
+    let closes_after = after.is_none_or(|c| !c.is_alphanumeric() && c != '_');
+    let closes_before = !quote || before.is_none_or(|c| !c.is_alphanumeric() && c != closing);
+    (closes_after && closes_before).then(|| PairEdit {
+        start,
+        end,
+        replacement: format!("{typed}{closing}"),
+        selection: (start + 1, start + 1),
+    })
+}
+
+/// Decide what Backspace does
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `selection` - Selection as sorted (start, end) character indices
+///
+/// # Returns
+/// The edit deleting an empty pair around the cursor, or None to delete
+/// normally
+#[must_use]
+pub fn backspace_edit(text: &str, selection: (usize, usize)) -> Option<PairEdit> {
+    let (start, end) = selection;
+    if start != end || start == 0 {
+        return None;
+    }
+    let mut chars = text.chars().skip(start - 1);
+    let open = chars.next()?;
+    let close = chars.next()?;
+    (closing_of(open) == Some(close)).then(|| PairEdit {
+        start: start - 1,
+        end: start + 1,
+        replacement: String::new(),
+        selection: (start - 1, start - 1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply an edit and mark the cursor or selection with | or [ ]
+    fn apply(text: &str, edit: &PairEdit) -> String {
+        let mut chars: Vec<char> = text.chars().collect();
+        chars.splice(edit.start..edit.end, edit.replacement.chars());
+        let (start, end) = edit.selection;
+        if start == end {
+            chars.insert(start, '|');
+        } else {
+            chars.insert(end, ']');
+            chars.insert(start, '[');
+        }
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn test_opening_characters_are_closed() {
+        for (open, expected) in [('(', "f(|)"), ('[', "f[|]"), ('{', "f{|}")] {
+            let edit = typed_edit("f", (1, 1), open).expect("Expected an edit");
+            assert_eq!(apply("f", &edit), expected);
+        }
+        let edit = typed_edit("x = ", (4, 4), '"').expect("Expected an edit");
+        assert_eq!(apply("x = ", &edit), "x = \"|\"");
+        let edit = typed_edit("é )", (1, 1), '(').expect("Expected an edit");
+        assert_eq!(apply("é )", &edit), "é(|) )");
+    }
+
+    #[test]
+    fn test_no_closing_before_words_or_after_letters_for_quotes() {
+        assert_eq!(typed_edit("word", (0, 0), '('), None);
+        assert_eq!(typed_edit("don", (3, 3), '\''), None);
+        assert_eq!(typed_edit("a", (1, 1), 'x'), None);
+        assert_eq!(typed_edit("a", (1, 1), ')'), None);
+    }
+
+    #[test]
+    fn test_closing_character_steps_over() {
+        let edit = typed_edit("(ab)", (3, 3), ')').expect("Expected an edit");
+        assert_eq!(apply("(ab)", &edit), "(ab)|");
+        let edit = typed_edit("\"\"", (1, 1), '"').expect("Expected an edit");
+        assert_eq!(apply("\"\"", &edit), "\"\"|");
+    }
+
+    #[test]
+    fn test_selection_is_wrapped() {
+        let edit = typed_edit("say hé there", (4, 6), '(').expect("Expected an edit");
+        assert_eq!(apply("say hé there", &edit), "say ([hé]) there");
+        let edit = typed_edit("it's", (0, 4), '\'').expect("Expected an edit");
+        assert_eq!(apply("it's", &edit), "'[it's]'");
+    }
+
+    #[test]
+    fn test_backspace_deletes_empty_pair() {
+        let edit = backspace_edit("f()", (2, 2)).expect("Expected an edit");
+        assert_eq!(apply("f()", &edit), "f|");
+        assert_eq!(backspace_edit("f(x)", (2, 2)), None);
+        assert_eq!(backspace_edit("()", (0, 0)), None);
+        assert_eq!(backspace_edit("()", (0, 2)), None);
+        assert_eq!(backspace_edit("(", (1, 1)), None);
+    }
+}
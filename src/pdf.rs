@@ -0,0 +1,327 @@
+//! PDF export (File > Export > PDF)
+//!
+//! This module writes a document as a text-only PDF. The text is set in
+//! Courier, one of the standard PDF fonts every viewer provides, so no font
+//! file has to be embedded. Courier is encoded with `WinAnsiEncoding`, which
+//! covers the printable Latin-1 characters; other characters are replaced
+//! with '?' and counted so the export can warn about them.
+
+use crate::file_ops::write_atomic;
+use crate::lines::tabs_to_spaces;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Width of a Courier character, as a fraction of the font size
+const COURIER_ADVANCE: f32 = 0.6;
+
+/// Columns between tab stops in exported text
+const PDF_TAB_WIDTH: usize = 4;
+
+/// Page size, margins, and font size of an exported document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLayout {
+    /// Page width in points (1/72 inch)
+    pub width: f32,
+    /// Page height in points
+    pub height: f32,
+    /// Margin on every side in points
+    pub margin: f32,
+    /// Font size in points
+    pub font_size: f32,
+}
+
+impl Default for PageLayout {
+    /// A4 paper with one-inch margins and 10 point text
+    fn default() -> Self {
+        Self {
+            width: 595.0,
+            height: 842.0,
+            margin: 72.0,
+            font_size: 10.0,
+        }
+    }
+}
+
+impl PageLayout {
+    /// Get the distance between baselines
+    ///
+    /// # Returns
+    /// Line height in points
+    #[must_use]
+    pub fn line_height(&self) -> f32 {
+        self.font_size * 1.2
+    }
+
+    /// Get the number of characters that fit on a line
+    ///
+    /// # Returns
+    /// Characters per line, at least 1
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn columns(&self) -> usize {
+        let text_width = 2.0f32.mul_add(-self.margin, self.width);
+        ((text_width / (self.font_size * COURIER_ADVANCE)) as usize).max(1)
+    }
+
+    /// Get the number of lines that fit on a page
+    ///
+    /// # Returns
+    /// Lines per page, at least 1
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn rows(&self) -> usize {
+        let text_height = 2.0f32.mul_add(-self.margin, self.height);
+        ((text_height / self.line_height()) as usize).max(1)
+    }
+}
+
+/// Split a document into pages of lines
+///
+/// Tabs are expanded and lines longer than the page is wide wrap onto the
+/// next line. An empty document gives one empty page.
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `layout` - Page layout
+///
+/// # Returns
+/// Lines of each page
+#[must_use]
+pub fn paginate(text: &str, layout: &PageLayout) -> Vec<Vec<String>> {
+    let columns = layout.columns();
+    let text = tabs_to_spaces(text, PDF_TAB_WIDTH);
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(chars.chunks(columns).map(|chunk| chunk.iter().collect()));
+    }
+    let mut pages: Vec<Vec<String>> = lines
+        .chunks(layout.rows())
+        .map(<[String]>::to_vec)
+        .collect();
+    if pages.is_empty() {
+        pages.push(Vec::new());
+    }
+    pages
+}
+
+/// Encode a line as a PDF string literal for `WinAnsiEncoding`
+///
+/// # Arguments
+/// * `line` - Line text without line breaks
+///
+/// # Returns
+/// Tuple of (string literal with parentheses, number of replaced characters)
+fn pdf_string(line: &str) -> (Vec<u8>, usize) {
+    let mut bytes = vec![b'('];
+    let mut replaced = 0;
+    for c in line.chars() {
+        match u8::try_from(u32::from(c)) {
+            Ok(byte @ (b'(' | b')' | b'\\')) => bytes.extend([b'\\', byte]),
+            Ok(byte @ (0x20..=0x7E | 0xA0..=0xFF)) => bytes.push(byte),
+            _ => {
+                bytes.push(b'?');
+                replaced += 1;
+            }
+        }
+    }
+    bytes.push(b')');
+    (bytes, replaced)
+}
+
+/// Build the content stream that draws the lines of one page
+///
+/// # Arguments
+/// * `lines` - Lines of the page
+/// * `layout` - Page layout
+///
+/// # Returns
+/// Tuple of (content stream, number of replaced characters)
+fn page_content(lines: &[String], layout: &PageLayout) -> (Vec<u8>, usize) {
+    let top = layout.height - layout.margin - layout.font_size;
+    let mut content = format!(
+        "BT\n/F1 {} Tf\n{} TL\n{} {top} Td\n",
+        layout.font_size,
+        layout.line_height(),
+        layout.margin
+    )
+    .into_bytes();
+    let mut replaced = 0;
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            content.extend(b"T*\n");
+        }
+        let (string, count) = pdf_string(line);
+        replaced += count;
+        content.extend(string);
+        content.extend(b" Tj\n");
+    }
+    content.extend(b"ET\n");
+    (content, replaced)
+}
+
+/// Render a document as a PDF file
+///
+/// # Arguments
+/// * `text` - Document text
+/// * `layout` - Page layout
+///
+/// # Returns
+/// Tuple of (PDF bytes, number of pages, number of characters that could
+/// not be represented and were replaced with '?')
+#[must_use]
+pub fn render_pdf(text: &str, layout: &PageLayout) -> (Vec<u8>, usize, usize) {
+    let pages = paginate(text, layout);
+    // Objects 1-3 are the catalog, the page tree, and the font; each page
+    // adds a page object and its content stream
+    let page_ids: Vec<usize> = (0..pages.len()).map(|index| 4 + 2 * index).collect();
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    let mut kids = String::new();
+    for id in &page_ids {
+        let _ = write!(kids, "{id} 0 R ");
+    }
+    objects.push(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.trim_end(),
+            pages.len()
+        )
+        .into_bytes(),
+    );
+    objects.push(
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec(),
+    );
+    let mut replaced = 0;
+    for (lines, id) in pages.iter().zip(&page_ids) {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                layout.width,
+                layout.height,
+                id + 1
+            )
+            .into_bytes(),
+        );
+        let (content, count) = page_content(lines, layout);
+        replaced += count;
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend(b"endstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", index + 1).into_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(table, "{offset:010} 00000 n ");
+    }
+    let _ = write!(
+        table,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    pdf.extend(table.into_bytes());
+    (pdf, pages.len(), replaced)
+}
+
+/// Export a document to a PDF file
+///
+/// # Arguments
+/// * `path` - File to write
+/// * `text` - Document text
+/// * `layout` - Page layout
+///
+/// # Returns
+/// Tuple of (number of pages, number of replaced characters), or error
+/// message
+pub fn export_pdf(path: &Path, text: &str, layout: &PageLayout) -> Result<(usize, usize), String> {
+    let (pdf, pages, replaced) = render_pdf(text, layout);
+    write_atomic(path, &pdf)?;
+    Ok((pages, replaced))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_fits_a4() {
+        let layout = PageLayout::default();
+        assert_eq!(layout.columns(), 75);
+        assert_eq!(layout.rows(), 58);
+    }
+
+    #[test]
+    fn test_paginate_wraps_and_splits_pages() {
+        let layout = PageLayout::default();
+        let long_line = "x".repeat(layout.columns() + 5);
+        let pages = paginate(&format!("{long_line}\n\n\tend"), &layout);
+        assert_eq!(
+            pages,
+            vec![vec![
+                "x".repeat(layout.columns()),
+                "xxxxx".to_string(),
+                String::new(),
+                "    end".to_string(),
+            ]]
+        );
+
+        let text = "line\n".repeat(layout.rows() * 2 + 1);
+        let pages = paginate(&text, &layout);
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[2], vec!["line".to_string()]);
+
+        assert_eq!(paginate("", &layout), vec![Vec::<String>::new()]);
+    }
+
+    #[test]
+    fn test_pdf_string_escapes_and_replaces() {
+        assert_eq!(pdf_string(r"a(b)\c"), (br"(a\(b\)\\c)".to_vec(), 0));
+        assert_eq!(pdf_string("café"), (b"(caf\xE9)".to_vec(), 0));
+        assert_eq!(pdf_string("5€ 日本"), (b"(5? ??)".to_vec(), 3));
+    }
+
+    #[test]
+    fn test_render_pdf_structure() {
+        let layout = PageLayout::default();
+        let text = "Grüße 日本\n".repeat(layout.rows() + 1);
+        let (pdf, pages, replaced) = render_pdf(&text, &layout);
+        assert_eq!(pages, 2);
+        assert_eq!(replaced, 2 * (layout.rows() + 1));
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        // The cross-reference table points at each object
+        let marker = b"startxref\n";
+        let start = pdf
+            .windows(marker.len())
+            .rposition(|window| window == marker)
+            .expect("No startxref")
+            + marker.len();
+        let trailer = String::from_utf8_lossy(&pdf[start..]).into_owned();
+        let xref: usize = trailer
+            .lines()
+            .next()
+            .and_then(|line| line.parse().ok())
+            .expect("Invalid startxref");
+        assert!(pdf[xref..].starts_with(b"xref\n0 8\n"));
+        let table = String::from_utf8_lossy(&pdf[xref..]).into_owned();
+        for (index, entry) in table.lines().skip(3).take(7).enumerate() {
+            let offset: usize = entry[..10].parse().expect("Invalid offset");
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", index + 1).as_bytes()));
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Window placement across monitors
+//!
+//! This module restores the saved window position only when enough of the
+//! window would be visible on a connected monitor. Otherwise the window is
+//! recentered, preferring the monitor it was last on. Monitors are probed
+//! from the winit event loop because egui only reports the current one.
+
+use std::sync::{Arc, Mutex};
+use winit::application::ApplicationHandler;
+use winit::event_loop::ActiveEventLoop;
+
+/// Width and height that must be visible on a monitor to keep a position
+pub const MIN_VISIBLE: f32 = 100.0;
+
+/// Rectangle on the desktop in logical points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenRect {
+    /// Left edge
+    pub x: f32,
+    /// Top edge
+    pub y: f32,
+    /// Width
+    pub width: f32,
+    /// Height
+    pub height: f32,
+}
+
+impl ScreenRect {
+    /// Get the overlapping width and height of two rectangles
+    ///
+    /// # Arguments
+    /// * `other` - Rectangle to intersect with
+    ///
+    /// # Returns
+    /// Tuple of (width, height), zero when they do not overlap
+    fn overlap(&self, other: &Self) -> (f32, f32) {
+        let width = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let height = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+        (width.max(0.0), height.max(0.0))
+    }
+}
+
+/// A connected monitor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    /// Monitor name reported by the system
+    pub name: Option<String>,
+    /// Monitor area in logical points
+    pub area: ScreenRect,
+}
+
+/// Monitors shared between the event loop and the app, primary first
+pub type SharedMonitors = Arc<Mutex<Vec<Monitor>>>;
+
+/// Find the monitor showing the largest part of a window
+///
+/// # Arguments
+/// * `window` - Window rectangle
+/// * `monitors` - Connected monitors
+///
+/// # Returns
+/// Index of the monitor, or None if the window is on no monitor
+#[must_use]
+pub fn monitor_for(window: &ScreenRect, monitors: &[Monitor]) -> Option<usize> {
+    monitors
+        .iter()
+        .enumerate()
+        .map(|(idx, monitor)| {
+            let (width, height) = window.overlap(&monitor.area);
+            (idx, width * height)
+        })
+        .filter(|&(_, area)| area > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(idx, _)| idx)
+}
+
+/// Validate a saved window rectangle against the connected monitors
+///
+/// The rectangle is kept if at least `MIN_VISIBLE` x `MIN_VISIBLE` points
+/// of it are on one monitor. Otherwise it is centered on the saved monitor
+/// if it is still connected, or on the primary monitor, with its size
+/// clamped to fit.
+///
+/// # Arguments
+/// * `saved` - Saved window rectangle
+/// * `saved_monitor` - Name of the monitor the window was on
+/// * `monitors` - Connected monitors, primary first
+///
+/// # Returns
+/// The rectangle to restore
+#[must_use]
+pub fn validate_placement(
+    saved: ScreenRect,
+    saved_monitor: Option<&str>,
+    monitors: &[Monitor],
+) -> ScreenRect {
+    let visible = monitors.iter().any(|monitor| {
+        let (width, height) = saved.overlap(&monitor.area);
+        width >= MIN_VISIBLE.min(saved.width) && height >= MIN_VISIBLE.min(saved.height)
+    });
+    if visible {
+        return saved;
+    }
+    let target = saved_monitor
+        .and_then(|name| {
+            monitors
+                .iter()
+                .find(|monitor| monitor.name.as_deref() == Some(name))
+        })
+        .or_else(|| monitors.first());
+    let Some(target) = target else {
+        return saved;
+    };
+    let area = target.area;
+    let width = saved.width.min(area.width);
+    let height = saved.height.min(area.height);
+    ScreenRect {
+        x: area.x + (area.width - width) / 2.0,
+        y: area.y + (area.height - height) / 2.0,
+        width,
+        height,
+    }
+}
+
+/// Event loop handler that records the connected monitors
+///
+/// Wraps the eframe application and forwards every event to it.
+pub struct MonitorProbe<A> {
+    /// Wrapped eframe application
+    app: A,
+    /// Monitors seen on the last probe
+    monitors: SharedMonitors,
+}
+
+impl<A> MonitorProbe<A> {
+    /// Wrap an application
+    ///
+    /// # Arguments
+    /// * `app` - eframe application
+    /// * `monitors` - Monitor list to keep up to date
+    ///
+    /// # Returns
+    /// The wrapping handler
+    pub const fn new(app: A, monitors: SharedMonitors) -> Self {
+        Self { app, monitors }
+    }
+
+    /// Refresh the monitor list
+    ///
+    /// # Arguments
+    /// * `event_loop` - Active event loop
+    fn probe(&self, event_loop: &ActiveEventLoop) {
+        let primary = event_loop.primary_monitor();
+        let mut monitors: Vec<(bool, Monitor)> = event_loop
+            .available_monitors()
+            .map(|handle| {
+                let scale = handle.scale_factor();
+                let position = handle.position().to_logical::<f32>(scale);
+                let size = handle.size().to_logical::<f32>(scale);
+                let monitor = Monitor {
+                    name: handle.name(),
+                    area: ScreenRect {
+                        x: position.x,
+                        y: position.y,
+                        width: size.width,
+                        height: size.height,
+                    },
+                };
+                (primary.as_ref() == Some(&handle), monitor)
+            })
+            .collect();
+        // Primary monitor first
+        monitors.sort_by_key(|(is_primary, _)| !is_primary);
+        if let Ok(mut shared) = self.monitors.lock() {
+            *shared = monitors.into_iter().map(|(_, monitor)| monitor).collect();
+        }
+    }
+}
+
+impl<T: 'static, A: ApplicationHandler<T>> ApplicationHandler<T> for MonitorProbe<A> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.probe(event_loop);
+        self.app.resumed(event_loop);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
+    ) {
+        // Monitors may have been connected or removed since the last probe
+        if matches!(
+            event,
+            winit::event::WindowEvent::Moved(_)
+                | winit::event::WindowEvent::ScaleFactorChanged { .. }
+        ) {
+            self.probe(event_loop);
+        }
+        self.app.window_event(event_loop, window_id, event);
+    }
+
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
+        self.app.new_events(event_loop, cause);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: T) {
+        self.app.user_event(event_loop, event);
+    }
+
+    fn device_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        device_id: winit::event::DeviceId,
+        event: winit::event::DeviceEvent,
+    ) {
+        self.app.device_event(event_loop, device_id, event);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.about_to_wait(event_loop);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.suspended(event_loop);
+    }
+
+    fn exiting(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.exiting(event_loop);
+    }
+
+    fn memory_warning(&mut self, event_loop: &ActiveEventLoop) {
+        self.app.memory_warning(event_loop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> ScreenRect {
+        ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn monitors() -> Vec<Monitor> {
+        vec![
+            Monitor {
+                name: Some("laptop".to_string()),
+                area: rect(0.0, 0.0, 1920.0, 1080.0),
+            },
+            Monitor {
+                name: Some("external".to_string()),
+                area: rect(1920.0, 0.0, 2560.0, 1440.0),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_visible_window_is_kept() {
+        let saved = rect(2000.0, 100.0, 640.0, 480.0);
+        assert_eq!(
+            validate_placement(saved, Some("external"), &monitors()),
+            saved
+        );
+        assert_eq!(monitor_for(&saved, &monitors()), Some(1));
+    }
+
+    #[test]
+    fn test_off_screen_window_is_recentered_on_saved_monitor() {
+        let saved = rect(5000.0, 3000.0, 640.0, 480.0);
+        let placed = validate_placement(saved, Some("external"), &monitors());
+        assert_eq!(placed, rect(2880.0, 480.0, 640.0, 480.0));
+        // An unknown monitor falls back to the primary one
+        let placed = validate_placement(saved, Some("projector"), &monitors());
+        assert_eq!(placed, rect(640.0, 300.0, 640.0, 480.0));
+        assert_eq!(monitor_for(&saved, &monitors()), None);
+    }
+
+    #[test]
+    fn test_partially_visible_window() {
+        // Only 50 points of the window are on the laptop monitor
+        let saved = rect(1870.0, 2000.0, 640.0, 480.0);
+        let laptop = &monitors()[..1];
+        let placed = validate_placement(saved, None, laptop);
+        assert_eq!(placed, rect(640.0, 300.0, 640.0, 480.0));
+        // 100 x 100 points visible is enough to keep it
+        let saved = rect(1820.0, 980.0, 640.0, 480.0);
+        assert_eq!(validate_placement(saved, None, laptop), saved);
+    }
+
+    #[test]
+    fn test_window_larger_than_shrunk_monitor_is_clamped() {
+        let saved = rect(3000.0, 100.0, 2400.0, 1300.0);
+        let laptop = vec![Monitor {
+            name: None,
+            area: rect(0.0, 0.0, 1366.0, 768.0),
+        }];
+        assert_eq!(
+            validate_placement(saved, Some("external"), &laptop),
+            rect(0.0, 0.0, 1366.0, 768.0)
+        );
+        // No monitor information keeps the saved rectangle
+        assert_eq!(validate_placement(saved, None, &[]), saved);
+    }
+}
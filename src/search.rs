@@ -4,9 +4,15 @@
 //! dialogs, search logic, and text replacement.
 
 use crate::app::NodepatApp;
+use crate::case::{CaseConversion, convert_case};
+use crate::editor::{byte_to_char, char_to_byte, sanitize_offset};
+use regex::{Captures, NoExpand, Regex, RegexBuilder, Replacer};
+use std::borrow::Cow;
+use std::ops::Range;
 
 /// Search state including find/replace text and options
 #[derive(Default)]
+#[allow(clippy::struct_excessive_bools)] // Independent search options
 pub struct SearchState {
     /// Text to find
     pub find_text: String,
@@ -16,160 +22,866 @@ pub struct SearchState {
     pub case_sensitive: bool,
     /// Search direction (true = down, false = up)
     pub search_down: bool,
-    /// Current search position
-    pub search_position: usize,
+    /// Continue from the other end of the document when no match is left
+    pub wrap_around: bool,
+    /// Treat the find text as a regular expression
+    pub use_regex: bool,
+    /// Only match whole words
+    pub whole_word: bool,
+    /// Give replacements the casing of the text they replace
+    pub preserve_case: bool,
+    /// Interpret \n, \t, \r and \\ in the find and replacement text
+    pub extended: bool,
+    /// Result of the last search, shown in the search dialogs
+    pub status: String,
 }
 
-impl SearchState {}
+impl SearchState {
+    /// Compile the find text as a regular expression
+    ///
+    /// `^` and `$` match at the start and end of every line. With whole
+    /// word matching, matches must not touch a word character on either side.
+    /// Without regular expressions the find text is matched literally.
+    ///
+    /// # Returns
+    /// Result containing the compiled pattern or error message
+    pub fn regex(&self) -> Result<Regex, String> {
+        let find_text = if self.use_regex {
+            // Validate on its own so errors point into the user's pattern
+            RegexBuilder::new(&self.find_text)
+                .build()
+                .map_err(|e| format!("Invalid regular expression: {e}"))?;
+            self.find_text.clone()
+        } else {
+            regex::escape(&self.needle())
+        };
+        let pattern = if self.whole_word {
+            format!(r"\b{{start-half}}(?:{find_text})\b{{end-half}}")
+        } else {
+            find_text
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .multi_line(true)
+            .crlf(true)
+            .build()
+            .map_err(|e| format!("Invalid regular expression: {e}"))
+    }
 
-/// Find next occurrence of search text
+    /// Get the text to find, with escapes interpreted in Extended mode
+    ///
+    /// Regular expressions understand the escapes themselves, so their
+    /// find text is used as typed.
+    ///
+    /// # Returns
+    /// The literal text to find
+    #[must_use]
+    pub fn needle(&self) -> Cow<'_, str> {
+        if self.extended && !self.use_regex {
+            Cow::Owned(unescape(&self.find_text))
+        } else {
+            Cow::Borrowed(&self.find_text)
+        }
+    }
+
+    /// Get the replacement text, with escapes interpreted in Extended mode
+    ///
+    /// # Returns
+    /// The text to insert for each match
+    #[must_use]
+    pub fn replacement(&self) -> Cow<'_, str> {
+        if self.extended {
+            Cow::Owned(unescape(&self.replace_text))
+        } else {
+            Cow::Borrowed(&self.replace_text)
+        }
+    }
+
+    /// Get the error of an invalid regular expression
+    ///
+    /// # Returns
+    /// The error message, or None if the pattern is valid or regular
+    /// expressions are off
+    #[must_use]
+    pub fn pattern_error(&self) -> Option<String> {
+        if self.use_regex && !self.find_text.is_empty() {
+            self.regex().err()
+        } else {
+            None
+        }
+    }
+
+    /// Check whether replacements take the casing of the matches
+    ///
+    /// # Returns
+    /// True for literal, case-insensitive replacing with Preserve case on
+    #[must_use]
+    pub const fn transfers_case(&self) -> bool {
+        self.preserve_case && !self.case_sensitive && !self.use_regex
+    }
+
+    /// Check whether searching goes through `regex`
+    ///
+    /// Ignoring case uses the regular expression engine too, which compares
+    /// characters caselessly and reports offsets into the original text.
+    /// Lowercasing the text instead would change the length of characters
+    /// like 'İ' and shift every offset after them.
+    ///
+    /// # Returns
+    /// True for regular expressions and case-insensitive searches
+    #[must_use]
+    pub const fn uses_regex(&self) -> bool {
+        self.use_regex || !self.case_sensitive
+    }
+}
+
+/// Interpret backslash escapes for Extended search mode
+///
+/// `\n`, `\r` and `\t` become a line feed, carriage return and tab, and
+/// `\\` a single backslash. Other backslashes are kept as typed.
 ///
 /// # Arguments
-/// * `app` - Application state
+/// * `text` - Text as typed into a search field
 ///
 /// # Returns
-/// True if match found, false otherwise
-pub fn find_next(app: &mut NodepatApp) -> bool {
-    if app.search_state.find_text.is_empty() {
-        return false;
+/// Text with the escapes replaced
+#[must_use]
+pub fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            // A trailing backslash is kept as typed, like an escaped one
+            Some('\\') | None => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+        }
     }
+    result
+}
 
-    let text = if app.search_state.case_sensitive {
-        app.editor_state.text.clone()
-    } else {
-        app.editor_state.text.to_lowercase()
+/// Write text as Extended search mode escapes, undoing `unescape`
+///
+/// # Arguments
+/// * `text` - Text to put into a single-line search field
+///
+/// # Returns
+/// Text with line breaks, tabs and backslashes escaped
+#[must_use]
+pub fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\\' => result.push_str("\\\\"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// State of the incremental search bar
+#[derive(Debug, Default)]
+pub struct IncrementalSearch {
+    /// Selection when the search started, restored by Escape
+    pub origin: (usize, usize),
+    /// The bar was just opened; focus its text field once
+    pub just_opened: bool,
+    /// Byte ranges of the matches in the editor text, updated every frame
+    pub matches: Vec<Range<usize>>,
+}
+
+impl IncrementalSearch {
+    /// Start a search from a selection
+    ///
+    /// # Arguments
+    /// * `origin` - Selection to go back to when the search is cancelled
+    ///
+    /// # Returns
+    /// Search bar state that focuses its field on the next frame
+    #[must_use]
+    pub const fn new(origin: (usize, usize)) -> Self {
+        Self {
+            origin,
+            just_opened: true,
+            matches: Vec::new(),
+        }
+    }
+
+    /// Get the position of the selected match among all matches
+    ///
+    /// # Arguments
+    /// * `text` - Editor text
+    /// * `selection` - Selection as (start, end) character indices
+    ///
+    /// # Returns
+    /// Index of the match that is selected exactly, if any
+    #[must_use]
+    pub fn current_match(&self, text: &str, selection: (usize, usize)) -> Option<usize> {
+        let selected = char_to_byte(text, selection.0)..char_to_byte(text, selection.1);
+        self.matches
+            .binary_search_by_key(&selected.start, |range| range.start)
+            .ok()
+            .filter(|&index| self.matches[index] == selected)
+    }
+
+    /// Describe the match position, e.g. "3 of 17"
+    ///
+    /// # Arguments
+    /// * `text` - Editor text
+    /// * `selection` - Selection as (start, end) character indices
+    ///
+    /// # Returns
+    /// The position, the match count without a selected match, or
+    /// "No matches"
+    #[must_use]
+    pub fn summary(&self, text: &str, selection: (usize, usize)) -> String {
+        let count = self.matches.len();
+        match self.current_match(text, selection) {
+            _ if count == 0 => "No matches".to_string(),
+            Some(index) => format!("{} of {count}", index + 1),
+            None if count == 1 => "1 match".to_string(),
+            None => format!("{count} matches"),
+        }
+    }
+}
+
+/// Check whether a match is a whole word
+///
+/// # Arguments
+/// * `text` - Text containing the match
+/// * `start` - Byte offset where the match starts
+/// * `end` - Byte offset where the match ends
+///
+/// # Returns
+/// True if the characters before and after the match are not letters,
+/// digits or underscores
+#[must_use]
+pub fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !text[..start].chars().next_back().is_some_and(is_word)
+        && !text[end..].chars().next().is_some_and(is_word)
+}
+
+/// Give a replacement the casing pattern of the text it replaces
+///
+/// "colour" to "color" gives "color", "Colour" gives "Color", and
+/// "COLOUR" gives "COLOR". Mixed casing like "cOLour" and matches
+/// without letters keep the replacement as typed.
+///
+/// # Arguments
+/// * `matched` - Text being replaced
+/// * `replacement` - Replacement text
+///
+/// # Returns
+/// The replacement in the casing of the match
+#[must_use]
+pub fn transfer_case(matched: &str, replacement: &str) -> String {
+    let mut letters = matched
+        .chars()
+        .filter(|c| c.is_lowercase() || c.is_uppercase());
+    let Some(first) = letters.next() else {
+        return replacement.to_string();
     };
+    let rest: Vec<char> = letters.collect();
+    let rest_lower = rest.iter().all(|c| c.is_lowercase());
+    if first.is_lowercase() {
+        if rest_lower {
+            convert_case(replacement, CaseConversion::Lower)
+        } else {
+            replacement.to_string()
+        }
+    } else if rest_lower {
+        let mut chars = replacement.chars();
+        chars.next().map_or_else(String::new, |first| {
+            first
+                .to_uppercase()
+                .chain(chars.flat_map(char::to_lowercase))
+                .collect()
+        })
+    } else if rest.iter().all(|c| c.is_uppercase()) {
+        convert_case(replacement, CaseConversion::Upper)
+    } else {
+        replacement.to_string()
+    }
+}
 
-    let search_text = if app.search_state.case_sensitive {
-        app.search_state.find_text.clone()
+/// Result of searching from a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindOutcome {
+    /// Match found at the byte offset
+    Found(usize),
+    /// Match found at the byte offset after wrapping around
+    Wrapped(usize),
+    /// No match before the end (or start) of the document, wrapping is off
+    ReachedEnd,
+    /// No match anywhere
+    NotFound,
+}
+
+/// Find the next match from a position
+///
+/// Searching down finds the first match starting at or after `position`;
+/// searching up finds the last match starting before it.
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `needle` - Text to find (not empty)
+/// * `position` - Byte offset to search from
+/// * `search_down` - Search towards the end of the document
+/// * `wrap_around` - Continue from the other end when no match is left
+/// * `whole_word` - Skip matches that are part of a longer word
+///
+/// # Returns
+/// Where the match starts, or why there is none
+#[must_use]
+pub fn find_from(
+    text: &str,
+    needle: &str,
+    position: usize,
+    search_down: bool,
+    wrap_around: bool,
+    whole_word: bool,
+) -> FindOutcome {
+    let position = sanitize_offset(text, position);
+    let accept = |pos: usize| !whole_word || is_whole_word(text, pos, pos + needle.len());
+    let (before, after) = text.split_at(position);
+    let found = if search_down {
+        after
+            .match_indices(needle)
+            .map(|(pos, _)| position + pos)
+            .find(|&pos| accept(pos))
+    } else {
+        before
+            .rmatch_indices(needle)
+            .map(|(pos, _)| pos)
+            .find(|&pos| accept(pos))
+    };
+    if let Some(pos) = found {
+        return FindOutcome::Found(pos);
+    }
+    let wrapped = if search_down {
+        literal_matches(text, needle, whole_word).next()
     } else {
-        app.search_state.find_text.to_lowercase()
+        text.rmatch_indices(needle)
+            .map(|(pos, _)| pos)
+            .find(|&pos| accept(pos))
     };
+    match wrapped {
+        Some(pos) if wrap_around => FindOutcome::Wrapped(pos),
+        Some(_) => FindOutcome::ReachedEnd,
+        None => FindOutcome::NotFound,
+    }
+}
 
-    let start_pos = if app.search_state.search_down {
-        app.search_state.search_position
+/// Find all literal matches from the start of a text
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `needle` - Text to find (not empty)
+/// * `whole_word` - Skip matches that are part of a longer word
+///
+/// # Returns
+/// Iterator over the byte offsets of non-overlapping matches
+fn literal_matches<'a>(
+    text: &'a str,
+    needle: &'a str,
+    whole_word: bool,
+) -> impl Iterator<Item = usize> + 'a {
+    text.match_indices(needle)
+        .map(|(pos, _)| pos)
+        .filter(move |&pos| !whole_word || is_whole_word(text, pos, pos + needle.len()))
+}
+
+/// Find the next regular expression match from a position
+///
+/// Works like `find_from`. Searching down skips an empty match at
+/// `position` itself, so repeated searches move past it.
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `regex` - Pattern to find
+/// * `position` - Byte offset to search from
+/// * `search_down` - Search towards the end of the document
+/// * `wrap_around` - Continue from the other end when no match is left
+///
+/// # Returns
+/// Where the match starts, or why there is none
+#[must_use]
+pub fn find_regex_from(
+    text: &str,
+    regex: &Regex,
+    position: usize,
+    search_down: bool,
+    wrap_around: bool,
+) -> FindOutcome {
+    let position = sanitize_offset(text, position);
+    let found = if search_down {
+        regex
+            .find_at(text, position)
+            .and_then(|m| {
+                // An empty match at the cursor would be found again and again
+                if m.is_empty() && m.start() == position {
+                    let next = position + text[position..].chars().next()?.len_utf8();
+                    regex.find_at(text, next)
+                } else {
+                    Some(m)
+                }
+            })
+            .map(|m| m.start())
     } else {
-        0
+        regex
+            .find_iter(text)
+            .map(|m| m.start())
+            .take_while(|&start| start < position)
+            .last()
     };
+    if let Some(pos) = found {
+        return FindOutcome::Found(pos);
+    }
+    let mut all = regex.find_iter(text).map(|m| m.start());
+    let wrapped = if search_down { all.next() } else { all.last() };
+    match wrapped {
+        Some(pos) if wrap_around => FindOutcome::Wrapped(pos),
+        Some(_) => FindOutcome::ReachedEnd,
+        None => FindOutcome::NotFound,
+    }
+}
 
-    if app.search_state.search_down {
-        if let Some(pos) = text[start_pos..].find(&search_text) {
-            app.search_state.search_position = start_pos + pos + search_text.len();
-            // TODO: Highlight/select the found text
-            true
-        } else {
-            // Wrap around
-            if let Some(pos) = text[..start_pos].find(&search_text) {
-                app.search_state.search_position = pos + search_text.len();
-                true
-            } else {
-                false
-            }
+/// Find next occurrence of search text
+///
+/// # Arguments
+/// * `app` - Application state
+///
+/// # Returns
+/// True if match found, false otherwise
+pub fn find_next(app: &mut NodepatApp) -> bool {
+    if app.search_state.find_text.is_empty() {
+        return false;
+    }
+
+    // The viewer searches the file on disk (forward only) in the
+    // background and shows the result in its header
+    if let Some(viewer) = &mut app.viewer {
+        if app.search_state.use_regex {
+            app.search_state.status =
+                "Regular expressions are not supported in the viewer".to_string();
+            return false;
         }
-    } else {
-        // Search up
-        if let Some(pos) = text[..start_pos].rfind(&search_text) {
-            app.search_state.search_position = pos;
-            true
+        let needle = app.search_state.needle();
+        return viewer.find_next(&needle, app.search_state.case_sensitive);
+    }
+
+    // Search from the cursor: after the selection going down, before it going up
+    let search_down = app.search_state.search_down;
+    let (selection_start, selection_end) = app.editor_state.selection;
+    let position = char_to_byte(
+        &app.editor_state.text,
+        if search_down {
+            selection_end
         } else {
-            // Wrap around
-            if let Some(pos) = text[start_pos..].rfind(&search_text) {
-                app.search_state.search_position = start_pos + pos;
-                true
-            } else {
-                false
+            selection_start
+        },
+    );
+    let (outcome, match_end) = match locate_match(app, position, search_down) {
+        Ok(located) => located,
+        Err(error) => {
+            app.search_state.status = error;
+            return false;
+        }
+    };
+    if outcome == FindOutcome::NotFound {
+        app.search_state.status.clear();
+        report_not_found(app);
+    }
+    select_match(app, outcome, match_end, search_down)
+}
+
+/// Select the match of a search and report wrapping or stopping
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `outcome` - Result of the search
+/// * `match_end` - Byte offset where a found match ends
+/// * `search_down` - Whether the search went towards the end
+///
+/// # Returns
+/// True if a match was selected
+fn select_match(
+    app: &mut NodepatApp,
+    outcome: FindOutcome,
+    match_end: usize,
+    search_down: bool,
+) -> bool {
+    match outcome {
+        FindOutcome::Found(pos) | FindOutcome::Wrapped(pos) => {
+            let editor_text = &app.editor_state.text;
+            let start = byte_to_char(editor_text, pos);
+            let end = byte_to_char(editor_text, match_end);
+            app.editor_state.select(start, end);
+            app.search_state.status.clear();
+            if matches!(outcome, FindOutcome::Wrapped(_)) {
+                report_search_status(
+                    app,
+                    if search_down {
+                        "Passed the end of the document, continuing from the top"
+                    } else {
+                        "Passed the start of the document, continuing from the bottom"
+                    },
+                );
             }
+            true
+        }
+        FindOutcome::ReachedEnd => {
+            report_search_status(app, "Cannot find further occurrences");
+            false
         }
+        FindOutcome::NotFound => false,
     }
 }
 
-/// Replace current match
+/// Select a match for the incremental search bar
+///
+/// Unlike Find Next, a missing match is not reported as a message, since
+/// the search bar shows it while the user types.
 ///
 /// # Arguments
 /// * `app` - Application state
+/// * `from` - Character index to search from
+/// * `search_down` - Search towards the end of the document
 ///
 /// # Returns
-/// True if replacement was made, false otherwise
-pub fn replace_current(app: &mut NodepatApp) -> bool {
+/// True if a match was selected
+pub fn find_incremental(app: &mut NodepatApp, from: usize, search_down: bool) -> bool {
+    app.search_state.status.clear();
     if app.search_state.find_text.is_empty() {
         return false;
     }
+    let position = char_to_byte(&app.editor_state.text, from);
+    match locate_match(app, position, search_down) {
+        Ok((outcome, match_end)) => select_match(app, outcome, match_end, search_down),
+        Err(error) => {
+            app.search_state.status = error;
+            false
+        }
+    }
+}
 
-    let text = if app.search_state.case_sensitive {
-        app.editor_state.text.clone()
-    } else {
-        app.editor_state.text.to_lowercase()
-    };
+/// Find every match of the find text, e.g. to highlight them
+///
+/// Empty regular expression matches are left out.
+///
+/// # Arguments
+/// * `state` - Search text and options
+/// * `text` - Text to search
+///
+/// # Returns
+/// Result containing the byte ranges of the matches in text order, or
+/// the error of an invalid regular expression
+pub fn match_ranges(state: &SearchState, text: &str) -> Result<Vec<Range<usize>>, String> {
+    if state.find_text.is_empty() {
+        return Ok(Vec::new());
+    }
+    if state.uses_regex() {
+        let regex = state.regex()?;
+        return Ok(regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect());
+    }
+    let needle = state.needle();
+    Ok(literal_matches(text, &needle, state.whole_word)
+        .map(|pos| pos..pos + needle.len())
+        .collect())
+}
 
-    let search_text = if app.search_state.case_sensitive {
-        app.search_state.find_text.clone()
-    } else {
-        app.search_state.find_text.to_lowercase()
+/// Show a Find Next status in the Find or Replace dialog
+///
+/// Without either dialog or the search bar open, e.g. for F3, the status
+/// is shown as a message instead, so wrapping or stopping is never silent.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `status` - Status to show
+fn report_search_status(app: &mut NodepatApp, status: &str) {
+    app.search_state.status = status.to_string();
+    if !app.show_find_dialog && !app.show_replace_dialog && app.search_bar.is_none() {
+        app.messages.info(status);
+    }
+}
+
+/// Tell the user that the find text does not occur in the document
+///
+/// # Arguments
+/// * `app` - Application state
+fn report_not_found(app: &mut NodepatApp) {
+    app.messages
+        .info(format!("Cannot find \"{}\"", app.search_state.find_text));
+}
+
+/// Find the next match in the editor text
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `position` - Byte offset to search from
+/// * `search_down` - Search towards the end of the document
+///
+/// # Returns
+/// Result containing the outcome and the byte offset where a found match
+/// ends, or the error of an invalid regular expression
+fn locate_match(
+    app: &NodepatApp,
+    position: usize,
+    search_down: bool,
+) -> Result<(FindOutcome, usize), String> {
+    let text = &app.editor_state.text;
+    let wrap_around = app.search_state.wrap_around;
+    if app.search_state.uses_regex() {
+        let regex = app.search_state.regex()?;
+        let outcome = find_regex_from(text, &regex, position, search_down, wrap_around);
+        let end = match outcome {
+            FindOutcome::Found(pos) | FindOutcome::Wrapped(pos) => {
+                regex.find_at(text, pos).map_or(pos, |m| m.end())
+            }
+            FindOutcome::ReachedEnd | FindOutcome::NotFound => 0,
+        };
+        return Ok((outcome, end));
+    }
+
+    let needle = app.search_state.needle();
+    let outcome = find_from(
+        text,
+        &needle,
+        position,
+        search_down,
+        wrap_around,
+        app.search_state.whole_word,
+    );
+    let end = match outcome {
+        FindOutcome::Found(pos) | FindOutcome::Wrapped(pos) => pos + needle.len(),
+        FindOutcome::ReachedEnd | FindOutcome::NotFound => 0,
     };
+    Ok((outcome, end))
+}
 
-    if let Some(pos) = text.find(&search_text) {
+/// Replace current match
+///
+/// # Arguments
+/// * `app` - Application state
+///
+/// # Returns
+/// True if replacement was made, false otherwise
+pub fn replace_current(app: &mut NodepatApp) -> bool {
+    if app.search_state.find_text.is_empty() || !app.is_editable() {
+        return false;
+    }
+    if app.search_state.uses_regex() {
+        return replace_current_regex(app);
+    }
+
+    let text = &app.editor_state.text;
+    let search_text = app.search_state.needle();
+    let found = literal_matches(text, &search_text, app.search_state.whole_word).next();
+    if let Some(pos) = found {
+        let range = pos..pos + search_text.len();
+        let replacement = app.search_state.replacement().into_owned();
         app.editor_state.save_undo_state();
-        app.editor_state
-            .text
-            .replace_range(pos..pos + search_text.len(), &app.search_state.replace_text);
+        app.editor_state.text.replace_range(range, &replacement);
         app.file_state.is_modified = true;
-        app.search_state.search_position = pos + app.search_state.replace_text.len();
         true
     } else {
+        report_not_found(app);
         false
     }
 }
 
-/// Replace all occurrences
+/// Replace the first regular expression match
+///
+/// With regular expressions on, `$1`, `${name}` and `$0` in the
+/// replacement text insert capture groups.
 ///
 /// # Arguments
 /// * `app` - Application state
 ///
 /// # Returns
-/// Number of replacements made
-pub fn replace_all(app: &mut NodepatApp) -> usize {
-    if app.search_state.find_text.is_empty() {
-        return 0;
+/// True if replacement was made, false otherwise
+fn replace_current_regex(app: &mut NodepatApp) -> bool {
+    let regex = match app.search_state.regex() {
+        Ok(regex) => regex,
+        Err(error) => {
+            app.search_state.status = error;
+            return false;
+        }
+    };
+    let Some(captures) = regex.captures(&app.editor_state.text) else {
+        report_not_found(app);
+        return false;
+    };
+    let mut replacement = String::new();
+    let replace_text = app.search_state.replacement();
+    if app.search_state.use_regex {
+        captures.expand(&replace_text, &mut replacement);
+    } else if app.search_state.transfers_case() {
+        replacement = transfer_case(&captures[0], &replace_text);
+    } else {
+        replacement = replace_text.into_owned();
     }
-
+    let range = captures.get(0).map_or(0..0, |m| m.range());
     app.editor_state.save_undo_state();
+    app.editor_state.text.replace_range(range, &replacement);
+    app.file_state.is_modified = true;
+    true
+}
 
-    let mut count = 0;
-    let search_text = &app.search_state.find_text;
-    let replace_text = &app.search_state.replace_text;
+/// Replace all regular expression matches
+///
+/// Empty matches are replaced once each, so patterns like `a*` terminate.
+///
+/// # Arguments
+/// * `text` - Text to edit
+/// * `regex` - Pattern to find
+/// * `replacement` - Replacement text, may reference capture groups
+///
+/// # Returns
+/// Number of replacements made
+pub fn replace_all_regex(text: &mut String, regex: &Regex, replacement: impl Replacer) -> usize {
+    let count = regex.find_iter(text).count();
+    if count > 0 {
+        *text = regex.replace_all(text, replacement).into_owned();
+    }
+    count
+}
 
-    if app.search_state.case_sensitive {
-        while app.editor_state.text.contains(search_text) {
-            app.editor_state.text = app.editor_state.text.replacen(search_text, replace_text, 1);
-            count += 1;
+/// Replace all occurrences and report the number of replacements
+///
+/// # Arguments
+/// * `app` - Application state
+///
+/// # Returns
+/// Number of replacements made
+pub fn replace_all(app: &mut NodepatApp) -> usize {
+    if app.search_state.find_text.is_empty() || !app.is_editable() {
+        return 0;
+    }
+    let count = replace_all_matches(app);
+    if count == 0 {
+        if app.search_state.pattern_error().is_none() {
+            report_not_found(app);
         }
     } else {
-        // Case-insensitive replacement is more complex
-        let mut text_lower = app.editor_state.text.to_lowercase();
-        let search_lower = search_text.to_lowercase();
+        let plural = if count == 1 { "" } else { "s" };
+        app.messages
+            .info(format!("Replaced {count} occurrence{plural}"));
+    }
+    count
+}
 
-        while let Some(pos) = text_lower.find(&search_lower) {
-            let end_pos = pos + search_text.len();
-            app.editor_state
-                .text
-                .replace_range(pos..end_pos, replace_text);
-            text_lower = app.editor_state.text.to_lowercase();
-            count += 1;
+/// Replace all occurrences
+///
+/// # Arguments
+/// * `app` - Application state
+///
+/// # Returns
+/// Number of replacements made
+fn replace_all_matches(app: &mut NodepatApp) -> usize {
+    if app.search_state.uses_regex() {
+        let regex = match app.search_state.regex() {
+            Ok(regex) => regex,
+            Err(error) => {
+                app.search_state.status = error;
+                return 0;
+            }
+        };
+        app.editor_state.save_undo_state();
+        let text = &mut app.editor_state.text;
+        let replace_text = app.search_state.replacement();
+        let replacement = replace_text.as_ref();
+        let count = if app.search_state.use_regex {
+            replace_all_regex(text, &regex, replacement)
+        } else if app.search_state.transfers_case() {
+            replace_all_regex(text, &regex, |captures: &Captures| {
+                transfer_case(&captures[0], replacement)
+            })
+        } else {
+            replace_all_regex(text, &regex, NoExpand(replacement))
+        };
+        if count > 0 {
+            app.file_state.is_modified = true;
         }
+        return count;
     }
 
+    app.editor_state.save_undo_state();
+
+    let (replaced, count) = replace_all_literal(
+        &app.editor_state.text,
+        &app.search_state.needle(),
+        &app.search_state.replacement(),
+        app.search_state.whole_word,
+    );
     if count > 0 {
+        app.editor_state.text = replaced;
         app.file_state.is_modified = true;
     }
 
     count
 }
 
+/// Replace all literal matches in one pass
+///
+/// The replaced text is built alongside, so the time grows with the text
+/// length and not with the number of matches. Replacement text is never
+/// searched again, even if it contains the find text.
+///
+/// # Arguments
+/// * `text` - Text to search
+/// * `needle` - Text to find (not empty)
+/// * `replacement` - Text to insert for each match
+/// * `whole_word` - Skip matches that are part of a longer word
+///
+/// # Returns
+/// Tuple of (replaced text, number of replacements)
+#[must_use]
+pub fn replace_all_literal(
+    text: &str,
+    needle: &str,
+    replacement: &str,
+    whole_word: bool,
+) -> (String, usize) {
+    let mut replaced = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut count = 0;
+    for pos in literal_matches(text, needle, whole_word) {
+        replaced.push_str(&text[copied..pos]);
+        replaced.push_str(replacement);
+        copied = pos + needle.len();
+        count += 1;
+    }
+    replaced.push_str(&text[copied..]);
+    (replaced, count)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::app::NodepatApp;
 
+    fn front_text(app: &NodepatApp) -> Option<&str> {
+        app.messages.front().map(|m| m.text.as_str())
+    }
+
     #[test]
     fn test_find_next() {
         let mut app = NodepatApp::default();
@@ -177,10 +889,471 @@ mod tests {
         app.search_state.find_text = "Hello".to_string();
         app.search_state.case_sensitive = false;
         app.search_state.search_down = true;
-        app.search_state.search_position = 0;
 
         assert!(find_next(&mut app));
-        assert_eq!(app.search_state.search_position, 5);
+        assert_eq!(app.editor_state.selection, (0, 5));
+        assert_eq!(app.editor_state.pending_selection, Some((0, 5)));
+    }
+
+    #[test]
+    fn test_find_next_starts_at_cursor() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "héllo wörld héllo".to_string();
+        app.search_state.find_text = "HÉLLO".to_string();
+        app.search_state.search_down = true;
+        app.search_state.wrap_around = true;
+        app.editor_state.selection = (3, 3);
+
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (12, 17));
+        assert!(app.search_state.status.is_empty());
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (0, 5));
+        assert_eq!(
+            app.search_state.status,
+            "Passed the end of the document, continuing from the top"
+        );
+        assert_eq!(
+            front_text(&app),
+            Some("Passed the end of the document, continuing from the top")
+        );
+    }
+
+    #[test]
+    fn test_find_next_stops_at_end_without_wrap() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "one two one".to_string();
+        app.search_state.find_text = "one".to_string();
+        app.search_state.search_down = true;
+        app.search_state.wrap_around = false;
+        app.show_find_dialog = true;
+
+        assert!(find_next(&mut app));
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (8, 11));
+        assert!(!find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (8, 11));
+        assert_eq!(app.search_state.status, "Cannot find further occurrences");
+        // The open dialog shows the status, so no message is queued
+        assert_eq!(front_text(&app), None);
+
+        app.search_state.wrap_around = true;
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (0, 3));
+    }
+
+    #[test]
+    fn test_find_next_stops_at_start_without_wrap() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "one two one".to_string();
+        app.search_state.find_text = "one".to_string();
+        app.search_state.search_down = false;
+        app.search_state.wrap_around = false;
+        app.editor_state.selection = (11, 11);
+
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (8, 11));
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (0, 3));
+        assert!(!find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (0, 3));
+        assert_eq!(app.search_state.status, "Cannot find further occurrences");
+    }
+
+    #[test]
+    fn test_find_next_reports_not_found() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "one two".to_string();
+        app.search_state.find_text = "three".to_string();
+
+        assert!(!find_next(&mut app));
+        assert_eq!(front_text(&app), Some("Cannot find \"three\""));
+        app.messages.pop();
+        assert!(!replace_current(&mut app));
+        assert_eq!(front_text(&app), Some("Cannot find \"three\""));
+    }
+
+    #[test]
+    fn test_unescape_and_escape() {
+        assert_eq!(unescape(r"a, b\n\tc\\n"), "a, b\n\tc\\n");
+        assert_eq!(unescape(r"\r\n"), "\r\n");
+        // Unknown escapes and a trailing backslash stay as typed
+        assert_eq!(unescape(r"C:\dir\"), r"C:\dir\");
+        assert_eq!(unescape("plain é"), "plain é");
+        for text in ["a\r\nb\tc", r"C:\dir\new", "\\n", ""] {
+            assert_eq!(unescape(&escape(text)), text);
+        }
+    }
+
+    #[test]
+    fn test_extended_search_across_lines() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "é, b\nc, d".to_string();
+        app.search_state.find_text = r"b\nc".to_string();
+        app.search_state.replace_text = r"x\ty".to_string();
+        app.search_state.case_sensitive = true;
+        app.search_state.search_down = true;
+        assert!(!find_next(&mut app));
+        app.messages.pop();
+
+        app.search_state.extended = true;
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (3, 6));
+        assert_eq!(app.editor_state.selected_text(), "b\nc");
+        let ranges = match_ranges(&app.search_state, &app.editor_state.text)
+            .expect("Failed to find matches");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 4..7);
+
+        assert!(replace_current(&mut app));
+        assert_eq!(app.editor_state.text, "é, x\ty, d");
+        app.search_state.find_text = ", ".to_string();
+        app.search_state.replace_text = r"\n".to_string();
+        assert_eq!(replace_all(&mut app), 2);
+        assert_eq!(app.editor_state.text, "é\nx\ty\nd");
+        // Regular expressions keep their own escapes; the replacement
+        // still takes Extended escapes
+        app.search_state.use_regex = true;
+        app.search_state.find_text = r"\n".to_string();
+        app.search_state.replace_text = r"\\".to_string();
+        assert_eq!(replace_all(&mut app), 2);
+        assert_eq!(app.editor_state.text, "é\\x\ty\\d");
+    }
+
+    #[test]
+    fn test_match_ranges_and_position() {
+        let mut state = SearchState {
+            find_text: "ÉT".to_string(),
+            ..SearchState::default()
+        };
+        let text = "été, et ÉTÉ";
+        let bar = IncrementalSearch {
+            matches: match_ranges(&state, text).expect("Failed to find matches"),
+            ..IncrementalSearch::new((0, 0))
+        };
+        assert_eq!(bar.matches, vec![0..3, 10..13]);
+        assert_eq!(bar.summary(text, (8, 10)), "2 of 2");
+        assert_eq!(bar.summary(text, (0, 2)), "1 of 2");
+        assert_eq!(bar.summary(text, (0, 1)), "2 matches");
+
+        state.case_sensitive = true;
+        state.whole_word = true;
+        assert_eq!(match_ranges(&state, text), Ok(Vec::new()));
+        state.find_text = "(".to_string();
+        state.use_regex = true;
+        assert!(match_ranges(&state, text).is_err());
+        state.find_text = "x*".to_string();
+        assert_eq!(match_ranges(&state, text), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_find_incremental_reports_in_the_bar() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "one two one".to_string();
+        app.search_state.find_text = "one".to_string();
+        app.search_state.wrap_around = true;
+        app.search_bar = Some(IncrementalSearch::new((4, 4)));
+
+        assert!(find_incremental(&mut app, 4, true));
+        assert_eq!(app.editor_state.selection, (8, 11));
+        assert!(find_incremental(&mut app, 11, true));
+        assert_eq!(app.editor_state.selection, (0, 3));
+        assert_eq!(
+            app.search_state.status,
+            "Passed the end of the document, continuing from the top"
+        );
+        assert!(find_incremental(&mut app, 0, false));
+        assert_eq!(app.editor_state.selection, (8, 11));
+
+        app.search_state.find_text = "three".to_string();
+        assert!(!find_incremental(&mut app, 4, true));
+        // The bar shows what happened, so no message is queued
+        assert_eq!(front_text(&app), None);
+    }
+
+    #[test]
+    fn test_find_from_outcomes() {
+        let text = "abc abc";
+        assert_eq!(
+            find_from(text, "abc", 1, true, true, false),
+            FindOutcome::Found(4)
+        );
+        assert_eq!(
+            find_from(text, "abc", 5, true, true, false),
+            FindOutcome::Wrapped(0)
+        );
+        assert_eq!(
+            find_from(text, "abc", 5, true, false, false),
+            FindOutcome::ReachedEnd
+        );
+        assert_eq!(
+            find_from(text, "abc", 4, false, true, false),
+            FindOutcome::Found(0)
+        );
+        assert_eq!(
+            find_from(text, "abc", 0, false, true, false),
+            FindOutcome::Wrapped(4)
+        );
+        assert_eq!(
+            find_from(text, "xyz", 0, true, true, false),
+            FindOutcome::NotFound
+        );
+        assert_eq!(
+            find_from(text, "xyz", 0, true, false, false),
+            FindOutcome::NotFound
+        );
+        // Positions past the end are clamped
+        assert_eq!(
+            find_from(text, "abc", 99, false, false, false),
+            FindOutcome::Found(4)
+        );
+    }
+
+    fn regex(pattern: &str) -> Regex {
+        let state = SearchState {
+            find_text: pattern.to_string(),
+            case_sensitive: true,
+            use_regex: true,
+            ..SearchState::default()
+        };
+        state.regex().expect("Invalid test pattern")
+    }
+
+    #[test]
+    fn test_replace_all_regex_capture_groups() {
+        let mut text = "John Smith\nJane Doe".to_string();
+        let count = replace_all_regex(&mut text, &regex(r"(\w+) (?<last>\w+)"), "${last}, $1");
+        assert_eq!(count, 2);
+        assert_eq!(text, "Smith, John\nDoe, Jane");
+    }
+
+    #[test]
+    fn test_replace_all_regex_anchors_across_lines() {
+        let mut text = "one\r\ntwo\nthree".to_string();
+        assert_eq!(replace_all_regex(&mut text, &regex("^"), "> "), 3);
+        assert_eq!(text, "> one\r\n> two\n> three");
+        assert_eq!(replace_all_regex(&mut text, &regex("$"), ";"), 3);
+        assert_eq!(text, "> one;\r\n> two;\n> three;");
+    }
+
+    #[test]
+    fn test_replace_all_regex_empty_matches_terminate() {
+        let mut text = "baaac".to_string();
+        assert_eq!(replace_all_regex(&mut text, &regex("a*"), "-"), 3);
+        assert_eq!(text, "-b-c-");
+    }
+
+    #[test]
+    fn test_find_regex_from_moves_past_empty_matches() {
+        let text = "ab";
+        let empty = regex("x*");
+        assert_eq!(
+            find_regex_from(text, &empty, 0, true, false),
+            FindOutcome::Found(1)
+        );
+        assert_eq!(
+            find_regex_from(text, &empty, 2, true, true),
+            FindOutcome::Wrapped(0)
+        );
+        assert_eq!(
+            find_regex_from("a1 b22", &regex(r"\d+"), 6, false, false),
+            FindOutcome::Found(4)
+        );
+    }
+
+    #[test]
+    fn test_find_next_regex() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "id=7\nID=42".to_string();
+        app.search_state.find_text = r"^id=\d+$".to_string();
+        app.search_state.use_regex = true;
+        app.search_state.search_down = true;
+        app.search_state.wrap_around = false;
+
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (0, 4));
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (5, 10));
+
+        app.search_state.case_sensitive = true;
+        assert!(!find_next(&mut app));
+        assert_eq!(app.search_state.status, "Cannot find further occurrences");
+    }
+
+    #[test]
+    fn test_invalid_regex_reports_error() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "text".to_string();
+        app.search_state.find_text = "(unclosed".to_string();
+        app.search_state.use_regex = true;
+
+        assert!(app.search_state.pattern_error().is_some());
+        assert!(!find_next(&mut app));
+        assert_eq!(replace_all(&mut app), 0);
+        assert!(
+            app.search_state
+                .status
+                .starts_with("Invalid regular expression")
+        );
+        assert_eq!(app.editor_state.text, "text");
+    }
+
+    #[test]
+    fn test_is_whole_word() {
+        let text = "cat, catalog (cat)_cat";
+        assert!(is_whole_word(text, 0, 3));
+        assert!(!is_whole_word(text, 5, 8));
+        assert!(is_whole_word(text, 14, 17));
+        assert!(!is_whole_word(text, 19, 22));
+        // Multibyte neighbours are letters too
+        let text = "écat cat";
+        assert!(!is_whole_word(text, 2, 5));
+        assert!(is_whole_word(text, 6, 9));
+    }
+
+    #[test]
+    fn test_find_from_whole_word() {
+        let text = "cat catalog cat";
+        assert_eq!(
+            find_from(text, "cat", 1, true, false, true),
+            FindOutcome::Found(12)
+        );
+        assert_eq!(
+            find_from(text, "cat", 12, false, false, true),
+            FindOutcome::Found(0)
+        );
+        assert_eq!(
+            find_from(text, "cat", 13, true, true, true),
+            FindOutcome::Wrapped(0)
+        );
+        assert_eq!(
+            find_from("catalog", "cat", 0, true, true, true),
+            FindOutcome::NotFound
+        );
+    }
+
+    #[test]
+    fn test_replace_whole_word() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "Cat scatter cat. cats (cat)".to_string();
+        app.search_state.find_text = "cat".to_string();
+        app.search_state.replace_text = "dog".to_string();
+        app.search_state.whole_word = true;
+        app.search_state.case_sensitive = false;
+
+        assert!(replace_current(&mut app));
+        assert_eq!(app.editor_state.text, "dog scatter cat. cats (cat)");
+        assert_eq!(replace_all(&mut app), 2);
+        assert_eq!(app.editor_state.text, "dog scatter dog. cats (dog)");
+        assert_eq!(replace_all(&mut app), 0);
+
+        app.search_state.use_regex = true;
+        app.search_state.find_text = "s?cat[st]*".to_string();
+        assert_eq!(replace_all(&mut app), 1);
+        assert_eq!(app.editor_state.text, "dog scatter dog. dog (dog)");
+    }
+
+    #[test]
+    fn test_ignore_case_keeps_offsets_with_multibyte_text() {
+        let mut app = NodepatApp::default();
+        // 'İ' and 'ß' change length when lowercased or uppercased
+        app.editor_state.text = "İß😀 ab AB".to_string();
+        app.search_state.find_text = "Ab".to_string();
+        app.search_state.search_down = true;
+
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (4, 6));
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (7, 9));
+
+        app.search_state.find_text = "SS".to_string();
+        assert!(!find_next(&mut app));
+        app.search_state.find_text = "ẞ😀".to_string();
+        app.editor_state.select(0, 0);
+        assert!(find_next(&mut app));
+        assert_eq!(app.editor_state.selection, (1, 3));
+    }
+
+    #[test]
+    fn test_ignore_case_replace_with_multibyte_text() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "İab ß AB $1".to_string();
+        app.search_state.find_text = "aB".to_string();
+        app.search_state.replace_text = "$1".to_string();
+
+        assert!(replace_current(&mut app));
+        assert_eq!(app.editor_state.text, "İ$1 ß AB $1");
+        assert_eq!(replace_all(&mut app), 1);
+        assert_eq!(app.editor_state.text, "İ$1 ß $1 $1");
+
+        app.search_state.find_text = "İ$1".to_string();
+        app.search_state.replace_text = "x".to_string();
+        assert_eq!(replace_all(&mut app), 1);
+        assert_eq!(app.editor_state.text, "x ß $1 $1");
+    }
+
+    #[test]
+    fn test_replace_all_literal_with_needle_in_replacement() {
+        assert_eq!(
+            replace_all_literal("a.a.a", "a", "aa", false),
+            ("aa.aa.aa".to_string(), 3)
+        );
+        assert_eq!(
+            replace_all_literal("cat cats", "cat", "[cat]", true),
+            ("[cat] cats".to_string(), 1)
+        );
+        assert_eq!(
+            replace_all_literal("none", "x", "y", false),
+            ("none".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn test_replace_all_many_matches() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "ab ".repeat(50_000);
+        app.search_state.find_text = "ab".to_string();
+        app.search_state.replace_text = "xab".to_string();
+        app.search_state.case_sensitive = true;
+
+        // The replacement contains the match, so a single pass over the
+        // original text is the only way to replace each match exactly once
+        assert_eq!(replace_all(&mut app), 50_000);
+        assert_eq!(app.editor_state.text.len(), 200_000);
+        assert!(app.editor_state.text.starts_with("xab xab "));
+
+        // Ignoring case takes the same single pass
+        app.search_state.case_sensitive = false;
+        app.search_state.find_text = "XAB".to_string();
+        app.search_state.replace_text = "ab".to_string();
+        assert_eq!(replace_all(&mut app), 50_000);
+        assert_eq!(app.editor_state.text, "ab ".repeat(50_000));
+    }
+
+    #[test]
+    fn test_transfer_case() {
+        assert_eq!(transfer_case("colour", "Color"), "color");
+        assert_eq!(transfer_case("Colour", "color"), "Color");
+        assert_eq!(transfer_case("COLOUR", "color"), "COLOR");
+        assert_eq!(transfer_case("C", "color"), "Color");
+        assert_eq!(transfer_case("ÉTÉ", "straße"), "STRASSE");
+        // Mixed casing and matches without letters keep the replacement
+        assert_eq!(transfer_case("cOLour", "coLor"), "coLor");
+        assert_eq!(transfer_case("42", "coLor"), "coLor");
+    }
+
+    #[test]
+    fn test_replace_preserving_case() {
+        let mut app = NodepatApp::default();
+        app.editor_state.text = "colour Colour COLOUR cOLOUR".to_string();
+        app.search_state.find_text = "colour".to_string();
+        app.search_state.replace_text = "color".to_string();
+        app.search_state.preserve_case = true;
+
+        assert!(replace_current(&mut app));
+        assert_eq!(app.editor_state.text, "color Colour COLOUR cOLOUR");
+        assert_eq!(replace_all(&mut app), 3);
+        assert_eq!(app.editor_state.text, "color Color COLOR color");
     }
 
     #[test]
@@ -194,5 +1367,10 @@ mod tests {
         let count = replace_all(&mut app);
         assert_eq!(count, 2);
         assert_eq!(app.editor_state.text, "Hi World Hi");
+        assert_eq!(front_text(&app), Some("Replaced 2 occurrences"));
+        app.messages.pop();
+
+        assert_eq!(replace_all(&mut app), 0);
+        assert_eq!(front_text(&app), Some("Cannot find \"Hello\""));
     }
 }
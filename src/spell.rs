@@ -0,0 +1,459 @@
+//! Spell checking
+//!
+//! This module finds misspelled words for the editor to underline. Words
+//! are looked up in a small bundled English word list and the user's own
+//! dictionary, a plain text file with one word per line next to the
+//! config file. Words with digits, URLs, and e-mail addresses are skipped.
+
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Bundled English word list, one lowercase word per line
+const BUNDLED_WORDS: &str = include_str!("../assets/words.txt");
+
+/// Most suggestions offered for a misspelled word
+pub const MAX_SUGGESTIONS: usize = 8;
+
+/// Letters tried when suggesting corrections
+const SUGGESTION_LETTERS: &str = "abcdefghijklmnopqrstuvwxyz'";
+
+/// Words known to the spell checker
+pub struct Dictionary {
+    /// Known words in lowercase
+    words: HashSet<String>,
+    /// User dictionary file that added words are appended to
+    user_path: PathBuf,
+}
+
+impl Dictionary {
+    /// Load the bundled word list and the user dictionary
+    ///
+    /// A missing user dictionary is treated as empty.
+    ///
+    /// # Arguments
+    /// * `user_path` - User dictionary file
+    ///
+    /// # Returns
+    /// The dictionary, or error message if the user dictionary exists but
+    /// cannot be read
+    pub fn load(user_path: &Path) -> Result<Self, String> {
+        let mut dictionary = Self::with_words(BUNDLED_WORDS, user_path);
+        match std::fs::read_to_string(user_path) {
+            Ok(text) => dictionary.words.extend(normalized_words(&text)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read dictionary: {e}")),
+        }
+        Ok(dictionary)
+    }
+
+    /// Create a dictionary from a word list
+    ///
+    /// # Arguments
+    /// * `words` - Words, one per line
+    /// * `user_path` - User dictionary file for added words
+    ///
+    /// # Returns
+    /// The dictionary
+    #[must_use]
+    pub fn with_words(words: &str, user_path: &Path) -> Self {
+        Self {
+            words: normalized_words(words).collect(),
+            user_path: user_path.to_path_buf(),
+        }
+    }
+
+    /// Check whether a word is spelled correctly
+    ///
+    /// Case is ignored and a possessive "'s" is allowed after known words.
+    ///
+    /// # Arguments
+    /// * `word` - Word to check
+    ///
+    /// # Returns
+    /// True if the word is known
+    #[must_use]
+    pub fn contains(&self, word: &str) -> bool {
+        let word = normalize(word);
+        self.words.contains(&word)
+            || word
+                .strip_suffix("'s")
+                .is_some_and(|stem| self.words.contains(stem))
+    }
+
+    /// Suggest known words one edit away from a misspelled word
+    ///
+    /// Edits are deleting, inserting, or replacing one letter, or swapping
+    /// two neighboring letters. A capitalized word gets capitalized
+    /// suggestions.
+    ///
+    /// # Arguments
+    /// * `word` - Misspelled word
+    ///
+    /// # Returns
+    /// Up to `MAX_SUGGESTIONS` words in alphabetical order
+    #[must_use]
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let lower: Vec<char> = normalize(word).chars().collect();
+        let mut candidates = HashSet::new();
+        for index in 0..=lower.len() {
+            if index < lower.len() {
+                let mut deleted = lower.clone();
+                deleted.remove(index);
+                candidates.insert(deleted);
+            }
+            if index + 1 < lower.len() {
+                let mut swapped = lower.clone();
+                swapped.swap(index, index + 1);
+                candidates.insert(swapped);
+            }
+            for letter in SUGGESTION_LETTERS.chars() {
+                let mut inserted = lower.clone();
+                inserted.insert(index, letter);
+                candidates.insert(inserted);
+                if index < lower.len() {
+                    let mut replaced = lower.clone();
+                    replaced[index] = letter;
+                    candidates.insert(replaced);
+                }
+            }
+        }
+        let mut suggestions: Vec<String> = candidates
+            .into_iter()
+            .map(|chars| chars.into_iter().collect::<String>())
+            .filter(|candidate| candidate.chars().count() > 1 && self.words.contains(candidate))
+            .collect();
+        suggestions.sort();
+        suggestions.truncate(MAX_SUGGESTIONS);
+        if word.chars().next().is_some_and(char::is_uppercase) {
+            suggestions = suggestions.iter().map(|s| capitalize(s)).collect();
+        }
+        suggestions
+    }
+
+    /// Add a word and append it to the user dictionary file
+    ///
+    /// # Arguments
+    /// * `word` - Word to add
+    ///
+    /// # Returns
+    /// Ok, or error message if the file cannot be written
+    pub fn add_word(&mut self, word: &str) -> Result<(), String> {
+        let word = normalize(word);
+        if let Some(parent) = self.user_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create dictionary directory: {e}"))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.user_path)
+            .map_err(|e| format!("Failed to open dictionary: {e}"))?;
+        writeln!(file, "{word}").map_err(|e| format!("Failed to write dictionary: {e}"))?;
+        self.words.insert(word);
+        Ok(())
+    }
+}
+
+/// Lowercase a word and use a straight apostrophe
+///
+/// # Arguments
+/// * `word` - Word to normalize
+///
+/// # Returns
+/// The normalized word
+fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('\u{2019}', "'")
+}
+
+/// Read the words of a word list
+///
+/// # Arguments
+/// * `text` - Words, one per line; blank lines are skipped
+///
+/// # Returns
+/// The normalized words
+fn normalized_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(normalize)
+}
+
+/// Capitalize the first letter of a word
+///
+/// # Arguments
+/// * `word` - Lowercase word
+///
+/// # Returns
+/// The word with an uppercase first letter
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+/// Check whether a piece of text between spaces is a link or address
+///
+/// # Arguments
+/// * `chunk` - Text without whitespace
+///
+/// # Returns
+/// True for URLs like "https://..." or "www.example.com" and e-mail
+/// addresses
+fn is_url(chunk: &str) -> bool {
+    chunk.contains("://")
+        || chunk
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .starts_with("www.")
+        || chunk.contains('@')
+}
+
+/// Find the words to check in a text
+///
+/// Words are letters with apostrophes between them. Words next to digits
+/// or underscores, single letters, and whole URLs are skipped.
+///
+/// # Arguments
+/// * `text` - Text to scan
+///
+/// # Returns
+/// Byte ranges of the words
+#[must_use]
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut chunk_start = 0;
+    let ends = text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')));
+    for (index, c) in ends {
+        if c.is_whitespace() {
+            if index > chunk_start && !is_url(&text[chunk_start..index]) {
+                chunk_words(text, chunk_start..index, &mut words);
+            }
+            chunk_start = index + c.len_utf8();
+        }
+    }
+    words
+}
+
+/// Find the words in a piece of text between spaces
+///
+/// # Arguments
+/// * `text` - Whole text
+/// * `chunk` - Byte range of the piece
+/// * `words` - Byte ranges of words, in `text`; extended
+fn chunk_words(text: &str, chunk: Range<usize>, words: &mut Vec<Range<usize>>) {
+    let is_apostrophe = |c: char| c == '\'' || c == '\u{2019}';
+    let mut word_start = None;
+    // Letters next to digits or underscores are part of a code or number
+    let mut skip = false;
+    let ends = text[chunk.clone()]
+        .char_indices()
+        .map(|(index, c)| (chunk.start + index, c))
+        .chain(std::iter::once((chunk.end, ' ')));
+    for (index, c) in ends {
+        if c.is_alphanumeric() || c == '_' || is_apostrophe(c) {
+            skip |= c.is_numeric() || c == '_';
+            word_start.get_or_insert(index);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            let raw = &text[start..index];
+            let trimmed = raw.trim_start_matches(is_apostrophe);
+            let word = trimmed.trim_end_matches(is_apostrophe);
+            let start = start + raw.len() - trimmed.len();
+            if !skip && word.chars().count() > 1 {
+                words.push(start..start + word.len());
+            }
+        }
+        skip = false;
+    }
+}
+
+/// Find the misspelled words of a text
+///
+/// # Arguments
+/// * `text` - Text to check
+/// * `dictionary` - Known words
+///
+/// # Returns
+/// Byte ranges of unknown words
+#[must_use]
+pub fn misspelled(text: &str, dictionary: &Dictionary) -> Vec<Range<usize>> {
+    words(text)
+        .into_iter()
+        .filter(|range| !dictionary.contains(&text[range.clone()]))
+        .collect()
+}
+
+/// A misspelled word picked for the spelling menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    /// Byte range of the word in the text
+    pub range: Range<usize>,
+    /// The misspelled word
+    pub word: String,
+    /// Corrections to offer
+    pub suggestions: Vec<String>,
+}
+
+/// Spell checker with the misspellings of the last checked text
+///
+/// The editor lays out its text every frame; checking only after a
+/// change keeps spell checking cheap while nothing is typed.
+pub struct SpellChecker {
+    /// Known words
+    pub dictionary: Dictionary,
+    /// Text the misspellings were found in
+    checked: Option<String>,
+    /// Byte ranges of misspelled words in `checked`
+    misspelled: Vec<Range<usize>>,
+}
+
+impl SpellChecker {
+    /// Create a spell checker
+    ///
+    /// # Arguments
+    /// * `dictionary` - Known words
+    ///
+    /// # Returns
+    /// The spell checker
+    #[must_use]
+    pub const fn new(dictionary: Dictionary) -> Self {
+        Self {
+            dictionary,
+            checked: None,
+            misspelled: Vec::new(),
+        }
+    }
+
+    /// Get the misspelled words of a text
+    ///
+    /// # Arguments
+    /// * `text` - Text to check
+    ///
+    /// # Returns
+    /// Byte ranges of misspelled words, in text order
+    pub fn misspelled(&mut self, text: &str) -> &[Range<usize>] {
+        if self.checked.as_deref() != Some(text) {
+            self.misspelled = misspelled(text, &self.dictionary);
+            self.checked = Some(text.to_string());
+        }
+        &self.misspelled
+    }
+
+    /// Find the misspelled word at a position
+    ///
+    /// # Arguments
+    /// * `text` - Text to check
+    /// * `byte` - Byte offset in the text
+    ///
+    /// # Returns
+    /// The misspelled word touching the offset and its suggestions, if any
+    pub fn misspelling_at(&mut self, text: &str, byte: usize) -> Option<Misspelling> {
+        let range = self
+            .misspelled(text)
+            .iter()
+            .find(|range| range.start <= byte && byte <= range.end)?
+            .clone();
+        let word = text[range.clone()].to_string();
+        Some(Misspelling {
+            suggestions: self.dictionary.suggestions(&word),
+            range,
+            word,
+        })
+    }
+
+    /// Add a word to the dictionary and check the text again
+    ///
+    /// # Arguments
+    /// * `word` - Word to add
+    ///
+    /// # Returns
+    /// Ok, or error message if the user dictionary cannot be written
+    pub fn add_word(&mut self, word: &str) -> Result<(), String> {
+        self.dictionary.add_word(word)?;
+        self.checked = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary(words: &str) -> Dictionary {
+        Dictionary::with_words(words, Path::new("unused.txt"))
+    }
+
+    /// Get the text of each word found
+    fn word_texts(text: &str) -> Vec<&str> {
+        words(text).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn test_words_skip_digits_and_urls() {
+        assert_eq!(
+            word_texts("Don't 'quote' x mp3 snake_case, see https://example.com/page or www.x.org"),
+            vec!["Don't", "quote", "see", "or"]
+        );
+        assert_eq!(word_texts("mail me@example.com\tnow"), vec!["mail", "now"]);
+        assert_eq!(word_texts("Grüße, naïve"), vec!["Grüße", "naïve"]);
+    }
+
+    #[test]
+    fn test_bundled_words_load() {
+        let dictionary = dictionary(BUNDLED_WORDS);
+        for word in ["the", "Window", "don't", "don’t", "editor's"] {
+            assert!(dictionary.contains(word), "{word} should be known");
+        }
+        assert!(!dictionary.contains("teh"));
+    }
+
+    #[test]
+    fn test_misspelled_ranges() {
+        let dictionary = dictionary("the\ncat\nsat");
+        let text = "The cat szt on teh mat2";
+        let ranges = misspelled(text, &dictionary);
+        let found: Vec<&str> = ranges.into_iter().map(|range| &text[range]).collect();
+        assert_eq!(found, vec!["szt", "on", "teh"]);
+
+        let mut checker = SpellChecker::new(dictionary);
+        let misspelling = checker.misspelling_at(text, 17).expect("No misspelling");
+        assert_eq!(misspelling.range, 15..18);
+        assert_eq!(misspelling.word, "teh");
+        assert_eq!(misspelling.suggestions, vec!["the"]);
+        assert_eq!(checker.misspelling_at(text, 5), None);
+    }
+
+    #[test]
+    fn test_suggestions_one_edit_away() {
+        let dictionary = dictionary("the\nten\nhe\nthey\ntea\ncat");
+        assert_eq!(dictionary.suggestions("teh"), vec!["tea", "ten", "the"]);
+        assert_eq!(dictionary.suggestions("Thy"), vec!["The", "They"]);
+        assert!(dictionary.suggestions("xyzzy").is_empty());
+    }
+
+    #[test]
+    fn test_add_word_appends_to_user_dictionary() {
+        let dir = std::env::temp_dir().join("test_Nodepat_spell_dictionary");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("dictionary.txt");
+        let mut checker = SpellChecker::new(Dictionary::with_words("hello", &path));
+        assert_eq!(checker.misspelled("hello Nodepat").len(), 1);
+        checker.add_word("Nodepat").expect("Failed to add word");
+        assert!(checker.misspelled("hello Nodepat").is_empty());
+        checker.add_word("egui").expect("Failed to add word");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("Failed to read dictionary"),
+            "nodepat\negui\n"
+        );
+
+        let loaded = Dictionary::load(&path).expect("Failed to load dictionary");
+        assert!(loaded.contains("Nodepat") && loaded.contains("the"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,200 @@
+//! Document statistics (View > Statistics)
+//!
+//! This module counts the characters, words, lines, and paragraphs of a
+//! text and its size in an encoding. A CRLF line break counts as one
+//! character, so the figures match for files with either line ending.
+
+use crate::file_ops::Encoding;
+
+/// Figures shown in the Statistics dialog
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStats {
+    /// Characters, counting a line break as one
+    pub characters: usize,
+    /// Characters other than whitespace
+    pub characters_no_spaces: usize,
+    /// Runs of characters between whitespace
+    pub words: usize,
+    /// Lines, 0 for an empty text
+    pub lines: usize,
+    /// Groups of non-blank lines separated by blank lines
+    pub paragraphs: usize,
+    /// Size in bytes when encoded
+    pub bytes: usize,
+}
+
+impl TextStats {
+    /// Count the figures of a text, e.g. the selection
+    ///
+    /// # Arguments
+    /// * `text` - Text to count
+    /// * `encoding` - Encoding name (one of `file_ops::ENCODINGS`)
+    ///
+    /// # Returns
+    /// The figures; the size does not include a byte order mark
+    #[must_use]
+    pub fn new(text: &str, encoding: &str) -> Self {
+        Self {
+            characters: count_characters(text),
+            characters_no_spaces: text.chars().filter(|c| !c.is_whitespace()).count(),
+            words: text.split_whitespace().count(),
+            lines: count_lines(text),
+            paragraphs: count_paragraphs(text),
+            bytes: encoded_size(text, encoding),
+        }
+    }
+
+    /// Count the figures of a whole document
+    ///
+    /// # Arguments
+    /// * `text` - Document text
+    /// * `encoding` - Encoding the document is saved in
+    ///
+    /// # Returns
+    /// The figures; the size includes the byte order mark the file starts
+    /// with
+    #[must_use]
+    pub fn for_file(text: &str, encoding: &str) -> Self {
+        let mut stats = Self::new(text, encoding);
+        stats.bytes += Encoding::from_name(encoding)
+            .unwrap_or_default()
+            .codec()
+            .bom()
+            .len();
+        stats
+    }
+}
+
+/// Count characters, with CRLF as one line break
+///
+/// # Arguments
+/// * `text` - Text to count
+///
+/// # Returns
+/// Number of characters
+#[must_use]
+pub fn count_characters(text: &str) -> usize {
+    text.chars().count() - text.matches("\r\n").count()
+}
+
+/// Count lines the way the editor numbers them
+///
+/// A line break at the end starts another, empty line.
+///
+/// # Arguments
+/// * `text` - Text to count
+///
+/// # Returns
+/// Number of lines, 0 for an empty text
+#[must_use]
+pub fn count_lines(text: &str) -> usize {
+    if text.is_empty() {
+        0
+    } else {
+        text.matches('\n').count() + 1
+    }
+}
+
+/// Count paragraphs
+///
+/// Any number of blank or whitespace-only lines separate two paragraphs.
+///
+/// # Arguments
+/// * `text` - Text to count
+///
+/// # Returns
+/// Number of paragraphs
+#[must_use]
+pub fn count_paragraphs(text: &str) -> usize {
+    let mut paragraphs = 0;
+    let mut in_paragraph = false;
+    for line in text.lines() {
+        let blank = line.trim().is_empty();
+        if !blank && !in_paragraph {
+            paragraphs += 1;
+        }
+        in_paragraph = !blank;
+    }
+    paragraphs
+}
+
+/// Get the size of a text in an encoding, without a byte order mark
+///
+/// # Arguments
+/// * `text` - Text to measure
+/// * `encoding` - Encoding name (one of `file_ops::ENCODINGS`)
+///
+/// # Returns
+/// Number of bytes
+#[must_use]
+pub fn encoded_size(text: &str, encoding: &str) -> usize {
+    match Encoding::from_name(encoding).unwrap_or_default() {
+        Encoding::Utf8 => text.len(),
+        Encoding::Utf16Le | Encoding::Utf16Be => text.encode_utf16().count() * 2,
+        // Characters that Windows-1252 lacks are saved as '?'
+        Encoding::Windows1252 => text.chars().count(),
+        // Multibyte code pages have to be encoded to be measured
+        legacy => legacy.codec().encode(text).len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_ops::encode_with;
+
+    #[test]
+    fn test_stats_of_text() {
+        let stats = TextStats::new("Hello wörld\r\n\r\n\r\nSecond  para\r\n", "UTF-8");
+        assert_eq!(
+            stats,
+            TextStats {
+                characters: 27,
+                characters_no_spaces: 20,
+                words: 4,
+                lines: 5,
+                paragraphs: 2,
+                bytes: 32,
+            }
+        );
+        assert_eq!(TextStats::new("", "UTF-8"), TextStats::default());
+    }
+
+    #[test]
+    fn test_crlf_counts_like_lf() {
+        let lf = TextStats::new("one\ntwo\n\nthree", "UTF-8");
+        let crlf = TextStats::new("one\r\ntwo\r\n\r\nthree", "UTF-8");
+        assert_eq!(lf.characters, crlf.characters);
+        assert_eq!(lf.lines, crlf.lines);
+        assert_eq!(lf.paragraphs, crlf.paragraphs);
+        assert_eq!(crlf.paragraphs, 2);
+    }
+
+    #[test]
+    fn test_blank_lines_separate_paragraphs_once() {
+        assert_eq!(count_paragraphs("a\nb\n\n \n\t\nc\n\n"), 2);
+        assert_eq!(count_paragraphs("\n\n  \n"), 0);
+        assert_eq!(count_paragraphs("single"), 1);
+    }
+
+    #[test]
+    fn test_unicode_words_and_characters() {
+        let stats = TextStats::new("日本語 テキスト\u{3000}naïve 👍", "UTF-8");
+        assert_eq!(stats.words, 4);
+        assert_eq!(stats.characters, 16);
+        assert_eq!(stats.characters_no_spaces, 13);
+    }
+
+    #[test]
+    fn test_size_matches_saved_file() {
+        let text = "Grüße 👍\r\n";
+        for encoding in crate::file_ops::ENCODINGS {
+            assert_eq!(
+                TextStats::for_file(text, encoding).bytes,
+                encode_with(text, encoding).len(),
+                "{encoding}"
+            );
+        }
+        assert_eq!(TextStats::new(text, "UTF-16 LE").bytes, 20);
+    }
+}
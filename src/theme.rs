@@ -0,0 +1,259 @@
+//! Editor color themes
+//!
+//! This module turns the `theme` section of the config into the colors of
+//! the editor: its background, text, selection and caret. Colors come from
+//! a bundled preset or from the light/dark built-in palette, and each color
+//! set in the config as a hex string replaces the palette's. Missing or
+//! malformed colors keep the palette's.
+
+use eframe::egui::{self, Color32};
+
+/// Editor background of the built-in dark palette
+const DARK_BACKGROUND: Color32 = Color32::from_rgb(30, 30, 30);
+/// Editor background of the built-in light palette
+const LIGHT_BACKGROUND: Color32 = Color32::from_rgb(255, 255, 255);
+
+/// The `theme` section of the config
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThemeSettings {
+    /// Name of a bundled preset; empty for the built-in palettes
+    pub preset: String,
+    /// Editor background as a hex color, e.g. "#1e1e1e"; empty for the
+    /// palette's
+    pub background: String,
+    /// Editor text color as a hex color
+    pub text: String,
+    /// Selection color as a hex color
+    pub selection: String,
+    /// Caret color as a hex color
+    pub caret: String,
+}
+
+/// Bundled color themes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemePreset {
+    /// Solarized Dark by Ethan Schoonover
+    SolarizedDark,
+    /// White text on black
+    HighContrast,
+}
+
+impl ThemePreset {
+    /// Get all presets, in menu order
+    #[must_use]
+    pub const fn all() -> [Self; 2] {
+        [Self::SolarizedDark, Self::HighContrast]
+    }
+
+    /// Get the name shown in the menu and stored in the config
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::SolarizedDark => "Solarized Dark",
+            Self::HighContrast => "High Contrast",
+        }
+    }
+
+    /// Find a preset by name
+    ///
+    /// # Arguments
+    /// * `name` - Preset name, as returned by `name`
+    ///
+    /// # Returns
+    /// The preset, or None for an unknown name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|preset| preset.name() == name)
+    }
+
+    /// Get the preset's colors
+    #[must_use]
+    pub const fn palette(self) -> Palette {
+        match self {
+            Self::SolarizedDark => Palette {
+                dark: true,
+                background: Color32::from_rgb(0x00, 0x2b, 0x36),
+                text: Some(Color32::from_rgb(0x83, 0x94, 0x96)),
+                selection: Some(Color32::from_rgb(0x07, 0x36, 0x42)),
+                caret: Some(Color32::from_rgb(0x93, 0xa1, 0xa1)),
+            },
+            Self::HighContrast => Palette {
+                dark: true,
+                background: Color32::BLACK,
+                text: Some(Color32::WHITE),
+                selection: Some(Color32::from_rgb(0x00, 0x78, 0xd7)),
+                caret: Some(Color32::YELLOW),
+            },
+        }
+    }
+}
+
+/// Colors of the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Whether the rest of the window uses the dark theme
+    pub dark: bool,
+    /// Editor background
+    pub background: Color32,
+    /// Editor text; None keeps egui's
+    pub text: Option<Color32>,
+    /// Selection background; None keeps egui's
+    pub selection: Option<Color32>,
+    /// Caret; None keeps egui's
+    pub caret: Option<Color32>,
+}
+
+impl Palette {
+    /// Get the built-in light or dark palette
+    ///
+    /// # Arguments
+    /// * `dark` - Dark palette instead of the light one
+    #[must_use]
+    pub const fn built_in(dark: bool) -> Self {
+        Self {
+            dark,
+            background: if dark {
+                DARK_BACKGROUND
+            } else {
+                LIGHT_BACKGROUND
+            },
+            text: None,
+            selection: None,
+            caret: None,
+        }
+    }
+
+    /// Get the palette the config asks for
+    ///
+    /// # Arguments
+    /// * `settings` - The `theme` section of the config
+    /// * `dark_mode` - Dark mode setting, used without a preset
+    #[must_use]
+    pub fn from_settings(settings: &ThemeSettings, dark_mode: bool) -> Self {
+        let mut palette = ThemePreset::from_name(&settings.preset)
+            .map_or_else(|| Self::built_in(dark_mode), ThemePreset::palette);
+        if let Some(background) = parse_hex_color(&settings.background) {
+            palette.background = background;
+        }
+        for (color, hex) in [
+            (&mut palette.text, &settings.text),
+            (&mut palette.selection, &settings.selection),
+            (&mut palette.caret, &settings.caret),
+        ] {
+            if let Some(parsed) = parse_hex_color(hex) {
+                *color = Some(parsed);
+            }
+        }
+        palette
+    }
+
+    /// Use the palette's text, selection and caret colors
+    ///
+    /// # Arguments
+    /// * `visuals` - Visuals of the editor's UI
+    pub const fn apply(&self, visuals: &mut egui::Visuals) {
+        if let Some(text) = self.text {
+            visuals.override_text_color = Some(text);
+        }
+        if let Some(selection) = self.selection {
+            visuals.selection.bg_fill = selection;
+        }
+        if let Some(caret) = self.caret {
+            visuals.text_cursor.stroke.color = caret;
+        }
+    }
+}
+
+/// Parse a hex color
+///
+/// # Arguments
+/// * `hex` - Color as "#rgb", "#rrggbb" or "#rrggbbaa"; the "#" is optional
+///
+/// # Returns
+/// The color, or None if `hex` is not a color
+#[must_use]
+pub fn parse_hex_color(hex: &str) -> Option<Color32> {
+    let hex = hex.trim();
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).ok();
+    match digits.len() {
+        3 => {
+            // "#abc" is short for "#aabbcc"
+            let mut short = digits.chars().filter_map(|c| c.to_digit(16));
+            let mut next = || short.next().and_then(|d| u8::try_from(d * 17).ok());
+            Some(Color32::from_rgb(next()?, next()?, next()?))
+        }
+        6 => Some(Color32::from_rgb(channel(0)?, channel(1)?, channel(2)?)),
+        8 => Some(Color32::from_rgba_unmultiplied(
+            channel(0)?,
+            channel(1)?,
+            channel(2)?,
+            channel(3)?,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(
+            parse_hex_color("#002b36"),
+            Some(Color32::from_rgb(0, 0x2b, 0x36))
+        );
+        assert_eq!(
+            parse_hex_color(" FFaa00 "),
+            Some(Color32::from_rgb(255, 0xaa, 0))
+        );
+        assert_eq!(
+            parse_hex_color("#fa0"),
+            Some(Color32::from_rgb(255, 0xaa, 0))
+        );
+        assert_eq!(
+            parse_hex_color("#ff000080"),
+            Some(Color32::from_rgba_unmultiplied(255, 0, 0, 0x80))
+        );
+        for malformed in ["", "#", "#12345", "#gg0000", "red", "#ffé000"] {
+            assert_eq!(parse_hex_color(malformed), None, "{malformed}");
+        }
+    }
+
+    #[test]
+    fn test_palette_falls_back_per_field() {
+        let mut settings = ThemeSettings::default();
+        assert_eq!(
+            Palette::from_settings(&settings, true),
+            Palette::built_in(true)
+        );
+
+        settings.background = "#102030".to_string();
+        settings.caret = "not a color".to_string();
+        let palette = Palette::from_settings(&settings, false);
+        assert!(!palette.dark);
+        assert_eq!(palette.background, Color32::from_rgb(0x10, 0x20, 0x30));
+        assert_eq!(palette.caret, None);
+
+        settings.preset = "High Contrast".to_string();
+        settings.text = "#00ff00".to_string();
+        let palette = Palette::from_settings(&settings, false);
+        assert!(palette.dark);
+        assert_eq!(palette.text, Some(Color32::GREEN));
+        assert_eq!(palette.caret, Some(Color32::YELLOW));
+
+        settings.preset = "Unknown".to_string();
+        assert!(!Palette::from_settings(&settings, false).dark);
+    }
+
+    #[test]
+    fn test_preset_names_round_trip() {
+        for preset in ThemePreset::all() {
+            assert_eq!(ThemePreset::from_name(preset.name()), Some(preset));
+        }
+    }
+}
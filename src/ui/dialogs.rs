@@ -4,10 +4,18 @@
 //! font selection, and about dialog.
 
 use crate::app::NodepatApp;
+use crate::clipboard_history::preview;
+use crate::convert::{AUTO_ENCODING, ConvertDialogState, ConvertJob};
+use crate::editor::{line_column_char, parse_line_column, place_selection};
+use crate::file_ops::{ENCODINGS, Encoding, UTF8_BOM};
+use crate::find_in_files::{FileMatch, FindInFilesState};
 use crate::format::FontFamily;
-use crate::ui::file_browser::FileBrowser;
+use crate::macros::MAX_MACRO_RUNS;
+use crate::messages::{MessageKind, MessageQueue};
+use crate::stats::TextStats;
+use crate::ui::file_browser::{ALL_FILES_FILTER, DEFAULT_FILTERS, FileBrowser, FileFilter};
+use crate::viewer::ViewerState;
 use eframe::egui;
-use std::path::PathBuf;
 
 /// Show all dialogs
 ///
@@ -30,12 +38,51 @@ pub fn show_dialogs(ctx: &egui::Context, app: &mut NodepatApp) {
     if app.show_goto_dialog {
         show_goto_dialog(ctx, app);
     }
+    if app.show_macro_dialog {
+        show_macro_dialog(ctx, app);
+    }
+    if app.show_paste_history {
+        show_paste_history(ctx, app);
+    }
     if app.show_open_dialog {
         show_open_dialog(ctx, app);
     }
     if app.show_save_dialog {
         show_save_dialog(ctx, app);
     }
+    if app.show_convert_dialog {
+        show_convert_dialog(ctx, app);
+    }
+    if app.show_find_in_files_dialog {
+        show_find_in_files_dialog(ctx, app);
+    }
+    if app.show_export_pdf_dialog {
+        show_export_pdf_dialog(ctx, app);
+    }
+    if app.show_statistics_dialog {
+        show_statistics_dialog(ctx, app);
+    }
+    if app.show_unsaved_dialog {
+        show_unsaved_dialog(ctx, app);
+    }
+    if app.show_autosave_dialog {
+        show_autosave_dialog(ctx, app);
+    }
+    if app.preferences_baseline.is_some() {
+        show_preferences_dialog(ctx, app);
+    }
+    if app.show_reload_dialog {
+        show_reload_dialog(ctx, app);
+    }
+    if app.show_overwrite_dialog {
+        show_overwrite_dialog(ctx, app);
+    }
+    if app.binary_file_prompt.is_some() {
+        show_binary_file_dialog(ctx, app);
+    }
+    if !app.messages.is_empty() {
+        show_message_dialog(ctx, app);
+    }
 }
 
 /// Show Find dialog
@@ -44,15 +91,27 @@ pub fn show_dialogs(ctx: &egui::Context, app: &mut NodepatApp) {
 /// * `ctx` - egui context
 /// * `app` - Application state
 fn show_find_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let mut close = false;
     egui::Window::new("Find")
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
             ui.vertical(|ui| {
                 ui.label("Find what:");
-                ui.text_edit_singleline(&mut app.search_state.find_text);
+                let (field, submitted) = show_primary_field(
+                    ui,
+                    &mut app.search_state.find_text,
+                    &mut app.find_dialog_opened,
+                );
+                if field.changed() {
+                    app.search_state.status.clear();
+                }
+                if submitted {
+                    crate::search::find_next(app);
+                    field.request_focus();
+                }
 
-                ui.checkbox(&mut app.search_state.case_sensitive, "Match case");
+                show_search_options(ui, app);
                 ui.horizontal(|ui| {
                     ui.radio_value(&mut app.search_state.search_down, true, "Down");
                     ui.radio_value(&mut app.search_state.search_down, false, "Up");
@@ -63,11 +122,17 @@ fn show_find_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
                         crate::search::find_next(app);
                     }
                     if ui.button("Cancel").clicked() {
-                        app.show_find_dialog = false;
+                        close = true;
                     }
                 });
+                show_search_status(ui, app);
+                close |= escape_pressed(ui);
             });
         });
+    if close {
+        app.show_find_dialog = false;
+        crate::editor::focus_editor(ctx);
+    }
 }
 
 /// Show Replace dialog
@@ -76,18 +141,50 @@ fn show_find_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
 /// * `ctx` - egui context
 /// * `app` - Application state
 fn show_replace_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let mut close = false;
     egui::Window::new("Replace")
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
             ui.vertical(|ui| {
                 ui.label("Find what:");
-                ui.text_edit_singleline(&mut app.search_state.find_text);
+                let (field, find_submitted) = show_primary_field(
+                    ui,
+                    &mut app.search_state.find_text,
+                    &mut app.replace_dialog_opened,
+                );
+                if field.changed() {
+                    app.search_state.status.clear();
+                }
 
                 ui.label("Replace with:");
-                ui.text_edit_singleline(&mut app.search_state.replace_text);
+                let replace_field = ui.text_edit_singleline(&mut app.search_state.replace_text);
+                let replace_submitted =
+                    replace_field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                // Enter presses the default button, Find Next
+                if find_submitted || replace_submitted {
+                    crate::search::find_next(app);
+                    if find_submitted {
+                        field.request_focus();
+                    } else {
+                        replace_field.request_focus();
+                    }
+                }
 
-                ui.checkbox(&mut app.search_state.case_sensitive, "Match case");
+                show_search_options(ui, app);
+                let preserve_case = ui
+                    .add_enabled(
+                        !app.search_state.case_sensitive && !app.search_state.use_regex,
+                        egui::Checkbox::new(&mut app.search_state.preserve_case, "Preserve case"),
+                    )
+                    .on_hover_text(
+                        "Replace \"Colour\" with \"Color\" and \"COLOUR\" with \"COLOR\"",
+                    )
+                    .on_disabled_hover_text("Needs Match case and Regular expression off");
+                if preserve_case.changed() {
+                    app.config.preserve_case = app.search_state.preserve_case;
+                    app.messages.report_config_save(app.config.save());
+                }
 
                 ui.horizontal(|ui| {
                     if ui.button("Find Next").clicked() {
@@ -97,16 +194,105 @@ fn show_replace_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
                         crate::search::replace_current(app);
                     }
                     if ui.button("Replace All").clicked() {
-                        let count = crate::search::replace_all(app);
-                        // Could show a message about how many replacements were made
-                        eprintln!("Replaced {count} occurrences");
+                        crate::search::replace_all(app);
                     }
                     if ui.button("Cancel").clicked() {
-                        app.show_replace_dialog = false;
+                        close = true;
                     }
                 });
+                show_search_status(ui, app);
+                close |= escape_pressed(ui);
             });
         });
+    if close {
+        app.show_replace_dialog = false;
+        crate::editor::focus_editor(ctx);
+    }
+}
+
+/// Show the text field a dialog starts in
+///
+/// The field takes the focus, with its text selected, when the dialog
+/// was just opened.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `text` - Field text
+/// * `just_opened` - Whether the dialog was just opened; cleared here
+///
+/// # Returns
+/// Tuple of (field response, whether Enter was pressed in the field)
+fn show_primary_field(
+    ui: &mut egui::Ui,
+    text: &mut String,
+    just_opened: &mut bool,
+) -> (egui::Response, bool) {
+    let response = ui.text_edit_singleline(text);
+    if std::mem::take(just_opened) {
+        response.request_focus();
+        place_selection(ui, response.id, 0, text.chars().count());
+    }
+    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+    (response, submitted)
+}
+
+/// Check whether Escape was pressed to cancel a dialog
+///
+/// # Arguments
+/// * `ui` - egui UI context
+///
+/// # Returns
+/// True if Escape was pressed this frame
+fn escape_pressed(ui: &egui::Ui) -> bool {
+    ui.input(|i| i.key_pressed(egui::Key::Escape))
+}
+
+/// Show the search option checkboxes, saving changes to the config
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_search_options(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let word_changed = ui
+        .checkbox(&mut app.search_state.whole_word, "Match whole word only")
+        .changed();
+    let case_changed = ui
+        .checkbox(&mut app.search_state.case_sensitive, "Match case")
+        .changed();
+    let wrap_changed = ui
+        .checkbox(&mut app.search_state.wrap_around, "Wrap around")
+        .changed();
+    let regex_changed = ui
+        .checkbox(&mut app.search_state.use_regex, "Regular expression")
+        .changed();
+    let extended_changed = ui
+        .checkbox(&mut app.search_state.extended, "Extended (\\n, \\t)")
+        .on_hover_text("\\n, \\r and \\t find and insert line breaks and tabs; \\\\ a backslash")
+        .changed();
+    if regex_changed {
+        app.search_state.status.clear();
+    }
+    if word_changed || case_changed || wrap_changed || regex_changed || extended_changed {
+        app.config.whole_word = app.search_state.whole_word;
+        app.config.match_case = app.search_state.case_sensitive;
+        app.config.wrap_around = app.search_state.wrap_around;
+        app.config.use_regex = app.search_state.use_regex;
+        app.config.extended_search = app.search_state.extended;
+        app.messages.report_config_save(app.config.save());
+    }
+}
+
+/// Show the result of the last search
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `app` - Application state
+fn show_search_status(ui: &mut egui::Ui, app: &NodepatApp) {
+    if let Some(error) = app.search_state.pattern_error() {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    } else if !app.search_state.status.is_empty() {
+        ui.label(&app.search_state.status);
+    }
 }
 
 /// Show Font dialog
@@ -115,16 +301,22 @@ fn show_replace_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
 /// * `ctx` - egui context
 /// * `app` - Application state
 fn show_font_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    if app.font_browser.is_some() {
+        show_font_browser(ctx, app);
+        return;
+    }
     egui::Window::new("Font")
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
             ui.vertical(|ui| {
                 ui.label("Font family:");
+                let has_custom_font = !app.format_settings.font_file.is_empty();
                 egui::ComboBox::from_id_salt("font_family")
                     .selected_text(app.format_settings.font_family_type.display_name())
                     .show_ui(ui, |ui| {
-                        for family in FontFamily::all() {
+                        let custom = has_custom_font.then_some(FontFamily::Custom);
+                        for family in FontFamily::all().into_iter().chain(custom) {
                             ui.selectable_value(
                                 &mut app.format_settings.font_family_type,
                                 family,
@@ -132,6 +324,7 @@ fn show_font_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
                             );
                         }
                     });
+                show_font_file_row(ui, app);
 
                 ui.label("Size:");
                 ui.add(egui::Slider::new(
@@ -142,12 +335,11 @@ fn show_font_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
                 ui.separator();
                 ui.label("Sample");
                 // Show sample text with current font settings
-                let font_id = match app.format_settings.font_family_type {
-                    FontFamily::Monospace => egui::FontId::monospace(app.format_settings.font_size),
-                    FontFamily::Proportional => {
-                        egui::FontId::proportional(app.format_settings.font_size)
-                    }
-                };
+                let font_id = crate::fonts::editor_font(
+                    ctx,
+                    app.format_settings.font_family_type,
+                    app.format_settings.font_size,
+                );
                 ui.style_mut()
                     .text_styles
                     .insert(egui::TextStyle::Body, font_id);
@@ -156,7 +348,7 @@ fn show_font_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
                 ui.horizontal(|ui| {
                     if ui.button("OK").clicked() {
                         app.config.update_from_format(&app.format_settings);
-                        let _ = app.config.save();
+                        app.save_config_later();
                         app.show_font_dialog = false;
                     }
                     if ui.button("Cancel").clicked() {
@@ -167,6 +359,341 @@ fn show_font_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
         });
 }
 
+/// File type filter of the Font dialog's file browser
+const FONT_FILTER: FileFilter = FileFilter {
+    name: "Font files (*.ttf, *.otf)",
+    extensions: &["ttf", "otf"],
+};
+
+/// Show the custom font file and the buttons to change it
+///
+/// # Arguments
+/// * `ui` - egui UI context of the Font dialog
+/// * `app` - Application state
+fn show_font_file_row(ui: &mut egui::Ui, app: &mut NodepatApp) {
+    let font_file = std::path::PathBuf::from(&app.format_settings.font_file);
+    if let Some(name) = font_file.file_name() {
+        ui.label(format!("Font file: {}", name.to_string_lossy()))
+            .on_hover_text(font_file.display().to_string());
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Load Font File...").clicked() {
+            let initial = font_file
+                .parent()
+                .filter(|dir| dir.is_dir())
+                .map(std::path::Path::to_path_buf)
+                .or_else(crate::ui::file_browser::home_dir);
+            let mut browser = FileBrowser::new(initial.as_deref(), false, vec![FONT_FILTER]);
+            browser.set_show_hidden(app.config.show_hidden_files);
+            app.font_browser = Some(browser);
+        }
+        let has_custom_font = !app.format_settings.font_file.is_empty();
+        if ui
+            .add_enabled(has_custom_font, egui::Button::new("Use Built-in Fonts"))
+            .clicked()
+        {
+            app.use_built_in_fonts(ui.ctx());
+        }
+    });
+}
+
+/// Show the file browser that picks a custom font file
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_font_browser(ctx: &egui::Context, app: &mut NodepatApp) {
+    let Some(browser) = &mut app.font_browser else {
+        return;
+    };
+    let Some(path) = show_browser(
+        ctx,
+        browser,
+        &mut app.config,
+        &mut app.messages,
+        "Load Font File",
+    ) else {
+        return;
+    };
+    app.font_browser = None;
+    if path.as_os_str().is_empty() {
+        // Cancelled: back to the Font dialog
+        return;
+    }
+    match app.load_custom_font(ctx, &path) {
+        Ok(()) => app.format_settings.font_family_type = FontFamily::Custom,
+        Err(e) => app.messages.error(format!(
+            "{} could not be loaded as a font.\n{e}",
+            path.display()
+        )),
+    }
+}
+
+/// Show the Preferences dialog
+///
+/// Changes apply live; OK and Apply save them, Cancel reverts to the
+/// settings as of opening the dialog or the last Apply.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_preferences_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let mut open = true;
+    let mut changed = false;
+    let mut close = None;
+    egui::Window::new("Preferences")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.strong("Editor");
+            changed |= show_editor_preferences(ui, &mut app.config);
+            ui.separator();
+            ui.strong("Files");
+            changed |= show_file_preferences(ui, &mut app.config);
+            ui.separator();
+            ui.strong("Appearance");
+            egui::Grid::new("appearance_preferences").show(ui, |ui| {
+                changed |= ui
+                    .checkbox(&mut app.config.dark_mode, "Dark mode")
+                    .changed();
+                ui.end_row();
+                changed |= ui
+                    .checkbox(&mut app.config.show_status_bar, "Status bar")
+                    .changed();
+                ui.end_row();
+                changed |= ui
+                    .checkbox(&mut app.config.show_line_numbers, "Line numbers")
+                    .changed();
+                ui.end_row();
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("OK").clicked() {
+                    close = Some(true);
+                }
+                if ui.button("Apply").clicked() {
+                    app.apply_preferences();
+                }
+                if ui.button("Cancel").clicked() {
+                    close = Some(false);
+                }
+            });
+        });
+    if changed {
+        app.apply_config();
+    }
+    if !open {
+        close = Some(false);
+    }
+    if let Some(keep) = close {
+        app.close_preferences(keep);
+    }
+}
+
+/// Show the editor section of the Preferences dialog
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `config` - Settings being edited
+///
+/// # Returns
+/// True if a setting changed
+fn show_editor_preferences(ui: &mut egui::Ui, config: &mut crate::config::Config) -> bool {
+    let mut changed = false;
+    egui::Grid::new("editor_preferences").show(ui, |ui| {
+        ui.label("Font family:");
+        egui::ComboBox::from_id_salt("preferences_font_family")
+            .selected_text(config.font_family_type.display_name())
+            .show_ui(ui, |ui| {
+                for family in FontFamily::all() {
+                    changed |= ui
+                        .selectable_value(
+                            &mut config.font_family_type,
+                            family,
+                            family.display_name(),
+                        )
+                        .changed();
+                }
+            });
+        ui.end_row();
+        ui.label("Font size:");
+        changed |= ui
+            .add(egui::Slider::new(&mut config.font_size, 8.0..=72.0))
+            .changed();
+        ui.end_row();
+        changed |= ui.checkbox(&mut config.word_wrap, "Word wrap").changed();
+        ui.end_row();
+        ui.label("Tab width:");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut config.tab_width).range(1..=crate::config::MAX_TAB_WIDTH),
+            )
+            .changed();
+        ui.end_row();
+        changed |= ui
+            .checkbox(&mut config.indent_with_spaces, "Insert spaces for Tab")
+            .changed();
+        ui.end_row();
+        changed |= ui
+            .checkbox(
+                &mut config.auto_close_brackets,
+                "Auto-close brackets and quotes",
+            )
+            .changed();
+        ui.end_row();
+        changed |= ui
+            .checkbox(
+                &mut config.incremental_find,
+                "Ctrl+F opens the incremental search bar",
+            )
+            .changed();
+        ui.end_row();
+        changed |= ui
+            .checkbox(
+                &mut config.always_show_zoom,
+                "Always show zoom in status bar",
+            )
+            .changed();
+        ui.end_row();
+        ui.label("Time/Date format:");
+        changed |= show_datetime_format(ui, &mut config.datetime_format);
+        ui.end_row();
+    });
+    changed
+}
+
+/// Time/Date formats offered in the Preferences dialog
+const DATETIME_PRESETS: [(&str, &str); 4] = [
+    ("Notepad", crate::editor::DEFAULT_DATETIME_FORMAT),
+    ("ISO 8601", "%Y-%m-%d %H:%M:%S"),
+    ("Day first", "%d.%m.%Y %H:%M"),
+    ("File name", "%Y%m%d-%H%M%S"),
+];
+
+/// Show the Time/Date format picker with a preview
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `format` - Format being edited
+///
+/// # Returns
+/// True if the format changed
+fn show_datetime_format(ui: &mut egui::Ui, format: &mut String) -> bool {
+    let mut changed = false;
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            let preset = DATETIME_PRESETS
+                .iter()
+                .find(|(_, preset)| preset == format)
+                .map_or("Custom", |(name, _)| name);
+            egui::ComboBox::from_id_salt("preferences_datetime_format")
+                .selected_text(preset)
+                .show_ui(ui, |ui| {
+                    for (name, preset) in DATETIME_PRESETS {
+                        if ui.selectable_label(format == preset, name).clicked() {
+                            *format = preset.to_string();
+                            changed = true;
+                        }
+                    }
+                });
+            changed |= ui.text_edit_singleline(format).changed();
+        });
+        let preview = crate::editor::format_time_date(chrono::Local::now().naive_local(), format);
+        ui.weak(format!("Preview: {preview}"))
+            .on_hover_text("%Y %y %m %d %H %I %M %S %p; %-d drops the leading zero");
+    });
+    changed
+}
+
+/// Show the files section of the Preferences dialog
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `config` - Settings being edited
+///
+/// # Returns
+/// True if a setting changed
+fn show_file_preferences(ui: &mut egui::Ui, config: &mut crate::config::Config) -> bool {
+    let mut changed = false;
+    egui::Grid::new("file_preferences").show(ui, |ui| {
+        changed |= ui
+            .checkbox(&mut config.autosave_enabled, "Autosave every")
+            .changed();
+        let mut minutes = (config.autosave_interval_secs / 60).max(1);
+        if ui
+            .add_enabled(
+                config.autosave_enabled,
+                egui::DragValue::new(&mut minutes)
+                    .range(1..=120)
+                    .suffix(" min"),
+            )
+            .changed()
+        {
+            config.autosave_interval_secs = minutes * 60;
+            changed = true;
+        }
+        ui.end_row();
+        changed |= ui
+            .checkbox(&mut config.create_backup, "Back up on save")
+            .changed();
+        ui.end_row();
+        changed |= ui
+            .checkbox(
+                &mut config.trim_trailing_on_save,
+                "Trim trailing whitespace on save",
+            )
+            .changed();
+        ui.end_row();
+        changed |= ui
+            .checkbox(&mut config.recovery_enabled, "Recovery files")
+            .changed();
+        ui.end_row();
+        ui.label("Recent files:");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut config.max_recent_files)
+                    .range(0..=crate::config::MAX_RECENT_FILES_LIMIT),
+            )
+            .on_hover_text("0 turns off the recent files list")
+            .changed();
+        ui.end_row();
+        ui.label("New documents:");
+        egui::ComboBox::from_id_salt("preferences_encoding")
+            .selected_text(config.default_encoding.as_str())
+            .show_ui(ui, |ui| {
+                for encoding in ENCODINGS {
+                    changed |= ui
+                        .selectable_value(
+                            &mut config.default_encoding,
+                            encoding.to_string(),
+                            encoding,
+                        )
+                        .changed();
+                }
+            });
+        ui.end_row();
+        ui.label("Non-Unicode files:");
+        egui::ComboBox::from_id_salt("preferences_legacy_encoding")
+            .selected_text(config.legacy_encoding.as_str())
+            .show_ui(ui, |ui| {
+                for encoding in Encoding::ALL.into_iter().filter(|e| !e.is_unicode()) {
+                    changed |= ui
+                        .selectable_value(
+                            &mut config.legacy_encoding,
+                            encoding.name().to_string(),
+                            encoding.name(),
+                        )
+                        .changed();
+                }
+            })
+            .response
+            .on_hover_text("Encoding assumed for opened files that are not valid UTF-8");
+        ui.end_row();
+    });
+    changed
+}
+
 /// Show About dialog
 ///
 /// # Arguments
@@ -192,33 +719,575 @@ fn show_about_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
         });
 }
 
+/// Show the Statistics dialog
+///
+/// The figures are counted every frame, so they follow edits while the
+/// dialog is open. A selection gets a second column.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_statistics_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let encoding = &app.file_state.encoding;
+    let mut document = TextStats::for_file(&app.editor_state.text, encoding);
+    if app.file_state.has_utf8_bom() {
+        document.bytes += UTF8_BOM.len();
+    }
+    let selected = app.editor_state.selected_text();
+    let selection = (!selected.is_empty()).then(|| TextStats::new(selected, encoding));
+    let size_label = format!("Bytes ({})", app.file_state.encoding_label());
+    let labels = [
+        "Characters",
+        "Characters (no spaces)",
+        "Words",
+        "Lines",
+        "Paragraphs",
+        &size_label,
+    ];
+    let figures = |s: &TextStats| {
+        [
+            s.characters,
+            s.characters_no_spaces,
+            s.words,
+            s.lines,
+            s.paragraphs,
+            s.bytes,
+        ]
+    };
+    let document = figures(&document);
+    let selection = selection.as_ref().map(figures);
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("Statistics")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui::Grid::new("statistics_grid")
+                .num_columns(3)
+                .spacing([24.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong("Document");
+                    if selection.is_some() {
+                        ui.strong("Selection");
+                    }
+                    ui.end_row();
+                    for (row, label) in labels.iter().enumerate() {
+                        ui.label(*label);
+                        ui.label(document[row].to_string());
+                        if let Some(selection) = &selection {
+                            ui.label(selection[row].to_string());
+                        }
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+    if !open || close {
+        app.show_statistics_dialog = false;
+    }
+}
+
 /// Show Go To dialog
 ///
 /// # Arguments
 /// * `ctx` - egui context
 /// * `app` - Application state
 fn show_goto_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let mut close = false;
+    let mut submitted = false;
     egui::Window::new("Go To Line")
         .collapsible(false)
         .resizable(false)
         .show(ctx, |ui| {
             ui.vertical(|ui| {
-                ui.label("Line number:");
-                ui.text_edit_singleline(&mut app.goto_line);
+                ui.label("Line number, or line:column:");
+                let (field, entered) =
+                    show_primary_field(ui, &mut app.goto_line, &mut app.goto_dialog_opened);
+                submitted |= entered;
+                if field.changed() {
+                    app.goto_error.clear();
+                }
+                if !app.goto_error.is_empty() {
+                    ui.colored_label(ui.visuals().error_fg_color, &app.goto_error);
+                }
+
+                ui.horizontal(|ui| {
+                    submitted |= ui.button("Go To").clicked();
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+                close |= escape_pressed(ui);
+            });
+        });
+    if submitted {
+        match parse_line_column(&app.goto_line) {
+            Ok((line, column)) => {
+                if let Some(viewer) = &mut app.viewer {
+                    viewer.go_to_line(line);
+                } else {
+                    let index = line_column_char(&app.editor_state.text, line, column);
+                    app.editor_state.select(index, index);
+                }
+                close = true;
+            }
+            Err(error) => {
+                app.goto_error = error;
+                // Select the input again so it can be retyped
+                app.goto_dialog_opened = true;
+            }
+        }
+    }
+    if close {
+        app.show_goto_dialog = false;
+        crate::editor::focus_editor(ctx);
+    }
+}
+
+/// Show the Run Macro Multiple Times dialog
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_macro_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let mut close = false;
+    let mut run = false;
+    egui::Window::new("Run Macro Multiple Times")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Run");
+                ui.add(egui::DragValue::new(&mut app.macro_runs).range(1..=MAX_MACRO_RUNS));
+                ui.label("times");
+            });
+            ui.horizontal(|ui| {
+                run = ui
+                    .add_enabled(app.can_play_macro(), egui::Button::new("Run"))
+                    .clicked();
+                close = ui.button("Cancel").clicked() || escape_pressed(ui);
+            });
+        });
+    if run {
+        app.play_macro(app.macro_runs);
+    }
+    if run || close {
+        app.show_macro_dialog = false;
+        crate::editor::focus_editor(ctx);
+    }
+}
+
+/// Characters shown of each clipboard history entry
+const HISTORY_PREVIEW_CHARS: usize = 60;
+/// Characters of a clipboard history entry shown in its tooltip
+const HISTORY_TOOLTIP_CHARS: usize = 500;
+
+/// Show the Paste from History popup
+///
+/// The arrow keys move between the entries, Enter or the entry's number
+/// pastes it.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_paste_history(ctx: &egui::Context, app: &mut NodepatApp) {
+    const NUMBER_KEYS: [egui::Key; 9] = [
+        egui::Key::Num1,
+        egui::Key::Num2,
+        egui::Key::Num3,
+        egui::Key::Num4,
+        egui::Key::Num5,
+        egui::Key::Num6,
+        egui::Key::Num7,
+        egui::Key::Num8,
+        egui::Key::Num9,
+    ];
+    let mut chosen = None;
+    let mut close = false;
+    egui::Window::new("Paste from History")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+        .show(ctx, |ui| {
+            for (index, text) in app.clipboard_history.iter().enumerate() {
+                let number = NUMBER_KEYS
+                    .get(index)
+                    .map_or(String::new(), |_| format!("{}  ", index + 1));
+                let label = format!("{number}{}", preview(text, HISTORY_PREVIEW_CHARS));
+                let response = ui
+                    .add(egui::Button::new(label).frame(false))
+                    .on_hover_text(preview(text, HISTORY_TOOLTIP_CHARS));
+                if index == 0 && std::mem::take(&mut app.paste_history_opened) {
+                    response.request_focus();
+                }
+                if response.clicked() {
+                    chosen = Some(index);
+                }
+            }
+            ui.separator();
+            close = ui.button("Cancel").clicked() || escape_pressed(ui);
+        });
+    // Digits typed into the editor or another field are not choices
+    if chosen.is_none() && !ctx.wants_keyboard_input() {
+        chosen = ctx.input(|i| NUMBER_KEYS.iter().position(|&key| i.key_pressed(key)));
+    }
+    if let Some(index) = chosen {
+        app.paste_from_history(index);
+    }
+    if chosen.is_some() || close {
+        app.show_paste_history = false;
+        crate::editor::focus_editor(ctx);
+    }
+}
 
+/// Show the unsaved changes dialog
+///
+/// Asks whether to save the document before the pending action
+/// discards it.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_unsaved_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    egui::Window::new("Nodepat")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.label(format!(
+                    "Do you want to save changes to {}?",
+                    app.document_name()
+                ));
                 ui.horizontal(|ui| {
-                    if ui.button("Go To").clicked() && app.goto_line.parse::<usize>().is_ok() {
-                        // TODO: Implement go to line functionality
-                        app.show_goto_dialog = false;
+                    if ui.button("Save").clicked() {
+                        app.show_unsaved_dialog = false;
+                        if app.file_state.file_path.is_empty() || app.is_read_only() {
+                            // Route through Save As, then resume
+                            app.resume_after_save = true;
+                            app.show_save_dialog = true;
+                        } else if app.file_state.changed_on_disk().is_some() {
+                            // Confirm overwriting the other program's changes first
+                            app.resume_after_save = true;
+                            app.show_overwrite_dialog = true;
+                        } else if crate::menu::save_to_current_path(app, ctx.input(|i| i.time)) {
+                            app.resume_pending_action();
+                        } else {
+                            app.cancel_pending_action();
+                        }
+                    }
+                    if ui.button("Don't Save").clicked() {
+                        app.show_unsaved_dialog = false;
+                        app.resume_pending_action();
                     }
                     if ui.button("Cancel").clicked() {
-                        app.show_goto_dialog = false;
+                        app.show_unsaved_dialog = false;
+                        app.cancel_pending_action();
                     }
                 });
             });
         });
 }
 
+/// Show the dialog offering to reload a file changed by another program
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_reload_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    egui::Window::new("Nodepat")
+        .id(egui::Id::new("reload_dialog"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.label(format!(
+                    "{} has been changed by another program. Reload?",
+                    app.document_name()
+                ));
+                if app.file_state.is_modified {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Reloading discards your unsaved changes.",
+                    );
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Reload").clicked() {
+                        app.show_reload_dialog = false;
+                        app.reload_document();
+                    }
+                    if ui.button("Ignore").clicked() {
+                        app.show_reload_dialog = false;
+                        app.ignored_disk_stamp = app.file_state.changed_on_disk();
+                    }
+                });
+            });
+        });
+}
+
+/// Show the warning before saving over a file changed by another program
+///
+/// Resumes or drops the pending action like the Save As dialog when the
+/// save was started from the unsaved changes dialog.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_overwrite_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    egui::Window::new("Nodepat")
+        .id(egui::Id::new("overwrite_dialog"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.label(format!(
+                    "{} has been changed by another program since it was opened.",
+                    app.document_name()
+                ));
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "Saving replaces those changes with yours.",
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Save Anyway").clicked() {
+                        app.show_overwrite_dialog = false;
+                        let saved = crate::menu::save_to_current_path(app, ctx.input(|i| i.time));
+                        if app.resume_after_save {
+                            if saved {
+                                app.resume_pending_action();
+                            } else {
+                                app.cancel_pending_action();
+                            }
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        app.show_overwrite_dialog = false;
+                        app.cancel_pending_action();
+                    }
+                });
+            });
+        });
+}
+
+/// Show the question whether to view a binary file as a hex dump
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_binary_file_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let Some(path) = app.binary_file_prompt.clone() else {
+        return;
+    };
+    let name = std::path::Path::new(&path)
+        .file_name()
+        .map_or_else(|| path.clone(), |n| n.to_string_lossy().to_string());
+    egui::Window::new("Nodepat")
+        .id(egui::Id::new("binary_file_dialog"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.label(format!("{name} looks like a binary file."));
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "Editing it as text and saving it would damage it.",
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Open Anyway (Read-Only)").clicked() {
+                        app.binary_file_prompt = None;
+                        match ViewerState::open_hex(std::path::Path::new(&path)) {
+                            Ok(viewer) => app.viewer = Some(viewer),
+                            Err(e) => app.messages.error(e),
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        app.binary_file_prompt = None;
+                    }
+                });
+            });
+        });
+}
+
+/// Show the dialog about failing autosave writes
+///
+/// Offers to move recovery files to another folder or to turn
+/// recovery files off.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_autosave_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    if let Some(browser) = &mut app.autosave.browser {
+        if let Some(path) = show_browser(
+            ctx,
+            browser,
+            &mut app.config,
+            &mut app.messages,
+            "Choose Recovery Folder",
+        ) {
+            app.autosave.browser = None;
+            if path.as_os_str().is_empty() {
+                // Cancelled: back to the autosave dialog
+                return;
+            }
+            match crate::autosave::validate_recovery_dir(&path) {
+                Ok(()) => {
+                    app.config.recovery_dir = path.to_string_lossy().to_string();
+                    app.messages.report_config_save(app.config.save());
+                    app.autosave.retry_now();
+                    app.show_autosave_dialog = false;
+                }
+                Err(e) => app.messages.error(e),
+            }
+        }
+        return;
+    }
+
+    let mut close = false;
+    egui::Window::new("Autosave")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                let failures = app.autosave.tracker.consecutive_failures();
+                ui.label(format!(
+                    "Unsaved changes could not be written to a recovery file {failures} times in a row."
+                ));
+                if let Some(failure) = app.autosave.tracker.last_failure() {
+                    ui.label(&failure.error);
+                    ui.label(format!("Target: {}", failure.target.display()));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Change Recovery Folder...").clicked() {
+                        let current = app.config.recovery_dir();
+                        let mut browser = FileBrowser::directory_picker(
+                            current.exists().then_some(current.as_path()),
+                        );
+                        browser.set_show_hidden(app.config.show_hidden_files);
+                        app.autosave.browser = Some(browser);
+                    }
+                    if ui.button("Disable Recovery Files").clicked() {
+                        app.config.recovery_enabled = false;
+                        app.messages.report_config_save(app.config.save());
+                        app.autosave = crate::autosave::Autosave::default();
+                        close = true;
+                    }
+                    if ui.button("Keep Trying").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        });
+    if close {
+        app.show_autosave_dialog = false;
+    }
+}
+
+/// Show the message dialog
+///
+/// Queued messages are shown one at a time, oldest first.
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_message_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let Some(message) = app.messages.front() else {
+        return;
+    };
+    let mut close = false;
+    egui::Window::new(&message.title)
+        .id(egui::Id::new("message_dialog"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    match message.kind {
+                        MessageKind::Info => {}
+                        MessageKind::Warning => {
+                            ui.colored_label(ui.visuals().warn_fg_color, "⚠");
+                        }
+                        MessageKind::Error => {
+                            ui.colored_label(ui.visuals().error_fg_color, "⛔");
+                        }
+                    }
+                    ui.label(&message.text);
+                });
+                if ui.button("OK").clicked() {
+                    close = true;
+                }
+            });
+        });
+    if close {
+        app.messages.pop();
+    }
+}
+
+/// Show a file browser and remember its hidden-files and filter settings
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `browser` - File browser to show
+/// * `config` - Configuration that stores the hidden-files setting
+/// * `messages` - Message queue for a failed settings save
+/// * `title` - Window title
+///
+/// # Returns
+/// The result of `FileBrowser::show`
+fn show_browser(
+    ctx: &egui::Context,
+    browser: &mut FileBrowser,
+    config: &mut crate::config::Config,
+    messages: &mut MessageQueue,
+    title: &str,
+) -> Option<std::path::PathBuf> {
+    let result = browser.show(ctx, title);
+    let filter = browser.take_filter_change();
+    if browser.show_hidden() != config.show_hidden_files || filter.is_some() {
+        config.show_hidden_files = browser.show_hidden();
+        if let Some(filter) = filter {
+            config.file_filter = filter.to_string();
+        }
+        messages.report_config_save(config.save());
+    }
+    result
+}
+
+/// Pick the folder a file browser starts in
+///
+/// The open document's folder wins. Without one, the last-used folder is
+/// used, or the home directory if that folder no longer exists.
+///
+/// # Arguments
+/// * `file_path` - Path of the open document, empty if unsaved
+/// * `last_dir` - Folder the dialog was last used in, empty if never
+///
+/// # Returns
+/// The starting folder, or None for the working directory
+fn browse_start_dir(file_path: &str, last_dir: &str) -> Option<std::path::PathBuf> {
+    if !file_path.is_empty() {
+        return std::path::Path::new(file_path)
+            .parent()
+            .map(std::path::Path::to_path_buf);
+    }
+    let last_dir = std::path::PathBuf::from(last_dir);
+    if !last_dir.as_os_str().is_empty() && last_dir.is_dir() {
+        Some(last_dir)
+    } else {
+        crate::ui::file_browser::home_dir()
+    }
+}
+
 /// Show Open file dialog
 ///
 /// # Arguments
@@ -227,44 +1296,56 @@ fn show_goto_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
 fn show_open_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
     // Initialize file browser if needed
     if app.file_browser.is_none() {
-        let initial_path = if app.file_state.file_path.is_empty() {
-            None
+        let document_dir = std::path::Path::new(&app.file_state.file_path).parent();
+        let initial_path = browse_start_dir(&app.file_state.file_path, &app.config.last_open_dir);
+        let mut browser =
+            FileBrowser::new(initial_path.as_deref(), false, DEFAULT_FILTERS.to_vec());
+        // Huge files opened in the viewer are usually logs, so show everything
+        if app.open_as_viewer {
+            browser.set_filter(ALL_FILES_FILTER);
         } else {
-            std::path::Path::new(&app.file_state.file_path).parent()
-        };
-        app.file_browser = Some(FileBrowser::new(
-            initial_path,
-            false,
-            Some("txt".to_string()),
-        ));
+            browser.set_filter(&app.config.file_filter);
+        }
+        browser.set_show_hidden(app.config.show_hidden_files);
+        if let Some(dir) = document_dir {
+            browser.set_document_dir(dir);
+        }
+        app.file_browser = Some(browser);
     }
 
     // Show file browser
     if let Some(ref mut browser) = app.file_browser
-        && let Some(path) = browser.show(ctx, "Open File")
+        && let Some(path) = show_browser(
+            ctx,
+            browser,
+            &mut app.config,
+            &mut app.messages,
+            "Open File",
+        )
     {
-        if path == PathBuf::from("") {
+        if path.as_os_str().is_empty() {
             // Cancelled
             app.file_browser = None;
             app.show_open_dialog = false;
             return;
         }
+        if let Some(dir) = path.parent() {
+            app.config.last_open_dir = dir.to_string_lossy().to_string();
+            app.messages.report_config_save(app.config.save());
+        }
 
-        if let Some(path_str) = path.to_str() {
-            match app.file_state.load_file(path_str) {
-                Ok(content) => {
-                    app.editor_state.text = content;
-                    app.editor_state.undo_history.clear();
-                    app.editor_state.redo_history.clear();
-                    app.file_state.add_to_recent_files(&mut app.config);
-                }
-                Err(e) => {
-                    eprintln!("Error loading file: {e}");
-                }
+        if app.open_as_viewer {
+            match ViewerState::open(&path) {
+                Ok(viewer) => app.viewer = Some(viewer),
+                Err(e) => app.messages.error(e),
             }
+        } else {
+            // Unsaved changes were handled before the dialog opened
+            app.open_file(&path.to_string_lossy());
         }
         app.file_browser = None;
         app.show_open_dialog = false;
+        app.open_as_viewer = false;
     }
 }
 
@@ -276,12 +1357,14 @@ fn show_open_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
 fn show_save_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
     // Initialize file browser if needed
     if app.file_browser.is_none() {
-        let initial_path = if app.file_state.file_path.is_empty() {
-            None
-        } else {
-            std::path::Path::new(&app.file_state.file_path).parent()
-        };
-        let mut browser = FileBrowser::new(initial_path, true, Some("txt".to_string()));
+        let document_dir = std::path::Path::new(&app.file_state.file_path).parent();
+        let initial_path = browse_start_dir(&app.file_state.file_path, &app.config.last_save_dir);
+        let mut browser = FileBrowser::new(initial_path.as_deref(), true, DEFAULT_FILTERS.to_vec());
+        browser.set_filter(&app.config.file_filter);
+        browser.set_show_hidden(app.config.show_hidden_files);
+        if let Some(dir) = document_dir {
+            browser.set_document_dir(dir);
+        }
         // Set initial filename if available
         if !app.file_state.file_path.is_empty()
             && let Some(filename) = std::path::Path::new(&app.file_state.file_path)
@@ -295,23 +1378,419 @@ fn show_save_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
 
     // Show file browser
     if let Some(ref mut browser) = app.file_browser
-        && let Some(path) = browser.show(ctx, "Save File")
+        && let Some(path) = show_browser(
+            ctx,
+            browser,
+            &mut app.config,
+            &mut app.messages,
+            "Save File",
+        )
     {
-        if path == PathBuf::from("") {
+        if path.as_os_str().is_empty() {
             // Cancelled
             app.file_browser = None;
             app.show_save_dialog = false;
+            app.cancel_pending_action();
             return;
         }
 
+        let mut saved = false;
         if let Some(path_str) = path.to_str() {
+            crate::menu::trim_on_save(app, ctx.input(|i| i.time));
             if let Err(e) = app.file_state.save_file(path_str, &app.editor_state.text) {
-                eprintln!("Error saving file: {e}");
+                app.messages
+                    .error(format!("{path_str} could not be saved.\n{e}"));
             } else {
-                app.file_state.add_to_recent_files(&mut app.config);
+                if let Some(dir) = path.parent() {
+                    app.config.last_save_dir = dir.to_string_lossy().to_string();
+                }
+                let config_saved = app.file_state.add_to_recent_files(&mut app.config);
+                app.messages.report_config_save(config_saved);
+                if let Some(warning) = app.file_state.backup_warning.take() {
+                    app.messages.warning(warning);
+                }
+                app.remember_position();
+                saved = true;
             }
+        } else {
+            app.messages
+                .error(format!("{} is not a valid file path.", path.display()));
         }
         app.file_browser = None;
         app.show_save_dialog = false;
+        if app.resume_after_save {
+            if saved {
+                app.resume_pending_action();
+            } else {
+                app.cancel_pending_action();
+            }
+        }
+    }
+}
+
+/// Show Convert Files' Encoding dialog
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_convert_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let state = &mut app.convert_state;
+    state.options.legacy_encoding = app.config.legacy();
+
+    // Directory picker
+    if let Some(ref mut browser) = state.browser
+        && let Some(path) = show_browser(
+            ctx,
+            browser,
+            &mut app.config,
+            &mut app.messages,
+            "Select Folder",
+        )
+    {
+        if !path.as_os_str().is_empty() {
+            state.directory = path.to_string_lossy().to_string();
+        }
+        state.browser = None;
+    }
+
+    let mut close = false;
+    egui::Window::new("Convert Files' Encoding")
+        .collapsible(false)
+        .resizable(true)
+        .show(ctx, |ui| {
+            let running = state.job.as_ref().is_some_and(|job| !job.status().3);
+            ui.add_enabled_ui(!running, |ui| {
+                show_convert_options(ui, state, app.config.show_hidden_files);
+            });
+            ui.separator();
+            close = show_convert_progress(ui, state, running);
+        });
+    if close {
+        app.show_convert_dialog = false;
+    }
+}
+
+/// Show the folder, pattern, and encoding options of the convert dialog
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `state` - Convert dialog state
+/// * `show_hidden` - List hidden files in the folder picker
+fn show_convert_options(ui: &mut egui::Ui, state: &mut ConvertDialogState, show_hidden: bool) {
+    ui.horizontal(|ui| {
+        ui.label("Folder:");
+        ui.text_edit_singleline(&mut state.directory);
+        if ui.button("Browse...").clicked() {
+            let initial = std::path::Path::new(&state.directory);
+            let initial = initial.is_dir().then_some(initial);
+            let mut browser = FileBrowser::directory_picker(initial);
+            browser.set_show_hidden(show_hidden);
+            state.browser = Some(browser);
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("File names:");
+        ui.text_edit_singleline(&mut state.pattern);
+    });
+    let options = &mut state.options;
+    ui.horizontal(|ui| {
+        ui.label("From:");
+        egui::ComboBox::from_id_salt("convert_source")
+            .selected_text(options.source_encoding.as_str())
+            .show_ui(ui, |ui| {
+                for encoding in std::iter::once(AUTO_ENCODING).chain(ENCODINGS) {
+                    ui.selectable_value(
+                        &mut options.source_encoding,
+                        encoding.to_string(),
+                        encoding,
+                    );
+                }
+            });
+        ui.label("To:");
+        egui::ComboBox::from_id_salt("convert_target")
+            .selected_text(options.target_encoding.as_str())
+            .show_ui(ui, |ui| {
+                for encoding in ENCODINGS {
+                    ui.selectable_value(
+                        &mut options.target_encoding,
+                        encoding.to_string(),
+                        encoding,
+                    );
+                }
+            });
+    });
+    ui.checkbox(&mut options.create_backup, "Keep a .bak copy of each file");
+    ui.checkbox(
+        &mut options.replace_unmappable,
+        "Replace unmappable characters",
+    );
+    ui.checkbox(
+        &mut options.dry_run,
+        "Dry run (only list what would change)",
+    );
+}
+
+/// Show the progress, summary, and buttons of the convert dialog
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `state` - Convert dialog state
+/// * `running` - Whether a conversion is in progress
+///
+/// # Returns
+/// True if the dialog should close
+fn show_convert_progress(ui: &mut egui::Ui, state: &mut ConvertDialogState, running: bool) -> bool {
+    let mut close = false;
+    if let Some(job) = &state.job {
+        let (done, total, current, _) = job.status();
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = if total == 0 {
+            1.0
+        } else {
+            done as f32 / total as f32
+        };
+        ui.add(egui::ProgressBar::new(fraction).text(format!("{done}/{total} {current}")));
+        let mut summary = job.summary();
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut summary)
+                        .interactive(true)
+                        .desired_width(f32::INFINITY)
+                        .font(egui::TextStyle::Monospace),
+                );
+            });
+        if running {
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    }
+
+    ui.horizontal(|ui| {
+        if running {
+            if ui.button("Cancel").clicked()
+                && let Some(job) = &state.job
+            {
+                job.cancel();
+            }
+        } else {
+            let enabled = std::path::Path::new(&state.directory).is_dir();
+            if ui
+                .add_enabled(enabled, egui::Button::new("Start"))
+                .clicked()
+            {
+                state.job = Some(ConvertJob::start(
+                    std::path::PathBuf::from(&state.directory),
+                    state.pattern.clone(),
+                    state.options.clone(),
+                ));
+            }
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        }
+    });
+    close
+}
+
+/// Show the Find in Files dialog
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_find_in_files_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    let legacy = app.config.legacy();
+    let state = &mut app.find_in_files;
+
+    // Directory picker
+    if let Some(ref mut browser) = state.browser
+        && let Some(path) = show_browser(
+            ctx,
+            browser,
+            &mut app.config,
+            &mut app.messages,
+            "Select Folder",
+        )
+    {
+        if !path.as_os_str().is_empty() {
+            state.directory = path.to_string_lossy().to_string();
+        }
+        state.browser = None;
+    }
+
+    let mut close = false;
+    let mut opened = None;
+    egui::Window::new("Find in Files")
+        .collapsible(false)
+        .resizable(true)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            let running = state.is_running();
+            ui.add_enabled_ui(!running, |ui| {
+                show_find_in_files_options(ui, state, app.config.show_hidden_files);
+            });
+            ui.horizontal(|ui| {
+                if running {
+                    if ui.button("Stop").clicked() {
+                        state.cancel();
+                    }
+                } else {
+                    let enabled = !state.find_text.is_empty()
+                        && std::path::Path::new(&state.directory).is_dir();
+                    if ui.add_enabled(enabled, egui::Button::new("Find")).clicked() {
+                        state.start(legacy);
+                    }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                }
+                ui.label(&state.status);
+            });
+            ui.separator();
+            opened = show_find_in_files_results(ui, &state.results);
+            close |= escape_pressed(ui);
+        });
+    if let Some(file_match) = opened {
+        open_file_match(app, &file_match);
+    }
+    if close {
+        app.find_in_files.cancel();
+        app.show_find_in_files_dialog = false;
+    }
+}
+
+/// Show the folder, file name, and search options of Find in Files
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `state` - Find in Files state
+/// * `show_hidden` - List hidden files in the folder picker
+fn show_find_in_files_options(ui: &mut egui::Ui, state: &mut FindInFilesState, show_hidden: bool) {
+    ui.horizontal(|ui| {
+        ui.label("Find what:");
+        ui.text_edit_singleline(&mut state.find_text);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Folder:");
+        ui.text_edit_singleline(&mut state.directory);
+        if ui.button("Browse...").clicked() {
+            let initial = std::path::Path::new(&state.directory);
+            let initial = initial.is_dir().then_some(initial);
+            let mut browser = FileBrowser::directory_picker(initial);
+            browser.set_show_hidden(show_hidden);
+            state.browser = Some(browser);
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("File names:");
+        ui.text_edit_singleline(&mut state.pattern);
+    });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut state.case_sensitive, "Match case");
+        ui.checkbox(&mut state.whole_word, "Match whole word only");
+    });
+}
+
+/// Show the results list of Find in Files
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `results` - Matching lines
+///
+/// # Returns
+/// The result the user clicked, if any
+fn show_find_in_files_results(ui: &mut egui::Ui, results: &[FileMatch]) -> Option<FileMatch> {
+    let mut clicked = None;
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .max_height(300.0)
+        .auto_shrink([false, true])
+        .show_rows(ui, row_height, results.len(), |ui, rows| {
+            for file_match in &results[rows] {
+                let label = egui::RichText::new(file_match.label()).monospace();
+                if ui
+                    .add(egui::Label::new(label).extend().sense(egui::Sense::click()))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    clicked = Some(file_match.clone());
+                }
+            }
+        });
+    clicked
+}
+
+/// Open the file of a Find in Files result at the matching line
+///
+/// The current document only moves its cursor; other files are opened,
+/// asking about unsaved changes first.
+///
+/// # Arguments
+/// * `app` - Application state
+/// * `file_match` - Result to open
+fn open_file_match(app: &mut NodepatApp, file_match: &FileMatch) {
+    let path = file_match.path.to_string_lossy().to_string();
+    if app.viewer.is_none() && path == app.file_state.file_path {
+        app.pending_goto_line = Some(file_match.line);
+    } else {
+        app.request_action(crate::app::PendingAction::OpenFileAtLine(
+            path,
+            file_match.line,
+        ));
+    }
+}
+
+/// File type filter of the Export PDF dialog
+const PDF_FILTER: FileFilter = FileFilter {
+    name: "PDF files (*.pdf)",
+    extensions: &["pdf"],
+};
+
+/// Show the Export PDF dialog
+///
+/// # Arguments
+/// * `ctx` - egui context
+/// * `app` - Application state
+fn show_export_pdf_dialog(ctx: &egui::Context, app: &mut NodepatApp) {
+    if app.file_browser.is_none() {
+        let initial_path = browse_start_dir(&app.file_state.file_path, &app.config.last_save_dir);
+        let mut browser = FileBrowser::new(initial_path.as_deref(), true, vec![PDF_FILTER]);
+        browser.set_show_hidden(app.config.show_hidden_files);
+        let stem = std::path::Path::new(&app.file_state.file_path)
+            .file_stem()
+            .map_or_else(|| "Untitled".into(), |stem| stem.to_string_lossy());
+        browser.set_selected_file(format!("{stem}.pdf"));
+        app.file_browser = Some(browser);
+    }
+
+    if let Some(ref mut browser) = app.file_browser
+        && let Some(path) = show_browser(
+            ctx,
+            browser,
+            &mut app.config,
+            &mut app.messages,
+            "Export PDF",
+        )
+    {
+        app.file_browser = None;
+        app.show_export_pdf_dialog = false;
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        let layout = crate::pdf::PageLayout::default();
+        match crate::pdf::export_pdf(&path, &app.editor_state.text, &layout) {
+            Ok((pages, 0)) => app
+                .messages
+                .info(format!("Exported {pages} page(s) to {}.", path.display())),
+            Ok((pages, replaced)) => app.messages.warning(format!(
+                "Exported {pages} page(s) to {}.\n{replaced} character(s) outside the \
+                 Latin-1 range cannot be shown in the PDF and were replaced with '?'.",
+                path.display()
+            )),
+            Err(e) => app
+                .messages
+                .error(format!("{} could not be exported.\n{e}", path.display())),
+        }
     }
 }
@@ -5,6 +5,7 @@
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// File browser dialog state
 pub struct FileBrowser {
@@ -14,14 +15,118 @@ pub struct FileBrowser {
     selected_file: String,
     /// File entries in current directory
     entries: Vec<FileEntry>,
+    /// Index of the highlighted entry in `entries`
+    selected_index: Option<usize>,
     /// Error message to display
     error_message: String,
     /// Mode: true for save, false for open
     is_save_mode: bool,
-    /// Filter for file extensions (e.g., "txt" for .txt files)
-    file_filter: Option<String>,
+    /// Pick a directory instead of a file
+    is_directory_mode: bool,
+    /// File type filters the user can pick from
+    filters: Vec<FileFilter>,
+    /// Index of the active filter in `filters`
+    filter_index: usize,
+    /// Filter the user picked since the last check
+    picked_filter: Option<&'static str>,
+    /// List hidden files and directories
+    show_hidden: bool,
+    /// Column and direction the listing is sorted by
+    sort: SortOrder,
+    /// Quick-access locations shown in the sidebar
+    places: Vec<(String, PathBuf)>,
+    /// Text of the path field
+    path_input: String,
+    /// Subdirectories suggested for the path field
+    suggestions: Vec<PathBuf>,
+    /// Show the path as breadcrumbs or as a text field
+    path_mode: PathMode,
 }
 
+/// How the current path is shown
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PathMode {
+    /// Clickable path components
+    Breadcrumbs,
+    /// Editable text field
+    Text,
+}
+
+/// Column the listing can be sorted by
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    /// Entry name
+    Name,
+    /// File size
+    Size,
+    /// Last modification time
+    Modified,
+}
+
+/// Sort column and direction
+#[derive(Clone, Copy)]
+struct SortOrder {
+    /// Column to sort by
+    key: SortKey,
+    /// Largest, newest, or last name first
+    descending: bool,
+}
+
+/// Named file type filter
+#[derive(Clone, Copy)]
+pub struct FileFilter {
+    /// Name shown in the filter list
+    pub name: &'static str,
+    /// Matching extensions without the dot; empty matches every file
+    pub extensions: &'static [&'static str],
+}
+
+impl FileFilter {
+    /// Check if a file name matches the filter
+    ///
+    /// # Arguments
+    /// * `name` - File name to check
+    ///
+    /// # Returns
+    /// True if the name has one of the filter's extensions
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.extensions.is_empty()
+            || self
+                .extensions
+                .iter()
+                .any(|extension| name.ends_with(&format!(".{extension}")))
+    }
+}
+
+/// Name of the filter that shows every file
+pub const ALL_FILES_FILTER: &str = "All files (*.*)";
+
+/// File type filters offered by the Open and Save dialogs
+pub const DEFAULT_FILTERS: [FileFilter; 5] = [
+    FileFilter {
+        name: "Text files (*.txt)",
+        extensions: &["txt"],
+    },
+    FileFilter {
+        name: "Markdown files (*.md)",
+        extensions: &["md", "markdown"],
+    },
+    FileFilter {
+        name: "Log files (*.log)",
+        extensions: &["log"],
+    },
+    FileFilter {
+        name: "CSV files (*.csv)",
+        extensions: &["csv"],
+    },
+    FileFilter {
+        name: ALL_FILES_FILTER,
+        extensions: &[],
+    },
+];
+
 /// File entry in directory listing
 #[derive(Clone)]
 struct FileEntry {
@@ -31,6 +136,10 @@ struct FileEntry {
     path: PathBuf,
     /// Is directory
     is_dir: bool,
+    /// File size in bytes
+    size: u64,
+    /// Last modification time, if known
+    modified: Option<SystemTime>,
 }
 
 impl FileBrowser {
@@ -39,16 +148,12 @@ impl FileBrowser {
     /// # Arguments
     /// * `initial_path` - Initial directory path (None for current directory)
     /// * `is_save_mode` - True for save dialog, false for open dialog
-    /// * `file_filter` - Optional file extension filter (e.g., "txt")
+    /// * `filters` - File type filters to choose from; empty lists every file
     ///
     /// # Returns
     /// New `FileBrowser` instance
     #[must_use]
-    pub fn new(
-        initial_path: Option<&Path>,
-        is_save_mode: bool,
-        file_filter: Option<String>,
-    ) -> Self {
+    pub fn new(initial_path: Option<&Path>, is_save_mode: bool, filters: Vec<FileFilter>) -> Self {
         let current_path = initial_path
             .map(PathBuf::from)
             .or_else(|| std::env::current_dir().ok())
@@ -58,14 +163,45 @@ impl FileBrowser {
             current_path,
             selected_file: String::new(),
             entries: Vec::new(),
+            selected_index: None,
             error_message: String::new(),
             is_save_mode,
-            file_filter,
+            is_directory_mode: false,
+            filters,
+            filter_index: 0,
+            picked_filter: None,
+            show_hidden: false,
+            sort: SortOrder {
+                key: SortKey::Name,
+                descending: false,
+            },
+            places: quick_access_places(),
+            path_input: String::new(),
+            suggestions: Vec::new(),
+            path_mode: PathMode::Breadcrumbs,
         };
         browser.refresh_entries();
         browser
     }
 
+    /// Create new file browser that picks a directory
+    ///
+    /// Only directories are listed and the dialog returns the directory
+    /// that is currently shown.
+    ///
+    /// # Arguments
+    /// * `initial_path` - Initial directory path (None for current directory)
+    ///
+    /// # Returns
+    /// New `FileBrowser` instance in directory-picker mode
+    #[must_use]
+    pub fn directory_picker(initial_path: Option<&Path>) -> Self {
+        let mut browser = Self::new(initial_path, false, Vec::new());
+        browser.is_directory_mode = true;
+        browser.refresh_entries();
+        browser
+    }
+
     /// Show file browser dialog
     ///
     /// # Arguments
@@ -84,25 +220,13 @@ impl FileBrowser {
             .resizable(true)
             .default_size([600.0, 400.0])
             .show(ctx, |ui| {
+                egui::SidePanel::left("file_browser_places")
+                    .resizable(false)
+                    .default_width(110.0)
+                    .show_inside(ui, |ui| self.show_places(ui));
                 ui.vertical(|ui| {
                     // Current path display and navigation
-                    ui.horizontal(|ui| {
-                        ui.label("Path:");
-                        let mut path_str = self.current_path.to_string_lossy().to_string();
-                        let path_edited = ui.text_edit_singleline(&mut path_str).changed();
-                        if (path_edited && ui.input(|i| i.key_pressed(egui::Key::Enter)))
-                            || ui.button("Go").clicked()
-                        {
-                            // Try to navigate to entered path
-                            let new_path = PathBuf::from(&path_str);
-                            if new_path.exists() && new_path.is_dir() {
-                                self.current_path = new_path;
-                                self.refresh_entries();
-                            } else {
-                                self.error_message = "Invalid directory path".to_string();
-                            }
-                        }
-                    });
+                    self.show_path_bar(ui);
 
                     // Error message
                     if !self.error_message.is_empty() {
@@ -121,40 +245,21 @@ impl FileBrowser {
                                 self.refresh_entries();
                             }
 
-                            // Directory and file entries
-                            let mut clicked_dir: Option<PathBuf> = None;
-                            let mut clicked_file: Option<String> = None;
-
-                            for entry in &self.entries {
-                                let label = if entry.is_dir {
-                                    format!("📁 {}", entry.name)
-                                } else {
-                                    format!("📄 {}", entry.name)
-                                };
-
-                                if ui.button(&label).clicked() {
-                                    if entry.is_dir {
-                                        clicked_dir = Some(entry.path.clone());
-                                    } else {
-                                        clicked_file = Some(entry.name.clone());
-                                    }
-                                }
-                            }
-
-                            // Handle clicks after loop to avoid borrow conflicts
-                            if let Some(dir_path) = clicked_dir {
-                                self.current_path = dir_path;
-                                self.refresh_entries();
-                            }
-                            if let Some(file_name) = clicked_file {
-                                self.selected_file = file_name;
+                            if self.show_entries(ui) {
+                                result = self.confirm();
+                                should_close = result.is_some();
                             }
                         });
 
                     ui.separator();
 
                     // File name input (for save mode)
-                    if self.is_save_mode {
+                    if self.is_directory_mode {
+                        ui.horizontal(|ui| {
+                            ui.label("Folder:");
+                            ui.label(self.current_path.to_string_lossy());
+                        });
+                    } else if self.is_save_mode {
                         ui.horizontal(|ui| {
                             ui.label("File name:");
                             ui.text_edit_singleline(&mut self.selected_file);
@@ -170,29 +275,36 @@ impl FileBrowser {
                         });
                     }
 
+                    if !self.is_directory_mode {
+                        self.show_filter_list(ui);
+                    }
+
                     // Buttons
                     ui.horizontal(|ui| {
-                        let button_text = if self.is_save_mode { "Save" } else { "Open" };
-                        let enabled = !self.selected_file.is_empty();
+                        let button_text = if self.is_directory_mode {
+                            "Select Folder"
+                        } else if self.is_save_mode {
+                            "Save"
+                        } else {
+                            "Open"
+                        };
+                        let enabled = self.is_directory_mode || !self.selected_file.is_empty();
 
                         if ui
                             .add_enabled(enabled, egui::Button::new(button_text))
                             .clicked()
                         {
-                            let file_path = self.current_path.join(&self.selected_file);
-
-                            // Validate file path
-                            if self.is_save_mode || file_path.exists() {
-                                result = Some(file_path);
-                                should_close = true;
-                            } else {
-                                self.error_message = "File does not exist".to_string();
-                            }
+                            result = self.confirm();
+                            should_close = result.is_some();
                         }
 
                         if ui.button("Cancel").clicked() {
                             should_close = true;
                         }
+
+                        if self.is_save_mode && !self.selected_file.is_empty() {
+                            ui.label(format!("Saves as: {}", self.save_file_name()));
+                        }
                     });
                 });
             });
@@ -205,41 +317,423 @@ impl FileBrowser {
         result
     }
 
+    /// Show the path as breadcrumbs or as a text field
+    ///
+    /// In the text field, Tab accepts the first suggestion and Enter or
+    /// Go navigates.
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    fn show_path_bar(&mut self, ui: &mut egui::Ui) {
+        let id = ui.make_persistent_id("file_browser_path");
+        let mut go = false;
+        let mut crumb = None;
+        ui.horizontal(|ui| {
+            ui.label("Path:");
+            if self.path_mode == PathMode::Text {
+                go = self.show_path_field(ui, id);
+            } else {
+                crumb = show_breadcrumbs(ui, &self.current_path);
+            }
+            let (icon, hint) = if self.path_mode == PathMode::Text {
+                ("»", "Show the path as breadcrumbs")
+            } else {
+                ("✏", "Type or paste a path")
+            };
+            if ui.button(icon).on_hover_text(hint).clicked() {
+                self.toggle_path_mode();
+                if self.path_mode == PathMode::Text {
+                    ui.memory_mut(|m| m.request_focus(id));
+                }
+            }
+            if ui
+                .checkbox(&mut self.show_hidden, "Show hidden files")
+                .changed()
+            {
+                self.refresh_entries();
+            }
+        });
+
+        let mut accepted = None;
+        if self.path_mode == PathMode::Text && !self.suggestions.is_empty() {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (index, suggestion) in self.suggestions.iter().enumerate() {
+                    if ui
+                        .selectable_label(false, suggestion.to_string_lossy())
+                        .clicked()
+                    {
+                        accepted = Some(index);
+                    }
+                }
+            });
+        }
+        if let Some(index) = accepted {
+            self.accept_suggestion(index);
+            ui.memory_mut(|m| m.request_focus(id));
+            let end = self.path_input.chars().count();
+            crate::editor::place_selection(ui, id, end, end);
+        }
+
+        if go {
+            // Try to navigate to entered path
+            let new_path = PathBuf::from(expand_path(&self.path_input, |name| {
+                std::env::var(name).ok()
+            }));
+            if new_path.is_dir() {
+                self.current_path = new_path;
+                self.path_mode = PathMode::Breadcrumbs;
+                self.refresh_entries();
+            } else {
+                self.error_message = "Invalid directory path".to_string();
+            }
+        }
+        if let Some(path) = crumb {
+            self.current_path = path;
+            self.refresh_entries();
+        }
+    }
+
+    /// Show the editable path field
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    /// * `id` - Id of the path field
+    ///
+    /// # Returns
+    /// True if the user asked to go to the typed path
+    fn show_path_field(&mut self, ui: &mut egui::Ui, id: egui::Id) -> bool {
+        let tab = ui.memory(|m| m.has_focus(id))
+            && !self.suggestions.is_empty()
+            && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab));
+        if tab {
+            self.accept_suggestion(0);
+            let end = self.path_input.chars().count();
+            crate::editor::place_selection(ui, id, end, end);
+        }
+        let response = ui.add(egui::TextEdit::singleline(&mut self.path_input).id(id));
+        if response.changed() {
+            self.update_suggestions();
+        }
+        (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+            || ui.button("Go").clicked()
+    }
+
+    /// Switch between breadcrumbs and the path field
+    ///
+    /// The field starts with the current path.
+    fn toggle_path_mode(&mut self) {
+        self.path_mode = match self.path_mode {
+            PathMode::Breadcrumbs => PathMode::Text,
+            PathMode::Text => PathMode::Breadcrumbs,
+        };
+        self.path_input = self.current_path.to_string_lossy().to_string();
+        self.suggestions.clear();
+    }
+
+    /// Put a suggestion into the path field
+    ///
+    /// # Arguments
+    /// * `index` - Index into the suggestions
+    fn accept_suggestion(&mut self, index: usize) {
+        if let Some(suggestion) = self.suggestions.get(index) {
+            let mut input = suggestion.to_string_lossy().to_string();
+            input.push(std::path::MAIN_SEPARATOR);
+            self.path_input = input;
+            self.update_suggestions();
+        }
+    }
+
+    /// Suggest subdirectories that complete the path field
+    ///
+    /// While the typed parent is not a readable directory, the previous
+    /// suggestions are kept.
+    fn update_suggestions(&mut self) {
+        let input = expand_path(&self.path_input, |name| std::env::var(name).ok());
+        let ends_with_separator = input.ends_with(std::path::is_separator);
+        let path = Path::new(&input);
+        let (parent, prefix) = if ends_with_separator {
+            (path, String::new())
+        } else {
+            match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name)) => (parent, name.to_string_lossy().to_lowercase()),
+                _ => return,
+            }
+        };
+        let Ok(entries) = fs::read_dir(parent) else {
+            return;
+        };
+        let mut suggestions: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.to_lowercase().starts_with(&prefix)
+                    && (self.show_hidden || !is_hidden(entry, &name))
+                    && entry.path().is_dir()
+            })
+            .map(|entry| entry.path())
+            .collect();
+        suggestions.sort();
+        suggestions.truncate(MAX_SUGGESTIONS);
+        self.suggestions = suggestions;
+    }
+
+    /// Show the quick-access sidebar
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    fn show_places(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        for (name, path) in &self.places {
+            let selected = self.current_path == *path;
+            if ui
+                .selectable_label(selected, name)
+                .on_hover_text(path.to_string_lossy())
+                .clicked()
+            {
+                clicked = Some(path.clone());
+            }
+        }
+        if let Some(path) = clicked {
+            self.current_path = path;
+            self.refresh_entries();
+        }
+    }
+
+    /// Add the open document's folder to the sidebar
+    ///
+    /// # Arguments
+    /// * `dir` - Folder of the open document
+    pub fn set_document_dir(&mut self, dir: &Path) {
+        self.places.retain(|(name, _)| name != DOCUMENT_PLACE);
+        if dir.is_dir() {
+            self.places
+                .insert(0, (DOCUMENT_PLACE.to_string(), dir.to_path_buf()));
+        }
+    }
+
+    /// Show the directory and file entries
+    ///
+    /// A single click highlights an entry; a double click enters a
+    /// directory or confirms a file.
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    ///
+    /// # Returns
+    /// True if a file was double-clicked and the dialog should confirm
+    fn show_entries(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut clicked = None;
+        let mut double_clicked = None;
+        let mut sort_by = None;
+
+        egui::Grid::new("file_browser_entries")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (key, title) in [
+                    (SortKey::Name, "Name"),
+                    (SortKey::Size, "Size"),
+                    (SortKey::Modified, "Modified"),
+                ] {
+                    let title = match (self.sort.key == key, self.sort.descending) {
+                        (true, false) => format!("{title} ▲"),
+                        (true, true) => format!("{title} ▼"),
+                        (false, _) => title.to_string(),
+                    };
+                    if ui.button(title).clicked() {
+                        sort_by = Some(key);
+                    }
+                }
+                ui.end_row();
+
+                for (index, entry) in self.entries.iter().enumerate() {
+                    let label = if entry.is_dir {
+                        format!("📁 {}", entry.name)
+                    } else {
+                        format!("📄 {}", entry.name)
+                    };
+
+                    let response = ui.selectable_label(self.selected_index == Some(index), label);
+                    if response.double_clicked() {
+                        double_clicked = Some(index);
+                    } else if response.clicked() {
+                        clicked = Some(index);
+                    }
+                    if entry.is_dir {
+                        ui.label("");
+                    } else {
+                        ui.label(format_size(entry.size));
+                    }
+                    ui.label(
+                        entry
+                            .modified
+                            .map(|time| {
+                                format_modified(
+                                    chrono::DateTime::<chrono::Local>::from(time).naive_local(),
+                                )
+                            })
+                            .unwrap_or_default(),
+                    );
+                    ui.end_row();
+                }
+            });
+
+        // Handle clicks after loop to avoid borrow conflicts
+        if let Some(key) = sort_by {
+            self.sort_by(key);
+        }
+        if let Some(index) = clicked {
+            self.select_entry(index);
+        }
+        double_clicked.is_some_and(|index| self.activate_entry(index))
+    }
+
+    /// Sort by a column, toggling the direction if it is already used
+    ///
+    /// The highlighted entry stays highlighted.
+    ///
+    /// # Arguments
+    /// * `key` - Column to sort by
+    fn sort_by(&mut self, key: SortKey) {
+        if self.sort.key == key {
+            self.sort.descending = !self.sort.descending;
+        } else {
+            self.sort = SortOrder {
+                key,
+                descending: false,
+            };
+        }
+        let selected = self
+            .selected_index
+            .and_then(|index| self.entries.get(index))
+            .map(|entry| entry.path.clone());
+        self.sort_entries();
+        self.selected_index =
+            selected.and_then(|path| self.entries.iter().position(|entry| entry.path == path));
+    }
+
+    /// Sort the entries by the current sort order
+    ///
+    /// Directories always come before files.
+    fn sort_entries(&mut self) {
+        let sort = self.sort;
+        self.entries.sort_by(|a, b| {
+            let order = match sort.key {
+                SortKey::Name => a.name.cmp(&b.name),
+                SortKey::Size => a.size.cmp(&b.size),
+                SortKey::Modified => a.modified.cmp(&b.modified),
+            }
+            .then_with(|| a.name.cmp(&b.name));
+            let order = if sort.descending {
+                order.reverse()
+            } else {
+                order
+            };
+            b.is_dir.cmp(&a.is_dir).then(order)
+        });
+    }
+
+    /// Highlight an entry
+    ///
+    /// Selecting a file also makes it the file name to open or save.
+    ///
+    /// # Arguments
+    /// * `index` - Index into the entry list
+    fn select_entry(&mut self, index: usize) {
+        let Some(entry) = self.entries.get(index) else {
+            return;
+        };
+        if !entry.is_dir {
+            self.selected_file.clone_from(&entry.name);
+        }
+        self.selected_index = Some(index);
+    }
+
+    /// Activate an entry, as on double click
+    ///
+    /// # Arguments
+    /// * `index` - Index into the entry list
+    ///
+    /// # Returns
+    /// True if a file was activated and the dialog should confirm
+    fn activate_entry(&mut self, index: usize) -> bool {
+        let Some(entry) = self.entries.get(index) else {
+            return false;
+        };
+        if entry.is_dir {
+            self.current_path = entry.path.clone();
+            self.refresh_entries();
+            return false;
+        }
+        self.select_entry(index);
+        true
+    }
+
+    /// Confirm the dialog with the current selection
+    ///
+    /// # Returns
+    /// The chosen path, or None if the selection is not valid
+    fn confirm(&mut self) -> Option<PathBuf> {
+        if self.is_directory_mode {
+            return Some(self.current_path.clone());
+        }
+        if self.selected_file.is_empty() {
+            return None;
+        }
+        if self.is_save_mode {
+            return Some(self.current_path.join(self.save_file_name()));
+        }
+        let file_path = self.current_path.join(&self.selected_file);
+
+        // Validate file path
+        if file_path.exists() {
+            Some(file_path)
+        } else {
+            self.error_message = "File does not exist".to_string();
+            None
+        }
+    }
+
+    /// Get the file name a save writes to
+    ///
+    /// # Returns
+    /// The typed name with the active filter's extension added if needed
+    fn save_file_name(&self) -> String {
+        with_filter_extension(&self.selected_file, self.filters.get(self.filter_index))
+    }
+
     /// Refresh directory entries
     fn refresh_entries(&mut self) {
         self.entries.clear();
+        self.selected_index = None;
         self.error_message.clear();
+        self.path_input = self.current_path.to_string_lossy().to_string();
+        self.suggestions.clear();
 
         match fs::read_dir(&self.current_path) {
             Ok(entries) => {
-                let mut dirs = Vec::new();
-                let mut files = Vec::new();
-
                 for entry in entries.flatten() {
                     let path = entry.path();
                     let name = entry.file_name().to_string_lossy().to_string();
+                    if !self.show_hidden && is_hidden(&entry, &name) {
+                        continue;
+                    }
 
-                    if path.is_dir() {
-                        dirs.push(FileEntry {
-                            name,
-                            path,
-                            is_dir: true,
-                        });
-                    } else if self.matches_filter(&name) {
-                        files.push(FileEntry {
-                            name,
-                            path,
-                            is_dir: false,
-                        });
+                    let is_dir = path.is_dir();
+                    if !is_dir && (self.is_directory_mode || !self.matches_filter(&name)) {
+                        continue;
                     }
+                    let metadata = fs::metadata(&path).ok();
+                    self.entries.push(FileEntry {
+                        name,
+                        path,
+                        is_dir,
+                        size: metadata.as_ref().map_or(0, fs::Metadata::len),
+                        modified: metadata.and_then(|metadata| metadata.modified().ok()),
+                    });
                 }
 
-                // Sort: directories first, then files, both alphabetically
-                dirs.sort_by(|a, b| a.name.cmp(&b.name));
-                files.sort_by(|a, b| a.name.cmp(&b.name));
-
-                self.entries.extend(dirs);
-                self.entries.extend(files);
+                self.sort_entries();
             }
             Err(e) => {
                 self.error_message = format!("Failed to read directory: {e}");
@@ -255,7 +749,74 @@ impl FileBrowser {
         self.selected_file = filename;
     }
 
-    /// Check if file name matches filter
+    /// Show or hide hidden files and refresh the listing
+    ///
+    /// # Arguments
+    /// * `show_hidden` - True to list hidden files
+    pub fn set_show_hidden(&mut self, show_hidden: bool) {
+        if self.show_hidden != show_hidden {
+            self.show_hidden = show_hidden;
+            self.refresh_entries();
+        }
+    }
+
+    /// Check whether hidden files are listed
+    ///
+    /// # Returns
+    /// True if hidden files are listed
+    #[must_use]
+    pub const fn show_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    /// Make the named filter active and refresh the listing
+    ///
+    /// Unknown names leave the current filter active.
+    ///
+    /// # Arguments
+    /// * `name` - Filter name
+    pub fn set_filter(&mut self, name: &str) {
+        if let Some(index) = self.filters.iter().position(|filter| filter.name == name) {
+            self.filter_index = index;
+            self.refresh_entries();
+        }
+    }
+
+    /// Take the filter the user picked since the last call
+    ///
+    /// # Returns
+    /// The name of the newly picked filter, if the user changed it
+    pub const fn take_filter_change(&mut self) -> Option<&'static str> {
+        self.picked_filter.take()
+    }
+
+    /// Show the file type filter list
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    fn show_filter_list(&mut self, ui: &mut egui::Ui) {
+        let Some(current) = self.filters.get(self.filter_index) else {
+            return;
+        };
+        let mut index = self.filter_index;
+        ui.horizontal(|ui| {
+            ui.label("Files of type:");
+            egui::ComboBox::from_id_salt("file_browser_filter")
+                .selected_text(current.name)
+                .show_ui(ui, |ui| {
+                    for (i, filter) in self.filters.iter().enumerate() {
+                        ui.selectable_value(&mut index, i, filter.name);
+                    }
+                });
+        });
+        if index != self.filter_index {
+            self.filter_index = index;
+            self.picked_filter = self.filters.get(index).map(|filter| filter.name);
+            self.refresh_entries();
+        }
+    }
+
+    /// Check if file name matches the active filter
     ///
     /// # Arguments
     /// * `name` - File name to check
@@ -263,8 +824,508 @@ impl FileBrowser {
     /// # Returns
     /// True if matches filter or no filter set
     fn matches_filter(&self, name: &str) -> bool {
-        self.file_filter
-            .as_ref()
-            .is_none_or(|filter| name.to_lowercase().ends_with(&format!(".{filter}")))
+        self.filters
+            .get(self.filter_index)
+            .is_none_or(|filter| filter.matches(name))
+    }
+}
+
+/// Add a filter's extension to a file name that has none
+///
+/// A name ending in a dot gets the extension after that dot. Names with
+/// their own extension and filters matching every file leave the name
+/// unchanged.
+///
+/// # Arguments
+/// * `name` - File name as typed
+/// * `filter` - Active file type filter
+///
+/// # Returns
+/// The file name to write
+fn with_filter_extension(name: &str, filter: Option<&FileFilter>) -> String {
+    let Some(extension) = filter.and_then(|filter| filter.extensions.first()) else {
+        return name.to_string();
+    };
+    if let Some(stem) = name.strip_suffix('.') {
+        return format!("{stem}.{extension}");
+    }
+    if Path::new(name).extension().is_some() {
+        name.to_string()
+    } else {
+        format!("{name}.{extension}")
+    }
+}
+
+/// Most subdirectories suggested for the path field
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Expand the home directory and environment variables in a path
+///
+/// A leading `~` is the home directory on Unix and `%NAME%` is an
+/// environment variable on Windows. Unknown variables are kept as typed.
+///
+/// # Arguments
+/// * `input` - Path as typed
+/// * `var` - Looks up an environment variable
+///
+/// # Returns
+/// The expanded path
+fn expand_path(input: &str, var: impl Fn(&str) -> Option<String>) -> String {
+    if cfg!(windows) {
+        let mut expanded = String::new();
+        let mut rest = input;
+        while let Some(start) = rest.find('%') {
+            let after = &rest[start + 1..];
+            let Some(end) = after.find('%') else {
+                break;
+            };
+            let name = &after[..end];
+            expanded.push_str(&rest[..start]);
+            match var(name) {
+                Some(value) if !name.is_empty() => expanded.push_str(&value),
+                _ => expanded.push_str(&rest[start..start + end + 2]),
+            }
+            rest = &after[end + 1..];
+        }
+        expanded.push_str(rest);
+        expanded
+    } else {
+        match input.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                var("HOME").map_or_else(|| input.to_string(), |home| home + rest)
+            }
+            _ => input.to_string(),
+        }
+    }
+}
+
+/// Breadcrumbs shown before the middle ones collapse into a menu
+const MAX_BREADCRUMBS: usize = 5;
+
+/// Split a path into breadcrumbs
+///
+/// The first crumb holds the root, including a Windows drive or UNC
+/// share prefix.
+///
+/// # Arguments
+/// * `path` - Path to split
+///
+/// # Returns
+/// Crumb labels and the paths they lead to
+fn breadcrumbs(path: &Path) -> Vec<(String, PathBuf)> {
+    use std::path::Component;
+
+    let mut crumbs: Vec<(String, PathBuf)> = Vec::new();
+    let mut so_far = PathBuf::new();
+    for component in path.components() {
+        so_far.push(component);
+        match component {
+            Component::Prefix(_) | Component::RootDir if crumbs.len() == 1 && so_far.has_root() => {
+                // Drive or share followed by its root: one crumb
+                crumbs[0] = (so_far.to_string_lossy().to_string(), so_far.clone());
+            }
+            _ => {
+                let label = match component {
+                    Component::Normal(name) => name.to_string_lossy().to_string(),
+                    other => other.as_os_str().to_string_lossy().to_string(),
+                };
+                crumbs.push((label, so_far.clone()));
+            }
+        }
+    }
+    crumbs
+}
+
+/// Show a path as clickable breadcrumbs
+///
+/// Long paths keep the first and last crumbs; the middle ones move
+/// into a "…" menu.
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `path` - Path to show
+///
+/// # Returns
+/// The ancestor the user clicked, if any
+fn show_breadcrumbs(ui: &mut egui::Ui, path: &Path) -> Option<PathBuf> {
+    let crumbs = breadcrumbs(path);
+    let mut clicked = None;
+    let hidden = if crumbs.len() > MAX_BREADCRUMBS {
+        1..crumbs.len() + 1 - MAX_BREADCRUMBS + 1
+    } else {
+        0..0
+    };
+    for (index, (label, crumb_path)) in crumbs.iter().enumerate() {
+        if hidden.contains(&index) {
+            if index == hidden.start {
+                ui.label("›");
+                ui.menu_button("…", |ui| {
+                    for (label, crumb_path) in &crumbs[hidden.clone()] {
+                        if ui.button(label).clicked() {
+                            clicked = Some(crumb_path.clone());
+                            ui.close();
+                        }
+                    }
+                });
+            }
+            continue;
+        }
+        if index > 0 {
+            ui.label("›");
+        }
+        if ui.button(label).clicked() {
+            clicked = Some(crumb_path.clone());
+        }
+    }
+    clicked
+}
+
+/// Sidebar name of the open document's folder
+const DOCUMENT_PLACE: &str = "Current File";
+
+/// Find the user's home directory
+///
+/// # Returns
+/// The path from HOME or USERPROFILE, if set
+#[must_use]
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Find the common locations shown in the sidebar
+///
+/// Locations that do not exist on this machine are left out.
+///
+/// # Returns
+/// Sidebar names and paths, in display order
+fn quick_access_places() -> Vec<(String, PathBuf)> {
+    let mut places = Vec::new();
+    if let Some(home) = home_dir() {
+        for name in ["Desktop", "Documents", "Downloads"] {
+            places.push((name.to_string(), home.join(name)));
+        }
+        places.insert(0, ("Home".to_string(), home));
+    }
+    places.push(("Temp".to_string(), std::env::temp_dir()));
+    places.retain(|(_, path)| path.is_dir());
+    places.extend(drive_places());
+    places
+}
+
+/// Find the drives shown in the sidebar
+///
+/// Drives come from `GetLogicalDrives`, so nothing is read from them.
+/// Drives without readable media are listed too; opening one shows the
+/// directory read error.
+///
+/// # Returns
+/// Sidebar names and root paths of the drives
+#[cfg(windows)]
+fn drive_places() -> Vec<(String, PathBuf)> {
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        safe fn GetLogicalDrives() -> u32;
+    }
+
+    let mask = GetLogicalDrives();
+    (0u8..26)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| {
+            let root = format!("{}:\\", char::from(b'A' + bit));
+            (root.clone(), PathBuf::from(root))
+        })
+        .collect()
+}
+
+/// Find the drives shown in the sidebar
+///
+/// There are no drive letters outside Windows.
+///
+/// # Returns
+/// An empty list
+#[cfg(not(windows))]
+const fn drive_places() -> Vec<(String, PathBuf)> {
+    Vec::new()
+}
+
+/// Format a file size for display
+///
+/// # Arguments
+/// * `bytes` - Size in bytes
+///
+/// # Returns
+/// The size as bytes, or with one decimal in KB, MB, or GB
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut unit = 1024;
+    let mut index = 0;
+    while index + 1 < UNITS.len() && bytes >= unit * 1024 {
+        unit *= 1024;
+        index += 1;
+    }
+    let tenths = bytes * 10 / unit;
+    format!("{}.{} {}", tenths / 10, tenths % 10, UNITS[index])
+}
+
+/// Format a modification time for display
+///
+/// # Arguments
+/// * `time` - Modification time in local time
+///
+/// # Returns
+/// The time as "YYYY-MM-DD HH:MM"
+fn format_modified(time: chrono::NaiveDateTime) -> String {
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// Check whether a directory entry is hidden
+///
+/// Names starting with '.' are hidden on Unix.
+///
+/// # Arguments
+/// * `_entry` - Directory entry
+/// * `name` - Entry name
+///
+/// # Returns
+/// True if the entry is hidden
+#[cfg(not(windows))]
+fn is_hidden(_entry: &fs::DirEntry, name: &str) -> bool {
+    name.starts_with('.')
+}
+
+/// Check whether a directory entry is hidden
+///
+/// Entries with the hidden or system attribute are hidden on Windows.
+///
+/// # Arguments
+/// * `entry` - Directory entry
+/// * `_name` - Entry name
+///
+/// # Returns
+/// True if the entry is hidden
+#[cfg(windows)]
+fn is_hidden(entry: &fs::DirEntry, _name: &str) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    entry.metadata().is_ok_and(|metadata| {
+        metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click_selects_and_double_click_enters() {
+        let dir = std::env::temp_dir().join("test_Nodepat_browser_select");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).expect("Failed to create directory");
+        fs::write(dir.join("note.txt"), "text").expect("Failed to write file");
+
+        let mut browser = FileBrowser::new(Some(&dir), false, Vec::new());
+        // Directories are listed first
+        browser.select_entry(0);
+        assert_eq!(browser.selected_index, Some(0));
+        assert_eq!(browser.current_path, dir);
+        assert!(browser.selected_file.is_empty());
+
+        browser.select_entry(1);
+        assert_eq!(browser.selected_file, "note.txt");
+        assert_eq!(browser.confirm(), Some(dir.join("note.txt")));
+
+        assert!(!browser.activate_entry(0));
+        assert_eq!(browser.current_path, dir.join("sub"));
+        assert_eq!(browser.selected_index, None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_type_filter() {
+        let dir = std::env::temp_dir().join("test_Nodepat_browser_filter");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Failed to create directory");
+        for name in ["a.txt", "b.LOG", "README"] {
+            fs::write(dir.join(name), "text").expect("Failed to write file");
+        }
+
+        let mut browser = FileBrowser::new(Some(&dir), false, DEFAULT_FILTERS.to_vec());
+        assert_eq!(browser.entries.len(), 1);
+        browser.set_filter("Log files (*.log)");
+        assert_eq!(browser.entries[0].name, "b.LOG");
+        browser.set_filter(ALL_FILES_FILTER);
+        assert_eq!(browser.entries.len(), 3);
+        browser.set_filter("Unknown");
+        assert_eq!(browser.entries.len(), 3);
+        // Programmatic changes are not reported as user choices
+        assert_eq!(browser.take_filter_change(), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_document_dir_place() {
+        let dir = std::env::temp_dir();
+        let mut browser = FileBrowser::new(Some(&dir), false, Vec::new());
+        assert!(browser.places.iter().all(|(_, path)| path.is_dir()));
+
+        browser.set_document_dir(&dir);
+        browser.set_document_dir(&dir);
+        assert_eq!(browser.places[0], (DOCUMENT_PLACE.to_string(), dir));
+        let count = browser
+            .places
+            .iter()
+            .filter(|(name, _)| name == DOCUMENT_PLACE)
+            .count();
+        assert_eq!(count, 1);
+
+        browser.set_document_dir(Path::new("/nonexistent/Nodepat"));
+        assert!(
+            browser
+                .places
+                .iter()
+                .all(|(name, _)| name != DOCUMENT_PLACE)
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_expand_path() {
+        let var = |name: &str| (name == "HOME").then(|| "/home/user".to_string());
+        assert_eq!(expand_path("~", var), "/home/user");
+        assert_eq!(expand_path("~/notes", var), "/home/user/notes");
+        assert_eq!(expand_path("~other/notes", var), "~other/notes");
+        assert_eq!(expand_path("/tmp/~", var), "/tmp/~");
+    }
+
+    #[test]
+    fn test_path_suggestions_keep_last_valid_parent() {
+        let dir = std::env::temp_dir().join("test_Nodepat_browser_complete");
+        let _ = fs::remove_dir_all(&dir);
+        for name in ["alpha", "Almond", "beta"] {
+            fs::create_dir_all(dir.join(name)).expect("Failed to create directory");
+        }
+        fs::write(dir.join("album.txt"), "text").expect("Failed to write file");
+
+        let mut browser = FileBrowser::new(Some(&dir), false, Vec::new());
+        browser.path_input = dir.join("al").to_string_lossy().to_string();
+        browser.update_suggestions();
+        assert_eq!(browser.suggestions, [dir.join("Almond"), dir.join("alpha")]);
+
+        // A missing intermediate directory keeps the last suggestions
+        browser.path_input = dir.join("missing").join("x").to_string_lossy().to_string();
+        browser.update_suggestions();
+        assert_eq!(browser.suggestions.len(), 2);
+
+        browser.path_input = dir.join("al").to_string_lossy().to_string();
+        browser.update_suggestions();
+        browser.accept_suggestion(1);
+        assert!(browser.path_input.ends_with(std::path::MAIN_SEPARATOR));
+        assert!(browser.suggestions.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_breadcrumbs() {
+        let crumbs = breadcrumbs(Path::new("/home/user/notes"));
+        let labels: Vec<_> = crumbs.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, ["/", "home", "user", "notes"]);
+        assert_eq!(crumbs[2].1, PathBuf::from("/home/user"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_breadcrumbs() {
+        let crumbs = breadcrumbs(Path::new(r"C:\Users\me"));
+        let labels: Vec<_> = crumbs.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, [r"C:\", "Users", "me"]);
+        let crumbs = breadcrumbs(Path::new(r"\\server\share\docs"));
+        assert_eq!(crumbs[0].0, r"\\server\share\");
+        assert_eq!(crumbs.len(), 2);
+    }
+
+    #[test]
+    fn test_save_adds_filter_extension() {
+        let text = Some(&DEFAULT_FILTERS[0]);
+        assert_eq!(with_filter_extension("notes", text), "notes.txt");
+        assert_eq!(with_filter_extension("notes.txt", text), "notes.txt");
+        assert_eq!(with_filter_extension("notes.md", text), "notes.md");
+        assert_eq!(with_filter_extension("notes.", text), "notes.txt");
+        let all = DEFAULT_FILTERS.iter().find(|f| f.name == ALL_FILES_FILTER);
+        assert_eq!(with_filter_extension("notes", all), "notes");
+        assert_eq!(with_filter_extension("notes", None), "notes");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1229), "1.2 KB");
+        assert_eq!(format_size(3_565_159), "3.4 MB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+
+    #[test]
+    fn test_format_modified() {
+        let time = |secs| {
+            chrono::DateTime::from_timestamp(secs, 0)
+                .expect("Valid timestamp")
+                .naive_utc()
+        };
+        assert_eq!(format_modified(time(0)), "1970-01-01 00:00");
+        assert_eq!(format_modified(time(951_825_600)), "2000-02-29 12:00");
+        assert_eq!(format_modified(time(1_735_689_540)), "2024-12-31 23:59");
+    }
+
+    #[test]
+    fn test_sort_keeps_directories_first() {
+        let entry = |name: &str, is_dir, size| FileEntry {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            is_dir,
+            size,
+            modified: None,
+        };
+        let mut browser = FileBrowser::new(Some(Path::new("/nonexistent")), false, Vec::new());
+        browser.entries = vec![
+            entry("b.txt", false, 10),
+            entry("z", true, 0),
+            entry("a.txt", false, 30),
+        ];
+        browser.selected_index = Some(0);
+
+        browser.sort_by(SortKey::Size);
+        let names: Vec<_> = browser.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["z", "b.txt", "a.txt"]);
+        browser.sort_by(SortKey::Size);
+        let names: Vec<_> = browser.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["z", "a.txt", "b.txt"]);
+        // The highlighted entry follows the sort
+        assert_eq!(browser.selected_index, Some(2));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_hidden_files_toggle() {
+        let dir = std::env::temp_dir().join("test_Nodepat_browser_hidden");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Failed to create directory");
+        fs::write(dir.join(".hidden"), "text").expect("Failed to write file");
+        fs::write(dir.join("shown.txt"), "text").expect("Failed to write file");
+
+        let mut browser = FileBrowser::new(Some(&dir), false, Vec::new());
+        assert_eq!(browser.entries.len(), 1);
+        browser.set_show_hidden(true);
+        assert_eq!(browser.entries.len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,107 @@
+//! Markdown preview pane
+//!
+//! This module draws the blocks parsed by `crate::markdown` as egui rich
+//! text next to the editor.
+
+use crate::markdown::{Block, Span};
+use eframe::egui;
+
+/// Indent of each list nesting level and of quotes, in points
+const INDENT: f32 = 16.0;
+
+/// Show the rendered Markdown
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `blocks` - Parsed document
+/// * `font_size` - Size of body text; headings are scaled from it
+pub fn show_markdown_preview(ui: &mut egui::Ui, blocks: &[Block], font_size: f32) {
+    egui::ScrollArea::vertical()
+        .id_salt("markdown_preview")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui.spacing_mut().item_spacing.y = font_size * 0.5;
+            for block in blocks {
+                show_block(ui, block, font_size);
+            }
+        });
+}
+
+/// Show one block
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `block` - Block to draw
+/// * `font_size` - Size of body text
+fn show_block(ui: &mut egui::Ui, block: &Block, font_size: f32) {
+    match block {
+        Block::Heading(level, spans) => {
+            let scale = match level {
+                1 => 1.8,
+                2 => 1.5,
+                3 => 1.25,
+                _ => 1.1,
+            };
+            show_spans(ui, spans, font_size * scale, true);
+        }
+        Block::Paragraph(spans) => show_spans(ui, spans, font_size, false),
+        Block::ListItem {
+            marker,
+            depth,
+            spans,
+        } => {
+            ui.horizontal_wrapped(|ui| {
+                #[allow(clippy::cast_precision_loss)] // Nesting depth is small
+                ui.add_space(INDENT * (*depth as f32 + 1.0));
+                ui.label(egui::RichText::new(marker).size(font_size));
+                show_spans(ui, spans, font_size, false);
+            });
+        }
+        Block::Code(code) => {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(egui::RichText::new(code).monospace().size(font_size));
+            });
+        }
+        Block::Quote(spans) => {
+            ui.horizontal(|ui| {
+                ui.add_space(INDENT);
+                ui.separator();
+                ui.vertical(|ui| show_spans(ui, spans, font_size, false));
+            });
+        }
+        Block::Rule => {
+            ui.separator();
+        }
+    }
+}
+
+/// Show styled runs of text, wrapping at the pane width
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `spans` - Runs of text
+/// * `size` - Font size
+/// * `strong` - Whether every run is bold, as in headings
+fn show_spans(ui: &mut egui::Ui, spans: &[Span], size: f32, strong: bool) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            let mut text = egui::RichText::new(&span.text).size(size);
+            if span.code {
+                text = text.monospace().code();
+            }
+            if strong || span.bold {
+                text = text.strong();
+            }
+            if span.italic {
+                text = text.italics();
+            }
+            if let Some(url) = &span.link {
+                ui.hyperlink_to(text, url).on_hover_text(url);
+            } else {
+                ui.label(text);
+            }
+        }
+    });
+}
@@ -4,4 +4,7 @@
 
 pub mod dialogs;
 pub mod file_browser;
+pub mod markdown_preview;
+pub mod search_bar;
 pub mod status_bar;
+pub mod tab_bar;
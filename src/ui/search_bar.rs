@@ -0,0 +1,100 @@
+//! Incremental search bar widget
+//!
+//! This module draws the slim bar below the editor that searches as the
+//! user types, with the match position and the search status next to it.
+
+use crate::editor::place_selection;
+use eframe::egui;
+
+/// Id of the search bar's text field
+pub const SEARCH_FIELD_ID: &str = "search_bar_field";
+
+/// Search bar action the user chose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBarAction {
+    /// The search text changed
+    Changed,
+    /// Go to the next match (Enter)
+    Next,
+    /// Go to the previous match (Shift+Enter)
+    Previous,
+    /// Go back to where the search started and close the bar (Escape)
+    Cancel,
+    /// Close the bar, keeping the selected match (close button)
+    Close,
+}
+
+/// Show the search bar
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `find_text` - Text to find, shared with the Find dialog
+/// * `just_opened` - Whether the bar was just opened; cleared here
+/// * `summary` - Match position or status, e.g. "3 of 17"
+/// * `no_match` - Whether to show the field in the error color
+///
+/// # Returns
+/// The action the user chose, if any
+pub fn show_search_bar(
+    ui: &mut egui::Ui,
+    find_text: &mut String,
+    just_opened: &mut bool,
+    summary: &str,
+    no_match: bool,
+) -> Option<SearchBarAction> {
+    let field_id = egui::Id::new(SEARCH_FIELD_ID);
+    // Take Enter and Escape before the field, so it keeps the focus
+    let (previous, next, cancel) = if ui.memory(|m| m.has_focus(field_id)) {
+        ui.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::SHIFT, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        })
+    } else {
+        (false, false, false)
+    };
+    let mut action = if cancel {
+        Some(SearchBarAction::Cancel)
+    } else if previous {
+        Some(SearchBarAction::Previous)
+    } else {
+        next.then_some(SearchBarAction::Next)
+    };
+    ui.horizontal(|ui| {
+        ui.label("Find:");
+        let mut field = egui::TextEdit::singleline(find_text)
+            .id(field_id)
+            .desired_width(240.0)
+            .hint_text("Enter: next, Shift+Enter: previous");
+        if no_match {
+            field = field.background_color(ui.visuals().error_fg_color.gamma_multiply(0.35));
+        }
+        let response = ui.add(field);
+        if std::mem::take(just_opened) {
+            response.request_focus();
+            place_selection(ui, field_id, 0, find_text.chars().count());
+        }
+        if response.changed() {
+            action = Some(SearchBarAction::Changed);
+        }
+        if ui
+            .small_button("↑")
+            .on_hover_text("Previous match")
+            .clicked()
+        {
+            action = Some(SearchBarAction::Previous);
+        }
+        if ui.small_button("↓").on_hover_text("Next match").clicked() {
+            action = Some(SearchBarAction::Next);
+        }
+        ui.label(summary);
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.small_button("×").on_hover_text("Close").clicked() {
+                action = Some(SearchBarAction::Close);
+            }
+        });
+    });
+    action
+}
@@ -1,20 +1,174 @@
 //! Status bar widget
 //!
-//! This module implements the status bar that displays
-//! line and column position information.
+//! This module implements the status bar that displays line and column
+//! position, selection size, the insert/overwrite mode, and autosave
+//! warnings.
 
+use crate::autosave::WriteFailure;
 use crate::editor::EditorState;
+use crate::viewer::ViewerState;
 use eframe::egui;
 
+/// Status bar element the user clicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusBarClick {
+    /// The cursor position, to open Go To Line
+    GoToLine,
+    /// The zoom, to reset it to 100%
+    ResetZoom,
+}
+
 /// Show the status bar
 ///
 /// # Arguments
 /// * `ui` - egui UI context
 /// * `editor_state` - Editor state containing cursor position
-pub fn show_status_bar(ui: &mut egui::Ui, editor_state: &EditorState) {
+/// * `autosave_failure` - Last failed recovery write, if writes are failing
+/// * `notices` - Short-lived notices, e.g. the result of the latest
+///   periodic save
+/// * `read_only` - For a read-only document, whether an edit was just rejected
+/// * `goto_hint` - Tooltip of the cursor position
+/// * `zoom` - Zoom percentage to show, if any
+/// * `encoding` - Encoding of the document, shown at the right
+///
+/// # Returns
+/// The element the user clicked, if any
+#[allow(clippy::too_many_arguments)] // One argument per status bar element
+pub fn show_status_bar(
+    ui: &mut egui::Ui,
+    editor_state: &EditorState,
+    autosave_failure: Option<&WriteFailure>,
+    notices: &[&str],
+    read_only: Option<bool>,
+    goto_hint: &str,
+    zoom: Option<&str>,
+    encoding: &str,
+) -> Option<StatusBarClick> {
     ui.horizontal(|ui| {
         let line = editor_state.cursor_line;
         let col = editor_state.cursor_column;
-        ui.label(format!("Ln {line}, Col {col}"));
+        let position = ui
+            .add(egui::Label::new(format!("Ln {line}, Col {col}")).sense(egui::Sense::click()))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text(goto_hint);
+        let mut clicked = position.clicked().then_some(StatusBarClick::GoToLine);
+        if let Some(selection) = selection_summary(editor_state.selected_text()) {
+            ui.separator();
+            ui.label(selection);
+        }
+        if let Some(zoom) = zoom {
+            ui.separator();
+            if ui
+                .add(egui::Label::new(zoom).sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Reset zoom to 100%")
+                .clicked()
+            {
+                clicked = Some(StatusBarClick::ResetZoom);
+            }
+        }
+        if let Some(failure) = autosave_failure {
+            ui.separator();
+            ui.colored_label(ui.visuals().warn_fg_color, "\u{26a0} Autosave failed")
+                .on_hover_text(format!(
+                    "{}\nTarget: {}",
+                    failure.error,
+                    failure.target.display()
+                ));
+        }
+        for &notice in notices {
+            ui.separator();
+            ui.label(notice);
+        }
+        match read_only {
+            Some(true) => {
+                ui.separator();
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    crate::editor::READ_ONLY_DOCUMENT_HINT,
+                );
+                // Stop flashing once the hint expires
+                ui.ctx()
+                    .request_repaint_after(std::time::Duration::from_secs_f64(
+                        crate::autosave::NOTICE_SECS,
+                    ));
+            }
+            Some(false) => {
+                ui.separator();
+                ui.label("Read Only");
+            }
+            None => {}
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(encoding);
+            ui.separator();
+            ui.label(if editor_state.overwrite { "OVR" } else { "INS" })
+                .on_hover_text("Insert key switches between insert and overwrite");
+        });
+        clicked
+    })
+    .inner
+}
+
+/// Describe the size of the selection
+///
+/// # Arguments
+/// * `selected` - Selected text
+///
+/// # Returns
+/// E.g. "Sel: 12 chars (2 lines)", or None if nothing is selected
+fn selection_summary(selected: &str) -> Option<String> {
+    if selected.is_empty() {
+        return None;
+    }
+    let chars = selected.chars().count();
+    let lines = selected.matches('\n').count() + 1;
+    let plural = |count: usize, word: &str| {
+        if count == 1 {
+            format!("{count} {word}")
+        } else {
+            format!("{count} {word}s")
+        }
+    };
+    Some(format!(
+        "Sel: {} ({})",
+        plural(chars, "char"),
+        plural(lines, "line")
+    ))
+}
+
+/// Show the status bar for the read-only viewer
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `viewer` - Viewer state containing the selected line
+pub fn show_viewer_status_bar(ui: &mut egui::Ui, viewer: &ViewerState) {
+    ui.horizontal(|ui| {
+        let lines = viewer.line_count();
+        if let Some(line) = viewer.active_line() {
+            ui.label(format!("Ln {} of {lines}", line + 1));
+        } else {
+            ui.label(format!("{lines} lines"));
+        }
+        ui.separator();
+        ui.label(crate::viewer::READ_ONLY_HINT);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_summary() {
+        assert_eq!(selection_summary(""), None);
+        assert_eq!(
+            selection_summary("é"),
+            Some("Sel: 1 char (1 line)".to_string())
+        );
+        assert_eq!(
+            selection_summary("añb\ncd"),
+            Some("Sel: 6 chars (2 lines)".to_string())
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Tab bar widget
+//!
+//! This module draws one tab per open document above the editor, with a
+//! dot for unsaved changes and a close button. Tabs can be dragged to
+//! reorder them and have a context menu for closing several at once.
+
+use crate::document::{CloseSet, drop_index};
+use eframe::egui;
+
+/// What a tab shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabLabel {
+    /// Document name
+    pub name: String,
+    /// The document has unsaved changes
+    pub modified: bool,
+    /// Path of the document's file, empty for a new document
+    pub path: String,
+}
+
+/// Tab bar action the user chose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabBarClick {
+    /// Activate the tab at this index
+    Select(usize),
+    /// Close the tab at this index (close button, middle click or menu)
+    Close(usize),
+    /// Close a set of tabs relative to the tab at this index
+    CloseSet(usize, CloseSet),
+    /// Copy the file path of the tab at this index
+    CopyPath(usize),
+    /// Move the tab at `from` so that it ends up at `to`
+    Move {
+        /// Index of the dragged tab
+        from: usize,
+        /// Index of the tab after the move
+        to: usize,
+    },
+}
+
+/// Show the tab bar
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `tabs` - Label of each document, in tab order
+/// * `active` - Index of the active tab
+///
+/// # Returns
+/// The action the user chose, if any
+pub fn show_tab_bar(ui: &mut egui::Ui, tabs: &[TabLabel], active: usize) -> Option<TabBarClick> {
+    let mut clicked = None;
+    egui::ScrollArea::horizontal()
+        .id_salt("tab_bar")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let mut rects = Vec::with_capacity(tabs.len());
+                let mut dragged = None;
+                for (index, tab) in tabs.iter().enumerate() {
+                    let (response, action) = show_tab(ui, tab, index, index == active);
+                    clicked = action.or(clicked);
+                    if response.dragged() {
+                        dragged = Some((index, false));
+                    } else if response.drag_stopped() {
+                        dragged = Some((index, true));
+                    }
+                    rects.push(response.rect);
+                    ui.separator();
+                }
+                let pointer = ui.input(|i| i.pointer.latest_pos());
+                if let (Some((from, dropped)), Some(pointer)) = (dragged, pointer) {
+                    let gap = drop_gap(&rects, pointer.x);
+                    if dropped {
+                        let to = drop_index(from, gap);
+                        if to != from {
+                            clicked = Some(TabBarClick::Move { from, to });
+                        }
+                    } else {
+                        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                        paint_drop_indicator(ui, &rects, gap);
+                    }
+                }
+            });
+        });
+    clicked
+}
+
+/// Show one tab with its close button and context menu
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `tab` - What the tab shows
+/// * `index` - Index of the tab
+/// * `active` - Whether the tab is the active one
+///
+/// # Returns
+/// Tuple of (response covering the whole tab, action the user chose)
+fn show_tab(
+    ui: &mut egui::Ui,
+    tab: &TabLabel,
+    index: usize,
+    active: bool,
+) -> (egui::Response, Option<TabBarClick>) {
+    let mut clicked = None;
+    let label = if tab.modified {
+        format!("● {}", tab.name)
+    } else {
+        tab.name.clone()
+    };
+    let mut response =
+        ui.add(egui::Button::selectable(active, label).sense(egui::Sense::click_and_drag()));
+    if !tab.path.is_empty() {
+        response = response.on_hover_text(&tab.path);
+    }
+    if response.clicked() {
+        clicked = Some(TabBarClick::Select(index));
+    }
+    if response.middle_clicked() {
+        clicked = Some(TabBarClick::Close(index));
+    }
+    response.context_menu(|ui| {
+        let items = [
+            ("Close", Some(TabBarClick::Close(index))),
+            (
+                "Close Others",
+                Some(TabBarClick::CloseSet(index, CloseSet::Others)),
+            ),
+            (
+                "Close All to the Right",
+                Some(TabBarClick::CloseSet(index, CloseSet::ToTheRight)),
+            ),
+            (
+                "Copy Path",
+                (!tab.path.is_empty()).then_some(TabBarClick::CopyPath(index)),
+            ),
+        ];
+        for (name, action) in items {
+            if ui
+                .add_enabled(action.is_some(), egui::Button::new(name))
+                .clicked()
+            {
+                clicked = action;
+                ui.close();
+            }
+        }
+    });
+    let hint = if tab.modified {
+        "Close (has unsaved changes)"
+    } else {
+        "Close"
+    };
+    let close = ui.small_button("×").on_hover_text(hint);
+    if close.clicked() {
+        clicked = Some(TabBarClick::Close(index));
+    }
+    response.rect = response.rect.union(close.rect);
+    (response, clicked)
+}
+
+/// Get the gap between tabs the pointer is over
+///
+/// # Arguments
+/// * `rects` - Rectangle of each tab, in tab order
+/// * `x` - Horizontal pointer position
+///
+/// # Returns
+/// 0 before the first tab, the tab count after the last one
+fn drop_gap(rects: &[egui::Rect], x: f32) -> usize {
+    rects.iter().filter(|rect| rect.center().x < x).count()
+}
+
+/// Mark the gap a dragged tab would be dropped in
+///
+/// # Arguments
+/// * `ui` - egui UI context of the tab bar
+/// * `rects` - Rectangle of each tab, in tab order
+/// * `gap` - Gap to mark, as returned by `drop_gap`
+fn paint_drop_indicator(ui: &egui::Ui, rects: &[egui::Rect], gap: usize) {
+    let (Some(first), Some(last)) = (rects.first(), rects.last()) else {
+        return;
+    };
+    let half_spacing = ui.spacing().item_spacing.x / 2.0;
+    let x = rects.get(gap).map_or_else(
+        || last.right() + half_spacing,
+        |rect| rect.left() - half_spacing,
+    );
+    let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+    ui.painter().vline(x, first.y_range(), stroke);
+}
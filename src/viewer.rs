@@ -0,0 +1,1012 @@
+//! Read-only viewer for huge files
+//!
+//! This module implements a virtualized, read-only view over files that are
+//! too large for the editor. The file is read in chunks: a background thread
+//! indexes line starts, only the visible lines are decoded on demand, and
+//! search scans the raw bytes on another background thread without building
+//! a `String` of the whole file.
+//!
+//! Binary files are shown as a hex dump instead, one row per
+//! `HEX_ROW_BYTES` bytes, so they need no index.
+
+use eframe::egui;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Size of the chunks read while indexing and searching
+const CHUNK_SIZE: usize = 1 << 20;
+/// Maximum number of bytes of a single line decoded for display
+const MAX_LINE_DISPLAY: usize = 4096;
+/// Maximum number of bytes copied to the clipboard at once
+const MAX_COPY_BYTES: u64 = 8 << 20;
+/// Seconds between file size checks while following the tail
+const TAIL_POLL_SECS: f64 = 1.0;
+/// Number of bytes in each row of the hex dump
+pub const HEX_ROW_BYTES: usize = 16;
+/// Hint shown when an editing command is used in viewer mode
+pub const READ_ONLY_HINT: &str = "Read-only viewer: editing is disabled";
+
+/// Index of line start offsets within a file
+///
+/// The index is filled incrementally with `push_chunk`, so it can be
+/// shared with a background scanning thread and queried while incomplete.
+#[derive(Debug)]
+pub struct LineIndex {
+    /// Byte offset where each line starts (the first entry is always 0)
+    line_starts: Vec<u64>,
+    /// Number of bytes scanned so far
+    scanned: u64,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self {
+            line_starts: vec![0],
+            scanned: 0,
+        }
+    }
+}
+
+impl LineIndex {
+    /// Record the newlines of the next chunk of the file
+    ///
+    /// # Arguments
+    /// * `chunk` - Bytes following the already scanned part of the file
+    pub fn push_chunk(&mut self, chunk: &[u8]) {
+        let base = self.scanned;
+        self.line_starts.extend(
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| **byte == b'\n')
+                .map(|(i, _)| base + i as u64 + 1),
+        );
+        self.scanned += chunk.len() as u64;
+    }
+
+    /// Number of bytes indexed so far
+    #[must_use]
+    pub const fn scanned(&self) -> u64 {
+        self.scanned
+    }
+
+    /// Number of lines known so far
+    #[must_use]
+    pub const fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Get the byte range of a line, excluding its newline
+    ///
+    /// # Arguments
+    /// * `line` - 0-based line number
+    ///
+    /// # Returns
+    /// Tuple of (start, end) byte offsets, or None if the line is not indexed
+    #[must_use]
+    pub fn line_range(&self, line: usize) -> Option<(u64, u64)> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.scanned, |next| next - 1);
+        Some((start, end))
+    }
+
+    /// Find the line containing a byte offset
+    ///
+    /// # Arguments
+    /// * `offset` - Byte offset within the indexed part of the file
+    ///
+    /// # Returns
+    /// 0-based line number
+    #[must_use]
+    pub fn line_of_offset(&self, offset: u64) -> usize {
+        self.line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1)
+    }
+}
+
+/// Lock a shared line index, recovering from a panicked scanner thread
+fn lock_index(index: &Mutex<LineIndex>) -> MutexGuard<'_, LineIndex> {
+    index.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Index a file from the end of the already scanned part
+///
+/// # Arguments
+/// * `path` - File to index
+/// * `index` - Shared index to extend
+/// * `chunk_size` - Number of bytes read per step
+/// * `cancel` - Flag that stops the scan early when set
+///
+/// # Returns
+/// Result indicating success or the read error
+pub fn index_file(
+    path: &Path,
+    index: &Mutex<LineIndex>,
+    chunk_size: usize,
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    let start = lock_index(index).scanned();
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = vec![0; chunk_size.max(1)];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            return Ok(());
+        }
+        lock_index(index).push_chunk(&buf[..read]);
+    }
+}
+
+/// Read a byte range from a file
+///
+/// # Arguments
+/// * `file` - Open file handle
+/// * `start` - First byte to read
+/// * `end` - Byte offset to stop at (exclusive)
+///
+/// # Returns
+/// The bytes read, which may be fewer if the file is shorter
+fn read_range(file: &mut File, start: u64, end: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::new();
+    Read::by_ref(file)
+        .take(end.saturating_sub(start))
+        .read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Decode one line of raw bytes for display
+///
+/// Strips a trailing carriage return, decodes as UTF-8 (replacing invalid
+/// sequences) and truncates very long lines.
+///
+/// # Arguments
+/// * `bytes` - Raw line bytes without the newline
+///
+/// # Returns
+/// Displayable line text
+#[must_use]
+pub fn decode_line(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    if bytes.len() > MAX_LINE_DISPLAY {
+        let mut text = String::from_utf8_lossy(&bytes[..MAX_LINE_DISPLAY]).into_owned();
+        text.push('…');
+        text
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Format one row of a hex dump
+///
+/// # Arguments
+/// * `offset` - Byte offset of the row in the file
+/// * `bytes` - Up to `HEX_ROW_BYTES` bytes of the row
+///
+/// # Returns
+/// The offset, the bytes in hex, and the printable ASCII characters, e.g.
+/// `00000010  48 69 0a ...  |Hi.|`
+#[must_use]
+pub fn hex_dump_row(offset: u64, bytes: &[u8]) -> String {
+    let mut row = format!("{offset:08x} ");
+    for column in 0..HEX_ROW_BYTES {
+        // An extra space splits the row into two groups of eight
+        if column % 8 == 0 {
+            row.push(' ');
+        }
+        match bytes.get(column) {
+            Some(byte) => {
+                let _ = write!(row, "{byte:02x} ");
+            }
+            None => row.push_str("   "),
+        }
+    }
+    row.push('|');
+    row.extend(bytes.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            char::from(byte)
+        } else {
+            '.'
+        }
+    }));
+    row.push('|');
+    row
+}
+
+/// Find the next occurrence of a byte pattern in a file
+///
+/// The file is scanned in chunks that overlap by the pattern length, so
+/// matches spanning chunk boundaries are found. Case-insensitive matching
+/// folds ASCII letters only.
+///
+/// # Arguments
+/// * `path` - File to search
+/// * `needle` - Bytes to find
+/// * `from` - Byte offset to start searching at
+/// * `case_sensitive` - Whether ASCII case must match
+/// * `chunk_size` - Number of bytes read per step
+/// * `cancel` - Flag that stops the search early when set
+/// * `progress` - Called with the offset reached after each chunk
+///
+/// # Returns
+/// Byte offset of the match, or None if not found or cancelled
+pub fn find_forward(
+    path: &Path,
+    needle: &[u8],
+    from: u64,
+    case_sensitive: bool,
+    chunk_size: usize,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(u64),
+) -> io::Result<Option<u64>> {
+    if needle.is_empty() {
+        return Ok(None);
+    }
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(from))?;
+    let mut chunk = vec![0; chunk_size.max(1)];
+    let mut window: Vec<u8> = Vec::new();
+    let mut window_start = from;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        window.extend_from_slice(&chunk[..read]);
+        let found = window.windows(needle.len()).position(|candidate| {
+            if case_sensitive {
+                candidate == needle
+            } else {
+                candidate.eq_ignore_ascii_case(needle)
+            }
+        });
+        if let Some(pos) = found {
+            return Ok(Some(window_start + pos as u64));
+        }
+        // Keep the tail that could still start a match in the next chunk
+        let keep = (needle.len() - 1).min(window.len());
+        let consumed = window.len() - keep;
+        window.drain(..consumed);
+        window_start += consumed as u64;
+        progress(window_start + keep as u64);
+    }
+}
+
+/// Message from a search thread to the viewer
+enum SearchEvent {
+    /// Byte offset the search has reached
+    Progress(u64),
+    /// The search ended with a match offset, no match, or an error
+    Done(io::Result<Option<u64>>),
+}
+
+/// A search running on a background thread
+struct SearchJob {
+    /// Text searched for, for the status messages
+    needle: String,
+    /// Messages from the search thread
+    events: Receiver<SearchEvent>,
+    /// Set to stop the search thread
+    cancel: Arc<AtomicBool>,
+}
+
+/// State of an open viewer
+pub struct ViewerState {
+    /// Path of the viewed file
+    path: PathBuf,
+    /// Handle used to read visible lines
+    file: Option<File>,
+    /// Line index shared with the scanning thread
+    index: Arc<Mutex<LineIndex>>,
+    /// Whether a scanning thread is running
+    indexing: Arc<AtomicBool>,
+    /// Set to stop the scanning thread when the viewer is closed
+    cancel: Arc<AtomicBool>,
+    /// File size at the last check
+    file_len: u64,
+    /// Keep the view scrolled to the end while the file grows
+    pub tail_follow: bool,
+    /// Time of the last file size check
+    last_poll: f64,
+    /// Selected lines as (anchor, active), 0-based
+    selection: Option<(usize, usize)>,
+    /// Line to scroll into view on the next frame
+    scroll_to_line: Option<usize>,
+    /// Match offset waiting for the index to reach it
+    pending_match: Option<u64>,
+    /// Byte offset where the next search starts
+    search_from: u64,
+    /// Search running in the background
+    search: Option<SearchJob>,
+    /// Status message shown in the viewer header
+    pub status: String,
+    /// Show the file as a hex dump instead of lines of text
+    hex: bool,
+}
+
+impl ViewerState {
+    /// Open a file in the viewer and start indexing it
+    ///
+    /// # Arguments
+    /// * `path` - File to view
+    ///
+    /// # Returns
+    /// Viewer state, or error message if the file cannot be opened
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let viewer = Self::new(path, false)?;
+        viewer.start_indexing();
+        Ok(viewer)
+    }
+
+    /// Open a file in the viewer as a hex dump
+    ///
+    /// # Arguments
+    /// * `path` - File to view, usually a binary file
+    ///
+    /// # Returns
+    /// Viewer state, or error message if the file cannot be opened
+    pub fn open_hex(path: &Path) -> Result<Self, String> {
+        Self::new(path, true)
+    }
+
+    /// Open a file without indexing it
+    ///
+    /// # Arguments
+    /// * `path` - File to view
+    /// * `hex` - Show the file as a hex dump
+    ///
+    /// # Returns
+    /// Viewer state, or error message if the file cannot be opened
+    fn new(path: &Path, hex: bool) -> Result<Self, String> {
+        let metadata = fs::metadata(path).map_err(|e| format!("Failed to read file: {e}"))?;
+        if metadata.is_dir() {
+            return Err("Cannot view a directory".to_string());
+        }
+        let file = File::open(path).map_err(|e| format!("Failed to open file: {e}"))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Some(file),
+            index: Arc::new(Mutex::new(LineIndex::default())),
+            indexing: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(AtomicBool::new(false)),
+            file_len: metadata.len(),
+            tail_follow: false,
+            last_poll: 0.0,
+            selection: None,
+            scroll_to_line: None,
+            pending_match: None,
+            search_from: 0,
+            search: None,
+            status: String::new(),
+            hex,
+        })
+    }
+
+    /// Start a background scan from the end of the indexed part
+    fn start_indexing(&self) {
+        if self.indexing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let path = self.path.clone();
+        let index = Arc::clone(&self.index);
+        let indexing = Arc::clone(&self.indexing);
+        let cancel = Arc::clone(&self.cancel);
+        std::thread::spawn(move || {
+            // A failed scan is retried by the next tail poll
+            let _ = index_file(&path, &index, CHUNK_SIZE, &cancel);
+            indexing.store(false, Ordering::Release);
+        });
+    }
+
+    /// Path of the viewed file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the background scan is still running
+    #[must_use]
+    pub fn is_indexing(&self) -> bool {
+        self.indexing.load(Ordering::Acquire)
+    }
+
+    /// Number of lines indexed so far, or rows of the hex dump
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        if self.hex {
+            usize::try_from(self.file_len.div_ceil(HEX_ROW_BYTES as u64)).unwrap_or(usize::MAX)
+        } else {
+            lock_index(&self.index).line_count()
+        }
+    }
+
+    /// Get the byte range of a line or hex dump row
+    ///
+    /// # Arguments
+    /// * `line` - Line number (0-based)
+    ///
+    /// # Returns
+    /// (start, end) byte offsets, or None if the line is not known yet
+    fn line_range(&self, line: usize) -> Option<(u64, u64)> {
+        if self.hex {
+            let start = line as u64 * HEX_ROW_BYTES as u64;
+            (start < self.file_len)
+                .then(|| (start, (start + HEX_ROW_BYTES as u64).min(self.file_len)))
+        } else {
+            lock_index(&self.index).line_range(line)
+        }
+    }
+
+    /// Currently active line (0-based), if any is selected
+    #[must_use]
+    pub fn active_line(&self) -> Option<usize> {
+        self.selection.map(|(_, active)| active)
+    }
+
+    /// Select a line and scroll it into view
+    ///
+    /// # Arguments
+    /// * `line` - 1-based line number, clamped to the indexed lines
+    pub fn go_to_line(&mut self, line: usize) {
+        let last = self.line_count().saturating_sub(1);
+        let target = line.saturating_sub(1).min(last);
+        self.selection = Some((target, target));
+        self.scroll_to_line = Some(target);
+        self.tail_follow = false;
+        if let Some((start, _)) = self.line_range(target) {
+            self.search_from = start;
+        }
+    }
+
+    /// Start searching forward for text from the last match or selected
+    /// line
+    ///
+    /// The search runs in the background and replaces one that is still
+    /// running; its progress and result are shown in `status`.
+    ///
+    /// # Arguments
+    /// * `needle` - Text to find
+    /// * `case_sensitive` - Whether ASCII case must match
+    ///
+    /// # Returns
+    /// True if a search was started
+    pub fn find_next(&mut self, needle: &str, case_sensitive: bool) -> bool {
+        self.cancel_search();
+        if needle.is_empty() {
+            return false;
+        }
+        let (sender, events) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let path = self.path.clone();
+        let bytes = needle.as_bytes().to_vec();
+        let from = self.search_from;
+        let thread_cancel = Arc::clone(&cancel);
+        std::thread::spawn(move || {
+            let result = find_forward(
+                &path,
+                &bytes,
+                from,
+                case_sensitive,
+                CHUNK_SIZE,
+                &thread_cancel,
+                |offset| {
+                    let _ = sender.send(SearchEvent::Progress(offset));
+                },
+            );
+            // The viewer is gone if sending fails
+            let _ = sender.send(SearchEvent::Done(result));
+        });
+        self.status = format!("Searching for \"{needle}\"…");
+        self.search = Some(SearchJob {
+            needle: needle.to_string(),
+            events,
+            cancel,
+        });
+        true
+    }
+
+    /// Stop the background search, if one is running
+    fn cancel_search(&mut self) {
+        if let Some(job) = self.search.take() {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the progress and result of the background search
+    fn poll_search(&mut self) {
+        let Some(job) = &self.search else {
+            return;
+        };
+        let mut reached = None;
+        let result = loop {
+            match job.events.try_recv() {
+                Ok(SearchEvent::Progress(offset)) => reached = Some(offset),
+                Ok(SearchEvent::Done(result)) => break Some(result),
+                Err(TryRecvError::Empty) => break None,
+                Err(TryRecvError::Disconnected) => {
+                    break Some(Err(io::Error::other("the search stopped")));
+                }
+            }
+        };
+        let needle = job.needle.clone();
+        let Some(result) = result else {
+            if let Some(offset) = reached {
+                let percent = offset.saturating_mul(100) / self.file_len.max(1);
+                self.status = format!("Searching for \"{needle}\"… {}%", percent.min(100));
+            }
+            return;
+        };
+        self.search = None;
+        match result {
+            Ok(Some(offset)) => {
+                self.search_from = offset + 1;
+                self.pending_match = Some(offset);
+                self.tail_follow = false;
+                self.status.clear();
+            }
+            Ok(None) => {
+                // The next search starts over from the top
+                self.search_from = 0;
+                self.status = format!("Cannot find \"{needle}\" below this point");
+            }
+            Err(e) => self.status = format!("Search failed: {e}"),
+        }
+    }
+
+    /// Select the line of a found match once the index covers it
+    fn resolve_pending_match(&mut self) {
+        let Some(offset) = self.pending_match else {
+            return;
+        };
+        let line = if self.hex {
+            usize::try_from(offset / HEX_ROW_BYTES as u64).ok()
+        } else {
+            let index = lock_index(&self.index);
+            (offset < index.scanned()).then(|| index.line_of_offset(offset))
+        };
+        if let Some(line) = line {
+            self.selection = Some((line, line));
+            self.scroll_to_line = Some(line);
+            self.pending_match = None;
+        }
+    }
+
+    /// Read the selected lines as text
+    ///
+    /// # Returns
+    /// Selected text, or None if nothing is selected or reading fails
+    pub fn selected_text(&mut self) -> Option<String> {
+        let (anchor, active) = self.selection?;
+        let (first, last) = (anchor.min(active), anchor.max(active));
+        let (start, end) = (self.line_range(first)?.0, self.line_range(last)?.1);
+        let end = end.min(start + MAX_COPY_BYTES);
+        let bytes = read_range(self.file.as_mut()?, start, end).ok()?;
+        if self.hex {
+            let rows: Vec<String> = bytes
+                .chunks(HEX_ROW_BYTES)
+                .zip((start..).step_by(HEX_ROW_BYTES))
+                .map(|(row, offset)| hex_dump_row(offset, row))
+                .collect();
+            Some(rows.join("\n"))
+        } else {
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+    }
+
+    /// Check the file size and index any appended data
+    ///
+    /// # Arguments
+    /// * `now` - Current time in seconds
+    fn poll_file(&mut self, now: f64) {
+        if self.hex || now - self.last_poll < TAIL_POLL_SECS || self.is_indexing() {
+            return;
+        }
+        self.last_poll = now;
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return;
+        };
+        let len = metadata.len();
+        if len < self.file_len {
+            // The file was truncated or rotated, index it from scratch
+            *lock_index(&self.index) = LineIndex::default();
+            self.selection = None;
+            self.search_from = 0;
+            self.file = File::open(&self.path).ok();
+        }
+        if len != self.file_len || lock_index(&self.index).scanned() < len {
+            self.file_len = len;
+            self.start_indexing();
+        }
+    }
+
+    /// Read and decode a single line for display
+    ///
+    /// # Arguments
+    /// * `range` - Byte range of the line
+    fn display_line(&mut self, (start, end): (u64, u64)) -> String {
+        let end = end.min(start + MAX_LINE_DISPLAY as u64 + 1);
+        let hex = self.hex;
+        self.file
+            .as_mut()
+            .and_then(|file| read_range(file, start, end).ok())
+            .map_or_else(String::new, |bytes| {
+                if hex {
+                    hex_dump_row(start, &bytes)
+                } else {
+                    decode_line(&bytes)
+                }
+            })
+    }
+
+    /// Handle a click on a line, extending the selection with Shift
+    ///
+    /// # Arguments
+    /// * `line` - Clicked line (0-based)
+    /// * `extend` - Whether Shift was held
+    const fn click_line(&mut self, line: usize, extend: bool) {
+        self.selection = match self.selection {
+            Some((anchor, _)) if extend => Some((anchor, line)),
+            _ => Some((line, line)),
+        };
+    }
+
+    /// Check whether a line is within the selection
+    fn is_selected(&self, line: usize) -> bool {
+        self.selection.is_some_and(|(anchor, active)| {
+            (anchor.min(active)..=anchor.max(active)).contains(&line)
+        })
+    }
+
+    /// Show the header with file information and viewer options
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    fn show_header(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let name = self
+                .path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+            let lines = self.line_count();
+            if self.hex {
+                ui.label(format!(
+                    "{name}: {} bytes (binary, hex view)",
+                    self.file_len
+                ));
+            } else {
+                if self.is_indexing() {
+                    ui.label(format!("{name}: {lines} lines (indexing…)"));
+                } else {
+                    ui.label(format!("{name}: {lines} lines"));
+                }
+                ui.checkbox(&mut self.tail_follow, "Follow tail");
+            }
+            ui.separator();
+            if self.status.is_empty() {
+                ui.weak(READ_ONLY_HINT);
+            } else {
+                ui.label(&self.status);
+            }
+        });
+    }
+
+    /// Show the viewer
+    ///
+    /// # Arguments
+    /// * `ui` - egui UI context
+    /// * `font_id` - Font used for the file contents
+    pub fn show(&mut self, ui: &mut egui::Ui, font_id: &egui::FontId) {
+        let now = ui.input(|i| i.time);
+        self.poll_file(now);
+        self.poll_search();
+        self.resolve_pending_match();
+
+        self.show_header(ui);
+        ui.separator();
+
+        // The platform turns Ctrl+C into a copy event, not a key press
+        if ui.input(|i| {
+            i.events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Copy))
+        }) && let Some(text) = self.selected_text()
+        {
+            ui.ctx().copy_text(text);
+        }
+
+        let total_rows = self.line_count();
+        if self.tail_follow {
+            self.scroll_to_line = Some(total_rows.saturating_sub(1));
+        }
+        // Rows are selectable buttons, which add padding around the text
+        // and are at least as tall as other interactive widgets
+        let text_height = ui.fonts_mut(|fonts| fonts.row_height(font_id));
+        let row_height = ui
+            .spacing()
+            .button_padding
+            .y
+            .mul_add(2.0, text_height)
+            .max(ui.spacing().interact_size.y);
+        let spacing = ui.spacing().item_spacing.y;
+        let mut scroll_area = egui::ScrollArea::both().auto_shrink([false; 2]);
+        if let Some(line) = self.scroll_to_line.take() {
+            #[allow(clippy::cast_precision_loss)]
+            let offset = line as f32 * (row_height + spacing);
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+        let extend = ui.input(|i| i.modifiers.shift);
+        scroll_area.show_rows(ui, row_height, total_rows, |ui, rows| {
+            for line in rows {
+                let Some(range) = self.line_range(line) else {
+                    continue;
+                };
+                let text = self.display_line(range);
+                let label = egui::RichText::new(text).font(font_id.clone());
+                let selected = self.is_selected(line);
+                let response = ui.add(
+                    egui::Button::selectable(selected, label)
+                        .wrap_mode(egui::TextWrapMode::Extend)
+                        .min_size(egui::vec2(0.0, row_height)),
+                );
+                if response.clicked() {
+                    self.click_line(line, extend);
+                }
+            }
+        });
+
+        if self.is_indexing()
+            || self.tail_follow
+            || self.pending_match.is_some()
+            || self.search.is_some()
+        {
+            ui.ctx()
+                .request_repaint_after(std::time::Duration::from_millis(250));
+        }
+    }
+}
+
+impl Drop for ViewerState {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.cancel_search();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get a fixture path in the temp directory that no other test run uses
+    fn fixture_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("test_Nodepat_viewer_{}_{name}", std::process::id()))
+    }
+
+    /// Write a generated fixture with numbered lines to the temp directory
+    fn write_fixture(name: &str, lines: usize) -> PathBuf {
+        let mut content = String::new();
+        for i in 0..lines {
+            let _ = writeln!(content, "line {i} of the generated viewer fixture");
+        }
+        let path = fixture_path(name);
+        fs::write(&path, content).expect("Failed to write viewer fixture");
+        path
+    }
+
+    /// Find without cancelling or reporting progress
+    fn find(
+        path: &Path,
+        needle: &[u8],
+        from: u64,
+        case_sensitive: bool,
+        chunk_size: usize,
+    ) -> Option<u64> {
+        find_forward(
+            path,
+            needle,
+            from,
+            case_sensitive,
+            chunk_size,
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .expect("Search failed")
+    }
+
+    /// Wait for the viewer's background search to finish
+    fn wait_for_search(viewer: &mut ViewerState) {
+        while viewer.search.is_some() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            viewer.poll_search();
+        }
+    }
+
+    fn build_index(path: &Path, chunk_size: usize) -> LineIndex {
+        let index = Mutex::new(LineIndex::default());
+        index_file(path, &index, chunk_size, &AtomicBool::new(false))
+            .expect("Failed to index fixture");
+        index.into_inner().expect("Index mutex poisoned")
+    }
+
+    #[test]
+    fn test_index_large_fixture() {
+        let path = write_fixture("index.txt", 200_000);
+        let index = build_index(&path, 4093);
+
+        // 200k newline-terminated lines plus the empty line after the last one
+        assert_eq!(index.line_count(), 200_001);
+        assert_eq!(
+            index.scanned(),
+            fs::metadata(&path).expect("metadata").len()
+        );
+
+        let mut file = File::open(&path).expect("Failed to open fixture");
+        for line in [0, 1, 4093, 123_456, 199_999] {
+            let (start, end) = index.line_range(line).expect("Line not indexed");
+            let bytes = read_range(&mut file, start, end).expect("Failed to read line");
+            assert_eq!(
+                decode_line(&bytes),
+                format!("line {line} of the generated viewer fixture")
+            );
+            assert_eq!(index.line_of_offset(start), line);
+            assert_eq!(index.line_of_offset(end), line);
+        }
+        assert!(index.line_range(200_001).is_none());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_index_extends_after_append() {
+        let path = write_fixture("append.txt", 10);
+        let index = Mutex::new(LineIndex::default());
+        let cancel = AtomicBool::new(false);
+        index_file(&path, &index, 7, &cancel).expect("Failed to index fixture");
+        assert_eq!(lock_index(&index).line_count(), 11);
+
+        let mut content = fs::read(&path).expect("Failed to read fixture");
+        content.extend_from_slice(b"appended\ntail");
+        fs::write(&path, content).expect("Failed to append to fixture");
+        index_file(&path, &index, 7, &cancel).expect("Failed to extend index");
+
+        let index = lock_index(&index);
+        assert_eq!(index.line_count(), 12);
+        let mut file = File::open(&path).expect("Failed to open fixture");
+        let (start, end) = index.line_range(11).expect("Line not indexed");
+        let bytes = read_range(&mut file, start, end).expect("Failed to read line");
+        assert_eq!(decode_line(&bytes), "tail");
+        drop(index);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_find_forward_across_chunks() {
+        let path = write_fixture("find.txt", 50_000);
+        let needle = b"line 31337 of";
+        let expected = fs::read(&path)
+            .expect("Failed to read fixture")
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .expect("Fixture lacks needle") as u64;
+
+        // Chunk sizes that split the needle at different points
+        for chunk_size in [5, 64, 4096, CHUNK_SIZE] {
+            assert_eq!(find(&path, needle, 0, true, chunk_size), Some(expected));
+        }
+        assert_eq!(find(&path, b"LINE 31337 OF", 0, false, 64), Some(expected));
+        assert_eq!(find(&path, needle, expected + 1, true, 64), None);
+
+        // Progress reaches the end of the file, and a cancelled search
+        // finds nothing
+        let mut reached = 0;
+        let found = find_forward(
+            &path,
+            b"absent",
+            0,
+            true,
+            4096,
+            &AtomicBool::new(false),
+            |offset| {
+                reached = offset;
+            },
+        )
+        .expect("Search failed");
+        assert_eq!(found, None);
+        assert_eq!(reached, fs::metadata(&path).expect("metadata").len());
+        let found = find_forward(&path, needle, 0, true, 64, &AtomicBool::new(true), |_| {})
+            .expect("Search failed");
+        assert_eq!(found, None);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_decode_line() {
+        assert_eq!(decode_line(b"crlf line\r"), "crlf line");
+        assert_eq!(decode_line("ünïcode".as_bytes()), "ünïcode");
+        assert_eq!(decode_line(b"bad \xFF byte"), "bad \u{FFFD} byte");
+        let long = decode_line(&[b'x'; MAX_LINE_DISPLAY * 2]);
+        assert_eq!(long.chars().count(), MAX_LINE_DISPLAY + 1);
+        assert!(long.ends_with('…'));
+    }
+
+    #[test]
+    fn test_hex_dump_row() {
+        assert_eq!(
+            hex_dump_row(0x10, b"Hi\n\x00\xFFabcdefghijk"),
+            "00000010  48 69 0a 00 ff 61 62 63  64 65 66 67 68 69 6a 6b |Hi...abcdefghijk|"
+        );
+        assert_eq!(
+            hex_dump_row(0x20, b"end"),
+            format!("00000020  65 6e 64 {}|end|", " ".repeat(40))
+        );
+    }
+
+    #[test]
+    fn test_hex_viewer_rows() {
+        let path = fixture_path("hex.bin");
+        let data: Vec<u8> = (0..=40).collect();
+        fs::write(&path, &data).expect("Failed to write viewer fixture");
+
+        let mut viewer = ViewerState::open_hex(&path).expect("Failed to open viewer");
+        assert!(!viewer.is_indexing());
+        assert_eq!(viewer.line_count(), 3);
+        assert_eq!(viewer.line_range(2), Some((32, 41)));
+        assert_eq!(viewer.line_range(3), None);
+
+        viewer.go_to_line(3);
+        let copied = viewer.selected_text().expect("Nothing selected");
+        assert_eq!(copied, hex_dump_row(32, &data[32..]));
+
+        // A search selects the row of the match
+        viewer.go_to_line(1);
+        assert!(viewer.find_next("\u{14}", true));
+        wait_for_search(&mut viewer);
+        viewer.resolve_pending_match();
+        assert_eq!(viewer.active_line(), Some(1));
+
+        // Ctrl+C copies the selected row
+        let ctx = egui::Context::default();
+        let input = egui::RawInput {
+            events: vec![egui::Event::Copy],
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                viewer.show(ui, &egui::FontId::monospace(12.0));
+            });
+        });
+        assert!(
+            output
+                .platform_output
+                .commands
+                .contains(&egui::OutputCommand::CopyText(hex_dump_row(
+                    16,
+                    &data[16..32]
+                )))
+        );
+
+        // Searching past the last match reports it in the status
+        assert!(viewer.find_next("\u{14}", true));
+        wait_for_search(&mut viewer);
+        assert!(viewer.status.starts_with("Cannot find"));
+
+        let _ = fs::remove_file(&path);
+    }
+}